@@ -0,0 +1,221 @@
+//! 以库的形式使用 rdbkp2 的接口
+//!
+//! 与命令行不同，这里的函数不读取配置文件与全局配置：每次调用只使用参数中的设置，
+//! 并为本次调用单独连接 daemon，不同任务中的调用可以同时使用不同的设置。
+//! 调用始终以非交互模式进行，不会提示输入或确认。
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! let report = rdbkp2::api::backup_container(rdbkp2::api::BackupOptions::new(
+//!     "postgres",
+//!     "/var/backups/rdbkp2",
+//! ))
+//! .await?;
+//! rdbkp2::api::restore_backup(rdbkp2::api::RestoreOptions::new("postgres", report.path)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::commands::{backup, restore, retention::RetentionPolicy};
+use crate::config::Config;
+use crate::docker::DockerClient;
+use crate::{log_bail, utils};
+
+/// 备份参数
+#[derive(Debug, Clone)]
+pub struct BackupOptions {
+    /// 容器名称或 ID
+    pub container: String,
+    /// 备份文件的输出目录，不存在时自动创建
+    pub output_dir: PathBuf,
+    /// 只备份这些宿主机路径，为空时备份容器的所有挂载卷
+    pub files: Vec<String>,
+    /// 排除模式，语法与 `--exclude` 相同
+    pub exclude: Vec<String>,
+    /// 等待容器停止的超时时间 (秒)，0 表示一直等待
+    pub stop_timeout_secs: u64,
+    /// 备份完成后重启容器
+    pub restart: bool,
+    /// 不停止容器，直接读取运行中容器的挂载卷
+    pub no_stop: bool,
+    /// 并行压缩的最大线程数，`None` 时使用逻辑 CPU 数量
+    pub jobs: Option<usize>,
+    /// 备份成功后只保留该容器最近的 N 个备份
+    pub keep_last: Option<usize>,
+}
+
+impl BackupOptions {
+    /// 以默认设置备份 `container` 的所有挂载卷到 `output_dir`
+    pub fn new(container: impl Into<String>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            container: container.into(),
+            output_dir: output_dir.into(),
+            files: Vec::new(),
+            exclude: Vec::new(),
+            stop_timeout_secs: Config::default().timeout_secs,
+            restart: false,
+            no_stop: false,
+            jobs: None,
+            keep_last: None,
+        }
+    }
+}
+
+/// 备份结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// 写入的备份文件路径
+    pub path: PathBuf,
+    /// 备份文件的大小 (字节)
+    pub size: u64,
+}
+
+/// 恢复参数
+#[derive(Debug, Clone)]
+pub struct RestoreOptions {
+    /// 容器名称或 ID
+    pub container: String,
+    /// 备份文件路径
+    pub file: PathBuf,
+    /// 只恢复这些名称的卷，为空时恢复全部卷
+    pub volumes: Vec<String>,
+    /// 等待容器停止的超时时间 (秒)，0 表示一直等待
+    pub stop_timeout_secs: u64,
+    /// 恢复完成后重启容器
+    pub restart: bool,
+    /// 解压后按备份内的校验清单逐个校验文件
+    pub verify_checksums: bool,
+}
+
+impl RestoreOptions {
+    /// 以默认设置将 `file` 中的全部卷恢复到 `container`
+    pub fn new(container: impl Into<String>, file: impl Into<PathBuf>) -> Self {
+        Self {
+            container: container.into(),
+            file: file.into(),
+            volumes: Vec::new(),
+            stop_timeout_secs: Config::default().timeout_secs,
+            restart: false,
+            verify_checksums: false,
+        }
+    }
+}
+
+/// 备份一个容器，返回写入的备份文件
+pub async fn backup_container(opts: BackupOptions) -> Result<BackupReport> {
+    info!(?opts, "Starting library backup");
+    let config = Config {
+        backup_dir: opts.output_dir.clone(),
+        exclude: opts.exclude.join(","),
+        timeout_secs: opts.stop_timeout_secs,
+        restart: opts.restart,
+        ..call_config()
+    };
+    let options = backup::BackupOptions {
+        jobs: opts.jobs,
+        no_stop: opts.no_stop,
+        retention: RetentionPolicy {
+            keep_last: opts.keep_last,
+            keep_days: None,
+        },
+        ..Default::default()
+    };
+    let output = Some(opts.output_dir.to_string_lossy().to_string());
+
+    let path = Config::scope(config.clone(), async {
+        let client = DockerClient::connect(&config).await?;
+        backup::backup(&client, Some(opts.container), opts.files, output, options).await
+    })
+    .await?
+    .ok_or_else(|| anyhow::anyhow!(t!("commands.backup_produced_no_archive")))?;
+    let size = std::fs::metadata(&path)?.len();
+    Ok(BackupReport { path, size })
+}
+
+/// 将备份文件恢复到容器的挂载卷中
+///
+/// 写入卷路径通常需要 root 权限；权限不足时直接返回错误，不会尝试通过 sudo 重新运行
+pub async fn restore_backup(opts: RestoreOptions) -> Result<()> {
+    info!(?opts, "Starting library restore");
+    // 文件不存在时命令行会转而在备份目录中查找，库接口只恢复指定的文件
+    if !opts.file.is_file() {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.backup_file_not_a_file",
+                "path" = opts.file.to_string_lossy()
+            )
+        );
+    }
+    let file = utils::resolve_path(&opts.file)?;
+    let config = Config {
+        // 恢复前快照等写入备份目录的操作使用备份文件所在的目录
+        backup_dir: file.parent().map(PathBuf::from).unwrap_or_default(),
+        timeout_secs: opts.stop_timeout_secs,
+        restart: opts.restart,
+        ..call_config()
+    };
+    let options = restore::RestoreOptions {
+        volumes: opts.volumes,
+        verify_checksums: opts.verify_checksums,
+        ..Default::default()
+    };
+
+    Config::scope(config.clone(), async {
+        restore::require_privileges(&options)?;
+        let client = DockerClient::connect(&config).await?;
+        restore::restore(
+            &client,
+            Some(opts.container),
+            Some(file.to_string_lossy().to_string()),
+            None,
+            options,
+        )
+        .await
+    })
+    .await
+}
+
+/// 库调用使用的基础配置：非交互、自动确认、不显示进度
+fn call_config() -> Config {
+    Config {
+        interactive: false,
+        yes: true,
+        no_progress: true,
+        exclude: String::new(),
+        ..Config::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn restore_rejects_missing_file_without_searching() {
+        let opts = RestoreOptions::new("web", "/nonexistent/web.tar.xz");
+        let error = restore_backup(opts).await.unwrap_err();
+        assert!(error.to_string().contains("/nonexistent/web.tar.xz"));
+    }
+
+    #[tokio::test]
+    async fn scoped_config_replaces_global_config() {
+        let config = Config {
+            timeout_secs: 7,
+            ..call_config()
+        };
+        let inside = Config::scope(config, async { Config::global() }).await;
+        assert_eq!(inside.unwrap().timeout_secs, 7);
+        assert!(
+            Config::scope(call_config(), async {
+                !Config::global().unwrap().interactive
+            })
+            .await
+        );
+    }
+}
@@ -1,85 +1,873 @@
 use crate::{
-    commands::{MAPPING_FILE_NAME, container, prompt},
+    commands::{
+        CHECKSUMS_FILE_NAME, MAPPING_FILE_NAME,
+        container::{self, ComposeService},
+        interrupt,
+        lock::{ContainerLock, LockOperation},
+        prompt, restore, retention,
+    },
     config::Config,
-    docker::{BackupMapping, ContainerInfo, DockerClient, DockerClientInterface, VolumeInfo},
+    docker::{
+        BackupBase, BackupMapping, ContainerInfo, DockerClientInterface, LabeledContainer,
+        MAPPING_SCHEMA_VERSION, VolumeAlias, VolumeInfo,
+    },
     log_bail, log_println,
     utils::{self, create_timestamp_filename, ensure_dir_exists},
 };
 
 use anyhow::Result;
-use chrono::Local;
-use dialoguer::Input;
-use std::path::PathBuf;
+use chrono::{DateTime, Local};
+use dialoguer::{Input, MultiSelect};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tracing::{debug, info};
 
-pub async fn backup(
+/// 备份命令的附加选项
+#[derive(Debug, Clone, Default)]
+pub struct BackupOptions {
+    /// 容器仍在运行时直接中止备份，而不是自动停止容器
+    pub skip_if_running: bool,
+
+    /// 并行压缩的最大线程数，未设置时使用逻辑 CPU 数量
+    pub jobs: Option<usize>,
+
+    /// 每个数据流的 xz 压缩线程数，未设置时使用 `jobs` 中未用于并行压缩多个卷的线程
+    pub xz_threads: Option<u32>,
+
+    /// 多线程 xz 压缩时每个块的大小
+    pub xz_block_size: Option<u64>,
+
+    /// 备份成功后更新 `<container>_latest.tar.xz` 指向最新的备份文件
+    pub symlink_latest: bool,
+
+    /// 备份成功后按保留策略清理该容器的旧备份
+    pub retention: retention::RetentionPolicy,
+
+    /// 容器停止后、读取挂载卷前的等待秒数
+    pub settle_secs: u64,
+
+    /// 读取挂载卷前确认没有进程仍在占用其中的文件
+    pub snapshot_verify: bool,
+
+    /// 备份文件 (及新建的输出目录) 的所有者，未设置时通过 sudo 运行则使用调用 sudo 的用户
+    pub output_owner: Option<utils::FileOwner>,
+
+    /// 增量备份：只打包相对输出目录中该容器上一个备份新增或修改过的文件
+    pub incremental: bool,
+
+    /// 读取挂载卷期间冻结 (暂停) 容器而不是停止容器
+    pub freeze: bool,
+
+    /// 不停止容器，直接读取运行中容器的挂载卷
+    pub no_stop: bool,
+
+    /// 备份前报告占备份总大小比例不低于该值的子目录
+    pub warn_large_subtree: Option<f64>,
+
+    /// 备份文件名模板，未设置时使用 `<container>_<kind>_<timestamp>.tar.xz`
+    pub output_name: Option<OutputNameTemplate>,
+
+    /// 跳过修改时间早于该时长之前的文件
+    pub exclude_older_than: Option<Duration>,
+
+    /// 跳过大于该字节数的文件
+    pub exclude_larger_than: Option<u64>,
+
+    /// 在备份文件旁写出 JSON 格式的备份清单
+    pub manifest: bool,
+
+    /// 在归档中写入按卷的索引，只恢复部分卷时无需解压整个归档
+    pub index: bool,
+
+    /// 生成不依赖 rdbkp2、可以直接用 `tar xf` 解压的标准 tar 归档
+    pub output_format_compat: bool,
+
+    /// 归档的压缩格式，决定备份文件的扩展名
+    pub archive_format: utils::ArchiveFormat,
+
+    /// 在归档中记录目录条目与数字属主/权限，恢复时还原
+    pub preserve_ownership: bool,
+
+    /// 跟随符号链接打包其目标内容，默认将符号链接本身写入归档
+    pub follow_symlinks: bool,
+
+    /// 停止 (或冻结) 容器前在容器内执行的命令
+    pub pre_backup_exec: Option<String>,
+
+    /// 按 compose 项目与服务名查找要备份的容器，代替容器名称或 ID
+    pub compose_service: Option<ComposeService>,
+
+    /// 批量备份时同时备份的容器数，0 与 1 均表示逐个备份
+    pub concurrency: usize,
+}
+
+/// 指向最新备份的符号链接/指针文件的名称后缀
+const LATEST_SUFFIX: &str = "_latest";
+
+/// `--manifest` 在备份文件旁写出的 `<backup>.json`，监控等工具无需打开归档即可读取备份元数据
+#[derive(Debug, Serialize)]
+struct BackupManifest<'a> {
+    /// 归档中的 mapping 信息
+    mapping: &'a BackupMapping,
+    /// 写入归档的文件数
+    file_count: usize,
+    /// 写入归档的文件的未压缩总大小
+    uncompressed_bytes: u64,
+    /// 归档文件大小
+    compressed_bytes: u64,
+    /// 压缩比 (未压缩大小 / 归档文件大小)，保留三位小数；归档为空时为 `None`
+    compression_ratio: Option<f64>,
+    /// 压缩算法
+    compression: &'static str,
+    /// 归档文件的 SHA-256，与 `<backup>.sha256` 一致
+    sha256: &'a str,
+}
+
+/// 压缩比 (未压缩大小 / 归档文件大小)，任一大小为 0 时返回 `None`
+fn compression_ratio(uncompressed: u64, compressed: u64) -> Option<f64> {
+    (uncompressed > 0 && compressed > 0).then(|| uncompressed as f64 / compressed as f64)
+}
+
+/// 写出备份清单，键按字母顺序排列以保证输出稳定，返回清单文件路径
+fn write_backup_manifest(backup_path: &Path, manifest: &BackupManifest) -> Result<PathBuf> {
+    let manifest_path = utils::manifest_file_path(backup_path);
+    // serde_json::Value 的对象使用有序映射，经过它序列化后所有层级的键都是排序的
+    let value = serde_json::to_value(manifest)?;
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&value)? + "\n")?;
+    Ok(manifest_path)
+}
+
+/// `--output-name` 指定的备份文件名模板
+///
+/// 支持 `{container}`、`{date}` (`%Y%m%d`)、`{time}` (`%H%M%S`)、
+/// `{kind}` (`all`/`partial`/`incremental`) 与 `{ext}` 占位符，其中 `{time}` 是必需的，
+/// 否则同一天 (或任意时间) 的备份会覆盖之前的归档
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputNameTemplate(String);
+
+impl OutputNameTemplate {
+    const PLACEHOLDERS: [&str; 5] = ["container", "date", "time", "kind", "ext"];
+
+    /// 解析并校验模板：占位符必须已知且包含 `{time}`，且不能包含路径分隔符
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut rest = template;
+        while let Some(start) = rest.find(['{', '}']) {
+            let Some(tail) = rest[start..].strip_prefix('{') else {
+                anyhow::bail!(t!("commands.output_name_unbalanced", "template" = template));
+            };
+            let Some(end) = tail.find('}') else {
+                anyhow::bail!(t!("commands.output_name_unbalanced", "template" = template));
+            };
+            let placeholder = &tail[..end];
+            if !Self::PLACEHOLDERS.contains(&placeholder) {
+                anyhow::bail!(t!(
+                    "commands.output_name_unknown_placeholder",
+                    "placeholder" = placeholder,
+                    "supported" = Self::PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                ));
+            }
+            rest = &tail[end + 1..];
+        }
+        if !template.contains("{time}") {
+            anyhow::bail!(t!(
+                "commands.output_name_requires_time",
+                "template" = template
+            ));
+        }
+
+        let parsed = Self(template.to_string());
+        // 用示例值展开一次，提前发现会产生空文件名或路径穿越的模板
+        parsed.expand(
+            "container",
+            "all",
+            utils::ArchiveFormat::default().extension(),
+            Local::now(),
+        )?;
+        Ok(parsed)
+    }
+
+    /// 批量备份多个容器时模板必须包含 `{container}`，否则各容器的归档会写入同一个文件
+    fn ensure_per_container(&self, containers: usize) -> Result<()> {
+        if containers > 1 && !self.0.contains("{container}") {
+            anyhow::bail!(t!(
+                "commands.output_name_requires_container",
+                "template" = self.0
+            ));
+        }
+        Ok(())
+    }
+
+    /// 展开模板，未以扩展名 `ext` 结尾时补上
+    fn expand(
+        &self,
+        container: &str,
+        kind: &str,
+        ext: &str,
+        now: DateTime<Local>,
+    ) -> Result<String> {
+        let name = self
+            .0
+            .replace("{container}", container)
+            .replace("{date}", &now.format("%Y%m%d").to_string())
+            .replace("{time}", &now.format("%H%M%S").to_string())
+            .replace("{kind}", kind)
+            .replace("{ext}", ext);
+        let name = name.trim();
+
+        if name.is_empty()
+            || name.contains(['/', '\\'])
+            || name.trim_matches('.').is_empty()
+            || Path::new(name).components().count() != 1
+        {
+            anyhow::bail!(t!(
+                "commands.output_name_invalid",
+                "template" = self.0,
+                "name" = name
+            ));
+        }
+
+        if name.ends_with(&format!(".{}", ext)) {
+            Ok(name.to_string())
+        } else {
+            Ok(format!("{}.{}", name, ext))
+        }
+    }
+}
+
+impl BackupOptions {
+    /// 获取实际使用的并行压缩线程数
+    pub fn effective_jobs(&self) -> usize {
+        self.jobs
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1)
+            })
+            .max(1)
+    }
+
+    /// 批量备份中每个容器使用的选项：未指定 `--jobs` 时由同时备份的容器平分 CPU
+    fn for_concurrent_backups(&self) -> Self {
+        let mut options = self.clone();
+        options.concurrency = options.concurrency.max(1);
+        if options.jobs.is_none() && options.concurrency > 1 {
+            options.jobs = Some((options.effective_jobs() / options.concurrency).max(1));
+        }
+        options
+    }
+
+    /// 获取实际使用的备份文件所有者
+    pub fn effective_output_owner(&self) -> Option<utils::FileOwner> {
+        if cfg!(windows) {
+            return None;
+        }
+        self.output_owner.or_else(utils::FileOwner::sudo_invoker)
+    }
+}
+
+/// 备份单个容器，返回写入的备份文件路径，dry-run 时为 `None`
+pub async fn backup<T: DockerClientInterface>(
+    client: &T,
     container: Option<String>,
-    file: Option<String>,
+    files: Vec<String>,
     output: Option<String>,
-) -> Result<()> {
+    options: BackupOptions,
+) -> Result<Option<PathBuf>> {
     let config = Config::global()?;
     let interactive = config.interactive;
     let restart = config.restart;
-    let exclude_patterns = config.get_exclude_patterns();
 
     info!(
         ?container,
-        ?file,
+        ?files,
         ?output,
+        ?options,
         restart,
         interactive,
         "Starting backup operation"
     );
 
-    let client = DockerClient::global()?;
-    let container_info = container::select_container(&client, container, interactive).await?;
-
-    let output_dir = parse_output_dir(output, interactive, &container_info)?;
-    let (total_volumes, selected_volumes) =
-        select_volumes(file, interactive, &client, &container_info).await?;
+    let container_info = container::select_container(
+        client,
+        container,
+        options.compose_service.as_ref(),
+        interactive,
+    )
+    .await?;
 
-    perform_backup(
-        &client,
+    let output_dir = parse_output_dir(output, interactive, options.effective_output_owner())?;
+    backup_container(
+        client,
         &container_info,
         output_dir,
-        total_volumes,
-        selected_volumes,
-        &exclude_patterns,
+        files,
+        interactive,
+        &options,
     )
-    .await?;
+    .await
+}
 
-    if restart {
+/// 监听模式：先备份一次，之后每当挂载卷中的文件发生变更，等待 `debounce` 内不再有新的变更后再备份一次
+///
+/// 一直运行到 Ctrl-C；单次备份失败时只输出错误并继续监听。
+/// 备份期间 (如停止、重启容器时)、备份结束后 `debounce` 内产生的变更与输出目录中的变更不会触发新的备份
+pub async fn watch<T: DockerClientInterface>(
+    client: &T,
+    container: Option<String>,
+    files: Vec<String>,
+    output: Option<String>,
+    options: BackupOptions,
+    debounce: Duration,
+) -> Result<()> {
+    let config = Config::global()?;
+    let interactive = config.interactive;
+    info!(
+        ?container,
+        ?files,
+        ?output,
+        ?options,
+        ?debounce,
+        "Starting watch backup operation"
+    );
+
+    let container_info = container::select_container(
+        client,
+        container,
+        options.compose_service.as_ref(),
+        interactive,
+    )
+    .await?;
+    let output_dir = parse_output_dir(output, interactive, options.effective_output_owner())?;
+    // 只在开始时选择一次要备份的卷
+    let (total_volumes, selected_volumes) =
+        select_volumes(files, interactive, client, &container_info).await?;
+    if !(options.no_stop || options.freeze || config.restart) {
         log_println!(
-            "INFO",
+            "WARN",
             "{}",
             t!(
-                "commands.restarting_container",
+                "commands.watch_container_stays_stopped",
                 "name" = container_info.name
             )
         );
-        client.restart_container(&container_info.id).await?;
+    }
+
+    let (tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        match event {
+            // 只读访问不算变更
+            Ok(event) if !event.kind.is_access() => {
+                let _ = tx.send(event.paths);
+            }
+            Ok(_) => {}
+            Err(e) => debug!(?e, "File watcher error"),
+        }
+    })?;
+    for volume in &selected_volumes {
+        notify::Watcher::watch(
+            &mut watcher,
+            &volume.source,
+            notify::RecursiveMode::Recursive,
+        )?;
+    }
+
+    loop {
+        match backup_volumes(
+            client,
+            &container_info,
+            output_dir.clone(),
+            total_volumes,
+            selected_volumes.clone(),
+            &options,
+        )
+        .await
+        {
+            Ok(Some(path)) => log_println!(
+                "INFO",
+                "{}",
+                t!(
+                    "commands.watch_backup_written",
+                    "path" = path.to_string_lossy()
+                )
+            ),
+            // dry-run 只输出一次备份计划
+            Ok(None) => return Ok(()),
+            Err(e) => log_println!(
+                "ERROR",
+                "{}",
+                t!("commands.watch_backup_failed", "error" = e)
+            ),
+        }
+
+        // 丢弃备份期间以及备份结束后 `debounce` 内产生的变更
+        ignore_changes_for(&mut changes, debounce).await;
         log_println!(
             "INFO",
             "{}",
-            t!("commands.container_restarted", "name" = container_info.name)
+            t!(
+                "commands.watch_waiting",
+                "name" = container_info.name,
+                "paths" = selected_volumes
+                    .iter()
+                    .map(|v| v.source.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        );
+        if !wait_for_changes(&mut changes, &output_dir, debounce).await {
+            return Ok(());
+        }
+    }
+}
+
+/// 丢弃此前以及接下来 `grace` 内产生的变更
+///
+/// 重启后的服务在启动时写入卷 (如数据库的 WAL、pid 文件) 的变更在备份返回之后才陆续到达，
+/// 不忽略这些变更时 `--restart` 会让每次备份都触发下一次备份
+async fn ignore_changes_for(
+    changes: &mut tokio::sync::mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    grace: Duration,
+) {
+    let deadline = tokio::time::Instant::now() + grace;
+    while let Ok(Some(_)) = tokio::time::timeout_at(deadline, changes.recv()).await {}
+}
+
+/// 等待一次输出目录之外的变更，并在其后 `debounce` 内没有新的变更时返回 `true`；监听结束时返回 `false`
+async fn wait_for_changes(
+    changes: &mut tokio::sync::mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    output_dir: &Path,
+    debounce: Duration,
+) -> bool {
+    loop {
+        let Some(paths) = changes.recv().await else {
+            return false;
+        };
+        // 输出目录位于被监听的卷中时，写入备份文件本身不算变更
+        if paths.iter().any(|path| !path.starts_with(output_dir)) {
+            break;
+        }
+    }
+    loop {
+        match tokio::time::timeout(debounce, changes.recv()).await {
+            Err(_) => return true,
+            Ok(None) => return false,
+            Ok(Some(_)) => {}
+        }
+    }
+}
+
+/// 批量备份中单个容器的结果
+#[derive(Debug)]
+enum BulkBackupOutcome {
+    /// 已备份
+    BackedUp,
+    /// 已跳过，不算作失败
+    Skipped(SkipReason),
+    /// 备份失败
+    Failed(String),
+}
+
+/// 批量备份跳过容器的原因
+#[derive(Debug, PartialEq, Eq)]
+enum SkipReason {
+    /// 容器没有挂载卷 (仅 `--all`)
+    NoVolumes,
+    /// 指定了 `--skip-if-running` 而容器仍在运行，保存容器的状态
+    StillRunning(String),
+}
+
+impl BulkBackupOutcome {
+    /// 单个容器备份的结果，`--skip-if-running` 拒绝备份时记为跳过
+    fn from_result(result: Result<Self>) -> Self {
+        result.unwrap_or_else(|e| match e.downcast::<container::ContainerStillRunning>() {
+            Ok(refused) => Self::Skipped(SkipReason::StillRunning(refused.status)),
+            Err(e) => Self::Failed(e.to_string()),
+        })
+    }
+}
+
+/// 批量备份：将每个容器分别备份到输出目录中各自的归档文件
+///
+/// `all` 为真时备份所有容器 (交互模式下从列表中多选)，其中没有挂载卷的容器会被跳过；
+/// `labels` 非空时只备份同时带有这些标签的容器。
+/// 单个容器失败不影响其他容器，所有容器处理完后汇总输出结果，任一容器备份失败时返回错误
+pub async fn backup_many<T: DockerClientInterface>(
+    client: &T,
+    containers: Vec<String>,
+    all: bool,
+    labels: &[String],
+    output: Option<String>,
+    options: BackupOptions,
+) -> Result<()> {
+    let config = Config::global()?;
+    let interactive = config.interactive;
+    info!(
+        ?containers,
+        all,
+        ?labels,
+        ?output,
+        ?options,
+        "Starting bulk backup operation"
+    );
+
+    let targets = if !all {
+        containers
+    } else {
+        let candidates = container::containers_with_labels(client, labels).await?;
+        if candidates.is_empty() && !labels.is_empty() {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.no_containers_with_labels",
+                    "labels" = labels.join(", ")
+                )
+            );
+        }
+        let selected = if interactive {
+            prompt::select_containers_prompt(&candidates)?
+        } else {
+            candidates
+        };
+        selected.into_iter().map(|c| c.name).collect()
+    };
+    if targets.is_empty() {
+        log_bail!("ERROR", "{}", t!("commands.bulk_backup_no_containers"));
+    }
+    if let Some(template) = &options.output_name {
+        template.ensure_per_container(targets.len())?;
+    }
+
+    let output_dir = parse_output_dir(output, interactive, options.effective_output_owner())?;
+    let outcomes = backup_containers(client, &targets, all, &output_dir, &options).await;
+    finish_bulk_backup(&outcomes)
+}
+
+/// 备份 compose 项目中的所有服务容器，每个服务一个归档文件，文件名以项目名开头
+///
+/// 项目中的容器通过 `com.docker.compose.project` 标签查找，没有挂载卷的服务会被跳过
+pub async fn backup_compose<T: DockerClientInterface>(
+    client: &T,
+    compose_file: PathBuf,
+    output: Option<String>,
+    options: BackupOptions,
+) -> Result<()> {
+    let config = Config::global()?;
+    let project = utils::compose_project_name(&compose_file)?;
+    info!(
+        ?compose_file,
+        project,
+        ?output,
+        ?options,
+        "Starting compose project backup"
+    );
+
+    let containers = client
+        .find_containers_by_labels(&[format!("{}={project}", utils::COMPOSE_PROJECT_LABEL)])
+        .await?;
+    if containers.is_empty() {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.compose_no_containers", "project" = project)
         );
     }
+    if let Some(template) = &options.output_name {
+        template.ensure_per_container(containers.len())?;
+    }
+
+    let output_dir =
+        parse_output_dir(output, config.interactive, options.effective_output_owner())?;
+    let outcomes =
+        backup_compose_services(client, &project, &containers, &output_dir, &options).await;
+    finish_bulk_backup(&outcomes)
+}
+
+/// 备份 compose 项目中的每个容器 (按 `--concurrency` 同时备份多个)，结果以服务名标识
+///
+/// 同一服务有多个副本时改用容器名区分；未指定 `--output-name` 时文件名为
+/// `<项目>_<服务>_<类型>_<日期>_<时间>.tar.xz`
+async fn backup_compose_services<T: DockerClientInterface>(
+    client: &T,
+    project: &str,
+    containers: &[LabeledContainer],
+    output_dir: &Path,
+    options: &BackupOptions,
+) -> Vec<(String, BulkBackupOutcome)> {
+    let service_of = |container: &LabeledContainer| {
+        container
+            .labels
+            .get(utils::COMPOSE_SERVICE_LABEL)
+            .cloned()
+            .unwrap_or_else(|| container.info.name.clone())
+    };
+
+    let options = options.for_concurrent_backups();
+    let tasks = containers.iter().map(|container| {
+        let service = service_of(container);
+        let replicas = containers
+            .iter()
+            .filter(|c| service_of(c) == service)
+            .count();
+        let name = if replicas > 1 {
+            container.info.name.clone()
+        } else {
+            service
+        };
+        let options = &options;
+
+        async move {
+            let result = async {
+                if client
+                    .get_container_volumes(&container.info.id)
+                    .await?
+                    .is_empty()
+                {
+                    return Ok(BulkBackupOutcome::Skipped(SkipReason::NoVolumes));
+                }
+                let mut options = options.clone();
+                if options.output_name.is_none() {
+                    options.output_name = Some(OutputNameTemplate::parse(&format!(
+                        "{project}_{name}_{{kind}}_{{date}}_{{time}}.{{ext}}"
+                    ))?);
+                }
+                backup_container(
+                    client,
+                    &container.info,
+                    output_dir.to_path_buf(),
+                    vec![],
+                    false,
+                    &options,
+                )
+                .await?;
+                Ok::<_, anyhow::Error>(BulkBackupOutcome::BackedUp)
+            }
+            .await;
+
+            let outcome = BulkBackupOutcome::from_result(result);
+            (name, outcome)
+        }
+    });
+
+    run_concurrently(options.concurrency, tasks).await
+}
 
+/// 输出批量备份的汇总结果，任一容器备份失败时返回错误
+fn finish_bulk_backup(outcomes: &[(String, BulkBackupOutcome)]) -> Result<()> {
+    print_bulk_backup_summary(outcomes);
+
+    let failed = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, BulkBackupOutcome::Failed(_)))
+        .count();
+    if failed > 0 {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.bulk_backup_failed", "failed" = failed)
+        );
+    }
     Ok(())
 }
 
+/// 备份每个容器的所有挂载卷 (按 `--concurrency` 同时备份多个)，单个容器失败不影响其他容器
+///
+/// 容器名无法唯一匹配时不再提示选择，直接记为失败；结果按 `targets` 的顺序排列
+async fn backup_containers<T: DockerClientInterface>(
+    client: &T,
+    targets: &[String],
+    skip_without_volumes: bool,
+    output_dir: &Path,
+    options: &BackupOptions,
+) -> Vec<(String, BulkBackupOutcome)> {
+    // 先依次解析所有容器，同一容器被多次指定 (如名称与 ID) 时只备份一次
+    let mut resolved = Vec::new();
+    let mut seen = Vec::new();
+    for target in targets {
+        match container::select_container(client, Some(target.clone()), None, false).await {
+            Ok(container_info) if seen.contains(&container_info.id) => {}
+            Ok(container_info) => {
+                seen.push(container_info.id.clone());
+                resolved.push(Ok(container_info));
+            }
+            Err(e) => resolved.push(Err((target.clone(), e.to_string()))),
+        }
+    }
+
+    let options = options.for_concurrent_backups();
+    let tasks = resolved.into_iter().map(|resolved| {
+        let options = &options;
+        async move {
+            let container_info = match resolved {
+                Ok(container_info) => container_info,
+                Err((target, error)) => return (target, BulkBackupOutcome::Failed(error)),
+            };
+            let result = async {
+                if skip_without_volumes
+                    && client
+                        .get_container_volumes(&container_info.id)
+                        .await?
+                        .is_empty()
+                {
+                    return Ok(BulkBackupOutcome::Skipped(SkipReason::NoVolumes));
+                }
+                backup_container(
+                    client,
+                    &container_info,
+                    output_dir.to_path_buf(),
+                    vec![],
+                    false,
+                    options,
+                )
+                .await?;
+                Ok::<_, anyhow::Error>(BulkBackupOutcome::BackedUp)
+            }
+            .await;
+
+            let outcome = BulkBackupOutcome::from_result(result);
+            (container_info.name, outcome)
+        }
+    });
+
+    run_concurrently(options.concurrency, tasks).await
+}
+
+/// 在阻塞线程上运行 `f` 并沿用当前任务的配置，压缩、写入与校验归档时
+/// `--concurrency` 同时备份的其他容器不会被阻塞
+async fn run_blocking<R: Send + 'static>(
+    f: impl FnOnce() -> Result<R> + Send + 'static,
+) -> Result<R> {
+    let config = Config::global().ok();
+    tokio::task::spawn_blocking(move || match config {
+        Some(config) => Config::sync_scope(config, f),
+        None => f(),
+    })
+    .await?
+}
+
+/// 同时运行至多 `concurrency` 个任务，结果按任务的顺序返回
+async fn run_concurrently<F: Future>(
+    concurrency: usize,
+    tasks: impl IntoIterator<Item = F>,
+) -> Vec<F::Output> {
+    let semaphore = tokio::sync::Semaphore::new(concurrency.max(1));
+    let semaphore = &semaphore;
+    let all = futures_util::future::join_all(tasks.into_iter().map(|task| async move {
+        // 信号量不会被关闭，获取许可不会失败
+        let _permit = semaphore.acquire().await.ok();
+        task.await
+    }));
+
+    // 批量任务中不弹出确认提示 (停止容器、清理旧备份)，与非交互模式一样直接执行，
+    // 否则提示会与其他任务的输出交错，并阻塞同时进行的备份
+    // 多个进度条会互相覆盖，同时备份多个容器时改为按行输出进度日志
+    match Config::global() {
+        Ok(mut config) => {
+            config.interactive = false;
+            config.log_progress |= concurrency > 1;
+            Config::scope(config, all).await
+        }
+        Err(_) => all.await,
+    }
+}
+
+fn print_bulk_backup_summary(outcomes: &[(String, BulkBackupOutcome)]) {
+    log_println!("INFO", "{}", t!("commands.bulk_backup_summary"));
+    for (name, outcome) in outcomes {
+        let line = match outcome {
+            BulkBackupOutcome::BackedUp => t!("commands.bulk_backup_backed_up", "name" = name),
+            BulkBackupOutcome::Skipped(SkipReason::NoVolumes) => {
+                t!("commands.bulk_backup_skipped", "name" = name)
+            }
+            BulkBackupOutcome::Skipped(SkipReason::StillRunning(status)) => t!(
+                "commands.bulk_backup_skipped_running",
+                "name" = name,
+                "status" = status
+            ),
+            BulkBackupOutcome::Failed(error) => t!(
+                "commands.bulk_backup_container_failed",
+                "name" = name,
+                "error" = error
+            ),
+        };
+        println!("{}", line);
+    }
+}
+
+/// 备份单个已选定的容器，`interactive` 为真时提示选择要备份的卷
+///
+/// 返回写入的备份文件路径，dry-run 时为 `None`
+async fn backup_container<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    output_dir: PathBuf,
+    files: Vec<String>,
+    interactive: bool,
+    options: &BackupOptions,
+) -> Result<Option<PathBuf>> {
+    let (total_volumes, selected_volumes) =
+        select_volumes(files, interactive, client, container_info).await?;
+    backup_volumes(
+        client,
+        container_info,
+        output_dir,
+        total_volumes,
+        selected_volumes,
+        options,
+    )
+    .await
+}
+
+/// 备份容器中已选定的卷，按配置在备份完成后重启容器
+async fn backup_volumes<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    output_dir: PathBuf,
+    total_volumes: usize,
+    selected_volumes: Vec<VolumeInfo>,
+    options: &BackupOptions,
+) -> Result<Option<PathBuf>> {
+    // 配置未初始化 (如测试中) 时使用默认配置
+    let config = Config::global().unwrap_or_default();
+    let exclude_patterns = config.get_exclude_patterns()?;
+
+    // `--no-stop` 时容器一直在运行，无需重启
+    let restart = config.restart && !options.no_stop && !Config::is_dry_run();
+    let _restart_on_interrupt =
+        restart.then(|| interrupt::RestartOnInterrupt::track(container_info));
+    let result = perform_backup(
+        client,
+        container_info,
+        output_dir,
+        total_volumes,
+        selected_volumes,
+        &exclude_patterns
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>(),
+        options,
+    )
+    .await;
+
+    // 备份失败时同样重启，避免服务因一次失败的备份而一直处于停止状态
+    if restart {
+        container::restart_after(client, container_info, result, config.wait_healthy).await
+    } else {
+        result
+    }
+}
+
 fn parse_output_dir(
     output: Option<String>,
     interactive: bool,
-    container_info: &ContainerInfo,
+    owner: Option<utils::FileOwner>,
 ) -> Result<PathBuf> {
-    debug!(container_name = ?container_info.name, "Resolving output directory");
+    debug!(?output, "Resolving output directory");
     let config = Config::global()?;
 
     if let Some(output) = output {
-        let output_dir = PathBuf::from(output);
-        ensure_dir_exists(&output_dir)?;
-        return Ok(utils::absolute_canonicalize_path(&output_dir)?);
+        return prepare_output_dir(&PathBuf::from(output), owner);
     }
 
     if interactive {
@@ -90,47 +878,70 @@ fn parse_output_dir(
             .allow_empty(false)
             .interact_text()?;
 
-        let output_dir = PathBuf::from(input);
-        ensure_dir_exists(&output_dir)?;
-        return Ok(utils::absolute_canonicalize_path(&output_dir)?);
+        return prepare_output_dir(&PathBuf::from(input), owner);
+    }
+
+    Ok(utils::resolve_path(&config.backup_dir)?)
+}
+
+/// 创建并解析输出目录，dry-run 模式下不创建目录
+///
+/// 目录由本次运行新建时，将其所有者修改为 `owner`
+fn prepare_output_dir(output_dir: &Path, owner: Option<utils::FileOwner>) -> Result<PathBuf> {
+    let exists = output_dir.exists();
+    if Config::is_dry_run() && !exists {
+        return Ok(utils::absolute_simplified_path(output_dir)?);
+    }
+    ensure_dir_exists(output_dir)?;
+    let output_dir = utils::resolve_path(output_dir)?;
+    if !exists {
+        apply_output_owner(&[output_dir.as_path()], owner);
     }
+    Ok(output_dir)
+}
 
-    Ok(utils::absolute_canonicalize_path(&config.backup_dir)?)
+/// 修改备份产物的所有者，失败时只给出警告，不影响已完成的备份
+///
+/// 返回是否修改成功
+fn apply_output_owner(paths: &[&Path], owner: Option<utils::FileOwner>) -> bool {
+    let Some(owner) = owner else {
+        return false;
+    };
+    // 逐个尝试所有路径，不因某个路径失败而跳过其余路径
+    paths
+        .iter()
+        .fold(true, |ok, path| match utils::chown_path(path, &owner) {
+            Ok(()) => {
+                debug!(?path, %owner, "Changed output owner");
+                ok
+            }
+            Err(e) => {
+                log_println!(
+                    "WARN",
+                    "{}",
+                    t!(
+                        "commands.output_owner_failed",
+                        "path" = path.to_string_lossy(),
+                        "owner" = owner,
+                        "error" = e
+                    )
+                );
+                false
+            }
+        })
 }
 
 async fn select_volumes<T: DockerClientInterface>(
-    file: Option<String>,
+    files: Vec<String>,
     interactive: bool,
     client: &T,
     container_info: &ContainerInfo,
 ) -> Result<(usize, Vec<VolumeInfo>)> {
-    if let Some(file) = file {
-        let file_path = PathBuf::from(file);
-        let file_path = utils::absolute_canonicalize_path(&file_path)?;
-        if !file_path.exists() {
-            log_bail!(
-                "ERROR",
-                "{}",
-                t!(
-                    "commands.path_does_not_exist",
-                    "path" = file_path.to_string_lossy()
-                )
-            );
-        }
-
-        let volume = VolumeInfo {
-            source: file_path.clone(),
-            destination: file_path.clone(),
-            name: file_path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string(),
-        };
-
-        debug!(volume = ?volume, "Single path backup configured");
-        return Ok((1, vec![volume]));
-    }
+    if !files.is_empty() {
+        let volumes = path_volumes(&files)?;
+        debug!(volumes = ?volumes, "Path backup configured");
+        return Ok((volumes.len(), volumes));
+    }
 
     debug!(container_id = ?container_info.id, "Fetching volumes for container");
     let volumes = client.get_container_volumes(&container_info.id).await?;
@@ -153,146 +964,1371 @@ async fn select_volumes<T: DockerClientInterface>(
         volumes
     };
 
-    if selected_volumes.is_empty() {
-        log_bail!("ERROR", "{}", t!("commands.no_volumes_selected_for_backup"));
+    if selected_volumes.is_empty() {
+        log_bail!("ERROR", "{}", t!("commands.no_volumes_selected_for_backup"));
+    }
+
+    Ok((total_volumes, selected_volumes))
+}
+
+/// 为 `--file` 指定的每个路径构建一个卷
+///
+/// 卷名即路径的最后一级，也是压缩包内的顶层目录名，因此不允许重名
+fn path_volumes(files: &[String]) -> Result<Vec<VolumeInfo>> {
+    let mut volumes: Vec<VolumeInfo> = Vec::with_capacity(files.len());
+    for file in files {
+        let file_path = utils::resolve_path(&PathBuf::from(file))?;
+        if !file_path.exists() {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.path_does_not_exist",
+                    "path" = file_path.to_string_lossy()
+                )
+            );
+        }
+
+        let name = file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if let Some(existing) = volumes.iter().find(|v| v.name == name) {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.duplicate_path_name",
+                    "name" = name,
+                    "first" = existing.source.to_string_lossy(),
+                    "second" = file_path.to_string_lossy()
+                )
+            );
+        }
+
+        volumes.push(VolumeInfo {
+            source: file_path.clone(),
+            destination: file_path,
+            name,
+            volume_name: None,
+        });
+    }
+    Ok(volumes)
+}
+
+async fn perform_backup<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    output_dir: PathBuf,
+    total_volumes_count: usize,
+    selected_volumes: Vec<VolumeInfo>,
+    exclude_patterns: &[&str],
+    options: &BackupOptions,
+) -> Result<Option<PathBuf>> {
+    let exclude = utils::ExcludeMatcher::new(exclude_patterns)?;
+    let filtered_volumes: Vec<_> = selected_volumes
+        .into_iter()
+        .filter(|v| !exclude.is_excluded(&v.source, &v.source))
+        .collect();
+
+    if filtered_volumes.is_empty() {
+        log_bail!("ERROR", "{}", t!("commands.no_volumes_for_backup"));
+    }
+
+    let (filtered_volumes, aliases) = dedup_volumes(filtered_volumes);
+
+    let mut mapping = BackupMapping {
+        schema_version: MAPPING_SCHEMA_VERSION,
+        container_name: container_info.name.clone(),
+        container_id: container_info.id.clone(),
+        volumes: filtered_volumes.clone(),
+        backup_time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        payload_sha256: None,
+        aliases,
+        base: None,
+        files: Default::default(),
+        modified_after: None,
+        preserve_ownership: options.preserve_ownership,
+    };
+
+    let base = if options.incremental {
+        find_incremental_base(&output_dir, &container_info.name)?
+    } else {
+        None
+    };
+    if let Some((base_path, base_mapping)) = &base {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.incremental_base",
+                "path" = base_path.to_string_lossy()
+            )
+        );
+        mapping.base = Some(BackupBase {
+            file: base_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            backup_time: base_mapping.backup_time.clone(),
+            payload_sha256: base_mapping.payload_sha256.clone(),
+        });
+    }
+
+    let middle_name = if base.is_some() {
+        "incremental"
+    } else if total_volumes_count > filtered_volumes.len() {
+        "partial"
+    } else {
+        "all"
+    };
+    let extension = options.archive_format.extension();
+    let backup_filename = match &options.output_name {
+        Some(template) => {
+            template.expand(&container_info.name, middle_name, extension, Local::now())?
+        }
+        None => create_timestamp_filename(
+            &format!("{}_{}", container_info.name, middle_name),
+            &format!(".{}", extension),
+        ),
+    };
+    let backup_path = output_dir.join(&backup_filename);
+
+    let sources = filtered_volumes
+        .iter()
+        .map(|v| v.source.as_path())
+        .collect::<Vec<_>>();
+
+    let extra_excludes = match options.warn_large_subtree {
+        Some(ratio) => review_large_subtrees(&sources, &exclude, options.follow_symlinks, ratio)?,
+        None => Vec::new(),
+    };
+    let exclude_patterns = exclude_patterns
+        .iter()
+        .copied()
+        .chain(extra_excludes.iter().map(String::as_str))
+        .collect::<Vec<_>>();
+    let exclude_patterns = exclude_patterns.as_slice();
+    let exclude = if extra_excludes.is_empty() {
+        exclude
+    } else {
+        utils::ExcludeMatcher::new(exclude_patterns)?
+    };
+    let cutoff = options
+        .exclude_older_than
+        .map(|age| SystemTime::now() - age);
+    if let Some(cutoff) = cutoff {
+        let cutoff = DateTime::<Local>::from(cutoff)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.excluding_files_older_than", "time" = cutoff)
+        );
+        mapping.modified_after = Some(cutoff);
+    }
+    let exclude = exclude
+        .with_modified_before(cutoff)
+        .with_larger_than(options.exclude_larger_than);
+
+    if Config::is_dry_run() {
+        print_backup_plan(&backup_path, &filtered_volumes, exclude_patterns);
+    }
+
+    // 备份期间禁止其他进程恢复该容器的卷
+    let _lock = (!Config::is_dry_run())
+        .then(|| ContainerLock::acquire(container_info, LockOperation::Backup))
+        .transpose()?;
+
+    // clap 保证 --pre-backup-exec 与 --skip-if-running 不会同时出现
+    if let Some(command) = options.pre_backup_exec.as_deref() {
+        container::run_pre_backup_exec(client, container_info, command).await?;
+    }
+
+    let frozen = if options.skip_if_running {
+        container::ensure_container_not_running(client, container_info).await?;
+        false
+    } else if options.freeze {
+        container::freeze_container(client, container_info).await?
+    } else if options.no_stop {
+        container::warn_if_hot_backup(client, container_info).await?;
+        false
+    } else {
+        container::ensure_container_stopped(client, container_info).await?;
+        false
+    };
+
+    if Config::is_dry_run() {
+        log_println!("INFO", "{}", t!("commands.dry_run_no_changes"));
+        return Ok(None);
+    }
+
+    // 中断时同样需要解冻，正常解冻后取消登记
+    let unfreeze_on_interrupt =
+        frozen.then(|| interrupt::UnfreezeOnInterrupt::track(container_info));
+
+    // 只在读取挂载卷期间保持冻结，无论读取是否成功都要解冻
+    let previous = base.map(|(_, m)| m.files).unwrap_or_default();
+    let parts = async {
+        container::wait_for_volumes_settled(&sources, options.settle_secs, options.snapshot_verify)
+            .await?;
+        let compress_options = utils::CompressOptions {
+            compat: options.output_format_compat,
+            ownership: options.preserve_ownership,
+            follow_symlinks: options.follow_symlinks,
+            xz: utils::XzOptions {
+                threads: options.xz_threads,
+                block_size: options.xz_block_size,
+                ..Default::default()
+            },
+            io_limit: Config::io_limit(),
+            format: options.archive_format,
+        };
+        let sources = sources.iter().map(|s| s.to_path_buf()).collect::<Vec<_>>();
+        let (output_dir, exclude, jobs) = (
+            output_dir.clone(),
+            exclude.clone(),
+            options.effective_jobs(),
+        );
+        run_blocking(move || {
+            let parts = utils::compress_parts(
+                &sources,
+                &output_dir,
+                &exclude,
+                jobs,
+                &previous,
+                compress_options,
+            )?;
+            let payload_sha256 = parts.sha256()?;
+            Ok((parts, payload_sha256))
+        })
+        .await
+    }
+    .await;
+    if frozen {
+        container::unfreeze_container(client, container_info).await?;
+    }
+    drop(unfreeze_on_interrupt);
+    let (parts, payload_sha256) = parts?;
+    if mapping.base.is_some() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.incremental_files",
+                "changed" = utils::format_number(parts.items_count() as u64),
+                "total" = utils::format_number(parts.stamps().len() as u64)
+            )
+        );
+    }
+    mapping.payload_sha256 = Some(payload_sha256);
+    mapping.files = parts.stamps().clone();
+
+    let mapping_content = toml::to_string(&mapping)?;
+    let checksums_content = toml::to_string(parts.manifest())?;
+    let mut memory_files = vec![
+        (MAPPING_FILE_NAME, mapping_content),
+        (CHECKSUMS_FILE_NAME, checksums_content),
+    ];
+    if options.index {
+        // 每个源压缩为一个数据流，流的顶层目录即卷名
+        let index = utils::ArchiveIndex::from_lengths(
+            filtered_volumes
+                .iter()
+                .map(|v| v.name.as_str())
+                .zip(parts.part_lengths()?),
+        );
+        memory_files.push((utils::INDEX_FILE_NAME, toml::to_string(&index)?));
+    }
+    // 中断时删除未写完的归档及其附属文件
+    let mut partial = vec![utils::PartialFile::track(&backup_path)];
+    let archive_path = backup_path.clone();
+    let (parts, checksum) = run_blocking(move || {
+        parts.write_archive(
+            &archive_path,
+            &memory_files
+                .iter()
+                .map(|(name, content)| (*name, content.as_str()))
+                .collect::<Vec<_>>(),
+        )?;
+        let checksum = utils::sha256_file(&archive_path)?;
+        Ok((parts, checksum))
+    })
+    .await?;
+    let checksum_path = utils::write_checksum_file(&backup_path, &checksum)?;
+    partial.push(utils::PartialFile::track(&checksum_path));
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.checksum_written",
+            "checksum" = checksum,
+            "path" = checksum_path.to_string_lossy()
+        )
+    );
+
+    let archive_bytes = std::fs::metadata(&backup_path)?.len();
+    let ratio = compression_ratio(parts.total_bytes(), archive_bytes);
+    let volumes_count = utils::format_number(filtered_volumes.len() as u64);
+    let ratio_text = ratio.map(utils::format_ratio).unwrap_or_else(|| "-".into());
+    // 被 --exclude-larger-than 跳过的文件同样记录在汇总中，避免只在压缩开始时提示一次
+    let summary = match parts.oversized() {
+        (0, _) => t!(
+            "commands.backup_volumes_completed",
+            "volumes_count" = volumes_count,
+            "backup_path" = backup_path.to_string_lossy(),
+            "uncompressed" = utils::format_bytes(parts.total_bytes()),
+            "size" = utils::format_bytes(archive_bytes),
+            "ratio" = ratio_text
+        ),
+        (skipped, skipped_bytes) => t!(
+            "commands.backup_volumes_completed_skipped_large",
+            "volumes_count" = volumes_count,
+            "backup_path" = backup_path.to_string_lossy(),
+            "uncompressed" = utils::format_bytes(parts.total_bytes()),
+            "size" = utils::format_bytes(archive_bytes),
+            "ratio" = ratio_text,
+            "skipped" = utils::format_number(skipped),
+            "skipped_size" = utils::format_bytes(skipped_bytes)
+        ),
+    };
+    log_println!("INFO", "{}", summary);
+
+    let manifest_path = if options.manifest {
+        let manifest = BackupManifest {
+            mapping: &mapping,
+            file_count: parts.items_count(),
+            uncompressed_bytes: parts.total_bytes(),
+            compressed_bytes: archive_bytes,
+            compression_ratio: ratio.map(|r| (r * 1000.0).round() / 1000.0),
+            compression: options.archive_format.compression(),
+            sha256: &checksum,
+        };
+        let manifest_path = write_backup_manifest(&backup_path, &manifest)?;
+        partial.push(utils::PartialFile::track(&manifest_path));
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.backup_manifest_written",
+                "path" = manifest_path.to_string_lossy()
+            )
+        );
+        Some(manifest_path)
+    } else {
+        None
+    };
+
+    drop(partial);
+
+    let output_owner = options.effective_output_owner();
+    let mut owned_files = vec![backup_path.as_path(), checksum_path.as_path()];
+    owned_files.extend(manifest_path.as_deref());
+    if apply_output_owner(&owned_files, output_owner)
+        && let Some(owner) = output_owner
+    {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.output_owner_changed",
+                "path" = backup_path.to_string_lossy(),
+                "owner" = owner
+            )
+        );
+    }
+
+    if options.symlink_latest {
+        let latest_path = update_latest_pointer(
+            &output_dir,
+            &container_info.name,
+            &backup_path,
+            options.archive_format,
+        )?;
+        apply_output_owner(&[&latest_path], output_owner);
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.latest_pointer_updated",
+                "latest" = latest_path.to_string_lossy(),
+                "backup_path" = backup_path.to_string_lossy()
+            )
+        );
+    }
+
+    retention::prune_backups(
+        &output_dir,
+        &container_info.name,
+        &backup_path,
+        &options.retention,
+    )?;
+
+    Ok(Some(backup_path))
+}
+
+/// 报告占备份总大小比例不低于 `ratio` 的子目录
+///
+/// 仅作提示；交互模式下允许选择其中的目录，返回本次备份额外排除的路径
+fn review_large_subtrees(
+    sources: &[&Path],
+    exclude: &utils::ExcludeMatcher,
+    follow_symlinks: bool,
+    ratio: f64,
+) -> Result<Vec<String>> {
+    let (total, subtrees) = utils::find_large_subtrees(sources, exclude, follow_symlinks, ratio)?;
+    if subtrees.is_empty() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.no_large_subtrees",
+                "percent" = format!("{:.0}", ratio * 100.0)
+            )
+        );
+        return Ok(Vec::new());
+    }
+
+    let items = subtrees
+        .iter()
+        .map(|s| {
+            format!(
+                "{} ({}, {:.0}%)",
+                s.path.display(),
+                utils::format_bytes(s.size),
+                s.size as f64 * 100.0 / total as f64
+            )
+        })
+        .collect::<Vec<_>>();
+    log_println!(
+        "WARN",
+        "{}",
+        t!(
+            "commands.large_subtrees_found",
+            "percent" = format!("{:.0}", ratio * 100.0),
+            "total" = utils::format_bytes(total)
+        )
+    );
+    for item in &items {
+        log_println!("WARN", "  - {}", item);
+    }
+
+    if !Config::global()?.interactive || Config::is_dry_run() {
+        return Ok(Vec::new());
+    }
+    let selected = MultiSelect::new()
+        .with_prompt(t!("prompt.select_subtrees_to_exclude"))
+        .items(&items)
+        .interact()?;
+    Ok(selected
+        .into_iter()
+        .map(|i| subtrees[i].path.to_string_lossy().to_string())
+        .collect())
+}
+
+/// 查找增量备份的基础备份，即输出目录中该容器最新的备份
+///
+/// 找不到备份或最新的备份没有记录文件修改时间 (旧版本创建) 时返回 `None`，此时进行完整备份
+fn find_incremental_base(
+    output_dir: &Path,
+    container_name: &str,
+) -> Result<Option<(PathBuf, BackupMapping)>> {
+    let latest = retention::collect_backups(output_dir, container_name)?
+        .into_iter()
+        .max_by_key(|(_, timestamp)| *timestamp);
+
+    let base = latest.and_then(|(path, _)| {
+        let content = utils::read_file_from_archive(&path, MAPPING_FILE_NAME).ok()?;
+        let mapping = restore::parse_mapping(&content).ok()?;
+        (!mapping.files.is_empty()).then_some((path, mapping))
+    });
+
+    if base.is_none() {
+        log_println!(
+            "WARN",
+            "{}",
+            t!("commands.incremental_no_base", "name" = container_name)
+        );
+    }
+    Ok(base)
+}
+
+/// dry-run 模式下打印将要创建的备份文件、备份的卷以及排除规则
+fn print_backup_plan(backup_path: &Path, volumes: &[VolumeInfo], exclude_patterns: &[&str]) {
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.dry_run_backup_archive",
+            "path" = backup_path.to_string_lossy()
+        )
+    );
+    for volume in volumes {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.dry_run_volume",
+                "name" = volume.name,
+                "source" = volume.source.to_string_lossy(),
+                "destination" = volume.destination.to_string_lossy()
+            )
+        );
+    }
+    let exclude = exclude_patterns
+        .iter()
+        .filter(|p| !p.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    log_println!(
+        "INFO",
+        "{}",
+        t!("commands.dry_run_exclude", "patterns" = exclude)
+    );
+}
+
+/// 合并宿主机路径相同的卷，避免同一份数据被重复打包
+///
+/// 保留第一个卷，其余的记录为别名，恢复时可以据此还原到各自的路径
+fn dedup_volumes(volumes: Vec<VolumeInfo>) -> (Vec<VolumeInfo>, Vec<VolumeAlias>) {
+    let mut unique: Vec<(PathBuf, VolumeInfo)> = Vec::new();
+    let mut aliases = Vec::new();
+
+    for volume in volumes {
+        let canonical = utils::resolve_path(&volume.source).unwrap_or(volume.source.clone());
+        if let Some((_, kept)) = unique.iter().find(|(source, _)| *source == canonical) {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.duplicate_volume_source_merged",
+                    "name" = volume.name,
+                    "alias_of" = kept.name,
+                    "source" = canonical.to_string_lossy()
+                )
+            );
+            aliases.push(VolumeAlias {
+                alias_of: kept.name.clone(),
+                volume,
+            });
+            continue;
+        }
+        unique.push((canonical, volume));
+    }
+
+    (unique.into_iter().map(|(_, v)| v).collect(), aliases)
+}
+
+/// 判断路径是否为 `--symlink-latest` 创建的最新备份指针
+pub(crate) fn is_latest_pointer(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy())
+        .is_some_and(|n| {
+            utils::ArchiveFormat::ALL
+                .iter()
+                .map(|format| format.extension())
+                .chain(["txt"])
+                .any(|ext| n.ends_with(&format!("{}.{}", LATEST_SUFFIX, ext)))
+        })
+}
+
+/// 更新输出目录中指向最新备份的符号链接 `<container>_latest.<扩展名>`
+///
+/// 链接使用相对路径，移动整个备份目录后依然有效；已存在的旧链接 (包括其他压缩格式的) 会先被删除
+#[cfg(unix)]
+fn update_latest_pointer(
+    output_dir: &Path,
+    container_name: &str,
+    backup_path: &Path,
+    format: utils::ArchiveFormat,
+) -> Result<PathBuf> {
+    let pointer_path = |format: utils::ArchiveFormat| {
+        output_dir.join(format!(
+            "{}{}.{}",
+            container_name,
+            LATEST_SUFFIX,
+            format.extension()
+        ))
+    };
+    for stale in utils::ArchiveFormat::ALL.map(pointer_path) {
+        if stale.symlink_metadata().is_ok() {
+            debug!(?stale, "Removing stale latest symlink");
+            std::fs::remove_file(&stale)?;
+        }
+    }
+    let latest_path = pointer_path(format);
+
+    let target = backup_path.file_name().unwrap_or_default();
+    std::os::unix::fs::symlink(target, &latest_path)?;
+    Ok(latest_path)
+}
+
+/// Windows 下创建符号链接需要额外权限，因此写入记录最新备份文件名的指针文件 `<container>_latest.txt`
+#[cfg(not(unix))]
+fn update_latest_pointer(
+    output_dir: &Path,
+    container_name: &str,
+    backup_path: &Path,
+    _format: utils::ArchiveFormat,
+) -> Result<PathBuf> {
+    let latest_path = output_dir.join(format!("{}{}.txt", container_name, LATEST_SUFFIX));
+    let target = backup_path.file_name().unwrap_or_default();
+    std::fs::write(&latest_path, target.to_string_lossy().as_bytes())?;
+    Ok(latest_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::MockDockerClientInterface;
+    use assert_fs::TempDir;
+    use chrono::TimeZone;
+    use std::fs;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    async fn setup_test_volumes() -> Result<(TempDir, Vec<VolumeInfo>)> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+
+        let volumes = vec![
+            ("vol1", "test1.txt", "content1"),
+            ("vol2", "test2.txt", "content2"),
+        ];
+
+        let mut infos = Vec::new();
+        for (name, file, content) in volumes {
+            let vol_path = base_path.join(name);
+            fs::create_dir(&vol_path)?;
+            fs::write(vol_path.join(file), content)?;
+            infos.push(VolumeInfo {
+                name: name.to_string(),
+                source: vol_path.clone(),
+                destination: vol_path,
+                volume_name: None,
+            });
+        }
+
+        Ok((temp_dir, infos))
+    }
+
+    #[tokio::test]
+    async fn creates_backup_archive() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &BackupOptions::default(),
+        )
+        .await?;
+
+        let files = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 2);
+
+        let archive = files.iter().find(|f| !utils::is_checksum_file(f)).unwrap();
+        let checksum = utils::read_checksum_file(archive)?;
+        assert_eq!(checksum, Some(utils::sha256_file(archive)?));
+
+        let mapping: BackupMapping =
+            toml::from_str(&utils::read_file_from_archive(archive, MAPPING_FILE_NAME)?)?;
+        assert_eq!(
+            mapping.payload_sha256,
+            Some(utils::payload_sha256(archive)?)
+        );
+
+        let manifest: utils::ChecksumManifest = toml::from_str(&utils::read_file_from_archive(
+            archive,
+            CHECKSUMS_FILE_NAME,
+        )?)?;
+        assert!(!manifest.files.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn waits_for_changes_outside_output_dir_to_settle() {
+        let output_dir = Path::new("/backup");
+        let debounce = Duration::from_millis(50);
+        let (tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+
+        // 只有输出目录中的变更时不触发备份
+        tx.send(vec![output_dir.join("db_all.tar.xz")]).unwrap();
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(200),
+            wait_for_changes(&mut changes, output_dir, debounce),
+        );
+        assert!(waiting.await.is_err());
+
+        let sender = tx.clone();
+        let burst = tokio::spawn(async move {
+            for i in 0..3 {
+                sender
+                    .send(vec![PathBuf::from(format!("/data/{i}"))])
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+        let started = std::time::Instant::now();
+        assert!(wait_for_changes(&mut changes, output_dir, debounce).await);
+        // 连续的变更合并为一次，在最后一次变更之后才返回
+        assert!(started.elapsed() >= Duration::from_millis(40) + debounce);
+        burst.await.unwrap();
+
+        drop(tx);
+        assert!(!wait_for_changes(&mut changes, output_dir, debounce).await);
+    }
+
+    #[tokio::test]
+    async fn ignores_changes_right_after_a_backup() {
+        let output_dir = Path::new("/backup");
+        let grace = Duration::from_millis(100);
+        let (tx, mut changes) = tokio::sync::mpsc::unbounded_channel();
+
+        // 备份期间的变更与重启后服务启动时的写入都在宽限期内被丢弃
+        tx.send(vec![PathBuf::from("/data/during-backup")]).unwrap();
+        let sender = tx.clone();
+        let startup = tokio::spawn(async move {
+            for name in ["wal", "pid"] {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                sender
+                    .send(vec![PathBuf::from("/data").join(name)])
+                    .unwrap();
+            }
+        });
+        let started = std::time::Instant::now();
+        ignore_changes_for(&mut changes, grace).await;
+        assert!(started.elapsed() >= grace);
+        startup.await.unwrap();
+        assert!(changes.try_recv().is_err());
+
+        // 宽限期之后的变更照常触发备份
+        tx.send(vec![PathBuf::from("/data/new")]).unwrap();
+        assert!(wait_for_changes(&mut changes, output_dir, Duration::from_millis(10)).await);
+    }
+
+    #[tokio::test]
+    async fn runs_at_most_concurrency_tasks_at_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let tasks = (0..5).map(|i| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        });
+
+        assert_eq!(run_concurrently(2, tasks).await, [0, 1, 2, 3, 4]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        let options = BackupOptions {
+            concurrency: 4,
+            jobs: None,
+            ..Default::default()
+        }
+        .for_concurrent_backups();
+        assert!(options.jobs.is_some_and(|jobs| jobs >= 1));
+        let options = BackupOptions {
+            concurrency: 0,
+            jobs: Some(8),
+            ..Default::default()
+        }
+        .for_concurrent_backups();
+        assert_eq!((options.concurrency, options.jobs), (1, Some(8)));
+    }
+
+    #[tokio::test]
+    async fn backs_up_each_container_and_collects_failures() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = |name: &str| ContainerInfo {
+            id: format!("{name}-id"),
+            name: name.into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        client
+            .expect_find_containers()
+            .returning(move |name| match name {
+                "web" | "web-id" => Ok(vec![container("web")]),
+                "cache" => Ok(vec![container("cache")]),
+                _ => Ok(vec![]),
+            });
+        client.expect_get_container_volumes().returning(move |id| {
+            Ok(if id == "web-id" {
+                volumes.clone()
+            } else {
+                vec![]
+            })
+        });
+
+        let targets = ["web", "missing", "cache", "web-id"].map(String::from);
+        let outcomes = backup_containers(
+            &client,
+            &targets,
+            true,
+            output_dir.path(),
+            &BackupOptions::default(),
+        )
+        .await;
+
+        let names = outcomes.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["web", "missing", "cache"]);
+        assert!(matches!(outcomes[0].1, BulkBackupOutcome::BackedUp));
+        assert!(matches!(outcomes[1].1, BulkBackupOutcome::Failed(_)));
+        assert!(matches!(
+            outcomes[2].1,
+            BulkBackupOutcome::Skipped(SkipReason::NoVolumes)
+        ));
+
+        let archives = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|f| !utils::is_sidecar_file(f))
+            .collect::<Vec<_>>();
+        assert_eq!(archives.len(), 1);
+        assert!(
+            archives[0]
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("web_")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_if_running_skips_running_containers_in_bulk() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = |name: &str| ContainerInfo {
+            id: format!("{name}-id"),
+            name: name.into(),
+            ..Default::default()
+        };
+
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_find_containers()
+            .returning(move |name| Ok(vec![container(name)]));
+        client
+            .expect_get_container_volumes()
+            .returning(move |_| Ok(volumes.clone()));
+        client
+            .expect_get_container_status()
+            .returning(|id| Ok(if id == "web-id" { "running" } else { "exited" }.into()));
+        client.expect_stop_container().times(0);
+
+        let options = BackupOptions {
+            skip_if_running: true,
+            ..Default::default()
+        };
+        let targets = ["web", "db"].map(String::from);
+        let outcomes =
+            backup_containers(&client, &targets, false, output_dir.path(), &options).await;
+
+        assert!(matches!(
+            &outcomes[0].1,
+            BulkBackupOutcome::Skipped(SkipReason::StillRunning(status)) if status == "running"
+        ));
+        assert!(matches!(outcomes[1].1, BulkBackupOutcome::BackedUp));
+        // 跳过的容器不会使批量备份失败
+        assert!(finish_bulk_backup(&outcomes).is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn bulk_backup_stops_and_prunes_without_prompting() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let old_backup = output_dir.path().join("web_all_20240101_000000.tar.xz");
+        std::fs::write(&old_backup, "old")?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut client = MockDockerClientInterface::new();
+        client.expect_find_containers().returning(|name| {
+            Ok(vec![ContainerInfo {
+                id: format!("{name}-id"),
+                name: name.into(),
+                ..Default::default()
+            }])
+        });
+        client
+            .expect_get_container_volumes()
+            .returning(move |_| Ok(volumes.clone()));
+        let status = stopped.clone();
+        client.expect_get_container_status().returning(move |_| {
+            Ok(if status.load(Ordering::SeqCst) {
+                "exited"
+            } else {
+                "running"
+            }
+            .into())
+        });
+        let status = stopped.clone();
+        client.expect_stop_container().times(1).returning(move |_| {
+            status.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        client.expect_get_stop_timeout_secs().returning(|| 10);
+        client.expect_restart_container().returning(|_| Ok(()));
+
+        let options = BackupOptions {
+            retention: retention::RetentionPolicy {
+                keep_last: Some(1),
+                keep_days: None,
+            },
+            ..Default::default()
+        };
+        // 交互模式下单独备份时会确认停止容器与删除旧备份，批量备份中不能弹出提示
+        let config = Config {
+            interactive: true,
+            yes: false,
+            ..Config::default()
+        };
+        let targets = ["web".to_string()];
+        let outcomes = Config::scope(
+            config,
+            backup_containers(&client, &targets, false, output_dir.path(), &options),
+        )
+        .await;
+
+        assert!(matches!(outcomes[0].1, BulkBackupOutcome::BackedUp));
+        assert!(stopped.load(Ordering::SeqCst));
+        assert!(!old_backup.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backs_up_compose_services_with_project_prefix() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = |name: &str, service: &str| LabeledContainer {
+            info: ContainerInfo {
+                id: format!("{name}-id"),
+                name: name.into(),
+                status: "exited".into(),
+                ..Default::default()
+            },
+            labels: [
+                (utils::COMPOSE_PROJECT_LABEL.to_string(), "blog".to_string()),
+                (
+                    utils::COMPOSE_SERVICE_LABEL.to_string(),
+                    service.to_string(),
+                ),
+            ]
+            .into(),
+        };
+        let containers = [
+            container("blog-db-1", "db"),
+            container("blog-web-1", "web"),
+            container("blog-web-2", "web"),
+        ];
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        client.expect_get_container_volumes().returning(move |id| {
+            Ok(if id == "blog-db-1-id" {
+                volumes.clone()
+            } else {
+                vec![]
+            })
+        });
+
+        let outcomes = backup_compose_services(
+            &client,
+            "blog",
+            &containers,
+            output_dir.path(),
+            &BackupOptions::default(),
+        )
+        .await;
+
+        let names = outcomes.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["db", "blog-web-1", "blog-web-2"]);
+        assert!(matches!(outcomes[0].1, BulkBackupOutcome::BackedUp));
+        assert!(matches!(
+            outcomes[1].1,
+            BulkBackupOutcome::Skipped(SkipReason::NoVolumes)
+        ));
+
+        let archives = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|f| !utils::is_sidecar_file(Path::new(f)))
+            .collect::<Vec<_>>();
+        assert_eq!(archives.len(), 1);
+        assert!(archives[0].starts_with("blog_db_all_"), "{}", archives[0]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn indexed_archive_reads_only_selected_volumes() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+
+        let options = BackupOptions {
+            index: true,
+            ..Default::default()
+        };
+        perform_backup(
+            &MockDockerClientInterface::with_defaults(),
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &options,
+        )
+        .await?;
+
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| !utils::is_sidecar_file(f))
+            .unwrap();
+        let index = utils::ArchiveIndex::read(&archive).unwrap();
+        let names = index
+            .volumes
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["vol1", "vol2"]);
+        assert_eq!(index.volumes[1].offset, index.volumes[0].length);
+
+        let (_, len) = utils::open_archive_volumes(&archive, &["vol2"])?;
+        assert!(len < fs::metadata(&archive)?.len());
+
+        let restored = TempDir::new()?;
+        utils::unpack_archive_filtered(&archive, &restored.to_path_buf(), &["vol2"], |_| true)?;
+        assert_eq!(
+            fs::read_to_string(restored.join("vol2/test2.txt"))?,
+            "content2"
+        );
+        assert!(!restored.join("vol1").exists());
+        assert!(restored.join(MAPPING_FILE_NAME).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn writes_json_manifest_next_to_archive() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+
+        let options = BackupOptions {
+            manifest: true,
+            ..Default::default()
+        };
+        perform_backup(
+            &MockDockerClientInterface::with_defaults(),
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &options,
+        )
+        .await?;
+
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| !utils::is_sidecar_file(f))
+            .unwrap();
+        let content = fs::read_to_string(utils::manifest_file_path(&archive))?;
+        let manifest: serde_json::Value = serde_json::from_str(&content)?;
+        let keys = manifest.as_object().unwrap().keys().collect::<Vec<_>>();
+        assert!(keys.is_sorted());
+        assert_eq!(manifest["compression"], "xz");
+        assert_eq!(manifest["sha256"], utils::sha256_file(&archive)?);
+        assert_eq!(manifest["compressed_bytes"], fs::metadata(&archive)?.len());
+        assert_eq!(manifest["file_count"], 2);
+        assert_eq!(manifest["uncompressed_bytes"], 16);
+        assert!(manifest["compression_ratio"].as_f64().unwrap() > 0.0);
+        assert_eq!(manifest["mapping"]["container_name"], "container");
+        Ok(())
     }
 
-    Ok((total_volumes, selected_volumes))
-}
-
-async fn perform_backup<T: DockerClientInterface>(
-    client: &T,
-    container_info: &ContainerInfo,
-    output_dir: PathBuf,
-    total_volumes_count: usize,
-    selected_volumes: Vec<VolumeInfo>,
-    exclude_patterns: &[&str],
-) -> Result<()> {
-    let filtered_volumes: Vec<_> = selected_volumes
-        .into_iter()
-        .filter(|v| {
-            !exclude_patterns
-                .iter()
-                .any(|pattern| v.source.to_string_lossy().contains(pattern))
-        })
-        .collect();
+    #[tokio::test]
+    async fn writes_gzip_archive_with_tar_gz_extension() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
 
-    if filtered_volumes.is_empty() {
-        log_bail!("ERROR", "{}", t!("commands.no_volumes_for_backup"));
-    }
+        let options = BackupOptions {
+            archive_format: utils::ArchiveFormat::TarGz,
+            index: true,
+            manifest: true,
+            ..Default::default()
+        };
+        perform_backup(
+            &MockDockerClientInterface::with_defaults(),
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &options,
+        )
+        .await?;
 
-    let mapping = BackupMapping {
-        container_name: container_info.name.clone(),
-        container_id: container_info.id.clone(),
-        volumes: filtered_volumes.clone(),
-        backup_time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    };
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| !utils::is_sidecar_file(f))
+            .unwrap();
+        assert!(archive.to_string_lossy().ends_with(".tar.gz"));
+        assert!(fs::read(&archive)?.starts_with(&[0x1F, 0x8B]));
 
-    let mapping_content = toml::to_string(&mapping)?;
-    let middle_name = if total_volumes_count > filtered_volumes.len() {
-        "partial"
-    } else {
-        "all"
-    };
-    let backup_filename = create_timestamp_filename(
-        &format!("{}_{}", container_info.name, middle_name),
-        ".tar.xz",
-    );
-    let backup_path = output_dir.join(&backup_filename);
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(utils::manifest_file_path(&archive))?)?;
+        assert_eq!(manifest["compression"], "gzip");
+        let mapping = utils::read_file_from_archive(&archive, MAPPING_FILE_NAME)?;
+        let mapping: BackupMapping = toml::from_str(&mapping)?;
+        assert_eq!(
+            mapping.payload_sha256,
+            Some(utils::payload_sha256(&archive)?)
+        );
 
-    let sources = filtered_volumes
-        .iter()
-        .map(|v| v.source.as_path())
-        .collect::<Vec<_>>();
+        let restored = TempDir::new()?;
+        utils::unpack_archive_filtered(&archive, &restored.to_path_buf(), &["vol2"], |_| true)?;
+        assert_eq!(
+            fs::read_to_string(restored.join("vol2/test2.txt"))?,
+            "content2"
+        );
+        assert!(!restored.join("vol1").exists());
+        Ok(())
+    }
 
-    container::ensure_container_stopped(client, container_info).await?;
+    #[tokio::test]
+    async fn incremental_backup_only_packs_changed_files() -> Result<()> {
+        let (dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+        let client = MockDockerClientInterface::with_defaults();
 
-    utils::compress_with_memory_file(
-        &sources,
-        &backup_path,
-        &[(MAPPING_FILE_NAME, mapping_content.as_str())],
-        exclude_patterns,
-    )?;
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes.clone(),
+            &[],
+            &BackupOptions::default(),
+        )
+        .await?;
+        // 备份文件名精确到秒，将完整备份改名为更早的时间以免与增量备份冲突
+        let full = output_dir
+            .path()
+            .join("container_all_20000101_000000.tar.xz");
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| !utils::is_checksum_file(f))
+            .unwrap();
+        fs::rename(&archive, &full)?;
+        fs::rename(
+            utils::checksum_file_path(&archive),
+            utils::checksum_file_path(&full),
+        )?;
 
-    log_println!(
-        "INFO",
-        "{}",
-        t!(
-            "commands.backup_volumes_completed",
-            "volumes_count" = filtered_volumes.len(),
-            "backup_path" = backup_path.to_string_lossy()
+        fs::write(dir.path().join("vol2/test2.txt"), "changed content")?;
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &BackupOptions {
+                incremental: true,
+                ..Default::default()
+            },
         )
-    );
+        .await?;
 
-    Ok(())
-}
+        let incremental = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| f.to_string_lossy().contains("_incremental_") && !utils::is_checksum_file(f))
+            .unwrap();
+        let mapping: BackupMapping = toml::from_str(&utils::read_file_from_archive(
+            &incremental,
+            MAPPING_FILE_NAME,
+        )?)?;
+        let base = mapping.base.unwrap();
+        assert_eq!(base.file, "container_all_20000101_000000.tar.xz");
+        assert_eq!(mapping.files.len(), 2);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use assert_fs::TempDir;
-    use std::fs;
+        let scan = utils::scan_archive(&incremental)?;
+        assert_eq!(scan.dirs.get("vol1").map(|d| d.files).unwrap_or(0), 0);
+        assert_eq!(scan.dirs["vol2"].files, 1);
+        Ok(())
+    }
 
-    async fn setup_test_volumes() -> Result<(TempDir, Vec<VolumeInfo>)> {
+    #[tokio::test]
+    async fn backs_up_multiple_paths_into_one_archive() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let base_path = temp_dir.path();
+        for (dir, content) in [("a/data", "first"), ("b/data", "second"), ("logs", "third")] {
+            fs::create_dir_all(base_path.join(dir))?;
+            fs::write(base_path.join(dir).join("file.txt"), content)?;
+        }
+        let paths = |names: &[&str]| {
+            names
+                .iter()
+                .map(|p| base_path.join(p).to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
 
-        let volumes = vec![
-            ("vol1", "test1.txt", "content1"),
-            ("vol2", "test2.txt", "content2"),
-        ];
+        // 同名目录在压缩包内会冲突
+        assert!(path_volumes(&paths(&["a/data", "b/data"])).is_err());
+        assert!(path_volumes(&paths(&["missing"])).is_err());
 
-        let mut infos = Vec::new();
-        for (name, file, content) in volumes {
-            let vol_path = base_path.join(name);
-            fs::create_dir(&vol_path)?;
-            fs::write(vol_path.join(file), content)?;
-            infos.push(VolumeInfo {
-                name: name.to_string(),
-                source: vol_path.clone(),
-                destination: vol_path,
-            });
-        }
+        let volumes = path_volumes(&paths(&["a/data", "logs"]))?;
 
-        DockerClient::init(10)?;
-        Ok((temp_dir, infos))
+        let client = MockDockerClientInterface::with_defaults();
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+        let output_dir = TempDir::new()?;
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &BackupOptions::default(),
+        )
+        .await?;
+
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|f| !utils::is_checksum_file(f))
+            .unwrap();
+        assert!(archive.to_string_lossy().contains("_all_"));
+        let scan = utils::scan_archive(&archive)?;
+        for name in ["data", "logs"] {
+            assert_eq!(scan.dirs[name].files, 1);
+        }
+        Ok(())
     }
 
     #[tokio::test]
-    async fn creates_backup_archive() -> Result<()> {
+    async fn freeze_unpauses_container_even_when_backup_fails() -> Result<()> {
         let (_dir, volumes) = setup_test_volumes().await?;
-        let output_dir = TempDir::new()?;
-
-        DockerClient::init(10)?;
-
         let container = ContainerInfo {
             id: "id".into(),
             name: "container".into(),
             status: "running".into(),
+            ..Default::default()
+        };
+        let options = BackupOptions {
+            freeze: true,
+            ..Default::default()
         };
 
-        let mut client = DockerClient::global()?;
-        client
-            .expect_get_container_status()
-            .returning(|_| Ok("exited".to_string()));
+        let frozen_client = || {
+            let mut client = MockDockerClientInterface::new();
+            client
+                .expect_get_container_status()
+                .returning(|_| Ok("running".to_string()));
+            client.expect_stop_container().times(0);
+            client
+                .expect_pause_container()
+                .times(1)
+                .returning(|_| Ok(()));
+            client
+                .expect_unpause_container()
+                .times(1)
+                .returning(|_| Ok(()));
+            client
+        };
 
+        let output_dir = TempDir::new()?;
         perform_backup(
-            &client,
+            &frozen_client(),
             &container,
             output_dir.path().to_path_buf(),
             volumes.len(),
-            volumes,
+            volumes.clone(),
             &[],
+            &options,
         )
         .await?;
 
-        assert_eq!(
-            fs::read_dir(output_dir.path())?
-                .filter_map(|e| e.ok())
-                .count(),
-            1
-        );
+        // 输出目录不可写导致读取卷失败时同样要解冻
+        let not_a_dir = output_dir.path().join("file");
+        fs::write(&not_a_dir, "")?;
+        let result = perform_backup(
+            &frozen_client(),
+            &container,
+            not_a_dir.join("sub"),
+            volumes.len(),
+            volumes,
+            &[],
+            &options,
+        )
+        .await;
+        assert!(result.is_err());
         Ok(())
     }
 
@@ -310,17 +2346,18 @@ mod tests {
             name: "vol1".into(),
             source: base_path.join("vol1"),
             destination: base_path.join("vol1"),
+            volume_name: None,
         }];
 
         let container = ContainerInfo {
             id: "id".into(),
             name: "container".into(),
             status: "running".into(),
+            ..Default::default()
         };
 
         let output_dir = TempDir::new()?;
-        DockerClient::init(10)?;
-        let mut client = DockerClient::global()?;
+        let mut client = MockDockerClientInterface::with_defaults();
         client
             .expect_get_container_status()
             .returning(|_| Ok("exited".to_string()));
@@ -332,16 +2369,242 @@ mod tests {
             volumes.len(),
             volumes,
             &[".git", "node_modules"],
+            &BackupOptions::default(),
         )
         .await?;
 
-        let backup_file = fs::read_dir(output_dir.path())?.next().unwrap()?.path();
+        let backup_file = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| !utils::is_checksum_file(p))
+            .unwrap();
         let restore_dir = TempDir::new()?;
         let restore_path = restore_dir.path().to_path_buf();
-        crate::utils::unpack_archive(&backup_file, &restore_path)?;
+        crate::utils::unpack_archive_filtered(&backup_file, &restore_path, &[], |_| true)?;
 
         assert!(restore_dir.path().join("vol1/test.txt").exists());
         assert!(!restore_dir.path().join("vol1/node_modules").exists());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn excludes_files_older_than_cutoff() -> Result<()> {
+        let (_temp_dir, volumes) = setup_test_volumes().await?;
+        let old = volumes[0].source.join("old.log");
+        fs::write(&old, "stale")?;
+        fs::File::options()
+            .write(true)
+            .open(&old)?
+            .set_modified(SystemTime::now() - Duration::from_secs(10 * 86400))?;
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+        let output_dir = TempDir::new()?;
+        let client = MockDockerClientInterface::with_defaults();
+        let options = BackupOptions {
+            exclude_older_than: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes.clone(),
+            &[],
+            &options,
+        )
+        .await?;
+
+        let backup_file = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| !utils::is_checksum_file(p))
+            .unwrap();
+        let mapping: BackupMapping = toml::from_str(&utils::read_file_from_archive(
+            &backup_file,
+            MAPPING_FILE_NAME,
+        )?)?;
+        assert!(mapping.modified_after.is_some());
+
+        let restore_dir = TempDir::new()?;
+        utils::unpack_archive_filtered(
+            &backup_file,
+            &restore_dir.path().to_path_buf(),
+            &[],
+            |_| true,
+        )?;
+        assert!(restore_dir.path().join("vol1/test1.txt").exists());
+        assert!(!restore_dir.path().join("vol1/old.log").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn expands_output_name_template() -> Result<()> {
+        let now = Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let template = OutputNameTemplate::parse("{container}-{kind}-{date}T{time}.{ext}")?;
+        assert_eq!(
+            template.expand("web", "all", "tar.xz", now)?,
+            "web-all-20240102T030405.tar.xz"
+        );
+        // 模板未包含扩展名时自动补上
+        let template = OutputNameTemplate::parse("{container}-nightly-{time}")?;
+        assert_eq!(
+            template.expand("web", "partial", "tar.xz", now)?,
+            "web-nightly-030405.tar.xz"
+        );
+        assert_eq!(
+            template.expand("web", "partial", "tar.gz", now)?,
+            "web-nightly-030405.tar.gz"
+        );
+
+        // 同时备份多个容器时，不含 {container} 的模板会让所有归档写入同一个文件
+        assert!(template.ensure_per_container(3).is_ok());
+        let shared = OutputNameTemplate::parse("nightly-{date}{time}")?;
+        assert!(shared.ensure_per_container(1).is_ok());
+        assert!(shared.ensure_per_container(2).is_err());
+
+        for invalid in [
+            "",
+            "  ",
+            "..",
+            "../{container}",
+            "a/b",
+            "{unknown}",
+            "{container",
+            "x}",
+            // 不含 {time} 的模板会让之后的备份覆盖之前的归档
+            "{container}-nightly",
+            "{container}-{date}",
+        ] {
+            assert!(
+                OutputNameTemplate::parse(invalid).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_if_running_refuses_running_container() -> Result<()> {
+        let (_dir, volumes) = setup_test_volumes().await?;
+        let output_dir = TempDir::new()?;
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        let mut client = crate::docker::MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("running".to_string()));
+        client.expect_stop_container().times(0);
+
+        let options = BackupOptions {
+            skip_if_running: true,
+            ..Default::default()
+        };
+        let result = perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &options,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(output_dir.path())?.count(), 0);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn latest_symlink_points_to_newest_backup() -> Result<()> {
+        let output_dir = TempDir::new()?;
+        let first = output_dir
+            .path()
+            .join("container_all_20240101_000000.tar.xz");
+        let second = output_dir
+            .path()
+            .join("container_all_20240102_000000.tar.xz");
+        fs::write(&first, "first")?;
+        fs::write(&second, "second")?;
+
+        let xz = utils::ArchiveFormat::TarXz;
+        let latest = update_latest_pointer(output_dir.path(), "container", &first, xz)?;
+        assert_eq!(fs::read_to_string(&latest)?, "first");
+
+        // 旧链接应被替换为指向新的备份文件
+        update_latest_pointer(output_dir.path(), "container", &second, xz)?;
+        assert_eq!(fs::read_to_string(&latest)?, "second");
+        assert!(is_latest_pointer(&latest));
+        assert!(!is_latest_pointer(&second));
+
+        // 改用其他压缩格式后，旧格式的链接被删除
+        let gz = utils::ArchiveFormat::TarGz;
+        let gz_latest = update_latest_pointer(output_dir.path(), "container", &first, gz)?;
+        assert!(gz_latest.ends_with("container_latest.tar.gz"));
+        assert!(is_latest_pointer(&gz_latest));
+        assert!(latest.symlink_metadata().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn merges_mounts_sharing_a_source() -> Result<()> {
+        let (dir, mut volumes) = setup_test_volumes().await?;
+        volumes.push(VolumeInfo {
+            name: "vol1-alias".into(),
+            source: dir.path().join("vol1"),
+            destination: PathBuf::from("/app/other"),
+            volume_name: None,
+        });
+        let output_dir = TempDir::new()?;
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+        let mut client = crate::docker::MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+
+        perform_backup(
+            &client,
+            &container,
+            output_dir.path().to_path_buf(),
+            volumes.len(),
+            volumes,
+            &[],
+            &BackupOptions::default(),
+        )
+        .await?;
+
+        let archive = fs::read_dir(output_dir.path())?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| !utils::is_checksum_file(p))
+            .unwrap();
+        let mapping: BackupMapping =
+            toml::from_str(&utils::read_file_from_archive(&archive, MAPPING_FILE_NAME)?)?;
+
+        assert_eq!(mapping.volumes.len(), 2);
+        assert_eq!(mapping.aliases.len(), 1);
+        assert_eq!(mapping.aliases[0].alias_of, "vol1");
+        assert_eq!(mapping.aliases[0].volume.name, "vol1-alias");
+        Ok(())
+    }
 }
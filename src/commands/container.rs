@@ -1,19 +1,33 @@
 use crate::{
-    commands::prompt,
-    docker::{ContainerInfo, DockerClient, DockerClientInterface},
-    log_bail, log_println,
+    commands::{OutputFormat, prompt},
+    config::Config,
+    docker::{ContainerInfo, DockerClientInterface},
+    log_bail, log_println, utils,
 };
 
 use anyhow::Result;
-use dialoguer::{Input, Select};
+use dialoguer::{Confirm, Input, Select};
+use std::path::Path;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
-pub async fn list_containers() -> Result<()> {
-    debug!("Listing Docker containers");
-    let client = DockerClient::global()?;
-    let containers = client.list_containers().await?;
+pub async fn list_containers<T: DockerClientInterface>(
+    client: &T,
+    format: OutputFormat,
+    labels: &[String],
+) -> Result<()> {
+    debug!(?labels, "Listing Docker containers");
+    let containers = containers_with_labels(client, labels).await?;
+
+    if format == OutputFormat::Json {
+        println!("{}", containers_to_json(&containers)?);
+        info!(
+            container_count = containers.len(),
+            "Container list printed as JSON"
+        );
+        return Ok(());
+    }
 
     if containers.is_empty() {
         println!("{}", t!("commands.no_containers_available"));
@@ -25,11 +39,91 @@ pub async fn list_containers() -> Result<()> {
     Ok(())
 }
 
+/// 列出满足所有标签条件的容器，`labels` 为空时列出全部容器
+pub async fn containers_with_labels<T: DockerClientInterface>(
+    client: &T,
+    labels: &[String],
+) -> Result<Vec<ContainerInfo>> {
+    if labels.is_empty() {
+        return client.list_containers().await;
+    }
+    Ok(client
+        .find_containers_by_labels(labels)
+        .await?
+        .into_iter()
+        .map(|container| container.info)
+        .collect())
+}
+
+fn containers_to_json(containers: &[ContainerInfo]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(containers)?)
+}
+
+/// 在读取挂载卷前等待文件系统稳定
+///
+/// 先等待 `settle_secs` 秒，若启用 `verify` 则进一步确认没有进程仍打开卷中的文件。
+/// 无法执行该检查 (非 Linux 或无 `/proc`) 时仅给出警告并继续。
+pub async fn wait_for_volumes_settled(
+    sources: &[&Path],
+    settle_secs: u64,
+    verify: bool,
+) -> Result<()> {
+    if settle_secs > 0 {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.waiting_for_volumes_settle",
+                "seconds" = settle_secs
+            )
+        );
+        sleep(Duration::from_secs(settle_secs)).await;
+    }
+
+    if !verify {
+        return Ok(());
+    }
+
+    let Some(handles) = utils::find_open_handles(sources) else {
+        log_println!("WARN", "{}", t!("commands.open_handles_check_unavailable"));
+        return Ok(());
+    };
+
+    if let Some(handle) = handles.first() {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.volume_still_in_use",
+                "count" = handles.len(),
+                "pid" = handle.pid,
+                "path" = handle.path.to_string_lossy()
+            )
+        );
+    }
+
+    debug!("No open handles found under volume sources");
+    Ok(())
+}
+
+/// docker compose 项目中的一个服务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposeService {
+    pub project: String,
+    pub service: String,
+}
+
+/// 确定要操作的容器：指定了 `service` 时按 compose 标签查找，否则按名称或 ID 查找，
+/// 两者都未指定时在交互模式下提示选择
 pub async fn select_container<T: DockerClientInterface>(
     client: &T,
     container: Option<String>,
+    service: Option<&ComposeService>,
     interactive: bool,
 ) -> Result<ContainerInfo> {
+    if let Some(service) = service {
+        return resolve_compose_service(client, service, interactive).await;
+    }
     if container.is_none() && interactive {
         return prompt::select_container_prompt(client).await;
     }
@@ -54,6 +148,15 @@ pub async fn select_container<T: DockerClientInterface>(
         );
     }
 
+    find_container_by_name(client, container_input, interactive).await
+}
+
+/// 按名称或 ID 查找容器，没有或有多个匹配时在交互模式下提示
+async fn find_container_by_name<T: DockerClientInterface>(
+    client: &T,
+    container_input: String,
+    interactive: bool,
+) -> Result<ContainerInfo> {
     let matches = client.find_containers(&container_input).await?;
     match matches.len() {
         0 => handle_no_matches(client, container_input, interactive).await,
@@ -62,9 +165,52 @@ pub async fn select_container<T: DockerClientInterface>(
     }
 }
 
+/// 按 `com.docker.compose.project` 与 `com.docker.compose.service` 标签查找服务的容器
+///
+/// 没有容器带有这些标签时 (如容器不是由 compose 创建的)，回退到按 `<项目>-<服务>` 模糊匹配容器名称；
+/// 服务有多个副本时与名称匹配到多个容器的处理相同
+async fn resolve_compose_service<T: DockerClientInterface>(
+    client: &T,
+    service: &ComposeService,
+    interactive: bool,
+) -> Result<ContainerInfo> {
+    let labels = [
+        format!("{}={}", utils::COMPOSE_PROJECT_LABEL, service.project),
+        format!("{}={}", utils::COMPOSE_SERVICE_LABEL, service.service),
+    ];
+    let mut matches: Vec<ContainerInfo> = client
+        .find_containers_by_labels(&labels)
+        .await?
+        .into_iter()
+        .map(|container| container.info)
+        .collect();
+    match matches.len() {
+        0 => {
+            let name = format!("{}-{}", service.project, service.service);
+            debug!(
+                ?service,
+                name, "No container with compose labels, falling back to name matching"
+            );
+            find_container_by_name(client, name, interactive).await
+        }
+        1 => Ok(matches.remove(0)),
+        _ => handle_multiple_matches(matches, interactive),
+    }
+}
+
+/// 确保容器已停止，运行中的容器在交互模式下 (未指定 `--yes`) 需要先确认才会被停止
 pub async fn ensure_container_stopped<T: DockerClientInterface>(
     client: &T,
     container_info: &ContainerInfo,
+) -> Result<()> {
+    ensure_container_stopped_with(client, container_info, confirm_stop_prompt).await
+}
+
+/// 停止运行中的容器前调用 `confirm` 确认，参数为容器信息与停止超时秒数，返回 `false` 时中止操作
+async fn ensure_container_stopped_with<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    confirm: impl FnOnce(&ContainerInfo, u64) -> Result<bool>,
 ) -> Result<()> {
     let status = client.get_container_status(&container_info.id).await?;
     if !is_running(&status) {
@@ -76,6 +222,26 @@ pub async fn ensure_container_stopped<T: DockerClientInterface>(
         return Ok(());
     }
 
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.dry_run_would_stop", "name" = container_info.name)
+        );
+        return Ok(());
+    }
+
+    if !confirm(container_info, client.get_stop_timeout_secs())? {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.stop_container_declined",
+                "name" = container_info.name
+            )
+        );
+    }
+
     log_println!(
         "INFO",
         "{}",
@@ -88,6 +254,312 @@ pub async fn ensure_container_stopped<T: DockerClientInterface>(
     stop_container_with_timeout(client, container_info).await
 }
 
+/// 在停止容器前于容器内执行 `command` (通过 `sh -c`)，如数据库的刷盘命令
+///
+/// 容器未运行时无法执行，给出警告后跳过；命令以非零状态退出时中止备份，指定 `--yes` 时只给出警告
+pub async fn run_pre_backup_exec<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    command: &str,
+) -> Result<()> {
+    let status = client.get_container_status(&container_info.id).await?;
+    if !is_running(&status) {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.pre_backup_exec_skipped",
+                "name" = container_info.name
+            )
+        );
+        return Ok(());
+    }
+
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.dry_run_would_exec",
+                "name" = container_info.name,
+                "command" = command
+            )
+        );
+        return Ok(());
+    }
+
+    let Err(code) = exec_logged(client, container_info, command).await? else {
+        return Ok(());
+    };
+    if Config::global().map(|c| c.yes).unwrap_or(false) {
+        log_println!(
+            "WARN",
+            "{}",
+            t!("commands.pre_backup_exec_failed_continuing", "code" = code)
+        );
+        return Ok(());
+    }
+    log_bail!(
+        "ERROR",
+        "{}",
+        t!("commands.pre_backup_exec_failed", "code" = code)
+    );
+}
+
+/// 恢复并重启容器后在容器内执行 `command` (通过 `sh -c`)，命令以非零状态退出时返回错误
+pub async fn run_post_restore_exec<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    command: &str,
+) -> Result<()> {
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.dry_run_would_exec",
+                "name" = container_info.name,
+                "command" = command
+            )
+        );
+        return Ok(());
+    }
+
+    if let Err(code) = exec_logged(client, container_info, command).await? {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.post_restore_exec_failed",
+                "name" = container_info.name,
+                "code" = code
+            )
+        );
+    }
+    Ok(())
+}
+
+/// 在容器内通过 `sh -c` 执行命令并将输出逐行写入日志，命令以非零状态退出时返回 `Err(退出码)`
+async fn exec_logged<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    command: &str,
+) -> Result<std::result::Result<(), String>> {
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.running_in_container",
+            "name" = container_info.name,
+            "command" = command
+        )
+    );
+    let result = client
+        .exec_in_container(
+            &container_info.id,
+            vec!["sh".to_string(), "-c".to_string(), command.to_string()],
+        )
+        .await?;
+    for line in result.output.lines() {
+        log_println!("INFO", "  {}", line);
+    }
+
+    if result.exit_code == Some(0) {
+        return Ok(Ok(()));
+    }
+    Ok(Err(result
+        .exit_code
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "?".to_string())))
+}
+
+/// 交互模式下确认停止运行中的容器，说明容器将停机直到操作完成；`--yes` 或非交互模式下直接同意
+fn confirm_stop_prompt(container_info: &ContainerInfo, timeout_secs: u64) -> Result<bool> {
+    let Ok(config) = Config::global() else {
+        return Ok(true);
+    };
+    if !config.interactive || config.yes {
+        return Ok(true);
+    }
+
+    Ok(Confirm::new()
+        .with_prompt(t!(
+            "commands.confirm_stop_running_container",
+            "name" = container_info.name,
+            "timeout" = timeout_secs
+        ))
+        .default(false)
+        .interact()?)
+}
+
+/// 通过 cgroup freezer 冻结 (暂停) 运行中的容器，返回容器是否由本次调用冻结
+///
+/// 已暂停或未运行的容器保持原状并返回 `false`，调用方只应解冻由自己冻结的容器
+pub async fn freeze_container<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+) -> Result<bool> {
+    let status = client.get_container_status(&container_info.id).await?;
+    if !is_running(&status) {
+        debug!(
+            container = ?container_info.name,
+            status = ?status,
+            "Container not running, nothing to freeze"
+        );
+        return Ok(false);
+    }
+
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.dry_run_would_freeze",
+                "name" = container_info.name
+            )
+        );
+        return Ok(false);
+    }
+
+    log_println!(
+        "INFO",
+        "{}",
+        t!("commands.freezing_container", "name" = container_info.name)
+    );
+    client.pause_container(&container_info.id).await?;
+    Ok(true)
+}
+
+/// 解冻由 [`freeze_container`] 冻结的容器
+pub async fn unfreeze_container<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+) -> Result<()> {
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.unfreezing_container",
+            "name" = container_info.name
+        )
+    );
+    client.unpause_container(&container_info.id).await
+}
+
+/// 常见的有状态服务镜像，不停止容器直接读取其数据卷可能得到不一致的备份
+const STATEFUL_IMAGES: &[&str] = &[
+    "postgres",
+    "postgresql",
+    "postgis",
+    "timescaledb",
+    "mysql",
+    "mariadb",
+    "percona",
+    "mongo",
+    "mongodb",
+    "redis",
+    "valkey",
+    "elasticsearch",
+    "opensearch",
+    "cassandra",
+    "couchdb",
+    "influxdb",
+    "clickhouse-server",
+    "neo4j",
+];
+
+/// 判断镜像名是否为常见的数据库等有状态服务，忽略仓库地址、命名空间、标签与摘要
+///
+/// 如 `docker.io/bitnami/postgresql:16` 与 `mysql-server` 都会被识别
+pub(crate) fn is_stateful_image(image: &str) -> bool {
+    let image = image.split('@').next().unwrap_or(image);
+    let name = image.rsplit('/').next().unwrap_or(image);
+    let name = name.split(':').next().unwrap_or(name).to_ascii_lowercase();
+    STATEFUL_IMAGES.iter().any(|known| {
+        name == *known
+            || name
+                .strip_prefix(known)
+                .is_some_and(|rest| rest.starts_with(['-', '_']))
+    })
+}
+
+/// `--no-stop` 时不停止容器，运行中的容器若是数据库等有状态服务则醒目地警告热备份可能不一致
+pub async fn warn_if_hot_backup<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+) -> Result<()> {
+    let status = client.get_container_status(&container_info.id).await?;
+    if !is_running(&status) {
+        return Ok(());
+    }
+
+    if is_stateful_image(&container_info.image) {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.hot_backup_stateful_warning",
+                "name" = container_info.name,
+                "image" = container_info.image
+            )
+        );
+    } else {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.backing_up_running_container",
+                "name" = container_info.name
+            )
+        );
+    }
+    Ok(())
+}
+
+/// `--skip-if-running` 时容器仍在运行，备份被拒绝
+///
+/// 批量备份据此将容器记为跳过而不是失败
+#[derive(Debug)]
+pub struct ContainerStillRunning {
+    pub name: String,
+    pub status: String,
+}
+
+impl std::fmt::Display for ContainerStillRunning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&t!(
+            "commands.container_still_running_skip",
+            "name" = self.name,
+            "status" = self.status
+        ))
+    }
+}
+
+impl std::error::Error for ContainerStillRunning {}
+
+/// 确认容器已被手动停止，若仍在运行则以 [`ContainerStillRunning`] 中止操作而不是停止容器
+pub async fn ensure_container_not_running<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+) -> Result<()> {
+    let status = client.get_container_status(&container_info.id).await?;
+    if is_running(&status) {
+        let error = ContainerStillRunning {
+            name: container_info.name.clone(),
+            status,
+        };
+        log_println!("ERROR", "{}", error);
+        return Err(error.into());
+    }
+
+    debug!(
+        container = ?container_info.name,
+        status = ?status,
+        "Container is not running"
+    );
+    Ok(())
+}
+
 fn handle_multiple_matches(
     matches: Vec<ContainerInfo>,
     interactive: bool,
@@ -118,6 +590,16 @@ async fn handle_no_matches<T: DockerClientInterface>(
     attempted: String,
     interactive: bool,
 ) -> Result<ContainerInfo> {
+    // 非交互模式下无法重新输入，除 --verbose 外不再列出容器，直接失败以便脚本处理
+    let verbose = Config::global().map(|c| c.verbose).unwrap_or(false);
+    if !interactive && !verbose {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.container_not_found_by_name", "name" = attempted)
+        );
+    }
+
     log_println!(
         "WARN",
         "{}",
@@ -158,6 +640,13 @@ async fn handle_no_matches<T: DockerClientInterface>(
     handle_multiple_matches(matches, true)
 }
 
+/// Docker 在停止超时后发送 SIGKILL，轮询状态时额外等待的时间
+const STOP_KILL_GRACE: Duration = Duration::from_secs(10);
+
+/// 等待容器停止期间输出进度的间隔
+const STOP_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 停止容器并等待其退出，`--timeout 0` 时不设期限
 async fn stop_container_with_timeout<T: DockerClientInterface>(
     client: &T,
     container_info: &ContainerInfo,
@@ -188,7 +677,11 @@ async fn stop_container_with_timeout<T: DockerClientInterface>(
         }
     }
 
-    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    // 超时为 0 时一直等待；否则在 Docker 发送 SIGKILL 后再多等待一会儿
+    let started = Instant::now();
+    let deadline =
+        (timeout_secs > 0).then(|| started + Duration::from_secs(timeout_secs) + STOP_KILL_GRACE);
+    let mut last_progress = started;
     loop {
         let status = client.get_container_status(&container_info.id).await?;
         if !is_running(&status) {
@@ -202,7 +695,20 @@ async fn stop_container_with_timeout<T: DockerClientInterface>(
             return Ok(());
         }
 
-        if Instant::now() >= deadline {
+        if last_progress.elapsed() >= STOP_PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            log_println!(
+                "INFO",
+                "{}",
+                t!(
+                    "commands.container_still_stopping",
+                    "name" = container_info.name,
+                    "status" = status
+                )
+            );
+        }
+
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
             log_bail!(
                 "ERROR",
                 "{}",
@@ -218,6 +724,162 @@ async fn stop_container_with_timeout<T: DockerClientInterface>(
     }
 }
 
+/// `--restart` 时在备份/恢复结束后重新运行容器，无论 `result` 成功与否
+///
+/// 成功时重启容器并按 `wait_healthy` 等待健康检查通过；失败时容器可能尚未被停止，只启动已停止的容器，
+/// 启动失败时给出错误提示，返回原来的错误
+pub async fn restart_after<T: DockerClientInterface, R>(
+    client: &T,
+    container_info: &ContainerInfo,
+    result: Result<R>,
+    wait_healthy: Option<Duration>,
+) -> Result<R> {
+    let restarting = || {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.restarting_container",
+                "name" = container_info.name
+            )
+        )
+    };
+    let restarted = || {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.container_restarted", "name" = container_info.name)
+        )
+    };
+
+    let value = match result {
+        Ok(value) => value,
+        Err(e) => {
+            let started = match client.get_container_status(&container_info.id).await {
+                Ok(status) if is_running(&status) => return Err(e),
+                Ok(_) => {
+                    restarting();
+                    client.start_container(&container_info.id).await
+                }
+                Err(status_error) => Err(status_error),
+            };
+            match started {
+                Ok(()) => restarted(),
+                Err(restart_error) => log_println!(
+                    "ERROR",
+                    "{}",
+                    t!(
+                        "commands.restart_after_failure_failed",
+                        "name" = container_info.name,
+                        "error" = restart_error
+                    )
+                ),
+            }
+            return Err(e);
+        }
+    };
+
+    restarting();
+    client.restart_container(&container_info.id).await?;
+    restarted();
+    if let Some(timeout) = wait_healthy {
+        wait_until_healthy(client, container_info, timeout).await?;
+    }
+    Ok(value)
+}
+
+/// 容器未定义健康检查时，等待其进入 running 状态的最长时间
+const NO_HEALTHCHECK_GRACE: Duration = Duration::from_secs(5);
+
+/// 轮询健康状态的间隔
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 重启容器后等待其健康检查通过，超过 `timeout` 仍未 `healthy` 时返回错误
+///
+/// 容器未定义健康检查时只等待其进入 running 状态，最多等待 [`NO_HEALTHCHECK_GRACE`]
+pub async fn wait_until_healthy<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    timeout: Duration,
+) -> Result<()> {
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.waiting_for_healthy",
+            "name" = container_info.name,
+            "timeout" = timeout.as_secs()
+        )
+    );
+
+    let start = Instant::now();
+    loop {
+        let Some(health) = client.get_container_health(&container_info.id).await? else {
+            log_println!(
+                "INFO",
+                "{}",
+                t!("commands.no_healthcheck", "name" = container_info.name)
+            );
+            return wait_until_running(client, container_info, timeout.min(NO_HEALTHCHECK_GRACE))
+                .await;
+        };
+        debug!(container = ?container_info.name, health, "Container health");
+
+        if health == "healthy" {
+            log_println!(
+                "INFO",
+                "{}",
+                t!("commands.container_healthy", "name" = container_info.name)
+            );
+            return Ok(());
+        }
+
+        if start.elapsed() >= timeout {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.wait_healthy_timeout",
+                    "name" = container_info.name,
+                    "timeout" = timeout.as_secs(),
+                    "status" = health
+                )
+            );
+        }
+
+        sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
+
+/// 等待容器进入 running 状态，超过 `grace` 仍未运行时返回错误
+async fn wait_until_running<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    grace: Duration,
+) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        let status = client.get_container_status(&container_info.id).await?;
+        if status == "running" {
+            return Ok(());
+        }
+
+        if start.elapsed() >= grace {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.container_not_running_after_restart",
+                    "name" = container_info.name,
+                    "status" = status
+                )
+            );
+        }
+
+        sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
+
 fn is_running(status: &str) -> bool {
     matches!(status, "running" | "restarting")
 }
@@ -225,32 +887,216 @@ fn is_running(status: &str) -> bool {
 fn print_container_table(containers: &[ContainerInfo]) {
     println!("\n{}:", t!("commands.available_containers"));
     println!(
-        "{:<20} {:<24} {:<20}",
+        "{:<20} {:<24} {:<24} {:<16} {:<20}",
         t!("commands.container_name"),
         t!("commands.container_id"),
+        t!("commands.container_image"),
+        t!("commands.container_created"),
         t!("commands.container_status")
     );
-    println!("{:-<64}", "");
+    println!("{:-<108}", "");
 
     for container in containers {
         println!(
-            "{:<20} {:<24} {:<20}",
-            container.name, container.id, container.status
+            "{:<20} {:<24} {:<24} {:<16} {:<20}",
+            container.name,
+            container.id,
+            container.image,
+            format_created(container.created),
+            container.status
         );
     }
 }
 
+/// 将容器创建时间 (Unix 时间戳) 格式化为本地时间，未知时显示 `-`
+fn format_created(created: i64) -> String {
+    chrono::DateTime::from_timestamp(created, 0)
+        .filter(|_| created > 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::docker::{ContainerInfo, DockerClient, MockDockerClientInterface};
+    use crate::docker::{ContainerInfo, ExecOutput, MockDockerClientInterface};
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    #[test]
+    fn serializes_containers_as_json_array() -> Result<()> {
+        let containers = vec![ContainerInfo {
+            id: "abc".into(),
+            name: "db".into(),
+            status: "running".into(),
+            image: "postgres:16".into(),
+            created: 1_700_000_000,
+        }];
+
+        let value: serde_json::Value = serde_json::from_str(&containers_to_json(&containers)?)?;
+        assert_eq!(
+            value,
+            serde_json::json!([{
+                "id": "abc",
+                "name": "db",
+                "status": "running",
+                "image": "postgres:16",
+                "created": 1_700_000_000,
+            }])
+        );
+        assert_eq!(containers_to_json(&[])?, "[]");
+        Ok(())
+    }
+
+    #[test]
+    fn formats_created_time_in_local_time() {
+        use chrono::TimeZone;
+
+        let created = chrono::Local
+            .with_ymd_and_hms(2024, 5, 6, 7, 8, 9)
+            .unwrap()
+            .timestamp();
+        assert_eq!(format_created(created), "2024-05-06 07:08");
+        assert_eq!(format_created(0), "-");
+    }
+
+    #[tokio::test]
+    async fn fails_fast_when_no_container_matches_non_interactively() {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_find_containers()
+            .times(1)
+            .returning(|_| Ok(Vec::new()));
+        client.expect_list_containers().times(0);
+
+        let err = select_container(&client, Some("missing".into()), None, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn resolves_compose_service_by_labels_then_name() -> Result<()> {
+        let web = ContainerInfo {
+            id: "id".into(),
+            name: "myproj-web-1".into(),
+            status: "running".into(),
+            image: "nginx".into(),
+            ..Default::default()
+        };
+        let service = ComposeService {
+            project: "myproj".into(),
+            service: "web".into(),
+        };
+
+        let mut client = MockDockerClientInterface::new();
+        let labeled = web.clone();
+        client
+            .expect_find_containers_by_labels()
+            .withf(|labels| {
+                labels
+                    == [
+                        "com.docker.compose.project=myproj".to_string(),
+                        "com.docker.compose.service=web".to_string(),
+                    ]
+            })
+            .times(1)
+            .returning(move |_| {
+                Ok(vec![crate::docker::LabeledContainer {
+                    info: labeled.clone(),
+                    labels: Default::default(),
+                }])
+            });
+        client.expect_find_containers().times(0);
+        let info = select_container(&client, None, Some(&service), false).await?;
+        assert_eq!(info.name, "myproj-web-1");
+
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_find_containers_by_labels()
+            .times(1)
+            .returning(|_| Ok(Vec::new()));
+        client
+            .expect_find_containers()
+            .withf(|name| name == "myproj-web")
+            .times(1)
+            .returning(move |_| Ok(vec![web.clone()]));
+        let info = select_container(&client, None, Some(&service), false).await?;
+        assert_eq!(info.name, "myproj-web-1");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filters_containers_by_labels() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_find_containers_by_labels()
+            .withf(|labels| labels == ["backup=true".to_string(), "tier".to_string()])
+            .times(1)
+            .returning(|_| {
+                Ok(vec![crate::docker::LabeledContainer {
+                    info: ContainerInfo {
+                        id: "id".into(),
+                        name: "db".into(),
+                        status: "running".into(),
+                        image: "postgres".into(),
+                        ..Default::default()
+                    },
+                    labels: Default::default(),
+                }])
+            });
+        client.expect_list_containers().times(0);
+
+        let labels = ["backup=true".to_string(), "tier".to_string()];
+        let containers = containers_with_labels(&client, &labels).await?;
+        assert_eq!(
+            containers
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["db"]
+        );
+
+        let mut client = MockDockerClientInterface::new();
+        client.expect_find_containers_by_labels().times(0);
+        client
+            .expect_list_containers()
+            .times(1)
+            .returning(|| Ok(Vec::new()));
+        assert!(containers_with_labels(&client, &[]).await?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn detects_stateful_images() {
+        for image in [
+            "postgres",
+            "postgres:16-alpine",
+            "docker.io/library/mysql:8",
+            "bitnami/postgresql:16",
+            "ghcr.io/org/mongo@sha256:abcd",
+            "mysql-server",
+            "Redis:7",
+        ] {
+            assert!(is_stateful_image(image), "{image}");
+        }
+        for image in [
+            "nginx:latest",
+            "postgrest/postgrest",
+            "myapp/redisinsight",
+            "",
+        ] {
+            assert!(!is_stateful_image(image), "{image}");
+        }
+    }
+
     #[tokio::test]
     async fn skips_stopping_when_not_running() -> Result<()> {
-        DockerClient::init(10)?;
-        let mut client = DockerClient::global()?;
+        let mut client = MockDockerClientInterface::with_defaults();
         client
             .expect_get_container_status()
             .returning(|_| Ok("exited".to_string()));
@@ -259,6 +1105,7 @@ mod tests {
             id: "id".into(),
             name: "name".into(),
             status: "exited".into(),
+            ..Default::default()
         };
 
         ensure_container_stopped(&client, &container).await?;
@@ -291,10 +1138,273 @@ mod tests {
             id: "id".into(),
             name: "name".into(),
             status: "running".into(),
+            ..Default::default()
         };
 
-        ensure_container_stopped(&client, &container).await?;
+        ensure_container_stopped_with(&client, &container, |_, _| Ok(true)).await?;
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn confirms_only_before_stopping_running_container() -> Result<()> {
+        let confirmed = Arc::new(AtomicUsize::new(0));
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+        client.expect_stop_container().times(0);
+        let counter = confirmed.clone();
+        ensure_container_stopped_with(&client, &container, move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(true)
+        })
+        .await?;
+        assert_eq!(confirmed.load(Ordering::SeqCst), 0);
+
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("running".to_string()));
+        client.expect_get_stop_timeout_secs().returning(|| 7);
+        client.expect_stop_container().times(0);
+        let counter = confirmed.clone();
+        let result = ensure_container_stopped_with(&client, &container, move |info, timeout| {
+            assert_eq!((info.name.as_str(), timeout), ("name", 7));
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(false)
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(confirmed.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn exec_hooks_abort_on_failure() -> Result<()> {
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+        let exec = |exit_code: i64| {
+            let mut client = MockDockerClientInterface::new();
+            client
+                .expect_get_container_status()
+                .returning(|_| Ok("running".to_string()));
+            client
+                .expect_exec_in_container()
+                .withf(|id, cmd| id == "id" && *cmd == ["sh", "-c", "sync"])
+                .times(1)
+                .returning(move |_, _| {
+                    Ok(ExecOutput {
+                        exit_code: Some(exit_code),
+                        output: "flushed\n".to_string(),
+                    })
+                });
+            client
+        };
+
+        run_pre_backup_exec(&exec(0), &container, "sync").await?;
+        assert!(
+            run_pre_backup_exec(&exec(1), &container, "sync")
+                .await
+                .is_err()
+        );
+
+        // 恢复后执行的命令失败时总是返回错误
+        assert!(
+            run_post_restore_exec(&exec(2), &container, "sync")
+                .await
+                .is_err()
+        );
+        run_post_restore_exec(&exec(0), &container, "sync").await?;
+
+        let mut stopped = MockDockerClientInterface::new();
+        stopped
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+        stopped.expect_exec_in_container().times(0);
+        run_pre_backup_exec(&stopped, &container, "sync").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_running_check_rejects_running_container() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("running".to_string()));
+        client.expect_stop_container().times(0);
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        assert!(
+            ensure_container_not_running(&client, &container)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_running_check_accepts_stopped_container() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+
+        ensure_container_not_running(&client, &container).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn waits_until_container_reports_healthy() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let health_counter = counter.clone();
+        client
+            .expect_get_container_health()
+            .times(2)
+            .returning(move |_| {
+                let call = health_counter.fetch_add(1, Ordering::SeqCst);
+                let health = if call == 0 { "starting" } else { "healthy" };
+                Ok(Some(health.to_string()))
+            });
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        wait_until_healthy(&client, &container, Duration::from_secs(10)).await?;
         assert_eq!(counter.load(Ordering::SeqCst), 2);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn restarts_stopped_container_even_when_operation_fails() -> Result<()> {
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "exited".into(),
+            ..Default::default()
+        };
+        let failed = || Err::<(), _>(anyhow::anyhow!("compression failed"));
+
+        // 失败后启动已停止的容器，并返回原来的错误
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .times(1)
+            .returning(|_| Ok("exited".to_string()));
+        client
+            .expect_start_container()
+            .times(1)
+            .returning(|_| Ok(()));
+        client.expect_restart_container().never();
+        let err = restart_after(&client, &container, failed(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("compression failed"));
+
+        // 失败时容器仍在运行 (如停止前就已失败)，不打断正在提供服务的容器
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_status()
+            .times(1)
+            .returning(|_| Ok("running".to_string()));
+        client.expect_start_container().never();
+        assert!(
+            restart_after(&client, &container, failed(), None)
+                .await
+                .is_err()
+        );
+
+        // 成功时照常重启
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_restart_container()
+            .times(1)
+            .returning(|_| Ok(()));
+        assert_eq!(restart_after(&client, &container, Ok(7), None).await?, 7);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn health_wait_times_out_while_unhealthy() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_health()
+            .returning(|_| Ok(Some("unhealthy".to_string())));
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        let err = wait_until_healthy(&client, &container, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unhealthy"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn health_wait_without_healthcheck_only_requires_running() -> Result<()> {
+        let mut client = MockDockerClientInterface::new();
+        client
+            .expect_get_container_health()
+            .times(1)
+            .returning(|_| Ok(None));
+        client
+            .expect_get_container_status()
+            .times(1)
+            .returning(|_| Ok("running".to_string()));
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "name".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        wait_until_healthy(&client, &container, Duration::from_secs(60)).await?;
+
+        let mut client = MockDockerClientInterface::new();
+        client.expect_get_container_health().returning(|_| Ok(None));
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+        assert!(
+            wait_until_healthy(&client, &container, Duration::ZERO)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,45 @@
+use crate::{
+    commands::OutputFormat,
+    utils::{self, ArchiveEntryHeader},
+};
+
+use anyhow::Result;
+use std::path::Path;
+use tracing::info;
+
+/// 打印备份文件中每个 tar 条目的头部信息，用于诊断路径、权限等问题
+pub fn dump_headers(archive: &str, format: OutputFormat) -> Result<()> {
+    let archive = utils::ensure_file_exists(Path::new(archive))?;
+    let headers = utils::read_archive_headers(&archive)?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&headers)?),
+        OutputFormat::Table => print_headers_table(&headers),
+    }
+
+    info!(?archive, entries = headers.len(), "Archive headers printed");
+    Ok(())
+}
+
+fn print_headers_table(headers: &[ArchiveEntryHeader]) {
+    println!(
+        "{:<4} {:<6} {:>6}:{:<6} {:>12} {:>11}  PATH",
+        "TYPE", "MODE", "UID", "GID", "SIZE", "MTIME"
+    );
+    let field = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    for header in headers {
+        println!(
+            "{:<4} {:<6} {:>6}:{:<6} {:>12} {:>11}  {}",
+            header.typeflag,
+            header.mode.as_deref().unwrap_or("-"),
+            field(header.uid),
+            field(header.gid),
+            header.size,
+            field(header.mtime),
+            header.path
+        );
+        if header.header_name != header.path {
+            println!("{:50}  (header name: {})", "", header.header_name);
+        }
+    }
+}
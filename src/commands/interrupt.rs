@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::docker::{ContainerInfo, DockerClient, DockerClientInterface};
+use crate::log_println;
+use crate::utils;
+
+/// 被 Ctrl-C 中断时的退出码 (128 + SIGINT)，与正常失败的退出码 1 区分
+pub(crate) const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// 本次运行中停止、中断后需要重新启动的容器
+static STOPPED_CONTAINERS: Mutex<Vec<ContainerInfo>> = Mutex::new(Vec::new());
+
+/// 备份时被冻结 (暂停)、中断后需要解冻的容器；启动被暂停的容器不会使其恢复运行
+static FROZEN_CONTAINERS: Mutex<Vec<ContainerInfo>> = Mutex::new(Vec::new());
+
+/// 正在原地恢复的容器，中断后其卷可能只恢复了一部分
+static RESTORES: Mutex<Vec<RestoreState>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone)]
+struct RestoreState {
+    container: ContainerInfo,
+    targets: Vec<String>,
+    snapshot: Option<PathBuf>,
+}
+
+/// 安装 Ctrl-C 处理：删除未写完的归档，重启被停止的容器，提示未完成的恢复，然后以
+/// [`INTERRUPTED_EXIT_CODE`] 退出
+///
+/// 压缩在当前线程中同步进行，处理任务运行在运行时的其他工作线程上，不会被压缩阻塞
+pub(crate) fn install() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        // 进度条不换行，先结束当前行
+        println!();
+        cleanup().await;
+        log_println!("WARN", "{}", t!("commands.interrupted"));
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    });
+}
+
+async fn cleanup() {
+    for path in utils::remove_partial_files() {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.interrupted_partial_removed",
+                "path" = path.to_string_lossy()
+            )
+        );
+    }
+
+    let restores = RESTORES
+        .lock()
+        .map(|mut restores| std::mem::take(&mut *restores))
+        .unwrap_or_default();
+    for restore in restores {
+        log_println!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.interrupted_restore_incomplete",
+                "name" = restore.container.name,
+                "targets" = restore.targets.join(", ")
+            )
+        );
+        if let Some(snapshot) = restore.snapshot {
+            log_println!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.pre_restore_snapshot_hint",
+                    "path" = snapshot.to_string_lossy(),
+                    "name" = restore.container.name
+                )
+            );
+        }
+    }
+
+    let frozen = FROZEN_CONTAINERS
+        .lock()
+        .map(|mut containers| std::mem::take(&mut *containers))
+        .unwrap_or_default();
+    let containers = STOPPED_CONTAINERS
+        .lock()
+        .map(|mut containers| std::mem::take(&mut *containers))
+        .unwrap_or_default();
+    if frozen.is_empty() && containers.is_empty() {
+        return;
+    }
+    let client = match DockerClient::global() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!(?e, "Failed to get Docker client after interruption");
+            return;
+        }
+    };
+    for container in frozen {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.unfreezing_container", "name" = container.name)
+        );
+        if let Err(e) = client.unpause_container(&container.id).await {
+            log_println!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.interrupted_unfreeze_failed",
+                    "name" = container.name,
+                    "error" = e
+                )
+            );
+        }
+    }
+    for container in containers {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.restarting_container", "name" = container.name)
+        );
+        // 中断时容器可能还未被停止，启动正在运行的容器不会产生影响
+        match client.start_container(&container.id).await {
+            Ok(()) => log_println!(
+                "INFO",
+                "{}",
+                t!("commands.container_restarted", "name" = container.name)
+            ),
+            Err(e) => log_println!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.interrupted_restart_failed",
+                    "name" = container.name,
+                    "error" = e
+                )
+            ),
+        }
+    }
+}
+
+/// 在值被丢弃前，中断时重新启动该容器 (用于 `--restart`)
+#[derive(Debug)]
+pub(crate) struct RestartOnInterrupt {
+    id: String,
+}
+
+impl RestartOnInterrupt {
+    pub fn track(container: &ContainerInfo) -> Self {
+        if let Ok(mut containers) = STOPPED_CONTAINERS.lock() {
+            containers.push(container.clone());
+        }
+        Self {
+            id: container.id.clone(),
+        }
+    }
+}
+
+impl Drop for RestartOnInterrupt {
+    fn drop(&mut self) {
+        if let Ok(mut containers) = STOPPED_CONTAINERS.lock()
+            && let Some(index) = containers.iter().position(|c| c.id == self.id)
+        {
+            containers.remove(index);
+        }
+    }
+}
+
+/// 在值被丢弃前，中断时解冻该容器 (用于 `--freeze`)
+#[derive(Debug)]
+pub(crate) struct UnfreezeOnInterrupt {
+    id: String,
+}
+
+impl UnfreezeOnInterrupt {
+    pub fn track(container: &ContainerInfo) -> Self {
+        if let Ok(mut containers) = FROZEN_CONTAINERS.lock() {
+            containers.push(container.clone());
+        }
+        Self {
+            id: container.id.clone(),
+        }
+    }
+}
+
+impl Drop for UnfreezeOnInterrupt {
+    fn drop(&mut self) {
+        if let Ok(mut containers) = FROZEN_CONTAINERS.lock()
+            && let Some(index) = containers.iter().position(|c| c.id == self.id)
+        {
+            containers.remove(index);
+        }
+    }
+}
+
+/// 在值被丢弃前，中断时提示该容器的卷只恢复了一部分
+#[derive(Debug)]
+pub(crate) struct RestoreInProgress {
+    id: String,
+}
+
+impl RestoreInProgress {
+    pub fn track(
+        container: &ContainerInfo,
+        targets: Vec<String>,
+        snapshot: Option<PathBuf>,
+    ) -> Self {
+        if let Ok(mut restores) = RESTORES.lock() {
+            restores.push(RestoreState {
+                container: container.clone(),
+                targets,
+                snapshot,
+            });
+        }
+        Self {
+            id: container.id.clone(),
+        }
+    }
+}
+
+impl Drop for RestoreInProgress {
+    fn drop(&mut self) {
+        if let Ok(mut restores) = RESTORES.lock()
+            && let Some(index) = restores.iter().position(|r| r.container.id == self.id)
+        {
+            restores.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guards_unregister_when_dropped() {
+        let container = ContainerInfo {
+            id: "interrupt-test".to_string(),
+            name: "web".to_string(),
+            status: "exited".to_string(),
+            ..Default::default()
+        };
+        let restarting = |id: &str| {
+            STOPPED_CONTAINERS
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|c| c.id == id)
+        };
+        let unfreezing = |id: &str| FROZEN_CONTAINERS.lock().unwrap().iter().any(|c| c.id == id);
+        let restoring = |id: &str| {
+            RESTORES
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|r| r.container.id == id)
+        };
+
+        let restart = RestartOnInterrupt::track(&container);
+        let unfreeze = UnfreezeOnInterrupt::track(&container);
+        let restore = RestoreInProgress::track(&container, vec!["/data".to_string()], None);
+        assert!(restarting(&container.id));
+        assert!(unfreezing(&container.id));
+        assert!(restoring(&container.id));
+
+        drop(restart);
+        drop(unfreeze);
+        drop(restore);
+        assert!(!restarting(&container.id));
+        assert!(!unfreezing(&container.id));
+        assert!(!restoring(&container.id));
+    }
+}
@@ -1,10 +1,25 @@
-use crate::{commands::symbollink, log_println};
+use crate::{commands::symbollink, log_bail, log_println, utils};
 use anyhow::{Context, Result};
 use semver::Version;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
 
 const CRATE_NAME: &str = "rdbkp2";
 const CARGO_IO_API: &str = "https://crates.io/api/v1/crates/";
+/// GitHub releases 的下载地址前缀，其后为 `v<版本>/<文件名>`
+const RELEASE_DOWNLOAD_URL: &str = "https://github.com/yuniqueunic/rdbkp2/releases/download/";
+/// GitHub releases 列表接口，用于读取各版本的发布说明
+const GITHUB_RELEASES_API: &str =
+    "https://api.github.com/repos/yuniqueunic/rdbkp2/releases?per_page=30";
+/// crates.io 与 GitHub 响应在临时目录中的缓存时长
+const RESPONSE_CACHE_TTL: Duration = Duration::from_secs(3 * 60 * 60);
+/// 备份/恢复后自动检查更新的间隔
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// 自动检查更新时请求 crates.io 的超时时间，避免拖慢备份/恢复
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Deserialize)]
 struct CrateResponse {
@@ -17,33 +32,73 @@ struct CrateVersion {
     yanked: bool,
 }
 
-/// 检查新版本
-pub async fn check_update() -> Result<()> {
-    let current_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
+/// 自动检查更新的记录，保存在 `dirs::cache_dir()/rdbkp2/update.json`
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct UpdateCheck {
+    /// 上次检查的时间 (Unix 时间戳，秒)
+    checked_at: u64,
+    /// 上次检查时得到的最新版本
+    latest_version: Option<String>,
+}
 
-    // 获取 crates.io 上的版本信息
-    let client = reqwest::Client::new();
-    let url = format!("{}{}", CARGO_IO_API, CRATE_NAME);
-    let response = client
-        .get(&url)
-        .header("User-Agent", format!("{}/{}", CRATE_NAME, current_version))
-        .send()
-        .await
-        .with_context(|| t!("lifecycle.can_not_connect_to_crates_io"))?;
+impl UpdateCheck {
+    fn path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(CRATE_NAME).join("update.json"))
+    }
 
-    let crate_info: CrateResponse = response
-        .json()
-        .await
-        .with_context(|| t!("lifecycle.can_not_parse_version_info"))?;
+    /// 读取检查记录，不存在或无法解析时返回默认值 (即需要检查)
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
 
-    // 找到最新的未被撤回的版本
-    let latest_version = crate_info
-        .versions
-        .iter()
-        .find(|v| !v.yanked)
-        .ok_or_else(|| anyhow::anyhow!(t!("lifecycle.no_available_version")))?;
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
 
-    let latest_version = Version::parse(&latest_version.num)?;
+    fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.checked_at) >= UPDATE_CHECK_INTERVAL.as_secs()
+    }
+
+    /// 记录中比 `current` 新的版本
+    fn newer_than(&self, current: &Version) -> Option<Version> {
+        self.latest_version
+            .as_deref()
+            .and_then(|v| Version::parse(v).ok())
+            .filter(|v| v > current)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// 检查新版本，`apply` 为真时下载当前平台的预编译二进制并替换正在运行的程序
+pub async fn check_update(apply: bool) -> Result<()> {
+    let current_exe = std::env::current_exe()?;
+    // Windows 上替换时留下的旧程序，此时已不再运行，可以删除
+    let _ = std::fs::remove_file(old_executable_path(&current_exe));
+
+    let current_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
+
+    let client = reqwest::Client::new();
+    let (crate_info, latest_version) = fetch_latest_version(&client).await?;
 
     if latest_version > current_version {
         log_println!(
@@ -61,6 +116,24 @@ pub async fn check_update() -> Result<()> {
                 )
             )
         );
+        let versions = versions_since(&crate_info.versions, &current_version, &latest_version);
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "lifecycle.versions_since_current",
+                "versions" = versions
+                    .iter()
+                    .map(Version::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        );
+        print_release_notes(&client, &versions).await;
+
+        if apply {
+            return apply_update(&client, &latest_version, &current_exe).await;
+        }
         log_println!(
             "INFO",
             "{}",
@@ -70,6 +143,7 @@ pub async fn check_update() -> Result<()> {
                 CRATE_NAME
             )
         );
+        log_println!("INFO", "{}", t!("lifecycle.update_apply_hint"));
     } else {
         log_println!(
             "INFO",
@@ -84,10 +158,340 @@ pub async fn check_update() -> Result<()> {
     Ok(())
 }
 
+/// 获取 crates.io 上的版本信息与最新的未被撤回的版本，并更新自动检查的记录
+async fn fetch_latest_version(client: &reqwest::Client) -> Result<(CrateResponse, Version)> {
+    let url = format!("{}{}", CARGO_IO_API, CRATE_NAME);
+    let crate_info: CrateResponse = fetch_json_cached(client, &url, "crates-io.json")
+        .await
+        .with_context(|| t!("lifecycle.can_not_connect_to_crates_io"))?;
+
+    // 找到最新的未被撤回的版本
+    let latest_version = crate_info
+        .versions
+        .iter()
+        .find(|v| !v.yanked)
+        .ok_or_else(|| anyhow::anyhow!(t!("lifecycle.no_available_version")))?;
+    let latest_version = Version::parse(&latest_version.num)?;
+
+    if let Some(path) = UpdateCheck::path() {
+        let check = UpdateCheck {
+            checked_at: unix_now(),
+            latest_version: Some(latest_version.to_string()),
+        };
+        if let Err(e) = check.save(&path) {
+            debug!(?path, ?e, "Failed to save update check");
+        }
+    }
+    Ok((crate_info, latest_version))
+}
+
+/// 备份/恢复成功后调用：距上次检查超过一天时检查新版本，发现新版本则打印一行提示
+///
+/// 检查失败只记录调试日志，不影响备份/恢复的结果；失败时同样更新检查时间，离线时不会每次都等待超时
+pub async fn notify_if_outdated() {
+    let Some(path) = UpdateCheck::path() else {
+        return;
+    };
+    let previous = UpdateCheck::load(&path);
+    if !previous.is_stale(unix_now()) {
+        return;
+    }
+
+    let latest = match reqwest::Client::builder()
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .build()
+    {
+        Ok(client) => fetch_latest_version(&client)
+            .await
+            .map(|(_, latest)| latest),
+        Err(e) => Err(e.into()),
+    };
+    let check = match latest {
+        Ok(latest) => UpdateCheck {
+            checked_at: unix_now(),
+            latest_version: Some(latest.to_string()),
+        },
+        Err(e) => {
+            debug!(?e, "Background update check failed");
+            let check = UpdateCheck {
+                checked_at: unix_now(),
+                ..previous
+            };
+            if let Err(e) = check.save(&path) {
+                debug!(?path, ?e, "Failed to save update check");
+            }
+            check
+        }
+    };
+
+    let current =
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap_or_else(|_| Version::new(0, 0, 0));
+    if let Some(latest) = check.newer_than(&current) {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "lifecycle.update_available_note",
+                "latest_version" = latest,
+                "current_version" = current
+            )
+        );
+    }
+}
+
+/// 当前版本之后 (不含) 到最新版本 (含) 之间所有未被撤回的版本，按从旧到新排列
+fn versions_since(versions: &[CrateVersion], current: &Version, latest: &Version) -> Vec<Version> {
+    let mut versions = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Version::parse(&v.num).ok())
+        .filter(|v| v > current && v <= latest)
+        .collect::<Vec<_>>();
+    versions.sort();
+    versions
+}
+
+/// 打印 `versions` 在 GitHub releases 中的发布说明
+///
+/// 发布说明只是辅助信息，获取失败时不影响版本检查
+async fn print_release_notes(client: &reqwest::Client, versions: &[Version]) {
+    let releases: Vec<GithubRelease> =
+        match fetch_json_cached(client, GITHUB_RELEASES_API, "github-releases.json").await {
+            Ok(releases) => releases,
+            Err(e) => {
+                debug!(?e, "Failed to fetch release notes");
+                return;
+            }
+        };
+
+    for version in versions {
+        let notes = releases
+            .iter()
+            .find(|r| r.tag_name.trim_start_matches('v') == version.to_string())
+            .and_then(|r| r.body.as_deref())
+            .map(str::trim)
+            .filter(|body| !body.is_empty());
+        if let Some(notes) = notes {
+            log_println!(
+                "INFO",
+                "{}",
+                t!("lifecycle.release_notes", "version" = version)
+            );
+            println!("{notes}\n");
+        }
+    }
+}
+
+/// 请求 JSON 接口，响应在当前用户的缓存目录中缓存 [`RESPONSE_CACHE_TTL`]，避免每次执行都请求接口
+///
+/// 不使用公共的临时目录，避免其他用户预先创建或链接缓存文件篡改版本信息；无法确定缓存目录时不缓存
+async fn fetch_json_cached<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    cache_name: &str,
+) -> Result<T> {
+    let cache_path = dirs::cache_dir().map(|dir| dir.join(CRATE_NAME).join(cache_name));
+    if let Some(value) = cache_path
+        .as_deref()
+        .and_then(|path| read_fresh_cache(path, RESPONSE_CACHE_TTL))
+    {
+        debug!(?cache_path, "Using cached response");
+        return Ok(value);
+    }
+
+    let body = client
+        .get(url)
+        .header(
+            "User-Agent",
+            format!("{}/{}", CRATE_NAME, env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())?
+        .text()
+        .await?;
+    let value =
+        serde_json::from_str(&body).with_context(|| t!("lifecycle.can_not_parse_version_info"))?;
+    if let Some(cache_path) = cache_path {
+        let written = cache_path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&cache_path, &body));
+        if let Err(e) = written {
+            debug!(?cache_path, ?e, "Failed to write response cache");
+        }
+    }
+    Ok(value)
+}
+
+/// 读取未过期且可以解析的缓存
+fn read_fresh_cache<T: DeserializeOwned>(path: &Path, ttl: Duration) -> Option<T> {
+    let age = std::fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > ttl {
+        return None;
+    }
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// 当前平台在 GitHub releases 中对应的预编译二进制的文件名，没有预编译版本时返回 `None`
+fn release_asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("rdbkp2-linux-x86_64"),
+        ("linux", "aarch64") => Some("rdbkp2-linux-aarch64"),
+        ("windows", "x86_64") => Some("rdbkp2-windows-x86_64.exe"),
+        ("macos", "x86_64") => Some("rdbkp2-darwin-x86_64"),
+        ("macos", "aarch64") => Some("rdbkp2-darwin-aarch64"),
+        _ => None,
+    }
+}
+
+/// 下载 `version` 的预编译二进制，校验 SHA-256 后替换 `current_exe`
+async fn apply_update(
+    client: &reqwest::Client,
+    version: &Version,
+    current_exe: &Path,
+) -> Result<()> {
+    let Some(asset) = release_asset_name() else {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "lifecycle.update_unsupported_platform",
+                "platform" = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+            )
+        );
+    };
+    let url = format!("{RELEASE_DOWNLOAD_URL}v{version}/{asset}");
+    log_println!(
+        "INFO",
+        "{}",
+        t!("lifecycle.downloading_update", "url" = url)
+    );
+
+    let binary = download(client, &url).await?;
+    let checksum = download(client, &format!("{url}.{}", utils::CHECKSUM_EXTENSION)).await?;
+    verify_download(&binary, &String::from_utf8_lossy(&checksum))?;
+
+    let current_exe = dunce::canonicalize(current_exe)?;
+    replace_executable(&current_exe, &binary).map_err(|e| {
+        if e.downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::PermissionDenied)
+        {
+            anyhow::anyhow!(t!(
+                "lifecycle.update_permission_denied",
+                "path" = current_exe.display()
+            ))
+        } else {
+            e
+        }
+    })?;
+
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "lifecycle.update_applied",
+            "version" = version,
+            "path" = current_exe.display()
+        )
+    );
+    Ok(())
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .header(
+            "User-Agent",
+            format!("{}/{}", CRATE_NAME, env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| t!("lifecycle.download_failed", "url" = url))?;
+    Ok(response
+        .bytes()
+        .await
+        .with_context(|| t!("lifecycle.download_failed", "url" = url))?
+        .to_vec())
+}
+
+/// 校验下载的二进制与 `<文件名>.sha256` 中记录的校验和 (`sha256sum` 格式) 是否一致
+fn verify_download(binary: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let actual = utils::sha256_reader(binary)?;
+    if expected != actual {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "lifecycle.update_checksum_mismatch",
+                "expected" = expected,
+                "actual" = actual
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Windows 上被替换下来的旧程序的路径
+fn old_executable_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    exe.with_file_name(name)
+}
+
+/// 用 `binary` 替换 `exe`
+///
+/// 先写入同一目录下的临时文件再重命名，替换是原子的，失败时原程序保持不变。
+/// Windows 上无法覆盖正在运行的程序，但可以重命名它，因此先将其移到 `<exe>.old`，
+/// 该文件在下次执行 `update` 时删除
+fn replace_executable(exe: &Path, binary: &[u8]) -> Result<()> {
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("{} has no parent directory", exe.display()))?;
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(binary)?;
+    temp.as_file().sync_all()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(exe)
+            .map(|m| m.permissions().mode())
+            .unwrap_or(0o755);
+        std::fs::set_permissions(temp.path(), std::fs::Permissions::from_mode(mode))?;
+    }
+
+    if cfg!(windows) {
+        let old = old_executable_path(exe);
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old)?;
+        if let Err(e) = temp.persist(exe) {
+            // 放回原程序，保证失败时仍可使用
+            let _ = std::fs::rename(&old, exe);
+            return Err(e.error.into());
+        }
+        return Ok(());
+    }
+
+    temp.persist(exe).map_err(|e| e.error)?;
+    Ok(())
+}
+
 /// 完全卸载，包括删除符号链接
 pub async fn uninstall() -> Result<()> {
     // 1. 删除符号链接
-    if let Err(e) = symbollink::remove_symbollink() {
+    if let Err(e) = symbollink::remove_symbollink(None) {
         log_println!(
             "WARN",
             "{}",
@@ -105,3 +509,99 @@ pub async fn uninstall() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_and_replaces_executable() -> Result<()> {
+        let binary = b"new binary";
+        let checksum = utils::sha256_reader(&binary[..])?;
+        verify_download(
+            binary,
+            &format!("{}  rdbkp2-linux-x86_64\n", checksum.to_uppercase()),
+        )?;
+        assert!(verify_download(binary, "deadbeef  rdbkp2-linux-x86_64").is_err());
+        assert!(verify_download(binary, "").is_err());
+
+        let dir = tempfile::tempdir()?;
+        let exe = dir.path().join("rdbkp2");
+        std::fs::write(&exe, "old binary")?;
+        replace_executable(&exe, binary)?;
+        assert_eq!(std::fs::read(&exe)?, binary);
+        assert_eq!(old_executable_path(&exe), dir.path().join("rdbkp2.old"));
+        Ok(())
+    }
+
+    #[test]
+    fn records_update_checks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("rdbkp2").join("update.json");
+        assert_eq!(UpdateCheck::load(&path), UpdateCheck::default());
+        assert!(UpdateCheck::default().is_stale(unix_now()));
+
+        let check = UpdateCheck {
+            checked_at: 1_000_000,
+            latest_version: Some("1.3.0".to_string()),
+        };
+        check.save(&path)?;
+        assert_eq!(UpdateCheck::load(&path), check);
+        assert!(!check.is_stale(1_000_000 + 60));
+        assert!(check.is_stale(1_000_000 + UPDATE_CHECK_INTERVAL.as_secs()));
+
+        assert_eq!(
+            check.newer_than(&Version::parse("1.2.0")?),
+            Some(Version::parse("1.3.0")?)
+        );
+        assert_eq!(check.newer_than(&Version::parse("1.3.0")?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn lists_versions_since_current_and_reads_fresh_cache() -> Result<()> {
+        let versions = [
+            ("1.3.0", false),
+            ("1.2.1", true),
+            ("1.2.0", false),
+            ("1.1.0", false),
+            ("1.0.0", false),
+        ]
+        .map(|(num, yanked)| CrateVersion {
+            num: num.to_string(),
+            yanked,
+        });
+        let since = versions_since(
+            &versions,
+            &Version::parse("1.1.0")?,
+            &Version::parse("1.3.0")?,
+        );
+        assert_eq!(
+            since,
+            vec![Version::parse("1.2.0")?, Version::parse("1.3.0")?]
+        );
+
+        let dir = tempfile::tempdir()?;
+        let cache = dir.path().join("cache.json");
+        assert_eq!(
+            read_fresh_cache::<Vec<u32>>(&cache, RESPONSE_CACHE_TTL),
+            None
+        );
+        std::fs::write(&cache, "[1, 2]")?;
+        assert_eq!(
+            read_fresh_cache::<Vec<u32>>(&cache, RESPONSE_CACHE_TTL),
+            Some(vec![1, 2])
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            read_fresh_cache::<Vec<u32>>(&cache, Duration::from_millis(10)),
+            None
+        );
+        std::fs::write(&cache, "not json")?;
+        assert_eq!(
+            read_fresh_cache::<Vec<u32>>(&cache, RESPONSE_CACHE_TTL),
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{docker::ContainerInfo, log_bail};
+use anyhow::Result;
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{Seek, Write},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// 锁文件所在目录名 (位于系统临时目录下)
+const LOCK_DIR_NAME: &str = "rdbkp2-locks";
+
+/// 需要锁定容器的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockOperation {
+    /// 备份：读取卷，期间卷不能被恢复写入
+    Backup,
+    /// 原地恢复：写入卷
+    RestoreInPlace,
+    /// 恢复到目录：不写入卷，可与其他恢复到目录的操作同时进行
+    RestoreToDirectory,
+}
+
+impl LockOperation {
+    fn is_shared(self) -> bool {
+        matches!(self, Self::RestoreToDirectory)
+    }
+
+    /// 写入锁文件的操作标识，与语言无关
+    fn key(self) -> &'static str {
+        match self {
+            Self::Backup => "backup",
+            Self::RestoreInPlace => "restore",
+            Self::RestoreToDirectory => "restore-to-directory",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        [Self::Backup, Self::RestoreInPlace, Self::RestoreToDirectory]
+            .into_iter()
+            .find(|op| op.key() == key)
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Self::Backup => t!("commands.lock_operation_backup"),
+            Self::RestoreInPlace => t!("commands.lock_operation_restore"),
+            Self::RestoreToDirectory => t!("commands.lock_operation_restore_to_directory"),
+        }
+        .to_string()
+    }
+}
+
+/// 容器级别的跨进程锁，防止同一容器的备份与恢复同时进行
+///
+/// 锁在值被丢弃 (文件关闭) 时释放，进程异常退出时由操作系统自动释放
+#[derive(Debug)]
+pub(crate) struct ContainerLock {
+    _file: File,
+}
+
+impl ContainerLock {
+    /// 为 `operation` 获取容器锁，已有冲突的操作在进行时立即返回错误
+    pub fn acquire(container: &ContainerInfo, operation: LockOperation) -> Result<Self> {
+        Self::acquire_in(&lock_dir()?, container, operation)
+    }
+
+    fn acquire_in(dir: &Path, container: &ContainerInfo, operation: LockOperation) -> Result<Self> {
+        let path = dir.join(format!("{}.lock", lock_name(container)));
+        let mut file = open_lock_file(&path)?;
+
+        let locked = if operation.is_shared() {
+            file.try_lock_shared()
+        } else {
+            file.try_lock()
+        };
+        match locked {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = describe_holder(&path);
+                log_bail!(
+                    "ERROR",
+                    "{}",
+                    t!(
+                        "commands.container_busy",
+                        "name" = container.name,
+                        "operation" = holder
+                    )
+                );
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // 记录持有者供其他进程提示冲突的操作；锁文件只读 (由其他用户创建) 时跳过
+        let holder = format!("{} {}", operation.key(), std::process::id());
+        if let Err(e) = file
+            .set_len(0)
+            .and_then(|_| file.rewind())
+            .and_then(|_| file.write_all(holder.as_bytes()))
+        {
+            debug!(?e, ?path, "Failed to record lock holder");
+        }
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// 锁文件名，优先使用容器 ID
+fn lock_name(container: &ContainerInfo) -> String {
+    let id = if container.id.is_empty() {
+        &container.name
+    } else {
+        &container.id
+    };
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// 锁目录，root 与普通用户运行时须使用同一目录才能互斥
+#[cfg(not(test))]
+fn lock_dir() -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(LOCK_DIR_NAME);
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)?;
+        // 与 /tmp 相同的权限，允许所有用户在其中创建锁文件
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o1777))?;
+        }
+    }
+    Ok(dir)
+}
+
+/// 测试并行运行且使用相同的容器 ID，每个测试线程使用独立的锁目录
+#[cfg(test)]
+fn lock_dir() -> Result<PathBuf> {
+    thread_local! {
+        static DIR: tempfile::TempDir = tempfile::tempdir().expect("create lock dir");
+    }
+    let dir = DIR.with(|dir| dir.path().join(LOCK_DIR_NAME));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 打开锁文件；其他用户创建的锁文件不可写时以只读方式打开 (只读文件同样可以加锁)
+fn open_lock_file(path: &Path) -> Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o666);
+    }
+
+    match options.open(path) {
+        Ok(file) => Ok(file),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            Ok(OpenOptions::new().read(true).open(path)?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 从锁文件中读取当前持有者的操作与进程 ID
+fn describe_holder(path: &Path) -> String {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let mut fields = content.split_whitespace();
+    match (
+        fields.next().and_then(LockOperation::from_key),
+        fields.next(),
+    ) {
+        (Some(operation), Some(pid)) => format!("{} (PID {})", operation.describe(), pid),
+        (Some(operation), None) => operation.describe(),
+        _ => t!("commands.lock_operation_unknown").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container() -> ContainerInfo {
+        ContainerInfo {
+            id: "abc123".into(),
+            name: "web".into(),
+            status: "running".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn conflicting_operations_are_rejected() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let container = container();
+
+        let backup = ContainerLock::acquire_in(dir.path(), &container, LockOperation::Backup)?;
+        let err = ContainerLock::acquire_in(dir.path(), &container, LockOperation::RestoreInPlace)
+            .unwrap_err();
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+        assert!(
+            ContainerLock::acquire_in(dir.path(), &container, LockOperation::RestoreToDirectory)
+                .is_err()
+        );
+        drop(backup);
+
+        // 恢复到目录之间共享锁，但会阻止备份
+        let _first =
+            ContainerLock::acquire_in(dir.path(), &container, LockOperation::RestoreToDirectory)?;
+        let _second =
+            ContainerLock::acquire_in(dir.path(), &container, LockOperation::RestoreToDirectory)?;
+        assert!(ContainerLock::acquire_in(dir.path(), &container, LockOperation::Backup).is_err());
+
+        // 其他容器不受影响
+        let other = ContainerInfo {
+            id: "def456".into(),
+            ..container
+        };
+        ContainerLock::acquire_in(dir.path(), &other, LockOperation::Backup)?;
+        Ok(())
+    }
+}
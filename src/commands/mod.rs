@@ -1,13 +1,29 @@
 pub(crate) mod backup;
 pub(crate) mod container;
+pub(crate) mod debug;
+pub(crate) mod interrupt;
 pub(crate) mod lifecycle;
+mod lock;
 mod privileges;
 pub(crate) mod prompt;
 pub(crate) mod restore;
+pub(crate) mod retention;
+pub(crate) mod settings;
+pub(crate) mod status;
 pub(crate) mod symbollink;
+pub(crate) mod verify;
 
 pub(crate) use backup::backup;
 pub(crate) use container::list_containers;
 pub(crate) use restore::restore;
 
-pub(crate) const MAPPING_FILE_NAME: &str = "mapping.toml";
+/// `list` 等命令的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 人类可读的表格
+    Table,
+    /// JSON，仅输出到 stdout
+    Json,
+}
+
+pub(crate) use crate::utils::{CHECKSUMS_FILE_NAME, MAPPING_FILE_NAME};
@@ -3,11 +3,12 @@ use anyhow::Result;
 #[cfg(target_os = "windows")]
 use runas::Command as RunasCommand;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use std::process::Command;
 
 use super::prompt;
+use crate::{config::DockerConfig, docker};
 
 // 检查是否有管理员权限
 pub(super) fn has_admin_privileges() -> bool {
@@ -15,6 +16,108 @@ pub(super) fn has_admin_privileges() -> bool {
     privilege::user::privileged()
 }
 
+/// 判断容器引擎是否以 rootless 模式运行：daemon socket 位于 `$XDG_RUNTIME_DIR` 下，
+/// 或当前进程运行在用户命名空间中
+///
+/// rootless 模式下卷的宿主机路径通常属于当前用户，恢复时不必预先要求管理员权限
+pub(super) fn is_rootless_engine(config: &DockerConfig) -> bool {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    let socket = docker::daemon_socket(config);
+    let rootless =
+        is_rootless_socket(socket.as_deref(), runtime_dir.as_deref()) || in_user_namespace();
+    tracing::debug!(?socket, rootless, "Detected container engine mode");
+    rootless
+}
+
+/// socket 是否位于当前用户的运行时目录下
+fn is_rootless_socket(socket: Option<&Path>, runtime_dir: Option<&Path>) -> bool {
+    match (socket, runtime_dir) {
+        (Some(socket), Some(dir)) => !dir.as_os_str().is_empty() && socket.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// 当前进程是否运行在非恒等映射的用户命名空间中 (如 rootlesskit、`podman unshare`)，
+/// 非 Linux 系统上总是返回 false
+fn in_user_namespace() -> bool {
+    std::fs::read_to_string("/proc/self/uid_map").is_ok_and(|map| !is_identity_uid_map(&map))
+}
+
+/// 初始用户命名空间的 uid_map 为恒等映射 `0 0 4294967295`
+fn is_identity_uid_map(map: &str) -> bool {
+    map.split_whitespace().eq(["0", "0", "4294967295"])
+}
+
+/// 判断当前用户能否在 `path` (或其最近的已存在的上级目录) 中创建文件
+///
+/// 只检查目录的权限，不在文件系统中创建任何内容，可用于预检与 dry-run
+pub(super) fn is_writable(path: &Path) -> bool {
+    path.ancestors()
+        .find(|dir| dir.is_dir())
+        .is_some_and(can_create_in)
+}
+
+/// 判断当前用户能否写入 `path` 及其中所有已存在的条目 (目录中可以创建文件，文件可以覆盖)
+///
+/// 只有顶层目录可写时，其中属于其他用户的子目录或文件仍会使直接写入在中途失败；
+/// `path` 不存在时同 [`is_writable`]。同样不在文件系统中创建任何内容
+pub(super) fn is_tree_writable(path: &Path) -> bool {
+    if std::fs::symlink_metadata(path).is_err() {
+        return is_writable(path);
+    }
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .all(|entry| {
+            entry.is_ok_and(|entry| {
+                let file_type = entry.file_type();
+                if file_type.is_dir() {
+                    can_create_in(entry.path())
+                } else if file_type.is_file() {
+                    can_overwrite(entry.path())
+                } else {
+                    true
+                }
+            })
+        })
+}
+
+/// 通过 `access(2)` 检查当前用户对文件是否有写入权限
+#[cfg(unix)]
+fn can_overwrite(file: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(file) = std::ffi::CString::new(file.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `file` 是以 NUL 结尾的有效字符串，access 不会保留该指针
+    unsafe { libc::access(file.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// Windows 上只能根据只读属性判断
+#[cfg(not(unix))]
+fn can_overwrite(file: &Path) -> bool {
+    std::fs::metadata(file).is_ok_and(|metadata| !metadata.permissions().readonly())
+}
+
+/// 通过 `access(2)` 检查当前用户对目录是否有写入与进入权限
+#[cfg(unix)]
+fn can_create_in(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(dir) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `dir` 是以 NUL 结尾的有效字符串，access 不会保留该指针
+    unsafe { libc::access(dir.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+/// Windows 上只能根据只读属性判断
+#[cfg(not(unix))]
+fn can_create_in(dir: &Path) -> bool {
+    std::fs::metadata(dir).is_ok_and(|metadata| !metadata.permissions().readonly())
+}
+
 pub(super) fn ensure_admin_privileges() -> Result<()> {
     if !has_admin_privileges() {
         prompt::require_admin_privileges_prompt()?;
@@ -26,7 +129,10 @@ pub(super) fn ensure_admin_privileges() -> Result<()> {
 #[allow(unreachable_code)]
 pub(super) fn restart_with_admin_privileges() -> Result<()> {
     let current_exe = std::env::current_exe()?;
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let language = crate::config::Config::global()
+        .map(|config| config.effective_language())
+        .unwrap_or_default();
+    let args = forwarded_args(std::env::args().skip(1).collect(), &language);
 
     #[cfg(debug_assertions)]
     {
@@ -55,8 +161,29 @@ pub(super) fn restart_with_admin_privileges() -> Result<()> {
     Ok(())
 }
 
+/// 以管理员权限重启时转发的命令行参数
+///
+/// 未显式指定 `--language` 时在最前面补上当前生效的语言，避免提权后的进程因读取 root 的配置文件
+/// 或 sudo 重置了环境变量而改用其他语言
+fn forwarded_args(mut args: Vec<String>, language: &str) -> Vec<String> {
+    let explicit = args
+        .iter()
+        .take_while(|arg| *arg != "--")
+        .any(|arg| arg.starts_with("--language") || arg.starts_with("-l"));
+    if !explicit && !language.is_empty() {
+        args.splice(0..0, ["--language".to_string(), language.to_string()]);
+    }
+    args
+}
+
 /// 使用特权方式复制文件或目录
-pub(super) fn privileged_copy(from: &Path, to: &Path) -> Result<()> {
+///
+/// `incremental` 为 true 时只复制目标中大小或修改时间不同的文件，见 [`privileged_sync`]
+pub(super) fn privileged_copy(from: &Path, to: &Path, incremental: bool) -> Result<()> {
+    if incremental {
+        return privileged_sync(from, to);
+    }
+
     // 检查源路径是文件还是目录
     let is_dir = std::fs::metadata(from)?.is_dir();
 
@@ -171,3 +298,136 @@ pub(super) fn privileged_copy(from: &Path, to: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// 增量复制文件或目录，跳过目标中大小与修改时间都与源相同的文件
+///
+/// 已有管理员权限时在进程内完成复制；Linux/macOS 下需要通过 sudo 复制时改用 `rsync -a`，
+/// 找不到 rsync 时给出警告并回退到完整复制
+fn privileged_sync(from: &Path, to: &Path) -> Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    if !has_admin_privileges() {
+        if !rsync_available() {
+            crate::log_println!("WARN", "{}", t!("privileges.rsync_not_found"));
+            return privileged_copy(from, to, false);
+        }
+        // 源为目录时以 `/` 结尾，rsync 才会复制目录中的内容而不是目录本身
+        let source = if from.is_dir() {
+            from.join("")
+        } else {
+            from.to_path_buf()
+        };
+        let status = Command::new("sudo")
+            .arg("rsync")
+            .arg("-a")
+            .arg(source)
+            .arg(to)
+            .status()
+            .map_err(|e| anyhow::anyhow!("{}", t!("privileges.copy_failed", "error" = e)))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(
+                "{}",
+                t!("privileges.copy_failed", "error" = "sudo rsync")
+            ));
+        }
+        return Ok(());
+    }
+
+    let stats = crate::utils::sync_tree(from, to)
+        .map_err(|e| anyhow::anyhow!("{}", t!("privileges.copy_failed", "error" = e)))?;
+    tracing::debug!(?from, ?to, ?stats, "Incremental copy finished");
+    println!(
+        "{}",
+        t!(
+            "privileges.incremental_copy_summary",
+            "copied" = stats.copied,
+            "skipped" = stats.skipped
+        )
+    );
+    Ok(())
+}
+
+/// 系统中是否可以执行 `rsync`
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn rsync_available() -> bool {
+    Command::new("rsync")
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwards_language_to_elevated_process() {
+        use crate::{Cli, Language};
+        use clap::Parser;
+
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let language = |args: Vec<String>| {
+            Cli::parse_from(std::iter::once("rdbkp2".to_string()).chain(args)).language
+        };
+
+        // 显式指定的语言原样转发
+        let explicit = forwarded_args(args(&["restore", "-l", "en"]), "zh-CN");
+        assert_eq!(explicit, args(&["restore", "-l", "en"]));
+        assert_eq!(language(explicit), Some(Language::En));
+
+        // 未指定时补上当前生效的语言
+        let resolved = forwarded_args(args(&["restore", "-c", "web"]), "en");
+        assert_eq!(language(resolved), Some(Language::En));
+        let resolved = forwarded_args(args(&["restore"]), "zh-CN");
+        assert_eq!(language(resolved), Some(Language::Zh));
+    }
+
+    #[test]
+    fn checks_writability_without_touching_the_filesystem() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target = dir.path().join("missing/volume");
+        assert!(is_writable(&target));
+        assert!(!dir.path().join("missing").exists());
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn tree_with_read_only_subdirectory_is_not_writable() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // root 不受文件权限限制
+        if has_admin_privileges() {
+            return Ok(());
+        }
+        let dir = tempfile::tempdir()?;
+        let locked = dir.path().join("volume/locked");
+        std::fs::create_dir_all(&locked)?;
+        std::fs::write(locked.join("data.txt"), "data")?;
+        assert!(is_tree_writable(&dir.path().join("volume")));
+
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555))?;
+        let writable = is_tree_writable(&dir.path().join("volume"));
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755))?;
+        assert!(is_writable(&dir.path().join("volume")));
+        assert!(!writable);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_rootless_socket_and_user_namespace() {
+        let runtime_dir = Path::new("/run/user/1000");
+        let rootless = Path::new("/run/user/1000/docker.sock");
+        let rootful = Path::new("/var/run/docker.sock");
+        assert!(is_rootless_socket(Some(rootless), Some(runtime_dir)));
+        assert!(!is_rootless_socket(Some(rootful), Some(runtime_dir)));
+        assert!(!is_rootless_socket(Some(rootless), None));
+        assert!(!is_rootless_socket(None, Some(runtime_dir)));
+        assert!(!is_rootless_socket(Some(rootless), Some(Path::new(""))));
+
+        assert!(is_identity_uid_map("         0          0 4294967295\n"));
+        assert!(!is_identity_uid_map("         0       1000          1\n"));
+    }
+}
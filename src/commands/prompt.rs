@@ -1,11 +1,13 @@
 use crate::{
     commands::privileges::{has_admin_privileges, restart_with_admin_privileges},
-    docker::{ContainerInfo, DockerClientInterface, VolumeInfo},
+    config::Config,
+    docker::{ContainerInfo, DockerClientInterface, SHORT_ID_LEN, VolumeInfo},
     log_bail, log_println,
 };
 
 use anyhow::Result;
-use dialoguer::{Confirm, MultiSelect, Select};
+use dialoguer::{Confirm, FuzzySelect, MultiSelect, Select, console::Term};
+use std::io::IsTerminal;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{debug, info};
 
@@ -28,6 +30,15 @@ pub(super) fn require_admin_privileges_prompt() -> Result<()> {
         return Ok(());
     }
 
+    // 非交互模式或没有终端时无法确认，直接报错而不是让提示出错或挂起
+    if !can_prompt() {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("prompt.admin_privileges_required_non_interactive")
+        );
+    }
+
     log_println!(
         "WARN",
         "❌ Please run as sudo user when restore the required container volume(s)."
@@ -50,22 +61,41 @@ pub(super) fn require_admin_privileges_prompt() -> Result<()> {
     )
 }
 
+/// 是否可以向用户显示确认提示：需处于交互模式，且 stdin 与 stderr 都连接到终端
+fn can_prompt() -> bool {
+    let interactive = Config::global().is_ok_and(|config| config.interactive);
+    interactive && std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
 pub(super) async fn select_container_prompt<T: DockerClientInterface>(
     client: &T,
 ) -> Result<ContainerInfo> {
     debug!("Getting container list for selection");
     let containers = client.list_containers().await?;
-    let container_names: Vec<&String> = containers.iter().map(|c| &c.name).collect();
-
-    debug!("Displaying container selection prompt");
-    let selection = Select::new()
-        .with_prompt(prompt_select(&format!(
-            "{}",
-            t!("prompt.select_container_prompt")
-        )))
-        .items(&container_names)
-        .default(0)
-        .interact()?;
+    let labels: Vec<String> = containers.iter().map(container_label).collect();
+
+    let selection = if supports_fuzzy_input() {
+        debug!("Displaying fuzzy container selection prompt");
+        FuzzySelect::new()
+            .with_prompt(prompt_select(&format!(
+                "{} {}",
+                t!("prompt.select_container_prompt"),
+                t!("prompt.type_to_filter")
+            )))
+            .items(&labels)
+            .default(0)
+            .interact()?
+    } else {
+        debug!("Terminal does not support fuzzy input, displaying container selection prompt");
+        Select::new()
+            .with_prompt(prompt_select(&format!(
+                "{}",
+                t!("prompt.select_container_prompt")
+            )))
+            .items(&labels)
+            .default(0)
+            .interact()?
+    };
 
     let selected = containers[selection].clone();
     info!(
@@ -76,12 +106,19 @@ pub(super) async fn select_container_prompt<T: DockerClientInterface>(
     Ok(selected)
 }
 
-#[allow(dead_code)]
-pub(super) async fn select_containers_prompt<T: DockerClientInterface>(
-    client: &T,
-) -> Result<Vec<ContainerInfo>> {
-    debug!("Getting container list for selection");
-    let containers = client.list_containers().await?;
+/// 容器在选择列表中显示的文本：`名称 (短 ID) [状态]`，便于区分同名或相似名称的容器
+fn container_label(container: &ContainerInfo) -> String {
+    let short_id = container.id.get(..SHORT_ID_LEN).unwrap_or(&container.id);
+    format!("{} ({}) [{}]", container.name, short_id, container.status)
+}
+
+/// 终端是否支持 [`FuzzySelect`] 需要的逐键输入与光标移动，不支持时改用 [`Select`]
+fn supports_fuzzy_input() -> bool {
+    Term::stderr().features().is_attended()
+        && std::env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+pub(super) fn select_containers_prompt(containers: &[ContainerInfo]) -> Result<Vec<ContainerInfo>> {
     let container_names: Vec<&String> = containers.iter().map(|c| &c.name).collect();
 
     debug!("Displaying container multi-selection prompt");
@@ -153,3 +190,23 @@ pub(super) fn select_volume_prompt(volumes: &[VolumeInfo]) -> Result<VolumeInfo>
     );
     Ok(selected)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_labels_show_short_id_and_status() {
+        let container = |id: &str| ContainerInfo {
+            id: id.into(),
+            name: "web".into(),
+            status: "Up 3 hours".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            container_label(&container(&"3f4a5b6c7d8e".repeat(5))),
+            "web (3f4a5b6c7d8e) [Up 3 hours]"
+        );
+        assert_eq!(container_label(&container("abc")), "web (abc) [Up 3 hours]");
+    }
+}
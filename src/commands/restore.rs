@@ -1,27 +1,367 @@
 use crate::{
-    commands::{MAPPING_FILE_NAME, container, prompt},
+    commands::{
+        CHECKSUMS_FILE_NAME, MAPPING_FILE_NAME, OutputFormat, backup,
+        container::{self, ComposeService},
+        interrupt,
+        lock::{ContainerLock, LockOperation},
+        prompt,
+    },
     config::Config,
-    docker::{BackupMapping, ContainerInfo, DockerClient, DockerClientInterface, VolumeInfo},
+    docker::{
+        BackupMapping, ContainerInfo, DockerClientInterface, MAPPING_SCHEMA_VERSION, UploadBody,
+        VolumeInfo,
+    },
     log_bail, log_println,
-    utils::{self, ensure_dir_exists, unpack_archive},
+    utils::{
+        self, ChecksumFailure, ChecksumManifest, ensure_dir_exists, unpack_archive_filtered,
+        unpack_archive_routed,
+    },
 };
 
 use anyhow::Result;
+use chrono::NaiveDate;
 use dialoguer::{Confirm, Input, Select};
-use std::path::PathBuf;
-use tempfile::tempdir;
-use tracing::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tempfile::{NamedTempFile, TempDir};
+use tracing::{debug, info, warn};
 
 use super::privileges;
 
-pub async fn restore(
+/// 恢复命令的附加选项
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// 跳过备份文件的校验和验证
+    pub no_verify: bool,
+
+    /// 只恢复这些名称的卷，为空时恢复全部卷 (交互模式下会提示选择)
+    pub volumes: Vec<String>,
+
+    /// 解压后按备份内的校验清单逐个校验文件
+    pub verify_checksums: bool,
+
+    /// 逐文件校验失败时仅给出警告而不中止恢复
+    pub best_effort: bool,
+
+    /// 从目录中选择备份文件时，只考虑文件名中的时间不早于该日期的备份
+    pub since: Option<NaiveDate>,
+
+    /// 通过 Docker API 将卷内容直接写入容器，而不是写入宿主机上的卷路径
+    pub into_container: bool,
+
+    /// 解压前打印每个卷将被恢复到的位置；非交互模式下只打印不恢复
+    pub print_mapping: Option<OutputFormat>,
+
+    /// 容器重启后在容器内执行的命令
+    pub post_restore_exec: Option<String>,
+
+    /// 解压到临时目录时使用的父目录，未设置时使用系统临时目录
+    pub temp_dir: Option<PathBuf>,
+
+    /// 允许将备份恢复到与备份来源不同名的容器，卷按容器内的挂载路径匹配
+    pub force_container: bool,
+
+    /// 原地恢复前先将卷的当前内容备份到备份目录中
+    pub snapshot_before_restore: bool,
+
+    /// 恢复到目录时各卷在输出目录中的布局
+    pub layout: RestoreLayout,
+
+    /// 按 compose 项目与服务名查找要恢复的容器，代替容器名称或 ID
+    pub compose_service: Option<ComposeService>,
+
+    /// 原地恢复时先解压到临时目录，再只复制卷中大小或修改时间有变化的文件
+    pub incremental_restore: bool,
+}
+
+/// 恢复到目录 (`--output`) 时输出目录的布局
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestoreLayout {
+    /// 按压缩包中的原样解压，单文件卷直接位于输出目录下
+    #[default]
+    Flat,
+    /// 每个卷 (包括备份时合并的卷) 都解压到 `<输出目录>/<卷名>/` 下
+    PerVolume,
+}
+
+impl RestoreOptions {
+    /// 是否只打印恢复映射而不进行恢复
+    fn is_preflight_only(&self, interactive: bool) -> bool {
+        self.print_mapping.is_some() && !interactive
+    }
+
+    /// 重启后要执行的命令；该命令只在容器重启后执行，未启用重启时给出提示并返回 `None`
+    fn post_restore_command(&self, restart: bool) -> Option<&str> {
+        let command = self.post_restore_exec.as_deref()?;
+        if !restart {
+            log_println!(
+                "WARN",
+                "{}",
+                t!("commands.post_restore_exec_requires_restart")
+            );
+            return None;
+        }
+        Some(command)
+    }
+}
+
+/// 待恢复的备份文件及其元数据
+struct BackupArchive<'a> {
+    /// 备份文件路径
+    path: &'a PathBuf,
+    /// 备份文件中的 mapping (已按选中的卷过滤)
+    mapping: BackupMapping,
+    /// 增量备份链中的基础备份 (从旧到新)，完整备份时为空
+    bases: Vec<PathBuf>,
+    /// 基础备份中存在、但在本次备份时已被删除的文件，恢复时跳过基础备份中的这些条目
+    deleted: BTreeSet<String>,
+    /// 启用 `--verify-checksums` 时读取的逐文件校验清单
+    manifest: Option<ChecksumManifest>,
+    /// 逐文件校验失败时是否仅警告
+    best_effort: bool,
+    /// 先解压到临时目录时使用的父目录，为 `None` 时使用系统临时目录
+    temp_dir: Option<PathBuf>,
+    /// 从临时目录复制到卷时跳过大小与修改时间未变化的文件
+    incremental_copy: bool,
+}
+
+impl BackupArchive<'_> {
+    /// 按恢复顺序排列的备份链：先是各基础备份，最后是备份文件本身
+    fn layers(&self) -> impl Iterator<Item = &Path> {
+        self.bases
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(std::iter::once(self.path.as_path()))
+    }
+
+    /// 选中的卷名，归档带有索引时据此只读取这些卷的数据
+    fn volume_names(&self) -> Vec<&str> {
+        self.mapping
+            .volumes
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect()
+    }
+
+    /// 判断压缩包条目是否属于选中的卷，且在备份时仍然存在
+    fn is_selected_entry(&self, path: &Path) -> bool {
+        path.components().next().is_some_and(|first| {
+            self.mapping
+                .volumes
+                .iter()
+                .any(|v| first.as_os_str() == v.name.as_str())
+        }) && !self.is_deleted(path)
+    }
+
+    /// 判断条目是否为基础备份之后被删除的文件
+    fn is_deleted(&self, path: &Path) -> bool {
+        !self.deleted.is_empty() && self.deleted.contains(&utils::archive_key(path))
+    }
+
+    /// 按归档条目头还原 `route` 映射到的已解压文件的属主与权限，备份未使用 `--preserve-ownership` 时直接返回
+    ///
+    /// 只在以管理员身份运行时才能还原，否则文件属于当前用户并给出警告
+    fn restore_ownership(&self, route: impl Fn(&Path) -> Option<PathBuf>) -> Result<()> {
+        if !self.mapping.preserve_ownership {
+            return Ok(());
+        }
+        if !privileges::has_admin_privileges() {
+            log_println!(
+                "WARN",
+                "{}",
+                t!("commands.ownership_not_restored_unprivileged")
+            );
+            return Ok(());
+        }
+        let names = self.volume_names();
+        let mut count = 0;
+        for layer in self.layers() {
+            count += utils::apply_archive_ownership(layer, &names, &route)?;
+        }
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.ownership_restored",
+                "count" = utils::format_number(count as u64)
+            )
+        );
+        Ok(())
+    }
+
+    /// 选中的卷在备份时记录的文件总大小，即全部解压所需的空间；旧版本备份中没有记录时返回 `None`
+    fn uncompressed_size(&self) -> Option<u64> {
+        if self.mapping.files.is_empty() {
+            return None;
+        }
+        let size = self
+            .mapping
+            .files
+            .iter()
+            .filter(|(key, _)| self.is_selected_entry(Path::new(key)))
+            .map(|(_, stamp)| stamp.size)
+            .sum();
+        Some(size)
+    }
+
+    /// 在 `--temp-dir` (未指定时为系统临时目录) 下创建解压用的临时目录
+    ///
+    /// 解压前检查可用空间，放不下选中的卷时提前报错，避免解压到一半才因空间不足失败
+    fn create_staging_dir(&self) -> Result<TempDir> {
+        let parent = self.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+        ensure_dir_exists(&parent)?;
+        if let Some(required) = self.uncompressed_size() {
+            let available = fs4::available_space(&parent)?;
+            debug!(
+                ?parent,
+                required, available, "Checking space for staging directory"
+            );
+            if available < required {
+                log_bail!(
+                    "ERROR",
+                    "{}",
+                    t!(
+                        "commands.temp_dir_insufficient_space",
+                        "path" = parent.to_string_lossy(),
+                        "required" = utils::format_bytes(required),
+                        "available" = utils::format_bytes(available)
+                    )
+                );
+            }
+        }
+        Ok(tempfile::tempdir_in(&parent)?)
+    }
+
+    /// 通过宿主机路径恢复的卷 (绑定挂载)
+    fn host_volumes(&self) -> impl Iterator<Item = &VolumeInfo> {
+        self.mapping
+            .volumes
+            .iter()
+            .filter(|v| v.volume_name.is_none())
+    }
+
+    /// 通过辅助容器恢复的 Docker 命名卷
+    fn named_volumes(&self) -> impl Iterator<Item = &VolumeInfo> {
+        self.mapping
+            .volumes
+            .iter()
+            .filter(|v| v.volume_name.is_some())
+    }
+
+    /// 原地恢复时压缩包条目 `<卷名>/...` 对应的宿主机路径，不属于选中的绑定挂载卷的条目返回 `None`
+    fn in_place_target(&self, path: &Path) -> Option<PathBuf> {
+        let mut components = path.components();
+        let first = components.next()?;
+        let volume = self
+            .host_volumes()
+            .find(|v| first.as_os_str() == v.name.as_str())?;
+        if self.is_deleted(path) {
+            return None;
+        }
+        // 单文件卷的条目即卷本身，不能拼接为 `<source>/`
+        let rest = components.as_path();
+        if rest.as_os_str().is_empty() {
+            return Some(volume.source.clone());
+        }
+        Some(volume.source.join(rest))
+    }
+
+    /// 原地恢复时每个绑定挂载卷 (含合并的别名卷) 将被写入的宿主机路径
+    fn restore_targets(&self) -> Vec<(&str, &Path)> {
+        let volumes = self
+            .host_volumes()
+            .map(|v| (v.name.as_str(), v.source.as_path()));
+        let aliases = self
+            .mapping
+            .aliases
+            .iter()
+            .filter(|a| {
+                self.host_volumes()
+                    .any(|v| v.name == a.alias_of && v.source != a.volume.source)
+            })
+            .map(|a| (a.volume.name.as_str(), a.volume.source.as_path()));
+        volumes.chain(aliases).collect()
+    }
+
+    /// 校验解压到 `root` 下的文件，未启用 `--verify-checksums` 时直接返回
+    fn verify_extracted(&self, root: &Path) -> Result<()> {
+        let Some(manifest) = &self.manifest else {
+            return Ok(());
+        };
+
+        let (verified, failures) =
+            manifest.verify(|path| self.is_selected_entry(path).then(|| root.join(path)))?;
+        for failure in &failures {
+            let message = match failure {
+                ChecksumFailure::Mismatch(name) => {
+                    t!("commands.file_checksum_mismatch", "name" = name)
+                }
+                ChecksumFailure::Missing(name) => {
+                    t!("commands.file_checksum_missing", "name" = name)
+                }
+            };
+            log_println!("WARN", "{}", message);
+        }
+
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.files_verified",
+                "verified" = utils::format_number(verified as u64),
+                "failed" = utils::format_number(failures.len() as u64)
+            )
+        );
+
+        if !failures.is_empty() && !self.best_effort {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.files_verification_failed",
+                    "failed" = failures.len()
+                )
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 恢复需要写入宿主机上的卷路径时，提示以管理员权限重新运行
+///
+/// 由调用方在连接 daemon 之前调用，以便无权访问 daemon socket 的用户同样能得到提示；
+/// `restore` 与 `restore_all` 本身不检查
+pub fn require_privileges(options: &RestoreOptions) -> Result<()> {
+    let config = Config::global()?;
+    // 写入容器时经由 Docker API 完成，不需要访问宿主机上的卷路径；
+    // rootless 模式下推迟到确定恢复目标后，再按实际写权限决定是否需要管理员权限
+    if !config.dry_run
+        && !options.into_container
+        && !options.is_preflight_only(config.interactive)
+        && !privileges::is_rootless_engine(&config.docker)
+    {
+        prompt::require_admin_privileges_prompt()?;
+    }
+    Ok(())
+}
+
+pub async fn restore<T: DockerClientInterface>(
+    client: &T,
     container: Option<String>,
     input: Option<String>,
     output: Option<String>,
+    options: RestoreOptions,
 ) -> Result<()> {
-    prompt::require_admin_privileges_prompt()?;
-
     let config = Config::global()?;
+    let preflight_only = options.is_preflight_only(config.interactive);
+    // JSON 格式的映射表写入 stdout，提示信息不能混在其中
+    let _stdout =
+        utils::StdoutReservation::acquire(options.print_mapping == Some(OutputFormat::Json));
+
     let interactive = config.interactive;
     let restart = config.restart;
     let yes = config.yes;
@@ -29,45 +369,263 @@ pub async fn restore(
     info!(
         ?container,
         ?input,
+        ?options,
         restart,
         interactive,
         "Starting restore operation"
     );
 
-    let client = DockerClient::global()?;
-    let container_info = container::select_container(&client, container, interactive).await?;
-    let file_path = parse_restore_file(input, interactive, &container_info)?;
+    let post_restore = options
+        .post_restore_command(restart)
+        .filter(|_| !preflight_only);
+
+    let container_info = container::select_container(
+        client,
+        container,
+        options.compose_service.as_ref(),
+        interactive,
+    )
+    .await?;
+    // 从标准输入读取的备份写入临时文件，恢复结束后删除
+    let stdin_spool = match input.as_deref() {
+        Some(STDIN_INPUT) => Some(spool_stdin(options.temp_dir.as_deref())?),
+        _ => None,
+    };
+    let file_path = match &stdin_spool {
+        Some((spool, _)) => spool.path().to_path_buf(),
+        None => parse_restore_file(input, interactive, &container_info, options.since)?,
+    };
 
-    restore_volumes(
-        &client,
+    let _restart_on_interrupt = (restart && !config.dry_run && !preflight_only)
+        .then(|| interrupt::RestartOnInterrupt::track(&container_info));
+    let result = restore_volumes(
+        client,
         &container_info,
         &file_path,
         output,
         interactive,
         yes,
+        &options,
     )
-    .await?;
+    .await;
 
-    if restart {
-        log_println!(
-            "INFO",
+    // 恢复失败时同样重启，避免容器因一次失败的恢复而一直处于停止状态
+    if restart && !config.dry_run && !preflight_only {
+        container::restart_after(client, &container_info, result, config.wait_healthy).await?;
+    } else {
+        result?;
+    }
+
+    // 重启失败时已在上面返回，不会执行该命令
+    if let Some(command) = post_restore {
+        container::run_post_restore_exec(client, &container_info, command).await?;
+    }
+
+    Ok(())
+}
+
+/// 批量恢复中单个容器的结果
+#[derive(Debug)]
+enum BulkRestoreOutcome {
+    /// 已恢复
+    Restored,
+    /// 主机上不存在同名容器，已跳过
+    Skipped,
+    /// 恢复失败
+    Failed(String),
+}
+
+/// 从目录中批量恢复：为每个存在匹配备份的容器恢复其最新的备份
+///
+/// 备份通过其 mapping 中记录的容器名与容器匹配，主机上不存在的容器会被跳过。
+/// 所有容器处理完后汇总输出结果，任一容器恢复失败时返回错误
+pub async fn restore_all<T: DockerClientInterface>(
+    client: &T,
+    input_dir: Option<String>,
+    options: RestoreOptions,
+) -> Result<()> {
+    let config = Config::global()?;
+    let _stdout =
+        utils::StdoutReservation::acquire(options.print_mapping == Some(OutputFormat::Json));
+
+    let input_dir = input_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config.backup_dir.clone());
+    let input_dir = utils::resolve_path(&input_dir)?;
+    info!(?input_dir, ?options, "Starting bulk restore operation");
+
+    let archives = latest_archives_by_container(&input_dir)?;
+    if archives.is_empty() {
+        log_bail!(
+            "ERROR",
             "{}",
             t!(
-                "commands.restarting_container",
-                "name" = container_info.name
+                "commands.bulk_restore_no_archives",
+                "dir" = input_dir.to_string_lossy()
             )
         );
-        client.restart_container(&container_info.id).await?;
-        log_println!(
-            "INFO",
-            "{}",
-            t!("commands.container_restarted", "name" = container_info.name)
+    }
+
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.bulk_restore_plan",
+            "count" = archives.len(),
+            "dir" = input_dir.to_string_lossy()
+        )
+    );
+    for (name, path) in &archives {
+        println!(
+            " - {} <- {}",
+            name,
+            path.file_name().unwrap_or_default().to_string_lossy()
         );
     }
 
+    if !config.yes && config.interactive && !config.dry_run {
+        let confirmed = Confirm::new()
+            .with_prompt(t!("commands.bulk_restore_confirm"))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            log_println!("INFO", "{}", t!("prompt.restore_cancelled"));
+            return Ok(());
+        }
+    }
+
+    let outcomes = restore_archives(
+        client,
+        &archives,
+        &options,
+        config.restart,
+        config.wait_healthy,
+    )
+    .await?;
+    print_bulk_restore_summary(&outcomes);
+
+    let failed = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, BulkRestoreOutcome::Failed(_)))
+        .count();
+    if failed > 0 {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.bulk_restore_failed", "failed" = failed)
+        );
+    }
     Ok(())
 }
 
+/// 依次将备份恢复到同名容器，单个容器失败不影响其他容器
+async fn restore_archives<T: DockerClientInterface>(
+    client: &T,
+    archives: &BTreeMap<String, PathBuf>,
+    options: &RestoreOptions,
+    restart: bool,
+    wait_healthy: Option<Duration>,
+) -> Result<Vec<(String, BulkRestoreOutcome)>> {
+    let containers = client.list_containers().await?;
+    let post_restore = options.post_restore_command(restart);
+    let mut outcomes = Vec::new();
+
+    for (name, path) in archives {
+        let Some(container_info) = containers.iter().find(|c| &c.name == name) else {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.bulk_restore_container_missing",
+                    "name" = name,
+                    "path" = path.to_string_lossy()
+                )
+            );
+            outcomes.push((name.clone(), BulkRestoreOutcome::Skipped));
+            continue;
+        };
+
+        let _restart_on_interrupt = (restart && !Config::is_dry_run())
+            .then(|| interrupt::RestartOnInterrupt::track(container_info));
+        let result = async {
+            let restored =
+                restore_volumes(client, container_info, path, None, false, true, options).await;
+            if restart && !Config::is_dry_run() {
+                container::restart_after(client, container_info, restored, wait_healthy).await?;
+            } else {
+                restored?;
+            }
+            if let Some(command) = post_restore {
+                container::run_post_restore_exec(client, container_info, command).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        let outcome = match result {
+            Ok(()) => BulkRestoreOutcome::Restored,
+            Err(e) => BulkRestoreOutcome::Failed(e.to_string()),
+        };
+        outcomes.push((name.clone(), outcome));
+    }
+
+    Ok(outcomes)
+}
+
+fn print_bulk_restore_summary(outcomes: &[(String, BulkRestoreOutcome)]) {
+    log_println!("INFO", "{}", t!("commands.bulk_restore_summary"));
+    for (name, outcome) in outcomes {
+        let line = match outcome {
+            BulkRestoreOutcome::Restored => t!("commands.bulk_restore_restored", "name" = name),
+            BulkRestoreOutcome::Skipped => t!("commands.bulk_restore_skipped", "name" = name),
+            BulkRestoreOutcome::Failed(error) => t!(
+                "commands.bulk_restore_container_failed",
+                "name" = name,
+                "error" = error
+            ),
+        };
+        println!("{}", line);
+    }
+}
+
+/// 查找目录 (含子目录) 中的备份文件，按 mapping 中的容器名分组并只保留最新的一个
+///
+/// 不按扩展名筛选，无法读取 mapping 的文件与原地恢复前的快照会被跳过
+fn latest_archives_by_container(dir: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    let mut files = utils::get_files_start_with(dir, "", true)?;
+    files.retain(|f| {
+        !utils::is_sidecar_file(f)
+            && !backup::is_latest_pointer(f)
+            && !is_pre_restore_snapshot(dir, f)
+    });
+
+    let mut latest: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+    for file in files {
+        let mapping = utils::read_file_from_archive(&file, MAPPING_FILE_NAME)
+            .and_then(|content| parse_mapping(&content));
+        let mapping = match mapping {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                warn!(?file, ?e, "Skipping unreadable backup archive");
+                continue;
+            }
+        };
+
+        // backup_time 的格式为 `%Y-%m-%d %H:%M:%S`，可直接按字符串比较
+        let is_newer = latest
+            .get(&mapping.container_name)
+            .is_none_or(|(time, _)| mapping.backup_time > *time);
+        if is_newer {
+            latest.insert(mapping.container_name, (mapping.backup_time, file));
+        }
+    }
+
+    Ok(latest
+        .into_iter()
+        .map(|(name, (_, path))| (name, path))
+        .collect())
+}
+
 async fn restore_volumes<T: DockerClientInterface>(
     client: &T,
     container_info: &ContainerInfo,
@@ -75,56 +633,141 @@ async fn restore_volumes<T: DockerClientInterface>(
     output: Option<String>,
     interactive: bool,
     yes: bool,
+    options: &RestoreOptions,
 ) -> Result<()> {
     let mapping_content = utils::read_file_from_archive(file_path, MAPPING_FILE_NAME)?;
-    let backup_mapping: BackupMapping = toml::from_str(&mapping_content)?;
+    let mut backup_mapping = parse_mapping(&mapping_content)?;
+
+    if !options.no_verify {
+        verify_backup_checksum(file_path, &backup_mapping)?;
+    }
 
     if container_info.name != backup_mapping.container_name {
-        log_bail!(
-            "ERROR",
+        if !options.force_container {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.backup_is_for_container",
+                    "backup_container" = backup_mapping.container_name,
+                    "restore_container" = container_info.name
+                )
+            );
+        }
+        log_println!(
+            "WARN",
             "{}",
             t!(
-                "commands.backup_is_for_container",
+                "commands.restoring_into_other_container",
+                "file" = file_path.to_string_lossy(),
                 "backup_container" = backup_mapping.container_name,
                 "restore_container" = container_info.name
             )
         );
+        let targets = client.get_container_volumes(&container_info.id).await?;
+        remap_volumes_by_destination(&mut backup_mapping, &targets);
+        if backup_mapping.volumes.is_empty() {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.no_volume_destination_matched",
+                    "name" = container_info.name
+                )
+            );
+        }
+    }
+
+    if let Some(cutoff) = &backup_mapping.modified_after {
+        log_println!(
+            "WARN",
+            "{}",
+            t!("commands.backup_is_age_filtered", "time" = cutoff)
+        );
+    }
+
+    let selected = select_restore_volumes(&backup_mapping.volumes, &options.volumes, interactive)?;
+    if selected.is_empty() {
+        log_println!("INFO", "{}", t!("prompt.restore_cancelled"));
+        return Ok(());
+    }
+    retain_volumes(&mut backup_mapping, &selected);
+
+    let (bases, deleted) = resolve_backup_chain(file_path, &backup_mapping, !options.no_verify)?;
+    let archive = BackupArchive {
+        path: file_path,
+        mapping: backup_mapping,
+        bases,
+        deleted,
+        manifest: load_checksum_manifest(file_path, options)?,
+        best_effort: options.best_effort,
+        temp_dir: options.temp_dir.clone(),
+        incremental_copy: options.incremental_restore,
+    };
+
+    if let Some(format) = options.print_mapping {
+        let rows = restore_mapping_rows(
+            &archive,
+            container_info,
+            output.as_deref().map(Path::new),
+            options.into_container,
+        );
+        print_restore_mapping(container_info, &rows, format)?;
+        if !interactive {
+            return Ok(());
+        }
+    }
+
+    if options.into_container {
+        return restore_into_container(client, container_info, &archive, interactive, yes).await;
+    }
+
+    if !Config::is_dry_run() {
+        ensure_host_targets_writable(&archive, container_info, output.as_deref().map(Path::new))?;
     }
 
     if let Some(output_path) = output {
         return restore_to_directory(
             client,
             container_info,
-            file_path,
+            &archive,
             output_path,
+            options.layout,
             interactive,
             yes,
         )
         .await;
     }
 
-    restore_in_place(
-        client,
-        container_info,
-        file_path,
-        &backup_mapping.volumes,
-        interactive,
-        yes,
-    )
-    .await
+    let snapshot = options.snapshot_before_restore;
+    restore_in_place(client, container_info, &archive, interactive, yes, snapshot).await
 }
 
 async fn restore_to_directory<T: DockerClientInterface>(
     client: &T,
     container_info: &ContainerInfo,
-    file_path: &PathBuf,
+    archive: &BackupArchive<'_>,
     output_path: String,
+    layout: RestoreLayout,
     interactive: bool,
     yes: bool,
 ) -> Result<()> {
     let output_path = PathBuf::from(output_path);
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.dry_run_restore_to_directory",
+                "path" = utils::absolute_simplified_path(&output_path)?.to_string_lossy()
+            )
+        );
+        container::ensure_container_stopped(client, container_info).await?;
+        log_println!("INFO", "{}", t!("commands.dry_run_no_changes"));
+        return Ok(());
+    }
     ensure_dir_exists(&output_path)?;
-    let output_path = utils::absolute_canonicalize_path(&output_path)?;
+    let output_path = utils::resolve_path(&output_path)?;
 
     if !yes && interactive {
         let confirmed = Confirm::new()
@@ -141,22 +784,60 @@ async fn restore_to_directory<T: DockerClientInterface>(
         }
     }
 
+    let _lock = ContainerLock::acquire(container_info, LockOperation::RestoreToDirectory)?;
     container::ensure_container_stopped(client, container_info).await?;
-    unpack_archive_to(container_info, file_path, &output_path).await
+    unpack_archive_to(container_info, archive, &output_path).await?;
+    if layout == RestoreLayout::PerVolume {
+        split_into_volume_dirs(&archive.mapping, &output_path)?;
+    }
+    Ok(())
 }
 
 async fn restore_in_place<T: DockerClientInterface>(
     client: &T,
     container_info: &ContainerInfo,
-    file_path: &PathBuf,
-    volumes: &[VolumeInfo],
+    archive: &BackupArchive<'_>,
     interactive: bool,
     yes: bool,
+    snapshot: bool,
 ) -> Result<()> {
+    if Config::is_dry_run() {
+        for (name, target) in archive.restore_targets() {
+            log_println!(
+                "INFO",
+                "{}",
+                t!(
+                    "commands.dry_run_restore_target",
+                    "name" = name,
+                    "path" = target.to_string_lossy()
+                )
+            );
+        }
+        for volume in archive.named_volumes() {
+            log_println!(
+                "INFO",
+                "{}",
+                t!(
+                    "commands.dry_run_restore_target",
+                    "name" = volume.name,
+                    "path" = named_volume_target(volume)
+                )
+            );
+        }
+        container::ensure_container_stopped(client, container_info).await?;
+        log_println!("INFO", "{}", t!("commands.dry_run_no_changes"));
+        return Ok(());
+    }
+
     if !yes && interactive {
-        let prompt_text = volumes
+        let prompt_text = archive
+            .mapping
+            .volumes
             .iter()
-            .map(|v| format!(" - {} -> {}", v.name, v.source.display()))
+            .map(|v| match v.volume_name {
+                Some(_) => format!(" - {} -> {}", v.name, named_volume_target(v)),
+                None => format!(" - {} -> {}", v.name, v.source.display()),
+            })
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -174,260 +855,1960 @@ async fn restore_in_place<T: DockerClientInterface>(
         }
     }
 
+    let _lock = ContainerLock::acquire(container_info, LockOperation::RestoreInPlace)?;
     container::ensure_container_stopped(client, container_info).await?;
-    unpack_archive_move(container_info, file_path, volumes).await
+
+    let snapshot = if snapshot {
+        let snapshot_dir = Config::global()
+            .unwrap_or_default()
+            .backup_dir
+            .join(PRE_RESTORE_DIR);
+        take_pre_restore_snapshot(container_info, archive, &snapshot_dir)?
+    } else {
+        None
+    };
+
+    // 中断时提示卷只恢复了一部分
+    let targets = archive
+        .restore_targets()
+        .into_iter()
+        .map(|(_, target)| target.to_string_lossy().to_string())
+        .chain(archive.named_volumes().map(named_volume_target))
+        .collect();
+    let _in_progress =
+        interrupt::RestoreInProgress::track(container_info, targets, snapshot.clone());
+    let result = async {
+        unpack_archive_move(container_info, archive).await?;
+        archive.restore_ownership(|path| archive.in_place_target(path))?;
+        restore_named_volumes(client, container_info, archive).await
+    }
+    .await;
+    if let (Err(_), Some(snapshot)) = (&result, &snapshot) {
+        log_println!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.pre_restore_snapshot_hint",
+                "path" = snapshot.to_string_lossy(),
+                "name" = container_info.name
+            )
+        );
+    }
+    result
 }
 
-fn parse_restore_file(
-    input: Option<String>,
-    interactive: bool,
-    container_info: &ContainerInfo,
-) -> Result<PathBuf> {
-    let config = Config::global()?;
+/// 原地恢复前的快照所在的子目录 (位于备份目录下)
+///
+/// 快照的容器名与普通备份相同且备份时间最新，放在单独的目录中，避免被当作普通备份恢复或计入保留策略
+const PRE_RESTORE_DIR: &str = "pre-restore";
 
-    fn try_get_backup_file(path: &PathBuf, container_name: &str) -> Result<Option<PathBuf>> {
-        if path.is_file() {
-            let file = utils::ensure_file_exists(path)?;
-            return Ok(Some(utils::absolute_canonicalize_path(&file)?));
-        }
+/// 判断 `file` 是否位于 `dir` 下存放原地恢复前快照的子目录中
+fn is_pre_restore_snapshot(dir: &Path, file: &Path) -> bool {
+    file.strip_prefix(dir)
+        .ok()
+        .and_then(Path::parent)
+        .is_some_and(|parent| {
+            parent
+                .components()
+                .any(|c| c.as_os_str() == PRE_RESTORE_DIR)
+        })
+}
 
-        if path.is_dir() {
-            let mut files = utils::get_files_start_with(path, container_name, true)?;
-            if files.is_empty() {
-                return Ok(None);
-            }
-            if files.len() == 1 {
-                return Ok(Some(utils::absolute_canonicalize_path(&files[0])?));
-            }
+/// 原地恢复前将即将被覆盖的卷的当前内容备份到 `snapshot_dir` 下的
+/// `<容器名>_pre-restore_<时间戳>.tar.xz`，返回其路径；没有可以备份的卷时返回 `None`
+///
+/// 快照与普通备份一样包含 mapping，恢复失败时可以直接用 `restore -f <快照>` 还原。
+/// 宿主机上不存在的卷 (如无法访问的命名卷) 与归档目录名重复的卷不会被包含
+fn take_pre_restore_snapshot(
+    container_info: &ContainerInfo,
+    archive: &BackupArchive<'_>,
+    snapshot_dir: &Path,
+) -> Result<Option<PathBuf>> {
+    let mut volumes: Vec<VolumeInfo> = Vec::new();
+    let candidates = archive
+        .mapping
+        .volumes
+        .iter()
+        .chain(archive.mapping.aliases.iter().map(|a| &a.volume));
+    for volume in candidates {
+        if volumes.iter().any(|v| v.source == volume.source) {
+            continue;
+        }
+        // 快照中的目录名取自宿主机路径的最后一级，与备份时的卷名规则一致
+        let name = volume
+            .source
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if !volume.source.exists() || volumes.iter().any(|v| v.name == name) {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.pre_restore_snapshot_skipped",
+                    "name" = volume.name,
+                    "path" = volume.source.to_string_lossy()
+                )
+            );
+            continue;
+        }
+        volumes.push(VolumeInfo {
+            name,
+            ..volume.clone()
+        });
+    }
+    if volumes.is_empty() {
+        return Ok(None);
+    }
 
-            files.sort_by(|a, b| {
-                let created = |p: &PathBuf| {
-                    std::fs::metadata(p)
-                        .and_then(|m| m.created())
-                        .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                };
-                created(b).cmp(&created(a))
-            });
+    ensure_dir_exists(snapshot_dir)?;
+    let snapshot_path = snapshot_dir.join(utils::create_timestamp_filename(
+        &format!("{}_pre-restore", container_info.name),
+        ".tar.xz",
+    ));
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.creating_pre_restore_snapshot",
+            "path" = snapshot_path.to_string_lossy()
+        )
+    );
 
-            let file_labels = files
-                .iter()
-                .map(|f| {
-                    format!(
-                        "[{:<19}] {:<45}",
-                        utils::format_file_time(f).unwrap_or_else(|_| "Unknown".to_string()),
-                        f.file_name().unwrap_or_default().to_string_lossy()
-                    )
-                })
-                .collect::<Vec<_>>();
+    let mapping = BackupMapping {
+        schema_version: MAPPING_SCHEMA_VERSION,
+        container_name: container_info.name.clone(),
+        container_id: container_info.id.clone(),
+        volumes,
+        backup_time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        payload_sha256: None,
+        aliases: Vec::new(),
+        base: None,
+        files: Default::default(),
+        modified_after: None,
+        preserve_ownership: false,
+    };
+    let sources = mapping
+        .volumes
+        .iter()
+        .map(|v| v.source.as_path())
+        .collect::<Vec<_>>();
+    utils::compress_with_memory_file(
+        &sources,
+        snapshot_path.as_path(),
+        &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+        &[],
+        utils::CompressOptions {
+            io_limit: Config::io_limit(),
+            ..Default::default()
+        },
+    )?;
+    utils::write_checksum_file(&snapshot_path, &utils::sha256_file(&snapshot_path)?)?;
 
-            let selection = Select::new()
-                .with_prompt(prompt::prompt_select(&format!(
-                    "{}",
-                    t!("commands.select_backup_file_to_restore")
-                )))
-                .items(&file_labels)
-                .default(0)
-                .interact()?;
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.pre_restore_snapshot_created",
+            "path" = snapshot_path.to_string_lossy(),
+            "size" = utils::format_bytes(std::fs::metadata(&snapshot_path)?.len())
+        )
+    );
+    Ok(Some(snapshot_path))
+}
 
-            return Ok(Some(utils::absolute_canonicalize_path(&files[selection])?));
-        }
+/// 恢复前预检中单个卷的恢复位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct RestoreMappingRow {
+    /// 备份中的卷名
+    volume: String,
+    /// 备份时记录的宿主机路径
+    source: PathBuf,
+    /// 备份时记录的容器内路径
+    destination: PathBuf,
+    /// 本次恢复将写入的位置
+    target: String,
+    /// 目标路径在本机上是否已存在，写入容器或命名卷时为 `None`
+    exists: Option<bool>,
+    /// 写入目标路径是否需要管理员权限
+    needs_privileges: bool,
+}
 
-        Ok(None)
+/// 计算每个选中的卷将被恢复到的位置，只读取文件系统元数据，不写入任何数据
+fn restore_mapping_rows(
+    archive: &BackupArchive<'_>,
+    container: &ContainerInfo,
+    output: Option<&Path>,
+    into_container: bool,
+) -> Vec<RestoreMappingRow> {
+    let admin = privileges::has_admin_privileges();
+    archive
+        .mapping
+        .volumes
+        .iter()
+        .map(|volume| {
+            let host_target = match output {
+                Some(output) => Some(output.join(&volume.name)),
+                None if into_container || volume.volume_name.is_some() => None,
+                None => Some(volume.source.clone()),
+            };
+            let target = match &host_target {
+                Some(path) => path.to_string_lossy().to_string(),
+                None if into_container => {
+                    format!("{}:{}", container.name, volume.destination.display())
+                }
+                None => named_volume_target(volume),
+            };
+            RestoreMappingRow {
+                volume: volume.name.clone(),
+                source: volume.source.clone(),
+                destination: volume.destination.clone(),
+                target,
+                exists: host_target.as_ref().map(|path| path.exists()),
+                needs_privileges: host_target
+                    .as_deref()
+                    .is_some_and(|path| !admin && !privileges::is_writable(path)),
+            }
+        })
+        .collect()
+}
+
+/// 存在当前用户无法写入的宿主机恢复目标时要求管理员权限
+///
+/// 通常在恢复开始前已经要求过管理员权限，此时不会再次提示；rootless 模式下跳过了那一步，
+/// 卷属于当前用户时无需提权
+fn ensure_host_targets_writable(
+    archive: &BackupArchive<'_>,
+    container: &ContainerInfo,
+    output: Option<&Path>,
+) -> Result<()> {
+    let rows = restore_mapping_rows(archive, container, output, false);
+    if let Some(row) = rows.iter().find(|row| row.needs_privileges) {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.restore_target_needs_privileges",
+                "path" = row.target
+            )
+        );
+        prompt::require_admin_privileges_prompt()?;
+    }
+    Ok(())
+}
+
+fn print_restore_mapping(
+    container: &ContainerInfo,
+    rows: &[RestoreMappingRow],
+    format: OutputFormat,
+) -> Result<()> {
+    if format == OutputFormat::Json {
+        utils::print_output(format_args!("{}", serde_json::to_string_pretty(rows)?));
+        return Ok(());
     }
 
-    if let Some(input) = input {
-        let input_path = PathBuf::from(input);
-        if let Some(file) = try_get_backup_file(&input_path, &container_info.name)? {
-            return Ok(file);
+    let flag = |value: bool| {
+        if value {
+            t!("commands.mapping_yes")
+        } else {
+            t!("commands.mapping_no")
         }
+    };
+    println!(
+        "\n{}:",
+        t!("commands.restore_mapping_title", "name" = container.name)
+    );
+    for row in rows {
+        println!("{}", row.volume);
+        println!(
+            "  {:<12} {}",
+            t!("commands.mapping_source"),
+            row.source.display()
+        );
+        println!(
+            "  {:<12} {}",
+            t!("commands.mapping_destination"),
+            row.destination.display()
+        );
+        println!("  {:<12} {}", t!("commands.mapping_target"), row.target);
+        println!(
+            "  {:<12} {}",
+            t!("commands.mapping_exists"),
+            row.exists.map_or_else(|| "-".into(), flag)
+        );
+        println!(
+            "  {:<12} {}",
+            t!("commands.mapping_needs_privileges"),
+            flag(row.needs_privileges)
+        );
     }
+    Ok(())
+}
 
-    if let Some(file) = try_get_backup_file(&config.backup_dir, &container_info.name)? {
-        return Ok(file);
+/// 命名卷在提示信息中的显示形式
+fn named_volume_target(volume: &VolumeInfo) -> String {
+    format!(
+        "volume:{}",
+        volume.volume_name.as_deref().unwrap_or_default()
+    )
+}
+
+/// 辅助容器中挂载命名卷的根目录，上传的 tar 解压到该目录下
+const HELPER_MOUNT_ROOT: &str = "/restore";
+
+/// 通过挂载了命名卷的临时辅助容器恢复 Docker 命名卷
+///
+/// 命名卷在宿主机上的路径可能不存在或不可访问 (如 Docker Desktop 的虚拟机中)，
+/// 因此通过 Docker API 上传到辅助容器中，辅助容器无论成功与否都会被删除
+async fn restore_named_volumes<T: DockerClientInterface>(
+    client: &T,
+    container: &ContainerInfo,
+    archive: &BackupArchive<'_>,
+) -> Result<()> {
+    let volumes = archive.named_volumes().collect::<Vec<_>>();
+    if volumes.is_empty() {
+        return Ok(());
     }
 
-    if interactive {
+    let mut binds = Vec::new();
+    let mut destinations = Vec::new();
+    for (index, volume) in volumes.iter().enumerate() {
+        let mount = format!("{}/{}", HELPER_MOUNT_ROOT, index);
+        binds.push(format!(
+            "{}:{}",
+            volume.volume_name.as_deref().unwrap_or_default(),
+            mount
+        ));
+        destinations.push((volume.name.clone(), PathBuf::from(mount)));
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.restoring_named_volume",
+                "name" = volume.name,
+                "volume" = volume.volume_name.as_deref().unwrap_or_default()
+            )
+        );
+    }
+
+    let helper = client.create_helper_container(&container.id, binds).await?;
+    debug!(helper, "Created helper container for named volumes");
+    let upload = ContainerUpload {
+        layers: archive.layers().map(Path::to_path_buf).collect(),
+        root: PathBuf::from(HELPER_MOUNT_ROOT),
+        destinations,
+        deleted: archive.deleted.clone(),
+    };
+    let result = upload_tar(client, &helper, upload).await;
+
+    if let Err(e) = client.remove_container(&helper).await {
         log_println!(
             "WARN",
             "{}",
             t!(
-                "commands.no_backup_files_found_for_container",
-                "container_name" = container_info.name
+                "commands.helper_container_remove_failed",
+                "id" = helper,
+                "error" = e
             )
         );
+    }
+    result
+}
 
-        let input: String = Input::new()
-            .with_prompt(t!("prompt.backup_file_path_input_prompt"))
-            .allow_empty(false)
-            .with_initial_text(config.backup_dir.to_string_lossy().to_string())
-            .interact_text()?;
+/// 通过 Docker API 将卷内容直接写入容器，适用于无法访问宿主机上卷路径的场景 (如远程 Docker daemon)
+async fn restore_into_container<T: DockerClientInterface>(
+    client: &T,
+    container_info: &ContainerInfo,
+    archive: &BackupArchive<'_>,
+    interactive: bool,
+    yes: bool,
+) -> Result<()> {
+    let targets = archive
+        .mapping
+        .volumes
+        .iter()
+        .map(|v| {
+            (
+                v.name.as_str(),
+                format!("{}:{}", container_info.name, v.destination.display()),
+            )
+        })
+        .collect::<Vec<_>>();
 
-        let input_path = PathBuf::from(input);
-        if let Some(file) = try_get_backup_file(&input_path, &container_info.name)? {
-            return Ok(file);
+    if Config::is_dry_run() {
+        for (name, target) in &targets {
+            log_println!(
+                "INFO",
+                "{}",
+                t!(
+                    "commands.dry_run_restore_target",
+                    "name" = name,
+                    "path" = target
+                )
+            );
         }
+        container::ensure_container_stopped(client, container_info).await?;
+        log_println!("INFO", "{}", t!("commands.dry_run_no_changes"));
+        return Ok(());
     }
 
-    log_bail!(
-        "ERROR",
-        "{}",
-        t!(
-            "commands.could_not_find_valid_backup_file_for_container",
-            "container_name" = container_info.name
-        )
-    )
+    if !yes && interactive {
+        let prompt_text = targets
+            .iter()
+            .map(|(name, target)| format!(" - {} -> {}", name, target))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let confirmed = Confirm::new()
+            .with_prompt(t!(
+                "commands.are_you_sure_you_want_to_restore_to",
+                "path" = prompt_text
+            ))
+            .default(true)
+            .interact()?;
+
+        if !confirmed {
+            log_println!("INFO", "{}", t!("prompt.restore_cancelled"));
+            return Ok(());
+        }
+    }
+
+    let _lock = ContainerLock::acquire(container_info, LockOperation::RestoreInPlace)?;
+    container::ensure_container_stopped(client, container_info).await?;
+    upload_into_container(client, container_info, archive).await
 }
 
-async fn unpack_archive_to(
+/// 写入容器时上传的 tar：压缩包条目 `<卷名>/...` 被重命名为卷在容器内的路径
+#[derive(Debug, Clone)]
+struct ContainerUpload {
+    /// 按恢复顺序排列的备份链
+    layers: Vec<PathBuf>,
+    /// tar 在容器内解压到的目录，`destinations` 中的路径都位于该目录下
+    root: PathBuf,
+    /// 归档中的卷目录名与其在容器内的挂载路径，备份时合并的卷与被打包的卷使用同一个目录名
+    destinations: Vec<(String, PathBuf)>,
+    /// 基础备份之后被删除、不应上传的文件
+    deleted: BTreeSet<String>,
+}
+
+impl ContainerUpload {
+    fn new(archive: &BackupArchive<'_>) -> Self {
+        let mut destinations = archive
+            .mapping
+            .volumes
+            .iter()
+            .map(|v| (v.name.clone(), v.destination.clone()))
+            .collect::<Vec<_>>();
+        // 合并的卷通过别名表找到被打包的卷，挂载路径不同时写入各自的路径
+        for alias in &archive.mapping.aliases {
+            let primary = destinations
+                .iter()
+                .find(|(name, _)| *name == alias.alias_of);
+            if primary.is_some_and(|(_, destination)| *destination != alias.volume.destination) {
+                destinations.push((alias.alias_of.clone(), alias.volume.destination.clone()));
+            }
+        }
+        Self {
+            layers: archive.layers().map(Path::to_path_buf).collect(),
+            root: PathBuf::from("/"),
+            destinations,
+            deleted: archive.deleted.clone(),
+        }
+    }
+
+    /// 条目在上传的 tar 中的路径，不属于 `destinations` 中的卷的条目返回 `None`
+    fn target(&self, destinations: &[&(String, PathBuf)], path: &Path) -> Option<PathBuf> {
+        let mut components = path.components();
+        let first = components.next()?;
+        let (_, destination) = destinations
+            .iter()
+            .find(|(name, _)| first.as_os_str() == name.as_str())?;
+        if self.deleted.contains(&utils::archive_key(path)) {
+            return None;
+        }
+        // tar 中只能使用相对路径，上传时解压到 `root`
+        let destination = destination
+            .strip_prefix(&self.root)
+            .unwrap_or(destination)
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .collect::<PathBuf>();
+        Some(destination.join(components.as_path()))
+    }
+
+    fn write_tar<W: std::io::Write>(&self, out: W) -> Result<()> {
+        let mut builder = tar::Builder::new(out);
+        // 同一目录名对应多个挂载路径 (合并的卷) 时，每一轮为每个目录名写入其中一个路径
+        let mut rounds: Vec<Vec<&(String, PathBuf)>> = Vec::new();
+        for destination in &self.destinations {
+            match rounds
+                .iter_mut()
+                .find(|round| round.iter().all(|(name, _)| *name != destination.0))
+            {
+                Some(round) => round.push(destination),
+                None => rounds.push(vec![destination]),
+            }
+        }
+        for round in &rounds {
+            let volumes = round
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            for layer in &self.layers {
+                utils::repack_archive_routed(layer, &volumes, &mut builder, |path| {
+                    self.target(round, path)
+                })?;
+            }
+        }
+        builder.into_inner()?.flush()?;
+        Ok(())
+    }
+}
+
+/// 将解压后的 tar 流式上传到容器，不在本地暂存，磁盘占用与卷大小无关
+///
+/// 容器不接受流式上传时，回退为先在本地临时文件中生成完整的 tar 再上传
+async fn upload_into_container<T: DockerClientInterface>(
+    client: &T,
     container: &ContainerInfo,
-    file_path: &PathBuf,
-    output_dir: &PathBuf,
+    archive: &BackupArchive<'_>,
 ) -> Result<()> {
-    info!(
-        container_name = ?container.name,
-        file_path = ?file_path,
-        output_dir = ?output_dir,
-        "Restoring archive to directory"
-    );
-
-    println!(
+    log_println!(
+        "INFO",
         "{}",
-        t!(
-            "commands.restoring_to",
-            "file_path" = file_path.to_string_lossy(),
-            "output_dir" = output_dir.to_string_lossy()
-        )
+        t!("commands.streaming_into_container", "name" = container.name)
     );
+    upload_tar(client, &container.id, ContainerUpload::new(archive)).await
+}
 
-    unpack_archive(file_path, output_dir)?;
-    Ok(())
+/// 将 `upload` 生成的 tar 上传到容器，优先流式上传，失败时回退为本地暂存后上传
+async fn upload_tar<T: DockerClientInterface>(
+    client: &T,
+    container_id: &str,
+    upload: ContainerUpload,
+) -> Result<()> {
+    let root = upload.root.to_string_lossy().to_string();
+    let (writer, body) = UploadBody::channel();
+    let producer = {
+        let upload = upload.clone();
+        tokio::task::spawn_blocking(move || upload.write_tar(writer))
+    };
+    let uploaded = client.upload_to_container(container_id, &root, body).await;
+    let produced = producer.await?;
+    match uploaded {
+        // 上传成功但读取备份失败时，直接返回读取错误 (不回退，回退同样会失败)
+        Ok(()) => return produced,
+        Err(e) => log_println!(
+            "WARN",
+            "{}",
+            t!("commands.stream_upload_fallback", "error" = e)
+        ),
+    }
+
+    let staged = tempfile::NamedTempFile::new()?;
+    let file = staged.reopen()?;
+    tokio::task::spawn_blocking(move || upload.write_tar(file)).await??;
+    client
+        .upload_to_container(
+            container_id,
+            &root,
+            UploadBody::Staged(staged.path().to_path_buf()),
+        )
+        .await
 }
 
-async fn unpack_archive_move(
-    container: &ContainerInfo,
-    file_path: &PathBuf,
+/// 确定要恢复的卷
+///
+/// 指定了 `--volume` 时按名称过滤，名称不存在则报错；
+/// 否则在交互模式下提示选择，非交互模式下恢复全部卷
+fn select_restore_volumes(
     volumes: &[VolumeInfo],
-) -> Result<()> {
-    info!(
-        container_name = ?container.name,
-        file_path = ?file_path,
-        "Restoring archive into volume mounts"
-    );
+    names: &[String],
+    interactive: bool,
+) -> Result<Vec<VolumeInfo>> {
+    if !names.is_empty() {
+        if let Some(missing) = names
+            .iter()
+            .find(|n| !volumes.iter().any(|v| &v.name == *n))
+        {
+            let available = volumes
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.volume_not_in_backup",
+                    "name" = missing,
+                    "available" = available
+                )
+            );
+        }
+        return Ok(volumes
+            .iter()
+            .filter(|v| names.contains(&v.name))
+            .cloned()
+            .collect());
+    }
 
-    let temp_dir = tempdir()?;
-    let temp_path = temp_dir.path().to_path_buf();
-    unpack_archive(file_path, &temp_path)?;
+    if interactive && volumes.len() > 1 {
+        return prompt::select_volumes_prompt(volumes);
+    }
 
-    for volume in volumes {
-        let temp_source = temp_path.join(&volume.name);
-        if !temp_source.exists() {
-            warn!(volume = ?volume.name, "Volume not found in backup, skipping");
-            continue;
+    Ok(volumes.to_vec())
+}
+
+/// 只保留选中的卷及其别名
+fn retain_volumes(mapping: &mut BackupMapping, selected: &[VolumeInfo]) {
+    mapping
+        .volumes
+        .retain(|v| selected.iter().any(|s| s.name == v.name));
+    mapping
+        .aliases
+        .retain(|a| selected.iter().any(|s| s.name == a.alias_of));
+}
+
+/// 将备份中的卷按容器内的挂载路径对应到目标容器的卷，改用目标卷的宿主机路径与卷名
+///
+/// 归档中的目录名 (`name`) 保持不变；目标容器中没有相同挂载路径的卷会给出警告并跳过
+fn remap_volumes_by_destination(mapping: &mut BackupMapping, targets: &[VolumeInfo]) {
+    let remap = |volume: &mut VolumeInfo| {
+        let Some(target) = targets.iter().find(|t| t.destination == volume.destination) else {
+            return false;
+        };
+        debug!(
+            name = volume.name,
+            from = ?volume.source,
+            to = ?target.source,
+            "Remapping volume by destination"
+        );
+        volume.source = target.source.clone();
+        volume.volume_name = target.volume_name.clone();
+        true
+    };
+
+    mapping.volumes.retain_mut(|volume| {
+        let matched = remap(volume);
+        if !matched {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.volume_destination_not_found",
+                    "name" = volume.name,
+                    "destination" = volume.destination.to_string_lossy()
+                )
+            );
         }
+        matched
+    });
+    mapping.aliases.retain_mut(|alias| remap(&mut alias.volume));
+}
 
-        println!(
-            "Restoring volume {} to {}",
-            volume.name,
-            volume.source.to_string_lossy()
+/// 启用 `--verify-checksums` 时读取备份中的逐文件校验清单
+///
+/// 旧版本的备份没有校验清单，此时给出警告并跳过逐文件校验
+fn load_checksum_manifest(
+    file_path: &Path,
+    options: &RestoreOptions,
+) -> Result<Option<ChecksumManifest>> {
+    if !options.verify_checksums {
+        return Ok(None);
+    }
+
+    match utils::read_file_from_archive(file_path, CHECKSUMS_FILE_NAME) {
+        Ok(content) => Ok(Some(toml::from_str(&content)?)),
+        Err(_) => {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.checksum_manifest_not_found",
+                    "path" = file_path.to_string_lossy()
+                )
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// 沿增量备份的 `base` 引用逐级查找基础备份，返回从旧到新排列的基础备份路径，
+/// 以及基础备份中存在、但不在本次备份文件列表 (`files`) 中的文件，即期间被删除的文件
+///
+/// 基础备份须与增量备份位于同一目录，且其数据校验和须与增量备份中记录的一致
+fn resolve_backup_chain(
+    file_path: &Path,
+    mapping: &BackupMapping,
+    verify: bool,
+) -> Result<(Vec<PathBuf>, BTreeSet<String>)> {
+    let dir = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut chain: Vec<PathBuf> = Vec::new();
+    let mut deleted = BTreeSet::new();
+    let mut next = mapping.base.clone();
+
+    while let Some(base) = next {
+        let path = dir.join(&base.file);
+        if !path.is_file() || path == file_path || chain.contains(&path) {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.incremental_base_missing",
+                    "path" = path.to_string_lossy()
+                )
+            );
+        }
+
+        let base_mapping =
+            parse_mapping(&utils::read_file_from_archive(&path, MAPPING_FILE_NAME)?)?;
+        if base_mapping.payload_sha256 != base.payload_sha256 {
+            log_bail!(
+                "ERROR",
+                "{}",
+                t!(
+                    "commands.incremental_base_mismatch",
+                    "path" = path.to_string_lossy()
+                )
+            );
+        }
+        if verify {
+            verify_backup_checksum(&path, &base_mapping)?;
+        }
+
+        // 旧版本备份中没有文件列表，无法得知哪些文件被删除
+        if !mapping.files.is_empty() {
+            deleted.extend(
+                base_mapping
+                    .files
+                    .into_keys()
+                    .filter(|key| !mapping.files.contains_key(key)),
+            );
+        }
+        next = base_mapping.base;
+        chain.push(path);
+    }
+
+    chain.reverse();
+    if !chain.is_empty() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.incremental_chain",
+                "count" = chain.len() + 1,
+                "base" = chain[0].to_string_lossy()
+            )
         );
+    }
+    Ok((chain, deleted))
+}
+
+/// 解析归档中的 `mapping.toml`，并将旧结构版本的 mapping 在内存中升级到当前结构
+pub(crate) fn parse_mapping(content: &str) -> Result<BackupMapping> {
+    let mapping: BackupMapping = toml::from_str(content)?;
+    migrate_mapping(mapping)
+}
+
+/// 按结构版本逐步升级 mapping；由更新版本的程序写入、无法识别的结构直接报错，避免按错误的布局恢复
+///
+/// mapping 的布局发生变化时递增 [`MAPPING_SCHEMA_VERSION`]，并在这里加入从上一个版本升级的步骤
+fn migrate_mapping(mut mapping: BackupMapping) -> Result<BackupMapping> {
+    if mapping.schema_version > MAPPING_SCHEMA_VERSION {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.mapping_schema_unsupported",
+                "version" = mapping.schema_version,
+                "supported" = MAPPING_SCHEMA_VERSION
+            )
+        );
+    }
+    if mapping.schema_version < MAPPING_SCHEMA_VERSION {
+        debug!(
+            from = mapping.schema_version,
+            to = MAPPING_SCHEMA_VERSION,
+            "Migrating backup mapping"
+        );
+        mapping.schema_version = MAPPING_SCHEMA_VERSION;
+    }
+    Ok(mapping)
+}
+
+/// 校验备份文件的完整性
+///
+/// 优先使用同目录下的 `<archive>.sha256` 校验整个文件，
+/// 校验和文件不存在时回退到 mapping 中记录的数据部分校验和
+pub(super) fn verify_backup_checksum(file_path: &Path, mapping: &BackupMapping) -> Result<()> {
+    let (expected, actual) = if let Some(expected) = utils::read_checksum_file(file_path)? {
+        (expected, utils::sha256_file(file_path)?)
+    } else if let Some(expected) = &mapping.payload_sha256 {
+        (expected.clone(), utils::payload_sha256(file_path)?)
+    } else {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.checksum_not_found",
+                "path" = file_path.to_string_lossy()
+            )
+        );
+        return Ok(());
+    };
+
+    if expected != actual {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.checksum_mismatch",
+                "path" = file_path.to_string_lossy(),
+                "expected" = expected,
+                "actual" = actual
+            )
+        );
+    }
+
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.checksum_verified",
+            "path" = file_path.to_string_lossy()
+        )
+    );
+    Ok(())
+}
+
+/// `-f -` 表示从标准输入读取备份
+const STDIN_INPUT: &str = "-";
+
+/// 将标准输入中的备份写入 `temp_dir` (未设置时为系统临时目录) 下的临时文件
+///
+/// 恢复前需要先读取归档开头的 mapping 校验容器与卷，之后再从头解压，标准输入无法回退，因此先完整写入磁盘
+fn spool_stdin(temp_dir: Option<&Path>) -> Result<(NamedTempFile, utils::PartialFile)> {
+    spool_reader(std::io::stdin().lock(), temp_dir)
+}
+
+fn spool_reader(
+    mut reader: impl std::io::Read,
+    temp_dir: Option<&Path>,
+) -> Result<(NamedTempFile, utils::PartialFile)> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("rdbkp2-stdin-");
+    let mut spool = match temp_dir {
+        Some(dir) => builder.tempfile_in(dir)?,
+        None => builder.tempfile()?,
+    };
+    // 中断时临时文件不会随值的丢弃而删除
+    let partial = utils::PartialFile::track(spool.path());
+    let bytes = std::io::copy(&mut reader, spool.as_file_mut())?;
+    if bytes == 0 {
+        log_bail!("ERROR", "{}", t!("commands.stdin_backup_empty"));
+    }
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.stdin_backup_read",
+            "size" = utils::format_bytes(bytes)
+        )
+    );
+    Ok((spool, partial))
+}
+
+fn parse_restore_file(
+    input: Option<String>,
+    interactive: bool,
+    container_info: &ContainerInfo,
+    since: Option<NaiveDate>,
+) -> Result<PathBuf> {
+    let config = Config::global()?;
+
+    let try_get_backup_file = |path: &PathBuf, container_name: &str| -> Result<Option<PathBuf>> {
+        if path.is_file() {
+            let file = utils::ensure_file_exists(path)?;
+            return Ok(Some(utils::resolve_path(&file)?));
+        }
+
+        if path.is_dir() {
+            let mut files = utils::get_files_start_with(path, container_name, true)?;
+            files.retain(|f| {
+                !utils::is_sidecar_file(f)
+                    && !backup::is_latest_pointer(f)
+                    && !is_pre_restore_snapshot(path, f)
+            });
+            if let Some(since) = since {
+                files.retain(|f| is_backup_since(f, container_name, since));
+            }
+            if files.is_empty() {
+                return Ok(None);
+            }
+            if files.len() == 1 {
+                return Ok(Some(utils::resolve_path(&files[0])?));
+            }
+
+            files.sort_by(|a, b| {
+                let created = |p: &PathBuf| {
+                    std::fs::metadata(p)
+                        .and_then(|m| m.created())
+                        .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                };
+                created(b).cmp(&created(a))
+            });
+
+            let file_labels = files
+                .iter()
+                .map(|f| {
+                    format!(
+                        "[{:<19}] {:<45}",
+                        utils::format_file_time(f).unwrap_or_else(|_| "Unknown".to_string()),
+                        f.file_name().unwrap_or_default().to_string_lossy()
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let selection = Select::new()
+                .with_prompt(prompt::prompt_select(&format!(
+                    "{}",
+                    t!("commands.select_backup_file_to_restore")
+                )))
+                .items(&file_labels)
+                .default(0)
+                .interact()?;
+
+            return Ok(Some(utils::resolve_path(&files[selection])?));
+        }
+
+        Ok(None)
+    };
+
+    if let Some(input) = input {
+        let input_path = PathBuf::from(input);
+        if let Some(file) = try_get_backup_file(&input_path, &container_info.name)? {
+            return Ok(file);
+        }
+    }
+
+    if let Some(file) = try_get_backup_file(&config.backup_dir, &container_info.name)? {
+        return Ok(file);
+    }
+
+    if interactive {
+        log_println!(
+            "WARN",
+            "{}",
+            t!(
+                "commands.no_backup_files_found_for_container",
+                "container_name" = container_info.name
+            )
+        );
+
+        let input: String = Input::new()
+            .with_prompt(t!("prompt.backup_file_path_input_prompt"))
+            .allow_empty(false)
+            .with_initial_text(config.backup_dir.to_string_lossy().to_string())
+            .interact_text()?;
+
+        let input_path = PathBuf::from(input);
+        if let Some(file) = try_get_backup_file(&input_path, &container_info.name)? {
+            return Ok(file);
+        }
+    }
+
+    log_bail!(
+        "ERROR",
+        "{}",
+        t!(
+            "commands.could_not_find_valid_backup_file_for_container",
+            "container_name" = container_info.name
+        )
+    )
+}
+
+/// 备份文件名中的时间 (见 [`utils::create_timestamp_filename`]) 是否不早于 `since` 当天 0 点，
+/// 无法解析时间的文件视为不满足
+fn is_backup_since(path: &Path, container_name: &str, since: NaiveDate) -> bool {
+    path.file_name()
+        .and_then(|name| utils::parse_timestamp_filename(&name.to_string_lossy(), container_name))
+        .is_some_and(|time| time.date() >= since)
+}
+
+async fn unpack_archive_to(
+    container: &ContainerInfo,
+    archive: &BackupArchive<'_>,
+    output_dir: &PathBuf,
+) -> Result<()> {
+    let file_path = archive.path;
+    info!(
+        container_name = ?container.name,
+        file_path = ?file_path,
+        output_dir = ?output_dir,
+        "Restoring archive to directory"
+    );
+
+    println!(
+        "{}",
+        t!(
+            "commands.restoring_to",
+            "file_path" = file_path.to_string_lossy(),
+            "output_dir" = output_dir.to_string_lossy()
+        )
+    );
+
+    let names = archive.volume_names();
+    for layer in archive.layers() {
+        unpack_archive_filtered(layer, output_dir.as_path(), &names, |path| {
+            path == Path::new(MAPPING_FILE_NAME) || archive.is_selected_entry(path)
+        })?;
+    }
+    archive.verify_extracted(output_dir)?;
+    archive.restore_ownership(|path| {
+        archive
+            .is_selected_entry(path)
+            .then(|| output_dir.join(path))
+    })
+}
+
+/// 将已解压到 `output_dir` 的内容整理为每个卷一个子目录
+///
+/// 单文件卷移动到 `<输出目录>/<卷名>/<文件名>`；备份时合并的卷从被打包的卷复制一份到自己的目录下
+fn split_into_volume_dirs(mapping: &BackupMapping, output_dir: &Path) -> Result<()> {
+    for volume in &mapping.volumes {
+        let extracted = output_dir.join(&volume.name);
+        if !std::fs::symlink_metadata(&extracted).is_ok_and(|meta| !meta.is_dir()) {
+            continue;
+        }
+        let staged = output_dir.join(format!(".{}.rdbkp2-tmp", volume.name));
+        std::fs::rename(&extracted, &staged)?;
+        std::fs::create_dir(&extracted)?;
+        std::fs::rename(&staged, extracted.join(&volume.name))?;
+    }
+
+    for alias in &mapping.aliases {
+        let target = output_dir.join(&alias.volume.name);
+        let primary = output_dir.join(&alias.alias_of);
+        if alias.volume.name == alias.alias_of || target.exists() || !primary.exists() {
+            continue;
+        }
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "commands.restoring_alias_volume",
+                "name" = alias.volume.name,
+                "alias_of" = alias.alias_of,
+                "path" = target.to_string_lossy()
+            )
+        );
+        std::fs::create_dir_all(&target)?;
+        let copy_options = fs_extra::dir::CopyOptions {
+            content_only: true,
+            ..Default::default()
+        };
+        fs_extra::dir::copy(&primary, &target, &copy_options)?;
+    }
+    Ok(())
+}
+
+async fn unpack_archive_move(container: &ContainerInfo, archive: &BackupArchive<'_>) -> Result<()> {
+    let file_path = archive.path;
+    let mapping = &archive.mapping;
+    let volumes = archive.host_volumes().collect::<Vec<_>>();
+    // 只有命名卷且无需逐文件校验时，无需读取压缩包
+    if volumes.is_empty() && archive.manifest.is_none() {
+        return Ok(());
+    }
+    info!(
+        container_name = ?container.name,
+        file_path = ?file_path,
+        "Restoring archive into volume mounts"
+    );
+
+    // 目标路径可直接写入时将条目流式解压到各卷中，避免临时目录占用双倍磁盘空间；
+    // 启用逐文件校验时仍先解压到临时目录，保证校验失败的数据不会写入卷；
+    // 增量恢复需要先解压才能与卷中的文件比较，同样使用临时目录
+    if archive.manifest.is_none() && !archive.incremental_copy && can_write_directly(&volumes) {
+        debug!("Streaming archive entries directly into volume mounts");
+        for volume in &volumes {
+            // 卷路径本身由解压时按条目类型创建，单文件卷不能被预先创建为目录
+            if let Some(parent) = volume.source.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            println!(
+                "Restoring volume {} to {}",
+                volume.name,
+                volume.source.to_string_lossy()
+            );
+        }
+        let names = volumes.iter().map(|v| v.name.as_str()).collect::<Vec<_>>();
+        for layer in archive.layers() {
+            unpack_archive_routed(layer, &names, |path| archive.in_place_target(path))?;
+        }
+
+        for alias in &mapping.aliases {
+            let Some(primary) = volumes.iter().find(|v| v.name == alias.alias_of) else {
+                continue;
+            };
+            if primary.source == alias.volume.source || !primary.source.exists() {
+                continue;
+            }
+
+            println!(
+                "Restoring volume {} to {}",
+                alias.volume.name,
+                alias.volume.source.to_string_lossy()
+            );
+
+            privileges::privileged_copy(&primary.source, &alias.volume.source, false)?;
+        }
+
+        return Ok(());
+    }
+
+    let temp_dir = archive.create_staging_dir()?;
+    let temp_path = temp_dir.path().to_path_buf();
+    let names = archive.volume_names();
+    for layer in archive.layers() {
+        unpack_archive_filtered(layer, temp_path.as_path(), &names, |path| {
+            archive.is_selected_entry(path)
+        })?;
+    }
+    // 校验在写入卷之前进行，避免将损坏的数据覆盖到卷中
+    archive.verify_extracted(&temp_path)?;
+
+    for volume in &volumes {
+        let temp_source = temp_path.join(&volume.name);
+        if !temp_source.exists() {
+            warn!(volume = ?volume.name, "Volume not found in backup, skipping");
+            continue;
+        }
+
+        println!(
+            "Restoring volume {} to {}",
+            volume.name,
+            volume.source.to_string_lossy()
+        );
+
+        privileges::privileged_copy(&temp_source, &volume.source, archive.incremental_copy)?;
+    }
+
+    // 备份时合并的卷若在当前主机上指向不同路径，则分别还原
+    for alias in &mapping.aliases {
+        let Some(primary) = volumes.iter().find(|v| v.name == alias.alias_of) else {
+            continue;
+        };
+        if primary.source == alias.volume.source {
+            continue;
+        }
+        let temp_source = temp_path.join(&alias.alias_of);
+        if !temp_source.exists() {
+            continue;
+        }
+
+        println!(
+            "Restoring volume {} to {}",
+            alias.volume.name,
+            alias.volume.source.to_string_lossy()
+        );
+
+        privileges::privileged_copy(&temp_source, &alias.volume.source, archive.incremental_copy)?;
+    }
+
+    Ok(())
+}
+
+/// 判断是否无需提权即可直接写入所有卷的宿主机路径
+///
+/// 检查卷目录及其中所有已存在的条目 (不存在时为其最近的已存在的上级目录) 的权限，
+/// 任一条目不可写时使用临时目录，避免流式解压在中途失败后卷只恢复了一部分。不会创建目录或文件
+fn can_write_directly(volumes: &[&VolumeInfo]) -> bool {
+    if privileges::has_admin_privileges() {
+        return true;
+    }
+    volumes.iter().all(|volume| {
+        // 单文件卷 (如 `file.conf`) 无法作为目录直接写入，需先解压到临时目录
+        let is_file = std::fs::metadata(&volume.source).is_ok_and(|m| !m.is_dir());
+        !is_file && privileges::is_tree_writable(&volume.source)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::{BackupBase, MockDockerClientInterface};
+    use assert_fs::{
+        TempDir,
+        fixture::{PathChild, PathCreateDir},
+    };
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    async fn setup_backup() -> Result<(TempDir, PathBuf, ContainerInfo)> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+
+        fs::create_dir_all(base_path.join("vol1"))?;
+        fs::write(base_path.join("vol1/data.txt"), "hello")?;
+        fs::create_dir_all(base_path.join("vol2"))?;
+        fs::write(base_path.join("vol2/other.txt"), "world")?;
+
+        let volumes = vec![
+            VolumeInfo {
+                name: "vol1".into(),
+                source: base_path.join("vol1"),
+                destination: base_path.join("vol1"),
+                volume_name: None,
+            },
+            VolumeInfo {
+                name: "vol2".into(),
+                source: base_path.join("vol2"),
+                destination: base_path.join("vol2"),
+                volume_name: None,
+            },
+        ];
+
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            status: "running".into(),
+            ..Default::default()
+        };
+
+        let output_dir = temp_dir.child("backup");
+        output_dir.create_dir_all()?;
+
+        let mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: container.name.clone(),
+            container_id: container.id.clone(),
+            volumes: volumes.clone(),
+            backup_time: "now".into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: Vec::new(),
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+
+        let mapping_content = toml::to_string(&mapping)?;
+        let backup_file = output_dir.child("backup.tar.xz");
+        let sources: Vec<_> = volumes.iter().map(|v| v.source.as_path()).collect();
+        crate::utils::compress_with_memory_file(
+            &sources,
+            backup_file.path(),
+            &[(MAPPING_FILE_NAME, mapping_content.as_str())],
+            &[],
+            crate::utils::CompressOptions::default(),
+        )?;
+
+        Ok((temp_dir, backup_file.path().to_path_buf(), container))
+    }
+
+    #[test]
+    fn migrates_mappings_without_schema_version() -> Result<()> {
+        // 引入结构版本之前写入的 mapping
+        let legacy = r#"
+container_name = "web"
+container_id = "abc"
+backup_time = "2024-01-01 00:00:00"
+version = "0.1.0"
+volumes = []
+"#;
+        let mapping = parse_mapping(legacy)?;
+        assert_eq!(mapping.schema_version, MAPPING_SCHEMA_VERSION);
+        assert_eq!(mapping.container_name, "web");
+
+        let written = toml::to_string(&mapping)?;
+        assert!(written.contains(&format!("schema_version = {MAPPING_SCHEMA_VERSION}")));
+
+        let newer = format!("schema_version = {}\n{legacy}", MAPPING_SCHEMA_VERSION + 1);
+        assert!(parse_mapping(&newer).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restore_to_custom_directory() -> Result<()> {
+        let (_temp_dir, backup_file, container) = setup_backup().await?;
+        let restore_dir = TempDir::new()?;
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        client
+            .expect_get_container_status()
+            .returning(|_| Ok("exited".to_string()));
+
+        client
+            .expect_stop_container()
+            .returning(|_| Ok(()))
+            .times(0..=1);
+        client
+            .expect_get_stop_timeout_secs()
+            .returning(|| 10)
+            .times(0..=1);
+
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            Some(restore_dir.path().to_string_lossy().to_string()),
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+
+        assert!(restore_dir.path().join("vol1/data.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn per_volume_layout_gives_each_volume_its_own_directory() -> Result<()> {
+        let output = TempDir::new()?;
+        fs::create_dir_all(output.path().join("data"))?;
+        fs::write(output.path().join("data/db.sqlite"), "db")?;
+        fs::write(output.path().join("app.conf"), "conf")?;
+
+        let volume = |name: &str| VolumeInfo {
+            name: name.into(),
+            source: PathBuf::from("/srv").join(name),
+            destination: PathBuf::from("/mnt").join(name),
+            volume_name: None,
+        };
+        let mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: "container".into(),
+            container_id: "id".into(),
+            volumes: vec![volume("data"), volume("app.conf")],
+            backup_time: "now".into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: vec![crate::docker::VolumeAlias {
+                volume: volume("data-link"),
+                alias_of: "data".into(),
+            }],
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+
+        split_into_volume_dirs(&mapping, output.path())?;
+
+        assert_eq!(
+            fs::read_to_string(output.path().join("data/db.sqlite"))?,
+            "db"
+        );
+        assert_eq!(
+            fs::read_to_string(output.path().join("app.conf/app.conf"))?,
+            "conf"
+        );
+        assert_eq!(
+            fs::read_to_string(output.path().join("data-link/db.sqlite"))?,
+            "db"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restores_in_place_by_streaming_into_volumes() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let data_file = temp_dir.path().join("vol1/data.txt");
+        fs::write(&data_file, "changed")?;
+        fs::remove_dir_all(temp_dir.path().join("vol2"))?;
+
+        let client = MockDockerClientInterface::with_defaults();
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            None,
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+
+        assert_eq!(fs::read_to_string(&data_file)?, "hello");
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("vol2/other.txt"))?,
+            "world"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn streaming_restore_recreates_missing_file_volume_as_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_file = temp_dir.path().join("conf/app.conf");
+        fs::create_dir_all(config_file.parent().unwrap())?;
+        fs::write(&config_file, "port = 80")?;
+        let container = ContainerInfo {
+            id: "id".into(),
+            name: "container".into(),
+            ..Default::default()
+        };
+        let mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: container.name.clone(),
+            container_id: container.id.clone(),
+            volumes: vec![VolumeInfo {
+                name: "app.conf".into(),
+                source: config_file.clone(),
+                destination: PathBuf::from("/etc/app.conf"),
+                volume_name: None,
+            }],
+            backup_time: "now".into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: Vec::new(),
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+        let backup_file = temp_dir.path().join("backup.tar.xz");
+        utils::compress_with_memory_file(
+            &[config_file.as_path()],
+            backup_file.as_path(),
+            &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+            &[],
+            utils::CompressOptions::default(),
+        )?;
+        fs::remove_dir_all(temp_dir.path().join("conf"))?;
+
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping,
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
+        };
+        unpack_archive_move(&container, &archive).await?;
+
+        assert!(config_file.is_file());
+        assert_eq!(fs::read_to_string(&config_file)?, "port = 80");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pre_restore_snapshot_can_undo_the_restore() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let data_file = temp_dir.path().join("vol1/data.txt");
+        fs::write(&data_file, "changed")?;
+        fs::remove_dir_all(temp_dir.path().join("vol2"))?;
+
+        let mapping_content = utils::read_file_from_archive(&backup_file, MAPPING_FILE_NAME)?;
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping: toml::from_str(&mapping_content)?,
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
+        };
+        let snapshot_dir = temp_dir.path().join(PRE_RESTORE_DIR);
+        let snapshot =
+            take_pre_restore_snapshot(&container, &archive, &snapshot_dir)?.expect("vol1 exists");
+        let file_name = snapshot.file_name().unwrap().to_string_lossy().to_string();
+        assert!(file_name.starts_with("container_pre-restore_"));
+        assert!(utils::read_checksum_file(&snapshot)?.is_some());
+
+        // 缺失的 vol2 不在快照中
+        let snapshot_mapping: BackupMapping = toml::from_str(&utils::read_file_from_archive(
+            &snapshot,
+            MAPPING_FILE_NAME,
+        )?)?;
+        let names = snapshot_mapping
+            .volumes
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["vol1"]);
+
+        let client = MockDockerClientInterface::with_defaults();
+        restore_in_place(&client, &container, &archive, false, true, false).await?;
+        assert_eq!(fs::read_to_string(&data_file)?, "hello");
+
+        // 用快照还原恢复前的内容
+        restore_volumes(
+            &client,
+            &container,
+            &snapshot,
+            None,
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+        assert_eq!(fs::read_to_string(&data_file)?, "changed");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restores_incremental_chain_on_top_of_base() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let vol1 = temp_dir.path().join("vol1");
+        fs::write(vol1.join("data.txt"), "hello again")?;
+
+        let base_mapping: BackupMapping = toml::from_str(&utils::read_file_from_archive(
+            &backup_file,
+            MAPPING_FILE_NAME,
+        )?)?;
+        // 增量备份只包含发生变化的 vol1，未变化的 vol2 来自基础备份
+        let mapping = BackupMapping {
+            base: Some(BackupBase {
+                file: "backup.tar.xz".into(),
+                backup_time: base_mapping.backup_time.clone(),
+                payload_sha256: base_mapping.payload_sha256.clone(),
+            }),
+            ..base_mapping
+        };
+        let incremental = backup_file.with_file_name("incremental.tar.xz");
+        crate::utils::compress_with_memory_file(
+            &[vol1.as_path()],
+            &incremental,
+            &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+            &[],
+            crate::utils::CompressOptions::default(),
+        )?;
+
+        let client = MockDockerClientInterface::with_defaults();
+        let restore_dir = TempDir::new()?;
+        restore_volumes(
+            &client,
+            &container,
+            &incremental,
+            Some(restore_dir.path().to_string_lossy().to_string()),
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+        assert_eq!(
+            fs::read_to_string(restore_dir.path().join("vol1/data.txt"))?,
+            "hello again"
+        );
+        assert_eq!(
+            fs::read_to_string(restore_dir.path().join("vol2/other.txt"))?,
+            "world"
+        );
+
+        // 基础备份缺失时拒绝恢复
+        fs::remove_file(&backup_file)?;
+        assert!(
+            restore_volumes(
+                &client,
+                &container,
+                &incremental,
+                Some(restore_dir.path().to_string_lossy().to_string()),
+                false,
+                true,
+                &RestoreOptions::default(),
+            )
+            .await
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn incremental_restore_skips_files_deleted_since_base() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let (vol1, vol2) = (temp_dir.path().join("vol1"), temp_dir.path().join("vol2"));
+        let stamp = utils::FileStamp {
+            mtime_ns: 0,
+            size: 5,
+        };
+
+        let base_mapping = BackupMapping {
+            files: BTreeMap::from([
+                ("vol1/data.txt".to_string(), stamp),
+                ("vol2/other.txt".to_string(), stamp),
+            ]),
+            ..parse_mapping(&utils::read_file_from_archive(
+                &backup_file,
+                MAPPING_FILE_NAME,
+            )?)?
+        };
+        let base = backup_file.with_file_name("full.tar.xz");
+        crate::utils::compress_with_memory_file(
+            &[vol1.as_path(), vol2.as_path()],
+            &base,
+            &[(MAPPING_FILE_NAME, toml::to_string(&base_mapping)?.as_str())],
+            &[],
+            crate::utils::CompressOptions::default(),
+        )?;
+
+        // vol2/other.txt 在基础备份之后被删除，增量备份的文件列表中不再包含它
+        fs::remove_file(vol2.join("other.txt"))?;
+        let mapping = BackupMapping {
+            base: Some(BackupBase {
+                file: "full.tar.xz".into(),
+                backup_time: base_mapping.backup_time.clone(),
+                payload_sha256: base_mapping.payload_sha256.clone(),
+            }),
+            files: BTreeMap::from([("vol1/data.txt".to_string(), stamp)]),
+            ..base_mapping
+        };
+        let incremental = backup_file.with_file_name("incremental.tar.xz");
+        crate::utils::compress_with_memory_file(
+            &[vol1.as_path()],
+            &incremental,
+            &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+            &[],
+            crate::utils::CompressOptions::default(),
+        )?;
+
+        let client = MockDockerClientInterface::with_defaults();
+        let restore_dir = TempDir::new()?;
+        restore_volumes(
+            &client,
+            &container,
+            &incremental,
+            Some(restore_dir.path().to_string_lossy().to_string()),
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+        assert_eq!(
+            fs::read_to_string(restore_dir.path().join("vol1/data.txt"))?,
+            "hello"
+        );
+        assert!(!restore_dir.path().join("vol2/other.txt").exists());
+
+        // 原地恢复同样不会把已删除的文件写回卷中
+        restore_volumes(
+            &client,
+            &container,
+            &incremental,
+            None,
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await?;
+        assert!(!vol2.join("other.txt").exists());
+        Ok(())
+    }
 
-        privileges::privileged_copy(&temp_source, &volume.source)?;
+    /// 读取上传到容器的 tar 中的条目路径与内容
+    fn uploaded_entries(tar: &[u8]) -> Result<BTreeMap<String, String>> {
+        let mut entries = BTreeMap::new();
+        for entry in tar::Archive::new(tar).entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().to_string();
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut content)?;
+            entries.insert(path, content);
+        }
+        Ok(entries)
     }
 
-    Ok(())
-}
+    /// 在独立线程中读完上传的数据流 (`blocking_recv` 不能在异步运行时中调用)
+    fn drain_upload(body: UploadBody) -> Result<Vec<u8>> {
+        match body {
+            UploadBody::Stream(mut receiver) => Ok(std::thread::spawn(move || {
+                let mut data = Vec::new();
+                while let Some(chunk) = receiver.blocking_recv() {
+                    data.extend_from_slice(&chunk);
+                }
+                data
+            })
+            .join()
+            .unwrap()),
+            UploadBody::Staged(path) => Ok(fs::read(path)?),
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use assert_fs::{
-        TempDir,
-        fixture::{PathChild, PathCreateDir},
-    };
-    use std::fs;
+    #[tokio::test]
+    async fn streams_backup_into_container() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let uploaded = Arc::new(Mutex::new(Vec::new()));
 
-    async fn setup_backup() -> Result<(TempDir, PathBuf, ContainerInfo)> {
-        let temp_dir = TempDir::new()?;
-        let base_path = temp_dir.path();
+        let mut client = MockDockerClientInterface::with_defaults();
+        let sink = uploaded.clone();
+        client
+            .expect_upload_to_container()
+            .withf(|_, path, _| path == "/")
+            .times(1)
+            .returning(move |_, _, body| {
+                assert!(matches!(body, UploadBody::Stream(_)));
+                *sink.lock().unwrap() = drain_upload(body)?;
+                Ok(())
+            });
 
-        fs::create_dir_all(base_path.join("vol1"))?;
-        fs::write(base_path.join("vol1/data.txt"), "hello")?;
+        let options = RestoreOptions {
+            into_container: true,
+            ..Default::default()
+        };
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            None,
+            false,
+            true,
+            &options,
+        )
+        .await?;
 
-        let volumes = vec![VolumeInfo {
-            name: "vol1".into(),
-            source: base_path.join("vol1"),
-            destination: base_path.join("vol1"),
-        }];
+        let entries = uploaded_entries(&uploaded.lock().unwrap())?;
+        let destination = temp_dir.path().join("vol1/data.txt");
+        let destination = destination.to_string_lossy();
+        assert_eq!(
+            entries
+                .get(destination.trim_start_matches('/'))
+                .map(String::as_str),
+            Some("hello")
+        );
+        assert!(!entries.keys().any(|k| k.contains(MAPPING_FILE_NAME)));
+        Ok(())
+    }
 
-        let container = ContainerInfo {
-            id: "id".into(),
-            name: "container".into(),
-            status: "running".into(),
+    #[tokio::test]
+    async fn container_upload_writes_merged_volumes_to_their_own_paths() -> Result<()> {
+        let (_temp_dir, backup_file, _container) = setup_backup().await?;
+        let mut mapping = parse_mapping(&utils::read_file_from_archive(
+            &backup_file,
+            MAPPING_FILE_NAME,
+        )?)?;
+        mapping.volumes.truncate(1);
+        mapping.volumes[0].destination = PathBuf::from("/data");
+        mapping.aliases.push(crate::docker::VolumeAlias {
+            volume: VolumeInfo {
+                name: "vol1-copy".into(),
+                destination: PathBuf::from("/copy"),
+                ..mapping.volumes[0].clone()
+            },
+            alias_of: "vol1".into(),
+        });
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping,
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
         };
 
-        let output_dir = temp_dir.child("backup");
-        output_dir.create_dir_all()?;
+        let mut tar = Vec::new();
+        ContainerUpload::new(&archive).write_tar(&mut tar)?;
+        let entries = uploaded_entries(&tar)?;
+        assert_eq!(
+            entries.get("data/data.txt").map(String::as_str),
+            Some("hello")
+        );
+        assert_eq!(
+            entries.get("copy/data.txt").map(String::as_str),
+            Some("hello")
+        );
+        Ok(())
+    }
 
-        let mapping = BackupMapping {
-            container_name: container.name.clone(),
-            container_id: container.id.clone(),
-            volumes: volumes.clone(),
-            backup_time: "now".into(),
-            version: "test".into(),
+    #[tokio::test]
+    async fn restores_named_volumes_through_helper_container() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        fs::remove_dir_all(temp_dir.path().join("vol1"))?;
+        fs::remove_dir_all(temp_dir.path().join("vol2"))?;
+
+        let mapping_content = utils::read_file_from_archive(&backup_file, MAPPING_FILE_NAME)?;
+        let mut mapping: BackupMapping = toml::from_str(&mapping_content)?;
+        mapping.volumes[1].volume_name = Some("app-data".into());
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping,
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
         };
 
-        let mapping_content = toml::to_string(&mapping)?;
-        let backup_file = output_dir.child("backup.tar.xz");
-        let sources: Vec<_> = volumes.iter().map(|v| v.source.as_path()).collect();
-        crate::utils::compress_with_memory_file(
-            &sources,
-            backup_file.path(),
-            &[(MAPPING_FILE_NAME, mapping_content.as_str())],
-            &[],
-        )?;
+        let uploaded = Arc::new(Mutex::new(Vec::new()));
+        let mut client = MockDockerClientInterface::with_defaults();
+        client
+            .expect_create_helper_container()
+            .withf(|image_of, binds| {
+                image_of == "id" && *binds == ["app-data:/restore/0".to_string()]
+            })
+            .times(1)
+            .returning(|_, _| Ok("helper".into()));
+        let sink = uploaded.clone();
+        client
+            .expect_upload_to_container()
+            .withf(|id, path, _| id == "helper" && path == "/restore")
+            .times(1)
+            .returning(move |_, _, body| {
+                *sink.lock().unwrap() = drain_upload(body)?;
+                Ok(())
+            });
+        client
+            .expect_remove_container()
+            .withf(|id| id == "helper")
+            .times(1)
+            .returning(|_| Ok(()));
 
-        Ok((temp_dir, backup_file.path().to_path_buf(), container))
+        restore_in_place(&client, &container, &archive, false, true, false).await?;
+
+        // 绑定挂载卷写入宿主机路径，命名卷只通过辅助容器写入
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("vol1/data.txt"))?,
+            "hello"
+        );
+        assert!(!temp_dir.path().join("vol2").exists());
+        let entries = uploaded_entries(&uploaded.lock().unwrap())?;
+        assert_eq!(
+            entries.get("0/other.txt").map(String::as_str),
+            Some("world")
+        );
+        assert!(!entries.keys().any(|k| k.contains("data.txt")));
+        Ok(())
     }
 
     #[tokio::test]
-    async fn restore_to_custom_directory() -> Result<()> {
-        DockerClient::init(10)?;
+    async fn stages_upload_when_stream_is_rejected() -> Result<()> {
         let (_temp_dir, backup_file, container) = setup_backup().await?;
-        let restore_dir = TempDir::new()?;
-
-        let mut client = DockerClient::global()?;
-        client
-            .expect_get_container_status()
-            .returning(|_| Ok("exited".to_string()));
+        let uploaded = Arc::new(Mutex::new(Vec::new()));
 
+        let mut client = MockDockerClientInterface::with_defaults();
+        let sink = uploaded.clone();
         client
-            .expect_stop_container()
-            .returning(|_| Ok(()))
-            .times(0..=1);
-        client
-            .expect_get_stop_timeout_secs()
-            .returning(|| 10)
-            .times(0..=1);
+            .expect_upload_to_container()
+            .times(2)
+            .returning(move |_, _, body| match body {
+                UploadBody::Stream(_) => Err(anyhow::anyhow!("chunked upload not supported")),
+                staged => {
+                    *sink.lock().unwrap() = drain_upload(staged)?;
+                    Ok(())
+                }
+            });
 
+        let options = RestoreOptions {
+            into_container: true,
+            ..Default::default()
+        };
         restore_volumes(
             &client,
             &container,
             &backup_file,
-            Some(restore_dir.path().to_string_lossy().to_string()),
+            None,
             false,
             true,
+            &options,
         )
         .await?;
 
-        assert!(restore_dir.path().join("vol1/data.txt").exists());
+        let entries = uploaded_entries(&uploaded.lock().unwrap())?;
+        assert!(entries.keys().any(|k| k.ends_with("vol2/other.txt")));
+        Ok(())
+    }
+
+    #[test]
+    fn filters_backups_by_since_date() {
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let is_since = |name: &str| is_backup_since(Path::new(name), "web", since);
+
+        assert!(is_since("/backups/web_all_20240101_000000.tar.xz"));
+        assert!(is_since("web_partial_20240315_120000.tar.xz"));
+        assert!(!is_since("web_all_20231231_235959.tar.xz"));
+        assert!(!is_since("web_db_all_20240315_120000.tar.xz"));
+        assert!(!is_since("web_backup.tar.xz"));
+    }
+
+    #[test]
+    fn picks_latest_archive_per_container() -> Result<()> {
+        let dir = TempDir::new()?;
+        let write_archive = |file: &str, container_name: &str, backup_time: &str| -> Result<()> {
+            let mapping = BackupMapping {
+                schema_version: MAPPING_SCHEMA_VERSION,
+                container_name: container_name.into(),
+                container_id: "id".into(),
+                volumes: Vec::new(),
+                backup_time: backup_time.into(),
+                version: "test".into(),
+                payload_sha256: None,
+                aliases: Vec::new(),
+                base: None,
+                files: Default::default(),
+                modified_after: None,
+                preserve_ownership: false,
+            };
+            crate::utils::compress_with_memory_file(
+                &[],
+                dir.path().join(file),
+                &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+                &[],
+                crate::utils::CompressOptions::default(),
+            )
+        };
+        write_archive("a_old.tar.xz", "app", "2024-01-01 00:00:00")?;
+        write_archive("a_new.tar.xz", "app", "2024-02-01 00:00:00")?;
+        write_archive("db.tar.xz", "db", "2024-01-15 00:00:00")?;
+        fs::write(dir.path().join("broken.tar.xz"), "not an archive")?;
+        // 原地恢复前的快照虽然更新，但不是普通备份
+        fs::create_dir(dir.path().join(PRE_RESTORE_DIR))?;
+        write_archive(
+            &format!("{PRE_RESTORE_DIR}/app_pre-restore.tar.xz"),
+            "app",
+            "2024-03-01 00:00:00",
+        )?;
+
+        let latest = latest_archives_by_container(dir.path())?;
+        assert_eq!(latest.len(), 2);
+        assert!(latest["app"].ends_with("a_new.tar.xz"));
+        assert!(latest["db"].ends_with("db.tar.xz"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn bulk_restore_skips_missing_containers() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let data_file = temp_dir.path().join("vol1/data.txt");
+        fs::write(&data_file, "changed")?;
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        let listed = container.clone();
+        client
+            .expect_list_containers()
+            .returning(move || Ok(vec![listed.clone()]));
+
+        let archives = BTreeMap::from([
+            (container.name.clone(), backup_file.clone()),
+            ("gone".to_string(), backup_file),
+        ]);
+        let outcomes =
+            restore_archives(&client, &archives, &RestoreOptions::default(), false, None).await?;
+
+        assert!(matches!(
+            outcomes.as_slice(),
+            [
+                (_, BulkRestoreOutcome::Restored),
+                (_, BulkRestoreOutcome::Skipped)
+            ]
+        ));
+        assert_eq!(fs::read_to_string(&data_file)?, "hello");
         Ok(())
     }
 
     #[tokio::test]
     async fn detect_container_mismatch() -> Result<()> {
-        DockerClient::init(10)?;
         let (_temp_dir, backup_file, _container) = setup_backup().await?;
-        let mut client = DockerClient::global()?;
+        let mut client = MockDockerClientInterface::with_defaults();
         client
             .expect_get_container_status()
             .returning(|_| Ok("exited".to_string()));
@@ -436,12 +2817,456 @@ mod tests {
             id: "other".into(),
             name: "other".into(),
             status: "running".into(),
+            ..Default::default()
+        };
+
+        let result = restore_volumes(
+            &client,
+            &other_container,
+            &backup_file,
+            None,
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_checksum_mismatch_unless_skipped() -> Result<()> {
+        let (_temp_dir, backup_file, container) = setup_backup().await?;
+        utils::write_checksum_file(&backup_file, &"0".repeat(64))?;
+        let client = MockDockerClientInterface::with_defaults();
+
+        let restore_dir = TempDir::new()?;
+        let output = Some(restore_dir.path().to_string_lossy().to_string());
+        let result = restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output.clone(),
+            false,
+            true,
+            &RestoreOptions::default(),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!restore_dir.path().join("vol1/data.txt").exists());
+
+        let options = RestoreOptions {
+            no_verify: true,
+            ..Default::default()
+        };
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output,
+            false,
+            true,
+            &options,
+        )
+        .await?;
+        assert!(restore_dir.path().join("vol1/data.txt").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn restores_only_selected_volumes() -> Result<()> {
+        let (_temp_dir, backup_file, container) = setup_backup().await?;
+        let client = MockDockerClientInterface::with_defaults();
+        let restore_dir = TempDir::new()?;
+        let output = Some(restore_dir.path().to_string_lossy().to_string());
+
+        let options = RestoreOptions {
+            volumes: vec!["vol2".into()],
+            ..Default::default()
+        };
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output.clone(),
+            false,
+            true,
+            &options,
+        )
+        .await?;
+        assert!(restore_dir.path().join("vol2/other.txt").exists());
+        assert!(!restore_dir.path().join("vol1").exists());
+
+        let options = RestoreOptions {
+            volumes: vec!["missing".into()],
+            ..Default::default()
         };
+        let result = restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output,
+            false,
+            true,
+            &options,
+        )
+        .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifies_extracted_files_against_manifest() -> Result<()> {
+        let (temp_dir, _, container) = setup_backup().await?;
+        let backup_file = temp_dir.path().join("verified.tar.xz");
+        let volumes: Vec<_> = ["vol1", "vol2"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        let parts = utils::compress_parts(
+            &volumes,
+            temp_dir.path(),
+            &Default::default(),
+            1,
+            &BTreeMap::new(),
+            Default::default(),
+        )?;
+
+        let mut manifest = parts.manifest().clone();
+        let mapping = utils::read_file_from_archive(
+            temp_dir.path().join("backup/backup.tar.xz"),
+            MAPPING_FILE_NAME,
+        )?;
+        manifest
+            .files
+            .insert("vol1/data.txt".into(), "0".repeat(64));
+        let checksums = toml::to_string(&manifest)?;
+        parts.write_archive(
+            &backup_file,
+            &[
+                (MAPPING_FILE_NAME, mapping.as_str()),
+                (CHECKSUMS_FILE_NAME, checksums.as_str()),
+            ],
+        )?;
 
-        let result =
-            restore_volumes(&client, &other_container, &backup_file, None, false, true).await;
+        let client = MockDockerClientInterface::with_defaults();
+        let restore_dir = TempDir::new()?;
+        let output = Some(restore_dir.path().to_string_lossy().to_string());
+        let mut options = RestoreOptions {
+            no_verify: true,
+            verify_checksums: true,
+            ..Default::default()
+        };
 
+        let result = restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output.clone(),
+            false,
+            true,
+            &options,
+        )
+        .await;
         assert!(result.is_err());
+
+        options.best_effort = true;
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            output,
+            false,
+            true,
+            &options,
+        )
+        .await?;
+        assert!(restore_dir.path().join("vol2/other.txt").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn print_mapping_is_read_only_preflight() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        fs::remove_dir_all(temp_dir.path().join("vol1"))?;
+
+        let mapping_content = utils::read_file_from_archive(&backup_file, MAPPING_FILE_NAME)?;
+        let mut mapping: BackupMapping = toml::from_str(&mapping_content)?;
+        mapping.volumes[1].volume_name = Some("app-data".into());
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping,
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
+        };
+
+        let rows = restore_mapping_rows(&archive, &container, None, false);
+        assert_eq!(
+            rows[0].target,
+            temp_dir.path().join("vol1").to_string_lossy()
+        );
+        assert_eq!(rows[0].exists, Some(false));
+        assert_eq!(rows[1].target, "volume:app-data");
+        assert_eq!(rows[1].exists, None);
+        assert!(!rows[1].needs_privileges);
+
+        let output = temp_dir.path().join("out");
+        let rows = restore_mapping_rows(&archive, &container, Some(&output), false);
+        assert_eq!(rows[1].target, output.join("vol2").to_string_lossy());
+
+        // 非交互模式下只打印，不写入任何卷
+        let client = MockDockerClientInterface::with_defaults();
+        let options = RestoreOptions {
+            print_mapping: Some(OutputFormat::Json),
+            ..Default::default()
+        };
+        restore_volumes(
+            &client,
+            &container,
+            &backup_file,
+            None,
+            false,
+            true,
+            &options,
+        )
+        .await?;
+        assert!(!temp_dir.path().join("vol1").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn print_mapping_json_keeps_stdout_parseable() -> Result<()> {
+        let (_temp_dir, backup_file, container) = setup_backup().await?;
+        // 校验通过的提示同样不能出现在 stdout 中
+        utils::write_checksum_file(&backup_file, &utils::sha256_file(&backup_file)?)?;
+
+        let mut client = MockDockerClientInterface::with_defaults();
+        let found = container.clone();
+        client
+            .expect_find_containers()
+            .returning(move |_| Ok(vec![found.clone()]));
+        let options = RestoreOptions {
+            print_mapping: Some(OutputFormat::Json),
+            ..Default::default()
+        };
+        let config = Config {
+            interactive: false,
+            yes: true,
+            ..Config::default()
+        };
+
+        let capture = utils::StdoutCapture::start();
+        let result = Config::scope(
+            config,
+            restore(
+                &client,
+                Some(container.name.clone()),
+                Some(backup_file.to_string_lossy().to_string()),
+                None,
+                options,
+            ),
+        )
+        .await;
+        let stdout = capture.finish();
+        result?;
+
+        let rows: serde_json::Value = serde_json::from_str(&stdout)?;
+        assert_eq!(rows.as_array().map(Vec::len), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn lists_restore_targets_including_aliases() {
+        let volume = |name: &str, source: &str| VolumeInfo {
+            name: name.into(),
+            source: PathBuf::from(source),
+            destination: PathBuf::from("/data"),
+            volume_name: None,
+        };
+        let backup_file = PathBuf::from("backup.tar.xz");
+        let archive = BackupArchive {
+            path: &backup_file,
+            mapping: BackupMapping {
+                schema_version: MAPPING_SCHEMA_VERSION,
+                container_name: "container".into(),
+                container_id: "id".into(),
+                volumes: vec![volume("data", "/srv/data")],
+                backup_time: "now".into(),
+                version: "test".into(),
+                payload_sha256: None,
+                aliases: vec![
+                    crate::docker::VolumeAlias {
+                        volume: volume("same", "/srv/data"),
+                        alias_of: "data".into(),
+                    },
+                    crate::docker::VolumeAlias {
+                        volume: volume("moved", "/srv/moved"),
+                        alias_of: "data".into(),
+                    },
+                ],
+                base: None,
+                files: Default::default(),
+                modified_after: None,
+                preserve_ownership: false,
+            },
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: None,
+            incremental_copy: false,
+        };
+
+        assert_eq!(
+            archive.restore_targets(),
+            vec![
+                ("data", Path::new("/srv/data")),
+                ("moved", Path::new("/srv/moved"))
+            ]
+        );
+    }
+
+    #[test]
+    fn remaps_volumes_to_target_container_by_destination() {
+        let volume =
+            |name: &str, source: &str, destination: &str, volume_name: Option<&str>| VolumeInfo {
+                name: name.into(),
+                source: PathBuf::from(source),
+                destination: PathBuf::from(destination),
+                volume_name: volume_name.map(Into::into),
+            };
+        let mut mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: "app-old".into(),
+            container_id: "old".into(),
+            volumes: vec![
+                volume("data", "/srv/old/data", "/data", None),
+                volume(
+                    "db",
+                    "/var/lib/docker/volumes/old_db/_data",
+                    "/db",
+                    Some("old_db"),
+                ),
+                volume("cache", "/srv/old/cache", "/cache", None),
+            ],
+            backup_time: "now".into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: vec![crate::docker::VolumeAlias {
+                volume: volume("logs", "/srv/old/logs", "/logs", None),
+                alias_of: "data".into(),
+            }],
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+        let targets = [
+            volume(
+                "new_db",
+                "/var/lib/docker/volumes/new_db/_data",
+                "/db",
+                Some("new_db"),
+            ),
+            volume("data", "/srv/new/data", "/data", None),
+        ];
+
+        remap_volumes_by_destination(&mut mapping, &targets);
+
+        let remapped = mapping
+            .volumes
+            .iter()
+            .map(|v| (v.name.as_str(), v.source.clone(), v.volume_name.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            remapped,
+            vec![
+                ("data", PathBuf::from("/srv/new/data"), None),
+                (
+                    "db",
+                    PathBuf::from("/var/lib/docker/volumes/new_db/_data"),
+                    Some("new_db")
+                ),
+            ]
+        );
+        assert!(mapping.aliases.is_empty());
+    }
+
+    #[tokio::test]
+    async fn spools_backup_read_from_stdin() -> Result<()> {
+        let (temp_dir, backup_file, container) = setup_backup().await?;
+        let spool_dir = temp_dir.path().join("spool");
+        std::fs::create_dir_all(&spool_dir)?;
+
+        let (spool, _partial) = spool_reader(std::fs::File::open(&backup_file)?, Some(&spool_dir))?;
+        assert!(spool.path().starts_with(&spool_dir));
+        let mapping = parse_mapping(&utils::read_file_from_archive(
+            spool.path(),
+            MAPPING_FILE_NAME,
+        )?)?;
+        assert_eq!(mapping.container_name, container.name);
+
+        assert!(spool_reader(std::io::empty(), Some(&spool_dir)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn staging_dir_checks_available_space() -> Result<()> {
+        let temp = TempDir::new()?;
+        let backup_file = PathBuf::from("backup.tar.xz");
+        let stamp = |size| utils::FileStamp { mtime_ns: 0, size };
+        let mut archive = BackupArchive {
+            path: &backup_file,
+            mapping: BackupMapping {
+                schema_version: MAPPING_SCHEMA_VERSION,
+                container_name: "container".into(),
+                container_id: "id".into(),
+                volumes: vec![VolumeInfo {
+                    name: "data".into(),
+                    source: PathBuf::from("/srv/data"),
+                    destination: PathBuf::from("/data"),
+                    volume_name: None,
+                }],
+                backup_time: "now".into(),
+                version: "test".into(),
+                payload_sha256: None,
+                aliases: Vec::new(),
+                base: None,
+                files: [
+                    ("data/a.txt".to_string(), stamp(1024)),
+                    ("other/b.txt".to_string(), stamp(u64::MAX / 2)),
+                ]
+                .into(),
+                modified_after: None,
+                preserve_ownership: false,
+            },
+            bases: Vec::new(),
+            deleted: BTreeSet::new(),
+            manifest: None,
+            best_effort: false,
+            temp_dir: Some(temp.path().join("staging")),
+            incremental_copy: false,
+        };
+
+        // 只计算选中的卷
+        assert_eq!(archive.uncompressed_size(), Some(1024));
+        let staging = archive.create_staging_dir()?;
+        assert!(staging.path().starts_with(temp.path().join("staging")));
+
+        archive
+            .mapping
+            .files
+            .insert("data/huge.bin".into(), stamp(u64::MAX / 2));
+        assert!(archive.create_staging_dir().is_err());
+
+        archive.mapping.files.clear();
+        assert_eq!(archive.uncompressed_size(), None);
+        archive.create_staging_dir()?;
         Ok(())
     }
 }
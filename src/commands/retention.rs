@@ -0,0 +1,633 @@
+use crate::{
+    commands::{MAPPING_FILE_NAME, backup, restore},
+    config::Config,
+    log_bail, log_println, utils,
+};
+
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use dialoguer::{Confirm, MultiSelect};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// 备份保留策略
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// 保留最近的 N 个备份
+    pub keep_last: Option<usize>,
+    /// 删除超过 D 天的备份 (按文件名中的时间戳计算)
+    pub keep_days: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none() && self.keep_days.is_none()
+    }
+}
+
+/// 按保留策略清理输出目录中同一容器的旧备份
+///
+/// `current` 为本次刚创建的备份，无论策略如何都不会被删除
+pub fn prune_backups(
+    output_dir: &Path,
+    container_name: &str,
+    current: &Path,
+    policy: &RetentionPolicy,
+) -> Result<()> {
+    if policy.is_empty() {
+        return Ok(());
+    }
+
+    let candidates = collect_backups(output_dir, container_name)?
+        .into_iter()
+        .filter(|(path, _)| path != current)
+        .collect::<Vec<_>>();
+    // 本次备份占用一个保留名额
+    let policy = RetentionPolicy {
+        keep_last: policy.keep_last.map(|n| n.saturating_sub(1)),
+        ..*policy
+    };
+    let mut expired = select_expired(candidates.clone(), &policy, Local::now().naive_local());
+
+    // 仍被保留的增量备份所依赖的基础备份不能删除，否则增量备份将无法恢复
+    let kept = candidates
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| !expired.contains(path))
+        .chain(std::iter::once(current.to_path_buf()))
+        .collect::<Vec<_>>();
+    let required = chain_bases(output_dir, &kept);
+    expired.retain(|path| !required.contains(path));
+
+    if expired.is_empty() {
+        debug!(?output_dir, container_name, "No backups to prune");
+        return Ok(());
+    }
+
+    let config = Config::global()?;
+    if config.interactive && !config.yes {
+        let file_list = expired
+            .iter()
+            .map(|p| format!(" - {}", p.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let confirmed = Confirm::new()
+            .with_prompt(t!(
+                "commands.confirm_prune_backups",
+                "count" = utils::format_number(expired.len() as u64),
+                "files" = file_list
+            ))
+            .default(false)
+            .interact()?;
+
+        if !confirmed {
+            log_println!("INFO", "{}", t!("commands.prune_cancelled"));
+            return Ok(());
+        }
+    }
+
+    for path in &expired {
+        remove_backup(path)?;
+    }
+
+    info!(count = expired.len(), "Old backups pruned");
+    Ok(())
+}
+
+/// 列出输出目录中属于该容器的备份文件及其时间戳
+///
+/// 默认命名的备份按文件名识别，其他文件 (如 `--output-name` 自定义的文件名) 按 mapping 中记录的容器名与备份时间识别
+pub(crate) fn collect_backups(
+    dir: &Path,
+    container_name: &str,
+) -> Result<Vec<(PathBuf, NaiveDateTime)>> {
+    let files = utils::get_files_start_with(dir, "", false)?;
+    Ok(files
+        .into_iter()
+        .filter(|f| !utils::is_sidecar_file(f) && !backup::is_latest_pointer(f))
+        .filter_map(|f| {
+            let name = f.file_name()?.to_string_lossy().to_string();
+            if let Some(timestamp) = utils::parse_timestamp_filename(&name, container_name) {
+                return Some((f, timestamp));
+            }
+            let (container, timestamp) = read_backup_identity(&f)?;
+            (container == container_name).then_some((f, timestamp))
+        })
+        .collect())
+}
+
+/// 从归档的 mapping 中读取容器名与备份时间，不是备份归档的文件返回 `None`
+fn read_backup_identity(path: &Path) -> Option<(String, NaiveDateTime)> {
+    let mapping = utils::read_file_from_archive(path, MAPPING_FILE_NAME)
+        .and_then(|content| restore::parse_mapping(&content))
+        .ok()?;
+    let timestamp =
+        NaiveDateTime::parse_from_str(&mapping.backup_time, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some((mapping.container_name, timestamp))
+}
+
+/// 按保留策略挑选需要删除的备份，超出 `keep_last` 或早于 `keep_days` 的都会被删除
+fn select_expired(
+    backups: Vec<(PathBuf, NaiveDateTime)>,
+    policy: &RetentionPolicy,
+    now: NaiveDateTime,
+) -> Vec<PathBuf> {
+    let deadline = policy
+        .keep_days
+        .and_then(|days| TimeDelta::try_days(days as i64))
+        .map(|age| now - age);
+    select_beyond(backups, policy.keep_last, deadline)
+}
+
+/// 挑选按时间从新到旧排在第 `keep_last` 个之后或早于 `deadline` 的备份
+fn select_beyond(
+    mut backups: Vec<(PathBuf, NaiveDateTime)>,
+    keep_last: Option<usize>,
+    deadline: Option<NaiveDateTime>,
+) -> Vec<PathBuf> {
+    backups.sort_by_key(|(_, timestamp)| std::cmp::Reverse(*timestamp));
+
+    backups
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, timestamp))| {
+            keep_last.is_some_and(|n| *index >= n)
+                || deadline.is_some_and(|deadline| *timestamp < deadline)
+        })
+        .map(|(_, (path, _))| path)
+        .collect()
+}
+
+/// 收集 `archives` 中的增量备份 (逐级) 依赖的所有基础备份
+fn chain_bases(dir: &Path, archives: &[PathBuf]) -> HashSet<PathBuf> {
+    let mut bases = HashSet::new();
+    for archive in archives {
+        let mut current = archive.clone();
+        while let Some(base) = utils::read_file_from_archive(&current, MAPPING_FILE_NAME)
+            .ok()
+            .and_then(|content| restore::parse_mapping(&content).ok())
+            .and_then(|mapping| mapping.base)
+        {
+            let base_path = dir.join(base.file);
+            if !bases.insert(base_path.clone()) {
+                break;
+            }
+            current = base_path;
+        }
+    }
+    bases
+}
+
+/// 删除备份文件及其校验和文件与备份清单，返回释放的字节数
+fn remove_backup(path: &Path) -> Result<u64> {
+    let mut freed = std::fs::metadata(path)?.len();
+    std::fs::remove_file(path)?;
+
+    for sidecar in [
+        utils::checksum_file_path(path),
+        utils::manifest_file_path(path),
+    ] {
+        if sidecar.exists() {
+            freed += std::fs::metadata(&sidecar)?.len();
+            std::fs::remove_file(&sidecar)?;
+        }
+    }
+
+    log_println!(
+        "INFO",
+        "{}",
+        t!("commands.backup_pruned", "path" = path.to_string_lossy())
+    );
+    Ok(freed)
+}
+
+/// `prune` 命令的非交互筛选条件，均未设置时在交互模式下手动选择要删除的备份
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneFilter {
+    /// 删除早于该时长之前创建的备份
+    pub older_than: Option<Duration>,
+    /// 每个容器保留最近的 N 个备份
+    pub keep: Option<usize>,
+}
+
+impl PruneFilter {
+    pub fn is_empty(&self) -> bool {
+        self.older_than.is_none() && self.keep.is_none()
+    }
+}
+
+/// 备份目录中的一个备份文件
+#[derive(Debug, Clone)]
+struct BackupEntry {
+    path: PathBuf,
+    timestamp: NaiveDateTime,
+    size: u64,
+}
+
+/// 清理备份目录：按容器分组列出其中的备份，按筛选条件或交互选择删除，最后输出释放的空间
+///
+/// `dir` 为空时使用配置中的备份目录。仍被保留的增量备份所依赖的基础备份不会被删除
+pub fn prune_directory(dir: Option<String>, filter: PruneFilter) -> Result<()> {
+    let config = Config::global()?;
+    let dir = dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config.backup_dir.clone());
+    let dir = utils::resolve_path(&dir)?;
+    info!(?dir, ?filter, "Pruning backup directory");
+
+    let groups = scan_backup_dir(&dir)?;
+    if groups.is_empty() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.prune_no_backups", "dir" = dir.to_string_lossy())
+        );
+        return Ok(());
+    }
+    print_backup_groups(&groups);
+
+    let mut selected = if !filter.is_empty() {
+        select_by_filter(&groups, &filter, Local::now().naive_local())
+    } else if config.interactive {
+        select_interactively(&groups)?
+    } else {
+        log_bail!("ERROR", "{}", t!("commands.prune_requires_filter"));
+    };
+
+    let kept = groups
+        .values()
+        .flatten()
+        .map(|b| b.path.clone())
+        .filter(|path| !selected.contains(path))
+        .collect::<Vec<_>>();
+    let required = chain_bases(&dir, &kept);
+    selected.retain(|path| {
+        let needed = required.contains(path);
+        if needed {
+            log_println!(
+                "WARN",
+                "{}",
+                t!(
+                    "commands.prune_base_required",
+                    "path" = path.to_string_lossy()
+                )
+            );
+        }
+        !needed
+    });
+
+    if selected.is_empty() {
+        log_println!("INFO", "{}", t!("commands.prune_nothing_selected"));
+        return Ok(());
+    }
+
+    let file_list = selected
+        .iter()
+        .map(|p| format!(" - {}", p.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if config.dry_run {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.dry_run_would_prune", "files" = file_list)
+        );
+        log_println!("INFO", "{}", t!("commands.dry_run_no_changes"));
+        return Ok(());
+    }
+    if !config.yes {
+        let confirmed = Confirm::new()
+            .with_prompt(t!(
+                "commands.confirm_prune_backups",
+                "count" = utils::format_number(selected.len() as u64),
+                "files" = file_list
+            ))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            log_println!("INFO", "{}", t!("commands.prune_cancelled"));
+            return Ok(());
+        }
+    }
+
+    let mut freed = 0;
+    for path in &selected {
+        freed += remove_backup(path)?;
+    }
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.prune_reclaimed",
+            "count" = utils::format_number(selected.len() as u64),
+            "size" = utils::format_bytes(freed)
+        )
+    );
+    Ok(())
+}
+
+/// 按文件名约定 (`<容器名>_<类型>_<时间戳>.tar.xz`) 将目录中的备份按容器分组，每组按时间从新到旧排列
+///
+/// 不符合该约定的文件按其 mapping 分组，无法读取 mapping 的文件会被跳过
+fn scan_backup_dir(dir: &Path) -> Result<BTreeMap<String, Vec<BackupEntry>>> {
+    let mut groups = BTreeMap::<String, Vec<BackupEntry>>::new();
+    for path in utils::get_files_start_with(dir, "", false)? {
+        if utils::is_sidecar_file(&path) || backup::is_latest_pointer(&path) {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let identity = match utils::split_timestamp_filename(&name) {
+            Some((container, timestamp)) => Some((container.to_string(), timestamp)),
+            None => read_backup_identity(&path),
+        };
+        let Some((container, timestamp)) = identity else {
+            debug!(
+                ?path,
+                "Skipping file not following the backup naming convention"
+            );
+            continue;
+        };
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        groups.entry(container).or_default().push(BackupEntry {
+            path: path.clone(),
+            timestamp,
+            size,
+        });
+    }
+    for backups in groups.values_mut() {
+        backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+    }
+    Ok(groups)
+}
+
+/// 一个备份在列表中的显示内容：时间、大小与文件名
+fn describe_backup(backup: &BackupEntry) -> String {
+    format!(
+        "{}  {:>10}  {}",
+        backup.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        utils::format_bytes(backup.size),
+        backup
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    )
+}
+
+fn print_backup_groups(groups: &BTreeMap<String, Vec<BackupEntry>>) {
+    for (container, backups) in groups {
+        println!(
+            "{}",
+            t!(
+                "commands.prune_group",
+                "container" = container,
+                "count" = utils::format_number(backups.len() as u64),
+                "size" = utils::format_bytes(backups.iter().map(|b| b.size).sum())
+            )
+        );
+        for backup in backups {
+            println!("  {}", describe_backup(backup));
+        }
+    }
+}
+
+/// 在每个容器的备份中挑选超出 `keep` 个或早于 `older_than` 的备份
+fn select_by_filter(
+    groups: &BTreeMap<String, Vec<BackupEntry>>,
+    filter: &PruneFilter,
+    now: NaiveDateTime,
+) -> Vec<PathBuf> {
+    let deadline = filter
+        .older_than
+        .and_then(|age| TimeDelta::from_std(age).ok())
+        .map(|age| now - age);
+    groups
+        .values()
+        .flat_map(|backups| {
+            let backups = backups
+                .iter()
+                .map(|b| (b.path.clone(), b.timestamp))
+                .collect();
+            select_beyond(backups, filter.keep, deadline)
+        })
+        .collect()
+}
+
+fn select_interactively(groups: &BTreeMap<String, Vec<BackupEntry>>) -> Result<Vec<PathBuf>> {
+    let backups = groups
+        .iter()
+        .flat_map(|(container, backups)| backups.iter().map(move |b| (container, b)))
+        .collect::<Vec<_>>();
+    let items = backups
+        .iter()
+        .map(|(container, backup)| format!("{container}  {}", describe_backup(backup)))
+        .collect::<Vec<_>>();
+    let selected = MultiSelect::new()
+        .with_prompt(t!("prompt.select_backups_to_prune"))
+        .items(&items)
+        .interact()?;
+    Ok(selected
+        .into_iter()
+        .map(|i| backups[i].1.path.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    fn timestamp(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y%m%d_%H%M%S").unwrap()
+    }
+
+    #[test]
+    fn keeps_last_n_backups() {
+        let backups = ["20240101_000000", "20240103_000000", "20240102_000000"]
+            .iter()
+            .map(|t| (PathBuf::from(*t), timestamp(t)))
+            .collect();
+
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_days: None,
+        };
+        let expired = select_expired(backups, &policy, timestamp("20240104_000000"));
+        assert_eq!(
+            expired,
+            vec![
+                PathBuf::from("20240102_000000"),
+                PathBuf::from("20240101_000000")
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_backups_older_than_keep_days() {
+        let backups = ["20240101_000000", "20240109_000000"]
+            .iter()
+            .map(|t| (PathBuf::from(*t), timestamp(t)))
+            .collect();
+
+        let policy = RetentionPolicy {
+            keep_last: None,
+            keep_days: Some(7),
+        };
+        let expired = select_expired(backups, &policy, timestamp("20240110_000000"));
+        assert_eq!(expired, vec![PathBuf::from("20240101_000000")]);
+    }
+
+    #[test]
+    fn collects_only_matching_container_archives() -> Result<()> {
+        let dir = TempDir::new()?;
+        for name in [
+            "web_all_20240101_000000.tar.xz",
+            "web_all_20240101_000000.tar.xz.sha256",
+            "web_db_all_20240101_000000.tar.xz",
+            "web_latest.tar.xz",
+        ] {
+            std::fs::write(dir.path().join(name), "")?;
+        }
+
+        let backups = collect_backups(dir.path(), "web")?;
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].0.ends_with("web_all_20240101_000000.tar.xz"));
+        Ok(())
+    }
+
+    #[test]
+    fn prunes_archives_named_by_output_template() -> Result<()> {
+        let dir = TempDir::new()?;
+        let write_archive = |name: &str, container_name: &str, backup_time: &str| -> Result<()> {
+            let mapping = crate::docker::BackupMapping {
+                schema_version: crate::docker::MAPPING_SCHEMA_VERSION,
+                container_name: container_name.into(),
+                container_id: "id".into(),
+                volumes: Vec::new(),
+                backup_time: backup_time.into(),
+                version: "test".into(),
+                payload_sha256: None,
+                aliases: Vec::new(),
+                base: None,
+                files: Default::default(),
+                modified_after: None,
+                preserve_ownership: false,
+            };
+            utils::compress_with_memory_file(
+                &[] as &[&Path],
+                dir.path().join(name).as_path(),
+                &[(MAPPING_FILE_NAME, toml::to_string(&mapping)?.as_str())],
+                &[],
+                utils::CompressOptions::default(),
+            )
+        };
+        write_archive(
+            "web-nightly-20240101T000000.tar.xz",
+            "web",
+            "2024-01-01 00:00:00",
+        )?;
+        write_archive(
+            "web-nightly-20240102T000000.tar.xz",
+            "web",
+            "2024-01-02 00:00:00",
+        )?;
+        write_archive(
+            "db-nightly-20240101T000000.tar.xz",
+            "db",
+            "2024-01-01 00:00:00",
+        )?;
+        write_archive(
+            "web-nightly-20240103T000000.tar.xz",
+            "web",
+            "2024-01-03 00:00:00",
+        )?;
+        let current = dir.path().join("web-nightly-20240103T000000.tar.xz");
+
+        let mut backups = collect_backups(dir.path(), "web")?;
+        backups.sort();
+        assert_eq!(backups.len(), 3);
+        assert_eq!(backups[0].1, timestamp("20240101_000000"));
+
+        let config = Config {
+            interactive: false,
+            ..Config::default()
+        };
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            keep_days: None,
+        };
+        Config::sync_scope(config, || {
+            prune_backups(dir.path(), "web", &current, &policy)
+        })?;
+
+        assert!(
+            !dir.path()
+                .join("web-nightly-20240101T000000.tar.xz")
+                .exists()
+        );
+        assert!(
+            dir.path()
+                .join("web-nightly-20240102T000000.tar.xz")
+                .exists()
+        );
+        assert!(current.exists());
+        assert!(
+            dir.path()
+                .join("db-nightly-20240101T000000.tar.xz")
+                .exists()
+        );
+
+        let groups = scan_backup_dir(dir.path())?;
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["db", "web"]);
+        Ok(())
+    }
+
+    #[test]
+    fn groups_directory_backups_and_applies_prune_filter() -> Result<()> {
+        let dir = TempDir::new()?;
+        for name in [
+            "web_all_20240101_000000.tar.xz",
+            "web_all_20240101_000000.tar.xz.sha256",
+            "web_all_20240105_000000.tar.xz",
+            "web_db_all_20240102_000000.tar.xz",
+            "web_latest.tar.xz",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), "data")?;
+        }
+
+        let groups = scan_backup_dir(dir.path())?;
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["web", "web_db"]);
+        assert!(
+            groups["web"][0]
+                .path
+                .ends_with("web_all_20240105_000000.tar.xz")
+        );
+        assert_eq!(groups["web"][0].size, 4);
+
+        let now = timestamp("20240106_000000");
+        let keep_one = PruneFilter {
+            keep: Some(1),
+            ..Default::default()
+        };
+        let expired = select_by_filter(&groups, &keep_one, now);
+        assert_eq!(expired, [dir.path().join("web_all_20240101_000000.tar.xz")]);
+
+        let older_than = PruneFilter {
+            older_than: Some(Duration::from_secs(3 * 24 * 3600)),
+            ..Default::default()
+        };
+        let expired = select_by_filter(&groups, &older_than, now);
+        assert_eq!(expired.len(), 2);
+
+        assert_eq!(remove_backup(&expired[0])?, 4 + 4);
+        assert!(
+            !dir.path()
+                .join("web_all_20240101_000000.tar.xz.sha256")
+                .exists()
+        );
+        Ok(())
+    }
+}
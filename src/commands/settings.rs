@@ -0,0 +1,62 @@
+use crate::{
+    config::{self, Config, Settings},
+    log_bail, log_println,
+};
+
+use anyhow::Result;
+use std::path::Path;
+use tracing::info;
+
+/// 以带注释的 TOML 格式将当前生效的设置 (配置文件与命令行参数合并后) 输出到 stdout
+pub fn export_settings() -> Result<()> {
+    let settings = Config::global()?.settings();
+    info!(?settings, "Exporting settings");
+    print!("{}", settings.to_toml()?);
+    Ok(())
+}
+
+/// 校验 `file` 中的设置并写入用户配置文件，文件中未出现的设置恢复为默认值
+///
+/// 出现未知的键或无效的值时直接返回错误，不修改现有的配置文件
+pub fn import_settings(file: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let settings = match Settings::from_toml(&content) {
+        Ok(settings) => settings,
+        Err(e) => log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "config.settings_invalid",
+                "path" = file.to_string_lossy(),
+                "error" = e
+            )
+        ),
+    };
+    info!(?file, ?settings, "Importing settings");
+
+    let Some(path) = config::user_config_path() else {
+        log_bail!("ERROR", "{}", t!("config.config_dir_unknown"));
+    };
+    if Config::is_dry_run() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "config.settings_import_dry_run",
+                "path" = path.to_string_lossy()
+            )
+        );
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    settings.save_to_file(&path)?;
+    log_println!(
+        "INFO",
+        "{}",
+        t!("config.settings_imported", "path" = path.to_string_lossy())
+    );
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::{
+    commands::{MAPPING_FILE_NAME, OutputFormat, restore},
+    config::Config,
+    docker::BackupMapping,
+    log_println, utils,
+};
+
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// 一个容器在备份目录中的备份概况
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ContainerBackups {
+    container: String,
+    count: usize,
+    /// 最近一次备份的时间 (`%Y-%m-%d %H:%M:%S`)，无法确定时为 `None`
+    latest: Option<String>,
+    /// 所有备份文件占用的磁盘空间 (字节)
+    size: u64,
+}
+
+/// 汇总备份目录中每个容器的备份数量、最近备份时间与占用空间
+///
+/// 通过每个压缩包中的 `mapping.toml` 按容器名分组，不依赖文件名；无法读取 mapping 的文件会被跳过
+pub fn backup_status(dir: Option<String>, format: OutputFormat) -> Result<()> {
+    let dir = dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Config::global().unwrap_or_default().backup_dir);
+    let dir = utils::resolve_path(&dir)?;
+    info!(?dir, "Summarizing backups");
+
+    let summary = summarize_backups(&dir)?;
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    if summary.is_empty() {
+        log_println!(
+            "INFO",
+            "{}",
+            t!("commands.status_no_backups", "dir" = dir.to_string_lossy())
+        );
+        return Ok(());
+    }
+    print_status_table(&dir, &summary);
+    Ok(())
+}
+
+fn summarize_backups(dir: &Path) -> Result<Vec<ContainerBackups>> {
+    let mut groups = BTreeMap::<String, ContainerBackups>::new();
+    for path in utils::get_files_start_with(dir, "", false)? {
+        if utils::is_sidecar_file(&path) {
+            continue;
+        }
+        let mapping = match utils::read_file_from_archive(&path, MAPPING_FILE_NAME)
+            .and_then(|content| restore::parse_mapping(&content))
+        {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                debug!(?path, ?e, "Skipping file without a readable mapping");
+                continue;
+            }
+        };
+
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let time = backup_time(&mapping, &path);
+        let group = groups
+            .entry(mapping.container_name.clone())
+            .or_insert_with(|| ContainerBackups {
+                container: mapping.container_name.clone(),
+                count: 0,
+                latest: None,
+                size: 0,
+            });
+        group.count += 1;
+        group.size += size;
+        if let Some(time) = time.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            && group.latest.as_ref().is_none_or(|latest| time > *latest)
+        {
+            group.latest = Some(time);
+        }
+    }
+    Ok(groups.into_values().collect())
+}
+
+/// 备份时间：优先使用 mapping 中记录的时间，其次使用文件名中的时间戳
+fn backup_time(mapping: &BackupMapping, path: &Path) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(&mapping.backup_time, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| {
+            let name = path.file_name()?.to_string_lossy();
+            utils::split_timestamp_filename(&name).map(|(_, time)| time)
+        })
+}
+
+fn print_status_table(dir: &Path, summary: &[ContainerBackups]) {
+    println!(
+        "\n{}:",
+        t!("commands.status_title", "dir" = dir.to_string_lossy())
+    );
+    println!(
+        "{:<24} {:>8} {:<20} {:>12}",
+        t!("commands.status_container"),
+        t!("commands.status_count"),
+        t!("commands.status_latest"),
+        t!("commands.status_size")
+    );
+    println!("{:-<67}", "");
+
+    for group in summary {
+        println!(
+            "{:<24} {:>8} {:<20} {:>12}",
+            group.container,
+            utils::format_number(group.count as u64),
+            group.latest.as_deref().unwrap_or("-"),
+            utils::format_bytes(group.size)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::MAPPING_SCHEMA_VERSION;
+    use assert_fs::TempDir;
+
+    fn write_backup(dir: &Path, file_name: &str, container: &str, time: &str) -> Result<()> {
+        let data = dir.join("data");
+        std::fs::create_dir_all(&data)?;
+        std::fs::write(data.join("file.txt"), file_name)?;
+        let mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: container.into(),
+            container_id: format!("{container}-id"),
+            volumes: Vec::new(),
+            backup_time: time.into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: Vec::new(),
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+        let mapping = toml::to_string(&mapping)?;
+        utils::compress_with_memory_file(
+            &[data.as_path()],
+            &dir.join("backups").join(file_name),
+            &[(MAPPING_FILE_NAME, mapping.as_str())],
+            &[],
+            utils::CompressOptions::default(),
+        )
+    }
+
+    #[test]
+    fn groups_backups_by_container_in_mapping() -> Result<()> {
+        let temp = TempDir::new()?;
+        let backups = temp.path().join("backups");
+        std::fs::create_dir_all(&backups)?;
+        write_backup(
+            temp.path(),
+            "web_20240101_000000.tar.xz",
+            "web",
+            "2024-01-01 00:00:00",
+        )?;
+        write_backup(
+            temp.path(),
+            "web_20240301_000000.tar.xz",
+            "web",
+            "2024-03-01 00:00:00",
+        )?;
+        // 文件名与容器名不一致时按 mapping 分组
+        write_backup(temp.path(), "renamed.tar.xz", "db", "2024-02-01 12:30:00")?;
+        std::fs::write(backups.join("notes.txt"), "not a backup")?;
+
+        let summary = summarize_backups(&backups)?;
+        let size = |name: &str| std::fs::metadata(backups.join(name)).unwrap().len();
+        assert_eq!(
+            summary,
+            vec![
+                ContainerBackups {
+                    container: "db".into(),
+                    count: 1,
+                    latest: Some("2024-02-01 12:30:00".into()),
+                    size: size("renamed.tar.xz"),
+                },
+                ContainerBackups {
+                    container: "web".into(),
+                    count: 2,
+                    latest: Some("2024-03-01 00:00:00".into()),
+                    size: size("web_20240101_000000.tar.xz") + size("web_20240301_000000.tar.xz"),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
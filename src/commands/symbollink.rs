@@ -1,10 +1,51 @@
-use crate::{commands::privileges, config::Config, log_println};
+#[cfg(unix)]
+use crate::commands::privileges;
+use crate::{config::Config, log_println};
 
 use anyhow::{Context, Result};
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
+/// unix 上符号链接的位置
+#[cfg(unix)]
 const SYMBOLINK_PATH: &str = "/usr/local/bin/rdbkp2";
 
+/// Windows 上启动脚本的文件名，放在 `%LOCALAPPDATA%\rdbkp2\bin` 下
+#[cfg(windows)]
+const SHIM_FILE_NAME: &str = "rdbkp2.cmd";
+
+/// 按平台确定 `link install` 默认创建的文件路径
+///
+/// - unix：`/usr/local/bin/rdbkp2` 符号链接
+/// - Windows：`%LOCALAPPDATA%\rdbkp2\bin\rdbkp2.cmd` 启动脚本 (创建符号链接需要开发者模式或管理员权限)
+fn default_symbollink_path() -> Result<PathBuf> {
+    #[cfg(unix)]
+    return Ok(PathBuf::from(SYMBOLINK_PATH));
+
+    #[cfg(windows)]
+    return dirs::data_local_dir()
+        .map(|dir| dir.join("rdbkp2").join("bin").join(SHIM_FILE_NAME))
+        .ok_or_else(|| anyhow::anyhow!(t!("symbollink.local_data_dir_not_found")));
+}
+
+/// 确定链接的位置：未指定 `--path` 时使用平台默认路径，相对路径按当前目录解析
+///
+/// Windows 上启动脚本必须带有可执行的扩展名，没有扩展名时补上 `.cmd`
+fn symbollink_path(path: Option<PathBuf>) -> Result<PathBuf> {
+    let Some(path) = path else {
+        return default_symbollink_path();
+    };
+    #[cfg(windows)]
+    let path = if path.extension().is_none() {
+        path.with_extension("cmd")
+    } else {
+        path
+    };
+    Ok(std::path::absolute(path)?)
+}
+
 /// 用户确认对话框
 fn confirm_action(prompt: &str) -> Result<bool> {
     let ensure = dialoguer::Confirm::new()
@@ -55,13 +96,17 @@ fn check_path_status(path: &Path, force: bool, is_create: bool) -> Result<bool>
     Ok(true)
 }
 
-pub(crate) fn create_symbollink() -> Result<()> {
-    privileges::ensure_admin_privileges()?;
-    let path = Path::new(SYMBOLINK_PATH);
+pub(crate) fn create_symbollink(path: Option<PathBuf>) -> Result<()> {
+    let path = symbollink_path(path)?;
+    let privileged = needs_privileges(&path);
+    if privileged {
+        #[cfg(unix)]
+        privileges::ensure_admin_privileges()?;
+    }
     let force = Config::global()?.yes;
 
     // 检查路径状态
-    if !check_path_status(path, force, true)? {
+    if !check_path_status(&path, force, true)? {
         return Ok(());
     }
 
@@ -79,73 +124,180 @@ pub(crate) fn create_symbollink() -> Result<()> {
     }
 
     let current_exe = std::env::current_exe()?;
-    let exe_path = current_exe.to_string_lossy().into_owned();
-
-    // 创建符号链接
-    privilege::runas::Command::new("ln")
-        .args(&["-sf", &exe_path, SYMBOLINK_PATH])
-        .run()
-        .with_context(|| {
-            format!(
-                "{}",
-                t!(
-                    "symbollink.failed_to_create_symbollink",
-                    "path" = SYMBOLINK_PATH
-                )
+    install_link(&current_exe, &path, privileged).with_context(|| {
+        format!(
+            "{}",
+            t!(
+                "symbollink.failed_to_create_symbollink",
+                "path" = path.display()
             )
-        })?;
+        )
+    })?;
 
     log_println!(
         "INFO",
         "{}",
         t!(
             "symbollink.success_create_symbollink",
-            "path" = SYMBOLINK_PATH
+            "path" = path.display()
         )
     );
+
+    if let Some(dir) = path
+        .parent()
+        .filter(|dir| !dir_in_path(dir, &std::env::var_os("PATH").unwrap_or_default()))
+    {
+        log_println!(
+            "WARN",
+            "{}",
+            t!("symbollink.link_dir_not_in_path", "dir" = dir.display())
+        );
+    }
     Ok(())
 }
 
-pub(crate) fn remove_symbollink() -> Result<()> {
-    privileges::ensure_admin_privileges()?;
-    let path = Path::new(SYMBOLINK_PATH);
+pub(crate) fn remove_symbollink(path: Option<PathBuf>) -> Result<()> {
+    let path = symbollink_path(path)?;
+    let privileged = needs_privileges(&path);
+    if privileged {
+        #[cfg(unix)]
+        privileges::ensure_admin_privileges()?;
+    }
     let force = Config::global()?.yes;
 
     if !path.exists() {
         log_println!(
             "INFO",
             "{}",
-            t!("symbollink.symbollink_not_exists", "path" = SYMBOLINK_PATH)
+            t!("symbollink.symbollink_not_exists", "path" = path.display())
         );
         return Ok(());
     }
 
     // 检查路径状态
-    if !check_path_status(path, force, false)? {
+    if !check_path_status(&path, force, false)? {
         return Ok(());
     }
 
     // 删除链接
-    privilege::runas::Command::new("rm")
-        .args(&["-f", SYMBOLINK_PATH])
-        .run()
-        .with_context(|| {
-            format!(
-                "{}",
-                t!(
-                    "symbollink.failed_to_remove_symbollink",
-                    "path" = SYMBOLINK_PATH
-                )
+    uninstall_link(&path, privileged).with_context(|| {
+        format!(
+            "{}",
+            t!(
+                "symbollink.failed_to_remove_symbollink",
+                "path" = path.display()
             )
-        })?;
+        )
+    })?;
 
     log_println!(
         "INFO",
         "{}",
         t!(
             "symbollink.success_remove_symbollink",
-            "path" = SYMBOLINK_PATH
+            "path" = path.display()
         )
     );
     Ok(())
 }
+
+/// 链接所在目录当前用户不可写时 (如默认的 `/usr/local/bin`) 需要管理员权限，
+/// Windows 上的启动脚本总是直接写入
+fn needs_privileges(path: &Path) -> bool {
+    #[cfg(unix)]
+    return !path.parent().is_some_and(privileges::is_writable);
+
+    #[cfg(windows)]
+    return false;
+}
+
+/// 创建指向当前可执行文件的符号链接，`privileged` 为真时通过管理员权限执行 `ln`
+#[cfg(unix)]
+fn install_link(exe: &Path, path: &Path, privileged: bool) -> Result<()> {
+    if privileged {
+        privilege::runas::Command::new("ln")
+            .args(&["-sf", &exe.to_string_lossy(), &path.to_string_lossy()])
+            .run()?;
+        return Ok(());
+    }
+
+    if path.symlink_metadata().is_ok() {
+        fs::remove_file(path)?;
+    }
+    std::os::unix::fs::symlink(exe, path)?;
+    Ok(())
+}
+
+/// 写入转发所有参数到当前可执行文件的启动脚本
+#[cfg(windows)]
+fn install_link(exe: &Path, path: &Path, _privileged: bool) -> Result<()> {
+    fs::write(path, shim_content(exe))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn uninstall_link(path: &Path, privileged: bool) -> Result<()> {
+    if privileged {
+        privilege::runas::Command::new("rm")
+            .args(&["-f", &path.to_string_lossy()])
+            .run()?;
+        return Ok(());
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// 删除启动脚本，默认位置的 `bin` 目录为空时一并删除
+#[cfg(windows)]
+fn uninstall_link(path: &Path, _privileged: bool) -> Result<()> {
+    fs::remove_file(path)?;
+    if default_symbollink_path().is_ok_and(|default| default == path) {
+        if let Some(dir) = path.parent() {
+            // 目录中还有其他文件时删除失败，保留即可
+            let _ = fs::remove_dir(dir);
+        }
+    }
+    Ok(())
+}
+
+/// 生成 `.cmd` 启动脚本的内容
+#[cfg(any(windows, test))]
+fn shim_content(exe: &Path) -> String {
+    format!("@echo off\r\n\"{}\" %*\r\n", exe.display())
+}
+
+/// 判断目录是否在 `PATH` 中 (忽略末尾的路径分隔符，Windows 上不区分大小写)
+fn dir_in_path(dir: &Path, path_var: &std::ffi::OsStr) -> bool {
+    let normalize = |p: &Path| {
+        let p = p.to_string_lossy();
+        let p = p.trim_end_matches(['\\', '/']);
+        if cfg!(windows) {
+            p.to_lowercase()
+        } else {
+            p.to_string()
+        }
+    };
+    std::env::split_paths(path_var).any(|p| normalize(&p) == normalize(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_windows_shim_and_checks_path() {
+        let exe = Path::new("/opt/rdbkp2/rdbkp2.exe");
+        assert_eq!(
+            shim_content(exe),
+            "@echo off\r\n\"/opt/rdbkp2/rdbkp2.exe\" %*\r\n"
+        );
+
+        let dir = Path::new("/home/u/.local/rdbkp2/bin");
+        let path_var = std::env::join_paths(["/usr/bin", "/home/u/.local/rdbkp2/bin/"]).unwrap();
+        assert!(dir_in_path(dir, &path_var));
+        assert!(!dir_in_path(
+            dir,
+            &std::env::join_paths(["/usr/bin"]).unwrap()
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    commands::{CHECKSUMS_FILE_NAME, MAPPING_FILE_NAME, restore},
+    docker::BackupMapping,
+    log_bail, log_println,
+    utils::{self, ArchiveDirStats, ArchiveScan, ChecksumManifest},
+};
+
+use anyhow::Result;
+use std::path::Path;
+use tracing::info;
+
+/// 检查备份文件是否完整，不向磁盘解压任何内容
+///
+/// 依次检查：压缩数据流能否完整解码、`mapping.toml` 是否存在且可解析、
+/// 整体校验和以及逐文件校验清单 (如果存在)，最后列出备份中的卷及其大小。
+/// 任一检查失败都会返回错误，使进程以非零状态码退出
+pub async fn verify(file: String) -> Result<()> {
+    let file_path = utils::ensure_file_exists(Path::new(&file))?;
+    info!(?file_path, "Verifying backup archive");
+
+    let scan = match utils::scan_archive(&file_path) {
+        Ok(scan) => scan,
+        Err(e) => log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.verify_archive_unreadable",
+                "path" = file_path.to_string_lossy(),
+                "error" = e
+            )
+        ),
+    };
+
+    let Some(mapping_content) = scan.root_files.get(MAPPING_FILE_NAME) else {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!(
+                "commands.verify_mapping_missing",
+                "path" = file_path.to_string_lossy()
+            )
+        );
+    };
+    let mapping = match restore::parse_mapping(mapping_content) {
+        Ok(mapping) => mapping,
+        Err(e) => log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.verify_mapping_invalid", "error" = e)
+        ),
+    };
+
+    restore::verify_backup_checksum(&file_path, &mapping)?;
+    verify_manifest(&scan)?;
+
+    print_summary(&mapping, &scan);
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.verify_succeeded",
+            "path" = file_path.to_string_lossy()
+        )
+    );
+    Ok(())
+}
+
+/// 使用备份中的逐文件校验清单校验解码出的数据，旧版本的备份没有清单时跳过
+fn verify_manifest(scan: &ArchiveScan) -> Result<()> {
+    let Some(content) = scan.root_files.get(CHECKSUMS_FILE_NAME) else {
+        return Ok(());
+    };
+    let manifest: ChecksumManifest = toml::from_str(content)?;
+
+    let failed = manifest
+        .files
+        .iter()
+        .filter(|(name, expected)| scan.checksums.get(*name) != Some(*expected))
+        .inspect(|(name, _)| {
+            log_println!(
+                "WARN",
+                "{}",
+                t!("commands.file_checksum_mismatch", "name" = name)
+            );
+        })
+        .count();
+
+    if failed > 0 {
+        log_bail!(
+            "ERROR",
+            "{}",
+            t!("commands.files_verification_failed", "failed" = failed)
+        );
+    }
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "commands.files_verified",
+            "verified" = utils::format_number(manifest.files.len() as u64),
+            "failed" = 0
+        )
+    );
+    Ok(())
+}
+
+fn print_summary(mapping: &BackupMapping, scan: &ArchiveScan) {
+    println!(
+        "{}",
+        t!(
+            "commands.verify_summary",
+            "container" = mapping.container_name,
+            "time" = mapping.backup_time,
+            "version" = mapping.version
+        )
+    );
+    println!("{:<24} {:>10} {:>12}  SOURCE", "VOLUME", "FILES", "SIZE");
+    for volume in &mapping.volumes {
+        let stats = scan.dirs.get(&volume.name).cloned().unwrap_or_default();
+        print_volume_row(&volume.name, &stats, &volume.source.to_string_lossy());
+    }
+    for alias in &mapping.aliases {
+        println!(
+            "{:<24} {:>10} {:>12}  {} (= {})",
+            alias.volume.name,
+            "-",
+            "-",
+            alias.volume.source.to_string_lossy(),
+            alias.alias_of
+        );
+    }
+}
+
+fn print_volume_row(name: &str, stats: &ArchiveDirStats, source: &str) {
+    println!(
+        "{:<24} {:>10} {:>12}  {}",
+        name,
+        utils::format_number(stats.files),
+        utils::format_bytes(stats.bytes),
+        source
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::{MAPPING_SCHEMA_VERSION, VolumeInfo};
+    use assert_fs::{
+        TempDir,
+        fixture::{FileWriteStr, PathChild, PathCreateDir},
+    };
+
+    fn write_backup(temp: &TempDir, with_mapping: bool) -> Result<std::path::PathBuf> {
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        source.child("data.txt").write_str("hello")?;
+
+        let mapping = BackupMapping {
+            schema_version: MAPPING_SCHEMA_VERSION,
+            container_name: "app".into(),
+            container_id: "id".into(),
+            volumes: vec![VolumeInfo {
+                name: "vol1".into(),
+                source: source.path().to_path_buf(),
+                destination: "/data".into(),
+                volume_name: None,
+            }],
+            backup_time: "now".into(),
+            version: "test".into(),
+            payload_sha256: None,
+            aliases: Vec::new(),
+            base: None,
+            files: Default::default(),
+            modified_after: None,
+            preserve_ownership: false,
+        };
+        let mapping = toml::to_string(&mapping)?;
+        let memory_files = if with_mapping {
+            vec![(MAPPING_FILE_NAME, mapping.as_str())]
+        } else {
+            Vec::new()
+        };
+
+        let archive = temp.child("app.tar.xz");
+        utils::compress_with_memory_file(
+            &[source.path()],
+            archive.path(),
+            &memory_files,
+            &[],
+            utils::CompressOptions::default(),
+        )?;
+        Ok(archive.path().to_path_buf())
+    }
+
+    #[tokio::test]
+    async fn verifies_intact_archive() -> Result<()> {
+        let temp = TempDir::new()?;
+        let archive = write_backup(&temp, true)?;
+        verify(archive.to_string_lossy().to_string()).await
+    }
+
+    #[tokio::test]
+    async fn rejects_truncated_archive_and_missing_mapping() -> Result<()> {
+        let temp = TempDir::new()?;
+        let archive = write_backup(&temp, true)?;
+        let bytes = std::fs::read(&archive)?;
+        std::fs::write(&archive, &bytes[..bytes.len() / 2])?;
+        assert!(verify(archive.to_string_lossy().to_string()).await.is_err());
+
+        let temp = TempDir::new()?;
+        let archive = write_backup(&temp, false)?;
+        assert!(verify(archive.to_string_lossy().to_string()).await.is_err());
+        Ok(())
+    }
+}
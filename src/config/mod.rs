@@ -1,39 +1,43 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
 };
 use tracing::{debug, error, instrument};
 
 use crate::utils;
 
-#[allow(dead_code)]
-#[deprecated(since = "1.0.0", note = "no need to load config file")]
-const DEFAULT_CONFIG_FILE_PATH: &str = "./config.toml";
-
-#[allow(dead_code, deprecated)]
-#[deprecated(since = "1.0.0", note = "no need to load config file")]
-#[instrument(level = "INFO", fields(cfg_path = DEFAULT_CONFIG_FILE_PATH))]
-pub fn load_config() -> Result<()> {
-    let cfg_path = PathBuf::from(DEFAULT_CONFIG_FILE_PATH);
-
-    if !cfg_path.exists() {
-        let cfg = Config::default();
-        cfg.save_to_file(cfg_path)?;
-        Config::init(cfg)?;
-    } else {
-        Config::init_from_file(&cfg_path)?;
-        Config::global()?.save_to_file(cfg_path)?;
+/// 用户配置文件相对于系统配置目录的路径
+const USER_CONFIG_FILE: &str = "rdbkp2/config.toml";
+
+/// 加载用户配置文件 (`<配置目录>/rdbkp2/config.toml`)，文件不存在时以默认值创建
+///
+/// 无法确定配置目录时返回默认配置
+#[instrument(level = "INFO")]
+pub fn load_user_config() -> Result<Config> {
+    match user_config_path() {
+        Some(path) => Config::load_or_create(path),
+        None => Ok(Config::default()),
     }
+}
 
-    Ok(())
+/// 用户配置文件的路径，无法确定配置目录时为 `None`
+pub fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(USER_CONFIG_FILE))
 }
 
 static CONFIG: OnceLock<Arc<RwLock<Option<Config>>>> = OnceLock::new();
 
+tokio::task_local! {
+    /// 当前任务使用的配置，优先于全局配置 (见 [`Config::scope`])
+    static SCOPED_CONFIG: Config;
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Config {
     /// 备份文件的默认输出目录
     pub backup_dir: PathBuf,
@@ -47,6 +51,10 @@ pub struct Config {
     /// 是否在操作 (备份/恢复) 后重启容器
     pub restart: bool,
 
+    /// 重启容器后等待健康检查通过的超时时间，`None` 表示不等待
+    #[serde(skip)]
+    pub wait_healthy: Option<Duration>,
+
     /// 是否显示详细日志
     pub verbose: bool,
 
@@ -56,16 +64,67 @@ pub struct Config {
     /// 排除模式：备份时将排除包含这些模式的文件/目录
     pub exclude: String,
 
-    /// 语言
-    pub language: String,
+    /// 额外读取排除模式的文件 (每行一个模式)，与 `exclude` 合并
+    #[serde(skip)]
+    pub exclude_from: Option<PathBuf>,
+
+    /// 语言，未设置时在运行时根据系统语言检测 (见 [`Config::effective_language`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// 是否禁用按语言格式化数字和文件大小
+    #[serde(default)]
+    pub no_locale_format: bool,
+
+    /// 是否跳过 canonicalize，只做不访问文件系统的路径规范化
+    #[serde(default)]
+    pub no_canonicalize: bool,
+
+    /// 是否只打印将要执行的操作而不做任何修改
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// 是否禁用压缩/解压进度显示
+    #[serde(default)]
+    pub no_progress: bool,
+
+    /// 以定期的日志行代替进度条显示进度，同时备份多个容器时多个进度条会互相覆盖
+    #[serde(skip)]
+    pub log_progress: bool,
+
+    /// 压缩时读取源文件的速率上限，单位为 MB/s，`None` 表示不限速
+    #[serde(default)]
+    pub io_limit: Option<u64>,
 
     /// Docker 相关配置
     pub docker: DockerConfig,
+
+    /// 命名的参数组合 (`[profiles.<name>]`)，通过 `--profile <name>` 使用
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// 配置文件中一组可复用的备份参数，未设置的参数沿用配置文件与默认值，命令行显式指定的参数优先
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// 同 `--exclude`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<String>,
+    /// 同 `backup --output`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// 同 `backup --archive-format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_format: Option<utils::ArchiveFormat>,
+    /// 同 `backup --keep-last`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct DockerConfig {
-    /// Docker daemon 的地址
+    /// Docker daemon 的地址 (支持 unix:// 与 tcp://)，为空时使用本地默认连接方式
     pub host: String,
 
     /// 是否使用 TLS
@@ -73,6 +132,29 @@ pub struct DockerConfig {
 
     /// 证书路径 (如果使用 TLS)
     pub cert_path: Option<PathBuf>,
+
+    /// 容器引擎，决定未指定 host 时使用的默认 socket
+    pub engine: ContainerEngine,
+
+    /// 停止容器时发送的信号，未设置时使用容器配置的 StopSignal
+    pub stop_signal: Option<String>,
+
+    /// Docker API 请求遇到连接失败或超时等瞬时错误时的重试次数，未设置时为 3
+    pub retries: Option<u32>,
+
+    /// 第一次重试前的等待时间 (毫秒)，之后每次翻倍，未设置时为 500
+    pub retry_delay_ms: Option<u64>,
+}
+
+/// 容器引擎
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerEngine {
+    /// Docker，默认使用本地 Docker socket
+    #[default]
+    Docker,
+    /// Podman，默认使用 `$XDG_RUNTIME_DIR/podman/podman.sock` (rootless) 或 `/run/podman/podman.sock`
+    Podman,
 }
 
 impl Default for Config {
@@ -83,23 +165,149 @@ impl Default for Config {
             backup_dir,
             interactive: true,
             restart: false,
+            wait_healthy: None,
             verbose: false,
             yes: false,
             exclude: ".git,node_modules,target".to_string(),
-            language: "zh-CN".to_string(),
-            docker: DockerConfig {
-                host: "unix:///var/run/docker.sock".to_string(),
-                tls: false,
-                cert_path: None,
-            },
+            exclude_from: None,
+            language: None,
+            no_locale_format: false,
+            no_canonicalize: false,
+            dry_run: false,
+            no_progress: false,
+            log_progress: false,
+            io_limit: None,
+            docker: DockerConfig::default(),
+            profiles: BTreeMap::new(),
             timeout_secs: 30,
         }
     }
 }
 
+/// 用户配置文件中保存的设置，也是 `config export`/`config import` 在机器之间迁移的内容；
+/// 其余字段只在运行时由命令行参数决定
+///
+/// 解析时拒绝未知的键，避免拼写错误的设置被静默忽略；未出现的键使用默认值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub backup_dir: PathBuf,
+    pub exclude: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub timeout_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_limit: Option<u64>,
+    pub docker: DockerSettings,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// [`Settings`] 中的 Docker 设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DockerSettings {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub host: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub tls: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_path: Option<PathBuf>,
+    pub engine: ContainerEngine,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_delay_ms: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Config::default().settings()
+    }
+}
+
+impl Settings {
+    /// 解析并校验设置文件的内容，出现未知的键或无效的值时返回错误
+    ///
+    /// 语言与停止信号会被规范化 (如 `zh` 转为 `zh-CN`，`int` 转为 `SIGINT`)
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let mut settings: Settings = toml::from_str(content)?;
+
+        if let Some(value) = &settings.language {
+            let Ok(language) = <crate::Language as clap::ValueEnum>::from_str(value, true) else {
+                anyhow::bail!(t!(
+                    "config.invalid_setting",
+                    "key" = "language",
+                    "value" = value
+                ));
+            };
+            settings.language = Some(language.into());
+        }
+
+        if let Some(signal) = &settings.docker.stop_signal {
+            settings.docker.stop_signal =
+                Some(crate::docker::parse_stop_signal(signal).map_err(anyhow::Error::msg)?);
+        }
+        if settings.io_limit == Some(0) {
+            anyhow::bail!(t!(
+                "config.invalid_setting",
+                "key" = "io_limit",
+                "value" = 0
+            ));
+        }
+        Ok(settings)
+    }
+
+    /// 序列化为 TOML，并在开头添加说明各个键的注释
+    pub fn to_toml(&self) -> Result<String> {
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            error!(?e, "Failed to serialize config");
+            e
+        })?;
+
+        // 手动添加注释
+        let comments = r#"
+# rdbkp2 配置文件，命令行参数优先于这里的设置
+#
+# backup_dir:   恢复时查找备份文件的默认目录
+# exclude:      排除模式，备份时将排除包含这些模式的文件/目录 (逗号分隔)
+# language:     (可选) 语言 (zh-CN, en, ja, ko, es, fr, de, it, ru, pt-BR)，未设置时根据系统语言检测
+# timeout_secs: 停止容器的超时时间 (单位：秒)
+# io_limit:     (可选) 压缩时读取源文件的速率上限 (单位：MB/s)
+# [docker] host: (可选) Docker daemon 的地址 (unix:// 或 tcp://)，同 --docker-host，未设置时使用本地默认连接方式
+# [docker] tls, cert_path: (可选) 是否使用 TLS 及证书目录，同 --docker-tls 与 --docker-cert-path
+# [docker] engine: 容器引擎 (docker, podman)，决定未设置 host 时默认连接的 socket
+# [docker] stop_signal, retries, retry_delay_ms: (可选) 同 --stop-signal、--retries 与 --retry-delay-ms
+# [profiles.<name>] exclude, output, archive_format, keep_last: (可选) 可复用的备份参数，通过 --profile <name> 使用
+"#;
+
+        // 将注释插入到文件内容的前面
+        Ok(format!("{}\n\n{}", comments.trim(), content))
+    }
+
+    /// 将设置连同注释写入文件
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = self.to_toml()?;
+        std::fs::write(path.as_ref(), content).map_err(|e| {
+            error!(?e, path = ?path.as_ref(), "Failed to write config file");
+            e
+        })?;
+        debug!(path = ?path.as_ref(), "Config saved to file");
+        Ok(())
+    }
+}
+
 impl Config {
     /// 获取全局配置实例
+    ///
+    /// 在 [`Config::scope`] 内调用时返回该作用域的配置
     pub fn global() -> Result<Config> {
+        if let Ok(config) = SCOPED_CONFIG.try_with(Config::clone) {
+            return Ok(config);
+        }
+
         let config_lock = CONFIG
             .get()
             .ok_or_else(|| anyhow::anyhow!(t!("config.initialize_failed")))?;
@@ -112,6 +320,31 @@ impl Config {
         Ok(config.clone().unwrap_or_default())
     }
 
+    /// 是否处于 dry-run 模式，配置未初始化时视为否
+    pub fn is_dry_run() -> bool {
+        Self::global().map(|c| c.dry_run).unwrap_or(false)
+    }
+
+    /// 压缩时读取源文件的速率上限 (字节/秒)，未限速或配置未初始化时为 `None`
+    pub fn io_limit() -> Option<u64> {
+        Self::global()
+            .ok()
+            .and_then(|c| c.io_limit)
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// 在 `future` 执行期间使用 `config` 代替全局配置，不修改也不要求初始化全局配置
+    ///
+    /// 作用域只对当前任务生效，不同任务可以同时使用不同的配置 (用于库接口)
+    pub async fn scope<F: Future>(config: Config, future: F) -> F::Output {
+        SCOPED_CONFIG.scope(config, future).await
+    }
+
+    /// 在同步代码中以 `config` 作为 [`Config::global`] 的结果运行 `f`，用于 `spawn_blocking` 的线程
+    pub fn sync_scope<R>(config: Config, f: impl FnOnce() -> R) -> R {
+        SCOPED_CONFIG.sync_scope(config, f)
+    }
+
     /// 初始化全局配置
     pub fn init(config: Config) -> Result<()> {
         let res = CONFIG.set(Arc::new(RwLock::new(Some(config))));
@@ -123,22 +356,36 @@ impl Config {
         Ok(())
     }
 
-    pub fn get_exclude_patterns(&self) -> Vec<&str> {
-        self.exclude.split(',').collect::<Vec<&str>>()
+    /// 排除模式：`exclude` 按逗号拆分，其后追加 `exclude_from` 文件中的模式
+    pub fn get_exclude_patterns(&self) -> Result<Vec<String>> {
+        let mut patterns = self
+            .exclude
+            .split(',')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if let Some(path) = &self.exclude_from {
+            patterns.extend(utils::read_exclude_file(path)?);
+        }
+        Ok(patterns)
     }
 
-    #[allow(dead_code)]
-    #[allow(deprecated)]
-    #[deprecated(since = "1.0.0", note = "no need to load config file")]
-    /// 从文件加载配置并初始化全局实例
-    pub fn init_from_file<P: AsRef<Path>>(path: P) -> Result<()> {
-        let config = Self::load_from_file(path)?;
-        Self::init(config)
+    /// 从文件加载配置，文件不存在时以默认值创建
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::load_from_file(path);
+        }
+
+        let config = Self::default();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        config.save_to_file(path)?;
+        debug!(?path, "Created default config file");
+        Ok(config)
     }
 
-    #[allow(dead_code)]
-    #[deprecated(since = "1.0.0", note = "no need to load config file")]
-    /// 从文件加载配置
+    /// 从文件加载配置，文件中未出现的字段使用默认值
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path.as_ref()).map_err(|e| {
             error!(?e, path = ?path.as_ref(), "Failed to read config file");
@@ -152,59 +399,58 @@ impl Config {
         Ok(config)
     }
 
-    #[allow(dead_code)]
-    #[deprecated(since = "1.0.0", note = "no need to load config file")]
-    /// 保存配置到文件，并保留注释
+    /// 保存用户设置 (见 [`Settings`]) 到文件，并添加注释
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let mut content = toml::to_string_pretty(self).map_err(|e| {
-            error!(?e, "Failed to serialize config");
-            e
-        })?;
-
-        // 手动添加注释
-        let comments = r#"
-    # Docker 容器数据备份工具配置文件
-
-    # 备份文件的默认输出目录
-    # backup_dir = "./backups"
-
-    # 停止容器操作的超时时间 (单位：秒)
-    # timeout = 30
-
-    # 是否使用交互模式
-    # interactive = true
-
-    # 是否在操作 (备份/恢复) 后重启容器
-    # restart = false
-
-    # 是否显示详细日志
-    # verbose = false
-
-    # 是否自动确认
-    # yes = false
-
-    # 排除模式：备份时将排除包含这些模式的文件/目录
-    # exclude = ".git,node_modules,target"
+        self.settings().save_to_file(path)
+    }
 
-    # Docker 相关配置
-    # [docker]
-    # Docker daemon 的地址
-    # host = "unix:///var/run/docker.sock"
-    # 是否使用 TLS
-    # tls = false
-    # 证书路径 (如果使用 TLS)
-    # cert_path = "/path/to/cert"
-    "#;
+    /// 可在机器之间迁移的用户设置
+    pub fn settings(&self) -> Settings {
+        Settings {
+            backup_dir: self.backup_dir.clone(),
+            exclude: self.exclude.clone(),
+            language: self.language.clone(),
+            timeout_secs: self.timeout_secs,
+            io_limit: self.io_limit,
+            docker: DockerSettings {
+                host: self.docker.host.clone(),
+                tls: self.docker.tls,
+                cert_path: self.docker.cert_path.clone(),
+                engine: self.docker.engine,
+                stop_signal: self.docker.stop_signal.clone(),
+                retries: self.docker.retries,
+                retry_delay_ms: self.docker.retry_delay_ms,
+            },
+            profiles: self.profiles.clone(),
+        }
+    }
 
-        // 将注释插入到文件内容的前面
-        content = format!("{}\n{}", comments.trim(), content);
+    /// 实际使用的语言：未在配置文件或命令行中指定时根据系统语言检测
+    pub fn effective_language(&self) -> String {
+        self.language
+            .clone()
+            .unwrap_or_else(|| crate::Language::detect().into())
+    }
 
-        std::fs::write(path.as_ref(), content).map_err(|e| {
-            error!(?e, path = ?path.as_ref(), "Failed to write config file");
-            e
-        })?;
-        debug!(path = ?path.as_ref(), "Config saved to file");
-        Ok(())
+    /// 按名称查找 `--profile` 指定的参数组合，配置文件中不存在时返回错误
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig> {
+        self.profiles.get(name).ok_or_else(|| {
+            let available = self
+                .profiles
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            anyhow::anyhow!(t!(
+                "config.profile_not_found",
+                "name" = name,
+                "available" = if available.is_empty() {
+                    "-"
+                } else {
+                    &available
+                }
+            ))
+        })
     }
 
     #[allow(dead_code)]
@@ -326,7 +572,6 @@ mod tests {
     }
 
     #[test]
-    #[allow(deprecated)]
     fn test_config_file_operations() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let config_path = temp_dir.path().join("config.toml");
@@ -341,4 +586,105 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_user_config_created_and_loaded() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("rdbkp2/config.toml");
+
+        // 首次运行时以默认值创建
+        let config = Config::load_or_create(&config_path)?;
+        assert!(config_path.exists());
+        assert_eq!(config.timeout_secs, Config::default().timeout_secs);
+
+        // 只保存用户配置，运行时选项与检测到的语言不写入文件
+        let content = std::fs::read_to_string(&config_path)?;
+        assert!(content.contains("timeout_secs = 30"));
+        assert!(!content.contains("dry_run"));
+        assert!(!content.contains("language ="));
+        assert_eq!(config.language, None);
+
+        // 部分字段缺失时使用默认值
+        std::fs::write(&config_path, "language = \"en\"\ntimeout_secs = 5\n")?;
+        let config = Config::load_or_create(&config_path)?;
+        assert_eq!(config.language.as_deref(), Some("en"));
+        assert_eq!(config.timeout_secs, 5);
+        assert_eq!(config.exclude, Config::default().exclude);
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_export_import_round_trip() -> Result<()> {
+        let config = Config {
+            exclude: ".git,cache".to_string(),
+            timeout_secs: 90,
+            io_limit: Some(50),
+            dry_run: true,
+            docker: DockerConfig {
+                host: "tcp://10.0.0.5:2376".to_string(),
+                tls: true,
+                cert_path: Some(PathBuf::from("/etc/docker/certs")),
+                engine: ContainerEngine::Podman,
+                stop_signal: Some("SIGINT".to_string()),
+                ..DockerConfig::default()
+            },
+            ..Config::default()
+        };
+
+        // 导出内容不包含运行时选项，导入后得到相同的设置
+        let exported = config.settings().to_toml()?;
+        assert!(!exported.contains("dry_run"));
+        assert_eq!(Settings::from_toml(&exported)?, config.settings());
+
+        // 语言与停止信号被规范化，未出现的键使用默认值
+        let settings = Settings::from_toml("language = \"zh\"\n[docker]\nstop_signal = \"int\"\n")?;
+        assert_eq!(settings.language.as_deref(), Some("zh-CN"));
+        assert_eq!(settings.docker.stop_signal.as_deref(), Some("SIGINT"));
+        assert_eq!(settings.timeout_secs, Config::default().timeout_secs);
+
+        // 未知的键与无效的值被拒绝
+        let error = Settings::from_toml("timeout = 5\n").unwrap_err();
+        assert!(error.to_string().contains("timeout"));
+        assert!(Settings::from_toml("[docker]\nsocket = \"tcp://x\"\n").is_err());
+        assert!(Settings::from_toml("language = \"xx\"\n").is_err());
+        assert!(Settings::from_toml("io_limit = 0\n").is_err());
+
+        // profile 随设置一起导出与导入，profile 中未知的键同样被拒绝
+        let mut config = Config::default();
+        config.profiles.insert(
+            "nightly".to_string(),
+            ProfileConfig {
+                archive_format: Some(utils::ArchiveFormat::TarGz),
+                keep_last: Some(7),
+                ..ProfileConfig::default()
+            },
+        );
+        let exported = config.settings().to_toml()?;
+        assert!(exported.contains("[profiles.nightly]"));
+        assert!(exported.contains("archive_format = \"tar.gz\""));
+        assert_eq!(Settings::from_toml(&exported)?, config.settings());
+        assert!(Settings::from_toml("[profiles.nightly]\nkeep = 3\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_patterns_merge_exclude_from_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let file = temp_dir.path().join("exclude.txt");
+        std::fs::write(&file, "# 日志\n**/*.log\n\ncache,tmp\n")?;
+
+        let mut config = Config {
+            exclude: ".git,target".to_string(),
+            exclude_from: Some(file),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.get_exclude_patterns()?,
+            vec![".git", "target", "**/*.log", "cache,tmp"]
+        );
+
+        config.exclude_from = Some(temp_dir.path().join("missing.txt"));
+        assert!(config.get_exclude_patterns().is_err());
+        Ok(())
+    }
 }
@@ -1,21 +1,110 @@
 use anyhow::{Context, Result};
 use bollard::{
-    Docker,
+    API_DEFAULT_VERSION, Docker,
+    exec::{CreateExecOptions, StartExecResults},
     query_parameters::{
-        InspectContainerOptions, ListContainersOptionsBuilder, RestartContainerOptions,
-        StartContainerOptions, StopContainerOptions,
+        CreateContainerOptionsBuilder, InspectContainerOptions, ListContainersOptions,
+        ListContainersOptionsBuilder, RemoveContainerOptionsBuilder, RestartContainerOptions,
+        StartContainerOptions, StopContainerOptionsBuilder, UploadToContainerOptionsBuilder,
+    },
+    secret::{
+        ContainerCreateBody, ContainerInspectResponse, ContainerStateStatusEnum, ContainerSummary,
+        HealthStatusEnum, HostConfig, MountPointTypeEnum,
     },
-    secret::ContainerStateStatusEnum,
 };
+use bytes::Bytes;
+use futures_util::StreamExt;
 use mockall::{automock, predicate::*};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
     path::PathBuf,
     sync::{Arc, OnceLock, RwLock},
+    time::Duration,
 };
+use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
-use crate::utils;
+use crate::{
+    config::{Config, ContainerEngine, DockerConfig},
+    log_bail,
+    utils::{self, FileStamp},
+};
+
+/// Docker API 请求的读写超时时间 (秒)
+const DOCKER_API_TIMEOUT_SECS: u64 = 120;
+
+/// 瞬时错误的默认重试次数
+const DEFAULT_RETRIES: u32 = 3;
+
+/// 第一次重试前的默认等待时间 (毫秒)
+const DEFAULT_RETRY_DELAY_MS: u64 = 500;
+
+/// 两次重试之间的最长等待时间
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// 流式上传时每个数据块的大小
+const UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
+
+/// 上传到容器中的 tar 数据，解压到容器的根目录
+#[derive(Debug)]
+pub enum UploadBody {
+    /// 边生成边上传的数据流，不占用本地磁盘
+    Stream(mpsc::Receiver<Bytes>),
+    /// 已完整写入本地临时文件的 tar，上传时从文件中按块读取
+    Staged(PathBuf),
+}
+
+impl UploadBody {
+    /// 创建数据流，写入 [`UploadStreamWriter`] 的数据会按块发送给上传请求
+    pub fn channel() -> (UploadStreamWriter, Self) {
+        let (sender, receiver) = mpsc::channel(4);
+        (
+            UploadStreamWriter {
+                sender,
+                buffer: Vec::with_capacity(UPLOAD_CHUNK_SIZE),
+            },
+            Self::Stream(receiver),
+        )
+    }
+}
+
+/// [`UploadBody::Stream`] 的写入端，需在阻塞线程中使用
+pub struct UploadStreamWriter {
+    sender: mpsc::Sender<Bytes>,
+    buffer: Vec<u8>,
+}
+
+impl UploadStreamWriter {
+    fn send_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::replace(
+            &mut self.buffer,
+            Vec::with_capacity(UPLOAD_CHUNK_SIZE),
+        ));
+        // 接收端关闭说明上传请求已结束 (通常是失败)
+        self.sender
+            .blocking_send(chunk)
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+    }
+}
+
+impl Write for UploadStreamWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= UPLOAD_CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_buffer()
+    }
+}
 
 // 定义 DockerClient 接口 trait，并使用 automock 为 test 生成 mock 实现
 #[automock]
@@ -26,17 +115,53 @@ pub trait DockerClientInterface: Send + Sync + Clone + 'static {
     async fn start_container(&self, container_id: &str) -> Result<()>;
     async fn restart_container(&self, container_id: &str) -> Result<()>;
     async fn stop_container(&self, container_id: &str) -> Result<()>;
+    async fn pause_container(&self, container_id: &str) -> Result<()>;
+    async fn unpause_container(&self, container_id: &str) -> Result<()>;
     async fn get_container_working_dir(&self, id: &str) -> Result<String>;
     async fn get_container_status(&self, id: &str) -> Result<String>;
+    /// 获取容器健康检查的状态 (`starting`/`healthy`/`unhealthy`)，容器未定义健康检查时返回 `None`
+    async fn get_container_health(&self, id: &str) -> Result<Option<String>>;
 
+    /// 按与 `docker` 相同的优先级查找容器：完整 ID 或不短于 12 位的 ID 前缀、精确名称，
+    /// 最后按名称或 ID 的子串模糊匹配 (见 [`resolve_container_reference`])
     async fn find_containers(&self, name_or_id: &str) -> Result<Vec<ContainerInfo>>;
+    /// 按完整的名称或 ID 精确查找容器，找不到时返回错误
     async fn find_container(&self, name_or_id: &str) -> Result<ContainerInfo>;
+    /// 列出满足所有标签条件 (`key=value` 或只要求标签存在的 `key`) 的容器 (包括已停止的)，
+    /// 同时返回每个容器的全部标签
+    async fn find_containers_by_labels(&self, labels: &[String]) -> Result<Vec<LabeledContainer>>;
+
+    /// 将 tar 数据上传并解压到容器内的 `path` 目录，容器停止时同样可用
+    async fn upload_to_container(
+        &self,
+        container_id: &str,
+        path: &str,
+        tar: UploadBody,
+    ) -> Result<()>;
+
+    /// 使用 `image_of` 容器的镜像创建 (不启动) 一个挂载了 `binds` 的辅助容器，返回其 ID
+    async fn create_helper_container(&self, image_of: &str, binds: Vec<String>) -> Result<String>;
+    /// 强制删除容器，保留其挂载的卷
+    async fn remove_container(&self, container_id: &str) -> Result<()>;
+
+    /// 在运行中的容器内执行命令并等待其结束，返回退出码与合并后的输出
+    async fn exec_in_container(&self, container_id: &str, cmd: Vec<String>) -> Result<ExecOutput>;
 
     fn get_stop_timeout_secs(&self) -> u64;
+
+    /// 检查 daemon 是否可以连接
+    async fn ping(&self) -> Result<()>;
 }
 
 impl Clone for MockDockerClientInterface {
     fn clone(&self) -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl MockDockerClientInterface {
+    /// 预设了常用返回值 (容器已停止、操作成功) 的 mock 客户端，测试中直接传给各命令
+    pub fn with_defaults() -> Self {
         let mut client = MockDockerClientInterface::new();
         client
             .expect_get_container_status()
@@ -44,6 +169,7 @@ impl Clone for MockDockerClientInterface {
         client.expect_stop_container().returning(|_| Ok(()));
         client.expect_get_stop_timeout_secs().returning(|| 10);
         client.expect_restart_container().returning(|_| Ok(()));
+        client.expect_ping().returning(|| Ok(()));
         client
     }
 }
@@ -61,10 +187,93 @@ pub(crate) static DOCKER_CLIENT_INSTANCE: OnceLock<Arc<RwLock<ClientType>>> = On
 pub struct DockerClient {
     client: Docker,
     stop_timeout_secs: u64,
+    stop_signal: Option<String>,
+    retry: RetryPolicy,
+}
+
+/// Docker API 请求遇到瞬时错误时的重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RetryPolicy {
+    /// 首次请求失败后最多重试的次数
+    retries: u32,
+    /// 第一次重试前的等待时间，之后每次翻倍
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn from_config(docker_config: &DockerConfig) -> Self {
+        Self {
+            retries: docker_config.retries.unwrap_or(DEFAULT_RETRIES),
+            base_delay: Duration::from_millis(
+                docker_config
+                    .retry_delay_ms
+                    .unwrap_or(DEFAULT_RETRY_DELAY_MS),
+            ),
+        }
+    }
+
+    /// 第 `attempt` 次重试 (从 0 开始) 前的等待时间
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_RETRY_DELAY)
+    }
+}
+
+/// 在容器内执行命令的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecOutput {
+    /// 退出码，daemon 未返回时为 `None`
+    pub exit_code: Option<i64>,
+    /// 按输出顺序合并的 stdout 与 stderr
+    pub output: String,
+}
+
+/// 解析 `--label`：`key=value` 或只要求标签存在的 `key`，去除键与值两侧的空白
+pub(crate) fn parse_label_filter(value: &str) -> std::result::Result<String, String> {
+    let (key, label_value) = match value.split_once('=') {
+        Some((key, label_value)) => (key.trim(), Some(label_value.trim())),
+        None => (value.trim(), None),
+    };
+    if key.is_empty() {
+        return Err(t!("docker.invalid_label_filter", "label" = value).to_string());
+    }
+    Ok(match label_value {
+        Some(label_value) => format!("{key}={label_value}"),
+        None => key.to_string(),
+    })
+}
+
+/// 解析 `--stop-signal`：信号编号原样保留，信号名转为大写并补全 `SIG` 前缀
+pub(crate) fn parse_stop_signal(value: &str) -> std::result::Result<String, String> {
+    let value = value.trim();
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(value.to_string());
+    }
+    let name = value.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-')
+    {
+        return Err(t!("docker.invalid_stop_signal", "signal" = value).to_string());
+    }
+    Ok(format!("SIG{name}"))
 }
 
 impl DockerClient {
+    /// 获取全局客户端，首次使用时才按全局配置初始化
+    ///
+    /// 不需要 Docker 的子命令 (如 `completions`) 从不调用该方法，因此在 daemon 未运行时同样可用
     pub fn global() -> Result<ClientType> {
+        if DOCKER_CLIENT_INSTANCE.get().is_none() {
+            let timeout_secs = Config::global()
+                .map(|c| c.timeout_secs)
+                .unwrap_or_else(|_| Config::default().timeout_secs);
+            Self::init(timeout_secs)?;
+        }
+
         let client_arc_lock = DOCKER_CLIENT_INSTANCE
             .get()
             .ok_or_else(|| anyhow::anyhow!(t!("docker.client_not_initialized")))?;
@@ -76,6 +285,25 @@ impl DockerClient {
         Ok(client_read_guard.clone())
     }
 
+    /// 获取全局客户端并确认 daemon 可以连接，连接失败时给出启动 daemon 的提示
+    ///
+    /// 命令行使用的便捷入口；需要连接多个 daemon 时使用 [`DockerClient::connect`]
+    pub async fn connected() -> Result<ClientType> {
+        let client = Self::global()?;
+        let engine = Config::global()
+            .map(|c| c.docker.engine)
+            .unwrap_or_default();
+        ensure_reachable(&client, engine).await?;
+        Ok(client)
+    }
+
+    /// 按 `config` 创建一个独立于全局客户端的新客户端，并确认 daemon 可以连接
+    pub async fn connect(config: &Config) -> Result<Self> {
+        let client = Self::with_config(&config.docker, config.timeout_secs)?;
+        ensure_reachable(&client, config.docker.engine).await?;
+        Ok(client)
+    }
+
     /// Initialize the global Docker client instance
     #[cfg(not(test))]
     pub fn init(stop_timeout_secs: u64) -> Result<()> {
@@ -94,19 +322,245 @@ impl DockerClient {
         Ok(())
     }
 
-    /// 创建新的 Docker 客户端
+    /// 按全局配置创建新的 Docker 客户端
     #[allow(dead_code)]
     fn new(stop_timeout_secs: u64) -> Result<Self> {
+        Self::with_config(&Config::global()?.docker, stop_timeout_secs)
+    }
+
+    /// 按 `docker_config` 创建新的 Docker 客户端
+    fn with_config(docker_config: &DockerConfig, stop_timeout_secs: u64) -> Result<Self> {
         debug!("Initializing Docker client");
-        let client = Docker::connect_with_local_defaults().map_err(|e| {
-            error!(?e, "Failed to connect to Docker daemon");
+        let client = connect(docker_config).map_err(|e| {
+            error!(?e, host = ?docker_config.host, "Failed to connect to Docker daemon");
             e
         })?;
         info!("Docker client initialized successfully");
         Ok(Self {
             client,
             stop_timeout_secs,
+            stop_signal: docker_config.stop_signal.clone(),
+            retry: RetryPolicy::from_config(docker_config),
+        })
+    }
+
+    /// 带重试地查询容器详情
+    async fn inspect(
+        &self,
+        id: &str,
+    ) -> std::result::Result<ContainerInspectResponse, bollard::errors::Error> {
+        with_retry(self.retry, "inspect_container", || {
+            self.client
+                .inspect_container(id, None::<InspectContainerOptions>)
+        })
+        .await
+    }
+
+    /// 带重试地列出容器
+    async fn list(
+        &self,
+        options: ListContainersOptions,
+    ) -> std::result::Result<Vec<ContainerSummary>, bollard::errors::Error> {
+        with_retry(self.retry, "list_containers", || {
+            self.client.list_containers(Some(options.clone()))
         })
+        .await
+    }
+}
+
+/// 错误是否为值得重试的瞬时错误：连接失败、请求超时或 daemon 暂时不可用
+///
+/// 容器不存在 (404) 等 daemon 明确拒绝的请求重试也不会成功，直接返回
+fn is_transient(error: &bollard::errors::Error) -> bool {
+    use bollard::errors::Error;
+    match error {
+        Error::RequestTimeoutError
+        | Error::IOError { .. }
+        | Error::HyperResponseError { .. }
+        | Error::HyperLegacyError { .. }
+        | Error::SocketNotFoundError(_) => true,
+        Error::DockerResponseServerError { status_code, .. } => {
+            matches!(status_code, 502..=504)
+        }
+        _ => false,
+    }
+}
+
+/// 执行 `operation`，遇到瞬时错误时按指数退避等待后重试，最多重试 `policy.retries` 次
+async fn with_retry<T, F, Fut>(
+    policy: RetryPolicy,
+    operation_name: &str,
+    mut operation: F,
+) -> std::result::Result<T, bollard::errors::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, bollard::errors::Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(e) if attempt < policy.retries && is_transient(&e) => {
+                let delay = policy.delay(attempt);
+                attempt += 1;
+                warn!(
+                    ?e,
+                    operation_name,
+                    attempt,
+                    ?delay,
+                    "Transient Docker API error, retrying"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 确认 daemon 可以连接，连接失败时给出启动 daemon 的提示
+async fn ensure_reachable<T: DockerClientInterface>(
+    client: &T,
+    engine: ContainerEngine,
+) -> Result<()> {
+    if let Err(e) = client.ping().await {
+        log_bail!("ERROR", "{}", daemon_unreachable_message(engine, &e));
+    }
+    Ok(())
+}
+
+/// 无法连接 daemon 时的提示，按容器引擎给出启动 daemon 的命令
+fn daemon_unreachable_message(engine: ContainerEngine, error: &anyhow::Error) -> String {
+    let hint = match engine {
+        ContainerEngine::Docker => t!("docker.start_docker_hint"),
+        ContainerEngine::Podman => t!("docker.start_podman_hint"),
+    };
+    t!("docker.daemon_unreachable", "error" = error, "hint" = hint).to_string()
+}
+
+/// Docker 在 unix 上的默认 socket
+#[cfg(unix)]
+const DOCKER_DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// rootful podman 的默认 socket
+const PODMAN_ROOTFUL_SOCKET: &str = "/run/podman/podman.sock";
+
+/// 按优先级列出 podman socket 的候选路径：先 rootless (`$XDG_RUNTIME_DIR`)，后 rootful
+fn podman_socket_candidates(runtime_dir: Option<PathBuf>) -> Vec<PathBuf> {
+    runtime_dir
+        .map(|dir| dir.join("podman/podman.sock"))
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from(PODMAN_ROOTFUL_SOCKET)))
+        .collect()
+}
+
+/// 按配置推断所连接的本地 daemon socket 路径，连接 TCP 地址或找不到 socket 时返回 `None`
+pub(crate) fn daemon_socket(config: &DockerConfig) -> Option<PathBuf> {
+    let host = config.host.trim();
+    if let Some(path) = host.strip_prefix("unix://") {
+        return Some(PathBuf::from(path));
+    }
+    if !host.is_empty() {
+        return None;
+    }
+    match config.engine {
+        #[cfg(unix)]
+        ContainerEngine::Docker => Some(PathBuf::from(DOCKER_DEFAULT_SOCKET)),
+        #[cfg(not(unix))]
+        ContainerEngine::Docker => None,
+        ContainerEngine::Podman => {
+            let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+            podman_socket_candidates(runtime_dir)
+                .into_iter()
+                .find(|socket| socket.exists())
+        }
+    }
+}
+
+/// 返回第一个存在的 socket，都不存在时列出所有候选路径报错
+fn find_socket(candidates: Vec<PathBuf>) -> Result<PathBuf> {
+    if let Some(socket) = candidates.iter().find(|socket| socket.exists()) {
+        return Ok(socket.clone());
+    }
+    let candidates = candidates
+        .iter()
+        .map(|socket| socket.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    log_bail!(
+        "ERROR",
+        "{}",
+        t!("docker.socket_not_found", "candidates" = candidates)
+    )
+}
+
+/// 按配置连接 Docker daemon
+///
+/// - `host` 为空时按 `engine` 使用本地默认 socket，socket 不存在时报错
+/// - `tls` 为真时使用 `cert_path` (默认 `~/.docker`) 下的 `ca.pem`、`cert.pem`、`key.pem`
+/// - 否则按 `unix://` 或 `tcp://` 协议连接
+fn connect(config: &DockerConfig) -> Result<Docker> {
+    let host = config.host.trim();
+    if host.is_empty() {
+        return match config.engine {
+            ContainerEngine::Docker => {
+                #[cfg(unix)]
+                find_socket(vec![PathBuf::from(DOCKER_DEFAULT_SOCKET)])?;
+                Ok(Docker::connect_with_local_defaults()?)
+            }
+            ContainerEngine::Podman => {
+                let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+                let socket = find_socket(podman_socket_candidates(runtime_dir))?;
+                debug!(?socket, "Connecting to podman socket");
+                connect_to_host(&format!("unix://{}", socket.display()))
+            }
+        };
+    }
+
+    if config.tls {
+        let cert_path = config
+            .cert_path
+            .clone()
+            .or_else(|| dirs::home_dir().map(|home| home.join(".docker")))
+            .ok_or_else(|| anyhow::anyhow!(t!("docker.cert_path_not_found")))?;
+        debug!(host, ?cert_path, "Connecting to Docker daemon with TLS");
+        return Ok(Docker::connect_with_ssl(
+            host,
+            &cert_path.join("key.pem"),
+            &cert_path.join("cert.pem"),
+            &cert_path.join("ca.pem"),
+            DOCKER_API_TIMEOUT_SECS,
+            API_DEFAULT_VERSION,
+        )?);
+    }
+
+    connect_to_host(host)
+}
+
+/// 按地址的 scheme 建立不使用 TLS 的连接
+fn connect_to_host(host: &str) -> Result<Docker> {
+    debug!(host, "Connecting to Docker daemon");
+    match host {
+        #[cfg(unix)]
+        h if h.starts_with("unix://") => Ok(Docker::connect_with_unix(
+            h,
+            DOCKER_API_TIMEOUT_SECS,
+            API_DEFAULT_VERSION,
+        )?),
+        #[cfg(windows)]
+        h if h.starts_with("npipe://") => Ok(Docker::connect_with_named_pipe(
+            h,
+            DOCKER_API_TIMEOUT_SECS,
+            API_DEFAULT_VERSION,
+        )?),
+        h if h.starts_with("tcp://") || h.starts_with("http://") => Ok(Docker::connect_with_http(
+            h,
+            DOCKER_API_TIMEOUT_SECS,
+            API_DEFAULT_VERSION,
+        )?),
+        _ => log_bail!(
+            "ERROR",
+            "{}",
+            t!("docker.unsupported_host_scheme", "host" = host)
+        ),
     }
 }
 
@@ -114,30 +568,18 @@ impl DockerClientInterface for DockerClient {
     /// 列出所有容器
     async fn list_containers(&self) -> Result<Vec<ContainerInfo>> {
         debug!("Listing all containers");
-        let options = Some(ListContainersOptionsBuilder::new().all(true).build());
+        let options = ListContainersOptionsBuilder::new().all(true).build();
 
-        let containers = self.client.list_containers(options).await.map_err(|e| {
+        let containers = self.list(options).await.map_err(|e| {
             error!(?e, "Failed to list containers");
             e
         })?;
 
         let mut result = Vec::new();
-        for container in containers {
-            let name = container
-                .names
-                .unwrap_or_default()
-                .first()
-                .cloned()
-                .unwrap_or_default()
-                .trim_start_matches('/')
-                .to_string();
-
-            debug!(container_id = ?container.id, container_name = ?name, "Found container");
-            result.push(ContainerInfo {
-                id: container.id.unwrap_or_default(),
-                name,
-                status: container.status.unwrap_or_default(),
-            });
+        for container in &containers {
+            let info = container_info(container);
+            debug!(container_id = ?info.id, container_name = ?info.name, "Found container");
+            result.push(info);
         }
 
         info!(
@@ -150,14 +592,10 @@ impl DockerClientInterface for DockerClient {
     /// 获取容器的卷信息
     async fn get_container_volumes(&self, container_id: &str) -> Result<Vec<VolumeInfo>> {
         debug!(container_id, "Getting volume information");
-        let details = self
-            .client
-            .inspect_container(container_id, None::<InspectContainerOptions>)
-            .await
-            .map_err(|e| {
-                error!(?e, container_id, "Failed to inspect container");
-                e
-            })?;
+        let details = self.inspect(container_id).await.map_err(|e| {
+            error!(?e, container_id, "Failed to inspect container");
+            e
+        })?;
 
         let working_dir = self.get_container_working_dir(container_id).await?;
         let working_dir_path = PathBuf::from(&working_dir);
@@ -179,7 +617,7 @@ impl DockerClientInterface for DockerClient {
 
                 // 将 source 转换为绝对路径
                 // 存在性检查
-                let source = utils::absolute_canonicalize_path(&source)
+                let source = utils::resolve_path(&source)
                     .context("Failed to canonicalize path for volume mount source")?;
 
                 // 将 destination 转化为容器内部的路径
@@ -193,11 +631,16 @@ impl DockerClientInterface for DockerClient {
                     .unwrap_or_default()
                     .to_string_lossy()
                     .to_string();
+                let volume_name = mount
+                    .name
+                    .clone()
+                    .filter(|_| mount.typ == Some(MountPointTypeEnum::VOLUME));
 
                 volumes.push(VolumeInfo {
                     name,
                     source,
                     destination,
+                    volume_name,
                 });
             } else {
                 warn!(
@@ -255,39 +698,101 @@ impl DockerClientInterface for DockerClient {
     }
 
     async fn stop_container(&self, container_id: &str) -> Result<()> {
-        debug!("Stopping container: {}", container_id);
+        debug!(container_id, signal = ?self.stop_signal, "Stopping container");
+
+        // 由 Docker 在超时后发送 SIGKILL；超时为 0 时对应 Docker 的 -1，即一直等待
+        let mut options =
+            StopContainerOptionsBuilder::new().t(stop_wait_secs(self.stop_timeout_secs));
+        if let Some(signal) = self.stop_signal.as_deref() {
+            options = options.signal(signal);
+        }
+        let options = options.build();
+        let result = with_retry(self.retry, "stop_container", || async {
+            match self
+                .client
+                .stop_container(container_id, Some(options.clone()))
+                .await
+            {
+                // 停止请求超时说明 daemon 仍在等待容器退出，不作为瞬时错误重试
+                Err(bollard::errors::Error::RequestTimeoutError) => Ok(false),
+                result => result.map(|()| true),
+            }
+        })
+        .await;
+        match result {
+            // 停止请求会等到容器停止才返回，超过客户端的请求超时后由调用方继续轮询状态
+            Ok(false) => {
+                debug!(
+                    container_id,
+                    "Stop request timed out, container still stopping"
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                error!(?e, "Failed to stop container");
+                return Err(e.into());
+            }
+            Ok(true) => {}
+        }
+
+        debug!("Container stopped: {:?}", container_id);
+
+        Ok(())
+    }
+
+    async fn pause_container(&self, container_id: &str) -> Result<()> {
+        debug!("Pausing container: {}", container_id);
 
-        let options: Option<StopContainerOptions> = None;
         self.client
-            .stop_container(container_id, options)
+            .pause_container(container_id)
             .await
             .map_err(|e| {
-                error!(?e, "Failed to stop container");
+                error!(?e, "Failed to pause container");
                 e
             })?;
 
-        debug!("Container stopped: {:?}", container_id);
+        debug!("Container paused: {:?}", container_id);
+
+        Ok(())
+    }
+
+    async fn unpause_container(&self, container_id: &str) -> Result<()> {
+        debug!("Unpausing container: {}", container_id);
+
+        self.client
+            .unpause_container(container_id)
+            .await
+            .map_err(|e| {
+                error!(?e, "Failed to unpause container");
+                e
+            })?;
+
+        debug!("Container unpaused: {:?}", container_id);
 
         Ok(())
     }
 
     async fn get_container_status(&self, id: &str) -> Result<String> {
-        let status = self
-            .client
-            .inspect_container(id, None::<InspectContainerOptions>)
-            .await?;
+        let status = self.inspect(id).await?;
         match_status(status)
     }
 
+    async fn get_container_health(&self, id: &str) -> Result<Option<String>> {
+        let status = self.inspect(id).await?;
+        Ok(match_health(status))
+    }
+
     fn get_stop_timeout_secs(&self) -> u64 {
         self.stop_timeout_secs
     }
 
+    async fn ping(&self) -> Result<()> {
+        self.client.ping().await?;
+        Ok(())
+    }
+
     async fn get_container_working_dir(&self, id: &str) -> Result<String> {
-        let status = self
-            .client
-            .inspect_container(id, None::<InspectContainerOptions>)
-            .await?;
+        let status = self.inspect(id).await?;
         let config = status
             .config
             .ok_or_else(|| anyhow::anyhow!(t!("docker.container_config_not_found")))?;
@@ -299,17 +804,148 @@ impl DockerClientInterface for DockerClient {
         Ok(working_dir)
     }
 
+    async fn upload_to_container(
+        &self,
+        container_id: &str,
+        path: &str,
+        tar: UploadBody,
+    ) -> Result<()> {
+        debug!(container_id, path, ?tar, "Uploading archive to container");
+        let options = Some(UploadToContainerOptionsBuilder::new().path(path).build());
+
+        let result = match tar {
+            UploadBody::Stream(receiver) => {
+                let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                    receiver.recv().await.map(|chunk| (chunk, receiver))
+                });
+                self.client
+                    .upload_to_container(container_id, options, bollard::body_stream(stream))
+                    .await
+            }
+            UploadBody::Staged(path) => {
+                let file = tokio::fs::File::open(&path).await?;
+                let stream = tokio_util::io::ReaderStream::with_capacity(file, UPLOAD_CHUNK_SIZE);
+                self.client
+                    .upload_to_container(container_id, options, bollard::body_try_stream(stream))
+                    .await
+            }
+        };
+        result.map_err(|e| {
+            error!(?e, container_id, "Failed to upload archive to container");
+            e
+        })?;
+
+        debug!(container_id, "Archive uploaded to container");
+        Ok(())
+    }
+
+    async fn create_helper_container(&self, image_of: &str, binds: Vec<String>) -> Result<String> {
+        let details = self.inspect(image_of).await?;
+        let image = details
+            .config
+            .and_then(|config| config.image)
+            .or(details.image)
+            .ok_or_else(|| anyhow::anyhow!("Container {} has no image", image_of))?;
+
+        debug!(image, ?binds, "Creating helper container");
+        let body = ContainerCreateBody {
+            image: Some(image),
+            host_config: Some(HostConfig {
+                binds: Some(binds),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let response = self
+            .client
+            .create_container(Some(CreateContainerOptionsBuilder::new().build()), body)
+            .await
+            .map_err(|e| {
+                error!(?e, "Failed to create helper container");
+                e
+            })?;
+
+        debug!(id = response.id, "Helper container created");
+        Ok(response.id)
+    }
+
+    async fn remove_container(&self, container_id: &str) -> Result<()> {
+        debug!("Removing container: {}", container_id);
+        let options = RemoveContainerOptionsBuilder::new()
+            .force(true)
+            .v(false)
+            .build();
+
+        self.client
+            .remove_container(container_id, Some(options))
+            .await
+            .map_err(|e| {
+                error!(?e, "Failed to remove container");
+                e
+            })?;
+
+        debug!("Container removed: {:?}", container_id);
+        Ok(())
+    }
+
+    async fn exec_in_container(&self, container_id: &str, cmd: Vec<String>) -> Result<ExecOutput> {
+        debug!(container_id, ?cmd, "Executing command in container");
+
+        let exec = self
+            .client
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    cmd: Some(cmd),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let mut output = String::new();
+        if let StartExecResults::Attached {
+            output: mut stream, ..
+        } = self.client.start_exec(&exec.id, None).await?
+        {
+            while let Some(chunk) = stream.next().await {
+                output.push_str(&String::from_utf8_lossy(&chunk?.into_bytes()));
+            }
+        }
+
+        let exit_code = self.client.inspect_exec(&exec.id).await?.exit_code;
+        debug!(container_id, ?exit_code, "Command in container finished");
+        Ok(ExecOutput { exit_code, output })
+    }
+
     /// Find containers by partial name or ID match
     async fn find_containers(&self, name_or_id: &str) -> Result<Vec<ContainerInfo>> {
         let containers = self.list_containers().await?;
-        let matches: Vec<ContainerInfo> = containers
+        Ok(resolve_container_reference(containers, name_or_id))
+    }
+
+    async fn find_containers_by_labels(&self, labels: &[String]) -> Result<Vec<LabeledContainer>> {
+        debug!(?labels, "Listing containers by labels");
+        // daemon 要求容器同时满足 label 过滤器中的每个条件
+        let filters = HashMap::from([("label", labels.to_vec())]);
+        let options = ListContainersOptionsBuilder::new()
+            .all(true)
+            .filters(&filters)
+            .build();
+
+        let containers = self.list(options).await.map_err(|e| {
+            error!(?e, ?labels, "Failed to list containers by labels");
+            e
+        })?;
+
+        Ok(containers
             .into_iter()
-            .filter(|c| {
-                c.name.to_lowercase().contains(&name_or_id.to_lowercase())
-                    || c.id.contains(name_or_id)
+            .map(|container| LabeledContainer {
+                info: container_info(&container),
+                labels: container.labels.unwrap_or_default().into_iter().collect(),
             })
-            .collect();
-        Ok(matches)
+            .collect())
     }
 
     /// Find a container by partial name or ID match
@@ -327,6 +963,52 @@ impl DockerClientInterface for DockerClient {
     }
 }
 
+/// 短 ID 的最小长度，与 `docker ps` 显示的短 ID 一致
+pub(crate) const SHORT_ID_LEN: usize = 12;
+
+/// 按照与 `docker` 相同的优先级解析容器引用
+///
+/// 1. 完整 ID 精确匹配
+/// 2. 短 ID (至少 12 位十六进制) 前缀匹配
+/// 3. 名称精确匹配
+/// 4. 名称或 ID 的模糊 (子串) 匹配
+///
+/// 命中某一级后不再继续向下匹配，避免短 ID 或精确名称出现多个候选
+pub(crate) fn resolve_container_reference(
+    containers: Vec<ContainerInfo>,
+    reference: &str,
+) -> Vec<ContainerInfo> {
+    let reference = reference.trim().trim_start_matches('/');
+
+    if let Some(container) = containers.iter().find(|c| c.id == reference) {
+        return vec![container.clone()];
+    }
+
+    let is_short_id =
+        reference.len() >= SHORT_ID_LEN && reference.chars().all(|c| c.is_ascii_hexdigit());
+    if is_short_id {
+        let reference = reference.to_lowercase();
+        let matches = containers
+            .iter()
+            .filter(|c| c.id.starts_with(&reference))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !matches.is_empty() {
+            return matches;
+        }
+    }
+
+    if let Some(container) = containers.iter().find(|c| c.name == reference) {
+        return vec![container.clone()];
+    }
+
+    let lowercase = reference.to_lowercase();
+    containers
+        .into_iter()
+        .filter(|c| c.name.to_lowercase().contains(&lowercase) || c.id.contains(reference))
+        .collect()
+}
+
 /// 匹配容器状态
 ///
 /// 将 bollard::secret::ContainerInspectResponse 中的状态转换为字符串
@@ -344,15 +1026,71 @@ fn match_status(status: bollard::secret::ContainerInspectResponse) -> Result<Str
     }
 }
 
-#[derive(Debug, Clone)]
+/// 提取容器健康检查的状态，未定义健康检查 (`none` 或缺失) 时返回 `None`
+fn match_health(status: bollard::secret::ContainerInspectResponse) -> Option<String> {
+    match status.state?.health?.status? {
+        HealthStatusEnum::EMPTY | HealthStatusEnum::NONE => None,
+        health => Some(health.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ContainerInfo {
     pub id: String,
     pub name: String,
     pub status: String,
+    /// 创建容器时使用的镜像名 (如 `postgres:16`)
+    pub image: String,
+    /// 容器的创建时间 (Unix 时间戳，秒)
+    pub created: i64,
+}
+
+/// 容器及其标签 (如 `com.docker.compose.project`)
+#[derive(Debug, Clone)]
+pub struct LabeledContainer {
+    pub info: ContainerInfo,
+    pub labels: BTreeMap<String, String>,
+}
+
+/// 停止容器时传给 Docker 的等待秒数 (`t`)，0 表示不限时，对应 Docker 的 -1
+fn stop_wait_secs(timeout_secs: u64) -> i32 {
+    if timeout_secs == 0 {
+        -1
+    } else {
+        i32::try_from(timeout_secs).unwrap_or(i32::MAX)
+    }
+}
+
+/// 从 `list_containers` 的结果中提取容器信息，容器名去掉开头的 `/`
+fn container_info(container: &ContainerSummary) -> ContainerInfo {
+    let name = container
+        .names
+        .as_ref()
+        .and_then(|names| names.first())
+        .map(|name| name.trim_start_matches('/').to_string())
+        .unwrap_or_default();
+    ContainerInfo {
+        id: container.id.clone().unwrap_or_default(),
+        name,
+        status: container.status.clone().unwrap_or_default(),
+        image: container.image.clone().unwrap_or_default(),
+        created: container.created.unwrap_or_default(),
+    }
+}
+
+/// 当前写入的 mapping 结构版本，mapping 的布局发生不兼容的变化时递增
+pub const MAPPING_SCHEMA_VERSION: u32 = 1;
+
+/// 引入结构版本之前的备份中不存在 `schema_version`，视为版本 1
+fn legacy_schema_version() -> u32 {
+    1
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BackupMapping {
+    /// mapping 的结构版本，恢复时据此将旧版本的 mapping 升级到当前结构
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     /// 容器名称
     pub container_name: String,
     /// 容器 ID
@@ -363,15 +1101,61 @@ pub struct BackupMapping {
     pub backup_time: String,
     /// 备份版本
     pub version: String,
+    /// 归档数据部分 (元数据流之后的所有字节) 的 SHA-256，旧版本备份中不存在
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_sha256: Option<String>,
+    /// 与其他卷共享同一宿主机路径，备份时被合并的卷
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<VolumeAlias>,
+    /// 增量备份所基于的上一个备份，为空表示完整备份
+    ///
+    /// 增量备份只包含相对上一个备份新增或修改过的文件。恢复时沿 `base` 逐级找到最早的完整备份，
+    /// 再按从旧到新的顺序依次解压整条备份链。备份链中的文件须位于同一目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<BackupBase>,
+    /// 备份时每个文件的修改时间与大小 (包括增量备份中因未变化而未打包的文件)，
+    /// 下一次增量备份据此判断文件是否变化，旧版本备份中不存在
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, FileStamp>,
+    /// 使用 `--exclude-older-than` 时的截止时间，备份只包含此后修改过的文件，并不完整
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_after: Option<String>,
+    /// 使用 `--preserve-ownership` 备份，归档中包含目录条目，恢复时按需还原属主与权限
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preserve_ownership: bool,
     // 备份的文件总数 (后续再考虑如何低开销的实现)
     // pub total_files: usize,
 }
 
+/// 增量备份引用的上一个备份
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupBase {
+    /// 上一个备份的文件名 (与增量备份位于同一目录)
+    pub file: String,
+    /// 上一个备份的备份时间
+    pub backup_time: String,
+    /// 上一个备份的数据部分校验和，用于确认恢复时找到的是同一个备份
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_sha256: Option<String>,
+}
+
+/// 备份时被合并的重复卷，恢复时按需还原到其自身的路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeAlias {
+    /// 被合并的卷信息
+    pub volume: VolumeInfo,
+    /// 实际被打包的卷名称 (即归档中的目录名)
+    pub alias_of: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolumeInfo {
     pub name: String,
     pub source: PathBuf,
     pub destination: PathBuf,
+    /// Docker 命名卷的卷名，绑定挂载时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_name: Option<String>,
 }
 
 #[cfg(test)]
@@ -385,6 +1169,191 @@ mod tests {
     use std::time::Duration;
     use tokio::{self, process::Command, time::sleep};
     use tracing::debug;
+
+    #[test]
+    fn zero_stop_timeout_waits_indefinitely() {
+        assert_eq!(stop_wait_secs(0), -1);
+        assert_eq!(stop_wait_secs(30), 30);
+        assert_eq!(stop_wait_secs(u64::MAX), i32::MAX);
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_with_backoff() {
+        use bollard::errors::Error;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let policy = RetryPolicy {
+            retries: 2,
+            base_delay: Duration::from_millis(1),
+        };
+        let server_error = |status_code| Error::DockerResponseServerError {
+            status_code,
+            message: "test".into(),
+        };
+
+        // 第一次连接失败，第二次成功
+        let calls = AtomicU32::new(0);
+        let result = with_retry(policy, "test", || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(server_error(503)),
+                _ => Ok("ok"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 容器不存在时不重试
+        let calls = AtomicU32::new(0);
+        let result = with_retry(policy, "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(server_error(404))
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::DockerResponseServerError {
+                status_code: 404,
+                ..
+            })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // 重试次数用尽后返回最后一次的错误
+        let calls = AtomicU32::new(0);
+        let result = with_retry(policy, "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(Error::RequestTimeoutError)
+        })
+        .await;
+        assert!(matches!(result, Err(Error::RequestTimeoutError)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(2));
+        assert_eq!(
+            RetryPolicy::from_config(&DockerConfig::default()).delay(u32::MAX),
+            MAX_RETRY_DELAY
+        );
+    }
+
+    fn container(id: &str, name: &str) -> ContainerInfo {
+        ContainerInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            status: "exited".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_container_references_by_priority() {
+        let long_id = format!("{}{}", "3f4a5b6c7d8e", "0".repeat(52));
+        let containers = vec![
+            container(&long_id, "web"),
+            container(
+                &format!("{}{}", "3f4a5b6c7d8f", "1".repeat(52)),
+                "web-worker",
+            ),
+            container(&format!("{}{}", "ab12", "2".repeat(60)), "db"),
+            container(&format!("{}{}", "db00", "3".repeat(60)), "cache"),
+        ];
+        let names = |reference: &str| {
+            resolve_container_reference(containers.clone(), reference)
+                .into_iter()
+                .map(|c| c.name)
+                .collect::<Vec<_>>()
+        };
+
+        // 完整 ID
+        assert_eq!(names(&long_id), vec!["web"]);
+        // 12 位短 ID 前缀
+        assert_eq!(names("3f4a5b6c7d8e"), vec!["web"]);
+        // 精确名称优先于模糊匹配，即使名称也是合法的十六进制前缀
+        assert_eq!(names("web"), vec!["web"]);
+        assert_eq!(names("db"), vec!["db"]);
+        assert_eq!(names("/db"), vec!["db"]);
+        // 模糊匹配
+        assert_eq!(names("work"), vec!["web-worker"]);
+        assert_eq!(names("3f4a5b"), vec!["web", "web-worker"]);
+        assert!(names("missing").is_empty());
+    }
+
+    fn docker_config(host: &str, tls: bool, cert_path: Option<PathBuf>) -> DockerConfig {
+        DockerConfig {
+            host: host.to_string(),
+            tls,
+            cert_path,
+            engine: ContainerEngine::Docker,
+            ..DockerConfig::default()
+        }
+    }
+
+    #[test]
+    fn parses_label_filters() {
+        assert_eq!(
+            parse_label_filter(" backup = true ").as_deref(),
+            Ok("backup=true")
+        );
+        assert_eq!(parse_label_filter("backup").as_deref(), Ok("backup"));
+        assert_eq!(parse_label_filter("tier=").as_deref(), Ok("tier="));
+        assert!(parse_label_filter("=true").is_err());
+        assert!(parse_label_filter(" ").is_err());
+    }
+
+    #[test]
+    fn normalizes_stop_signals() {
+        assert_eq!(parse_stop_signal("sigint").as_deref(), Ok("SIGINT"));
+        assert_eq!(parse_stop_signal("TERM").as_deref(), Ok("SIGTERM"));
+        assert_eq!(parse_stop_signal(" 15 ").as_deref(), Ok("15"));
+        assert_eq!(parse_stop_signal("RTMIN+3").as_deref(), Ok("SIGRTMIN+3"));
+        assert!(parse_stop_signal("").is_err());
+        assert!(parse_stop_signal("SIG").is_err());
+        assert!(parse_stop_signal("SIG TERM").is_err());
+    }
+
+    #[test]
+    fn suggests_starting_the_engine_when_unreachable() {
+        let error = anyhow::anyhow!("connection refused");
+        let docker = daemon_unreachable_message(ContainerEngine::Docker, &error);
+        assert!(docker.contains("connection refused"));
+        assert!(docker.contains("systemctl start docker"));
+
+        let podman = daemon_unreachable_message(ContainerEngine::Podman, &error);
+        assert!(podman.contains("systemctl --user start podman.socket"));
+    }
+
+    #[test]
+    fn finds_podman_socket_in_runtime_dir() {
+        let runtime_dir = tempfile::tempdir().unwrap();
+        let candidates = podman_socket_candidates(Some(runtime_dir.path().to_path_buf()));
+        assert_eq!(
+            candidates,
+            vec![
+                runtime_dir.path().join("podman/podman.sock"),
+                PathBuf::from(PODMAN_ROOTFUL_SOCKET)
+            ]
+        );
+
+        let socket = runtime_dir.path().join("podman/podman.sock");
+        assert!(find_socket(vec![socket.clone()]).is_err());
+        std::fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        std::fs::write(&socket, b"").unwrap();
+        assert_eq!(find_socket(candidates).unwrap(), socket);
+    }
+
+    #[tokio::test]
+    async fn connects_by_host_scheme() {
+        assert!(connect(&docker_config("tcp://127.0.0.1:2375", false, None)).is_ok());
+        assert!(connect(&docker_config("ftp://127.0.0.1", false, None)).is_err());
+
+        let missing_certs = tempfile::tempdir().unwrap();
+        let config = docker_config(
+            "tcp://127.0.0.1:2376",
+            true,
+            Some(missing_certs.path().to_path_buf()),
+        );
+        assert!(connect(&config).is_err());
+    }
     static INIT: Once = Once::new();
 
     fn setup() {
@@ -418,6 +1387,7 @@ mod tests {
                 id: "container1".to_string(),
                 name: "test-container".to_string(),
                 status: "running".to_string(),
+                ..Default::default()
             }])
         });
 
@@ -448,6 +1418,7 @@ mod tests {
                     name: "volume1".to_string(),
                     source: PathBuf::from("/host/path"),
                     destination: PathBuf::from("/container/path"),
+                    volume_name: None,
                 }])
             });
 
@@ -469,6 +1440,7 @@ mod tests {
                 id: "test_id_1".to_string(),
                 name: "test_container_1".to_string(),
                 status: "running".to_string(),
+                ..Default::default()
             }])
         });
         let containers = client.list_containers().await?;
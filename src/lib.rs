@@ -1,5 +1,5 @@
+pub mod api;
 mod commands;
-// #[deprecated(since = "1.0.0", note = "no need to load config file")]
 mod config;
 mod docker;
 mod utils;
@@ -8,17 +8,24 @@ mod utils;
 mod tests;
 
 use anyhow::Result;
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{
+    ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum, parser::ValueSource,
+};
+use docker::DockerClient;
 use std::io;
+use std::path::PathBuf;
 use tracing::{Level, info, instrument};
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, fmt, fmt::MakeWriter, layer::SubscriberExt, util::SubscriberInitExt,
+};
 
 #[macro_use]
 extern crate rust_i18n;
 
 rust_i18n::i18n!(
     "locales",
-    fallback = ["en", "ja", "ko", "es", "fr", "de", "it"]
+    fallback = ["en", "ja", "ko", "es", "fr", "de", "it", "ru", "pt-BR"]
 );
 
 #[allow(unused)]
@@ -42,29 +49,157 @@ struct Cli {
     #[arg(global = true, short, long, default_value = "true")]
     interactive: bool,
 
-    /// 是否在操作 (备份/恢复) 后重启容器 [default: false]
+    /// 是否在操作 (备份/恢复) 后重启容器，操作失败时同样重新启动被停止的容器 [default: false]
     #[arg(global = true, short, long, default_value = "false")]
     restart: bool,
 
-    /// 停止容器超时时间 (秒)
+    /// 重启容器后等待其健康检查通过，可用 `=` 指定超时时间 (如 `--wait-healthy=2m`) [default: 60s]
+    ///
+    /// 需要同时指定 --restart；超时仍未 healthy 时视为失败。容器未定义健康检查时只短暂等待其进入 running 状态
+    #[arg(
+        global = true,
+        long,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "60s",
+        value_parser = utils::parse_duration,
+        requires = "restart"
+    )]
+    wait_healthy: Option<std::time::Duration>,
+
+    /// 停止容器超时时间 (秒)，超时后由 Docker 强制终止容器；0 表示一直等待直到容器停止 [default: 30]
     #[arg(global = true, short, long, default_value = "30")]
     timeout: u64,
 
-    /// 排除模式：备份时将排除包含这些模式的文件/目录
+    /// 排除模式 (逗号分隔)：备份时将排除匹配这些模式的文件/目录
+    ///
+    /// 含 `*`、`?`、`[`、`{` 的模式按 glob 匹配相对于卷根目录的路径 (如 `**/*.log`、`cache/*`)；
+    /// 其余模式按路径组件匹配，`target` 只排除名为 `target` 的文件/目录
     #[arg(global = true, short, long, default_value = ".git,node_modules,target")]
     exclude: String,
 
+    /// 使用配置文件中 `[profiles.<NAME>]` 定义的参数组合 (exclude、output、archive_format、keep_last)
+    ///
+    /// 命令行显式指定的参数优先于 profile，profile 优先于配置文件中的其他设置；profile 不存在时报错
+    #[arg(global = true, long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// 从文件读取排除模式，每行一个 (类似 .gitignore)，支持 `#` 注释与空行
+    ///
+    /// 模式语法与 --exclude 相同，但行内的逗号不会被拆分；读取到的模式追加在 --exclude 的模式之后，文件不存在时报错
+    #[arg(global = true, long, value_name = "FILE")]
+    exclude_from: Option<PathBuf>,
+
     /// 是否自动确认 [default: false]
     #[arg(global = true, short, long, default_value = "false")]
     yes: bool,
 
-    /// 是否显示详细日志 [default: false]
-    #[arg(global = true, short, long, default_value = "false")]
-    verbose: bool,
+    /// 显示更详细的日志，可重复：-v 为 WARN，-vv 为 INFO，-vvv 为 DEBUG，-vvvv 为 TRACE [default: ERROR]
+    ///
+    /// 设置 RUST_LOG 时以 RUST_LOG 为准，可以按模块指定级别 (如 `RUST_LOG=rdbkp2::docker=trace`)
+    #[arg(global = true, short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// 日志格式：`json` 时每条日志输出为一行 JSON (包含所在 span 的字段)，便于日志收集
+    ///
+    /// 日志始终写入 stderr，命令结果与提示信息写入 stdout，两者不会混在同一个流中
+    #[arg(global = true, long, value_enum, default_value = "text")]
+    log_format: LogFormat,
+
+    /// 同时将日志写入按天轮转的文件，适用于 cron 等无人值守的备份
+    ///
+    /// 路径需用 `=` 指定 (如 `--log-file=/var/log/rdbkp2.log`)，省略时写入 <本地数据目录>/rdbkp2/logs/rdbkp2.log。
+    /// 实际文件名会追加日期 (如 rdbkp2.log.2026-10-16)；文件中至少记录 INFO 级别的日志，终端输出不受影响
+    #[arg(global = true, long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    log_file: Option<Option<PathBuf>>,
+
+    /// 日志文件保留的天数 (文件个数)，超出时删除最旧的文件
+    #[arg(global = true, long, default_value = "7", value_parser = clap::value_parser!(u16).range(1..))]
+    log_keep: u16,
+
+    /// 设置语言 [default: 根据系统语言 (LANG/LC_ALL 等) 检测，无法识别时为 en]
+    ///
+    /// 未指定时优先使用配置文件中的语言
+    #[arg(global = true, short, long, value_enum)]
+    language: Option<Language>,
+
+    /// 禁用按语言格式化数字和文件大小 (便于脚本解析) [default: false]
+    #[arg(global = true, long, default_value = "false")]
+    no_locale_format: bool,
+
+    /// 不对源路径和输出路径执行 canonicalize，只做简单的绝对路径规范化 [default: false]
+    ///
+    /// 适用于 NFS 等 canonicalize 缓慢或失败的网络文件系统，同时会保留路径中的符号链接。
+    /// 此模式下不会提前检查路径是否存在，路径错误将在实际读写时才会报错
+    #[arg(global = true, long, default_value = "false")]
+    no_canonicalize: bool,
+
+    /// 只打印将要执行的操作，不创建文件、不停止容器、不修改任何数据 [default: false]
+    #[arg(global = true, long, default_value = "false")]
+    dry_run: bool,
+
+    /// 不显示压缩/解压进度，适用于 CI 等不需要进度输出的场景 [default: false]
+    ///
+    /// 输出不是终端或启用 --verbose 时，进度会以定期的日志行代替进度条
+    #[arg(global = true, long, default_value = "false")]
+    no_progress: bool,
+
+    /// 限制压缩时读取源文件的速率，单位为 MB/s [default: 不限速]
+    ///
+    /// 以延长备份时间为代价，避免备份占满磁盘 IO 影响正在运行的服务；并行压缩的所有卷共享该限制。
+    /// 未指定时使用配置文件中的 `io_limit`
+    #[arg(global = true, long, value_name = "MB/s", value_parser = clap::value_parser!(u64).range(1..))]
+    io_limit: Option<u64>,
+
+    /// 备份/恢复成功后不自动检查新版本 [default: false]
+    ///
+    /// 默认每 24 小时最多检查一次 crates.io，发现新版本时打印一行提示；检查失败不影响备份/恢复
+    #[arg(global = true, long, default_value = "false")]
+    no_update_check: bool,
+
+    /// Docker daemon 地址，支持 unix:// 与 tcp:// [default: 本地默认连接方式]
+    #[arg(global = true, long, env = "DOCKER_HOST")]
+    docker_host: Option<String>,
 
-    /// 设置语言
-    #[arg(global = true, short, long, default_value = "zh", value_enum)]
-    language: Language,
+    /// 使用 TLS 连接 Docker daemon [default: false]
+    #[arg(
+        global = true,
+        long,
+        env = "DOCKER_TLS_VERIFY",
+        default_value = "false",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    docker_tls: bool,
+
+    /// TLS 证书目录，需包含 ca.pem、cert.pem 与 key.pem [default: ~/.docker]
+    #[arg(global = true, long, env = "DOCKER_CERT_PATH")]
+    docker_cert_path: Option<PathBuf>,
+
+    /// 容器引擎，未指定 --docker-host 时据此选择默认 socket [default: docker]
+    ///
+    /// podman 依次尝试 $XDG_RUNTIME_DIR/podman/podman.sock (rootless) 与 /run/podman/podman.sock
+    #[arg(global = true, long, value_enum, default_value = "docker")]
+    container_engine: ContainerEngine,
+
+    /// 停止容器时发送的信号，如 SIGINT、SIGQUIT 或信号编号 [default: 容器配置的 StopSignal，通常为 SIGTERM]
+    ///
+    /// 超时后容器仍未停止时由 daemon 发送 SIGKILL；也可以在配置文件的 `[docker] stop_signal` 中设置
+    #[arg(global = true, long, value_name = "SIGNAL", value_parser = docker::parse_stop_signal)]
+    stop_signal: Option<String>,
+
+    /// Docker API 请求遇到连接失败或超时等瞬时错误时的重试次数，0 表示不重试 [default: 3]
+    ///
+    /// 适用于备份期间 daemon 重启等短暂中断；容器不存在等错误不会重试。
+    /// 未指定时使用配置文件中的 `[docker] retries`
+    #[arg(global = true, long, value_name = "N")]
+    retries: Option<u32>,
+
+    /// 第一次重试前的等待时间 (毫秒)，之后每次重试翻倍 [default: 500]
+    ///
+    /// 未指定时使用配置文件中的 `[docker] retry_delay_ms`
+    #[arg(global = true, long, value_name = "MS")]
+    retry_delay_ms: Option<u64>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -76,8 +211,9 @@ enum Shell {
     PowerShell,
 }
 
-#[derive(Clone, ValueEnum, Debug)]
+#[derive(Clone, ValueEnum, Debug, PartialEq, Eq)]
 enum Language {
+    #[value(alias = "zh-CN")]
     Zh,
     En,
     Ja,
@@ -86,6 +222,122 @@ enum Language {
     Fr,
     De,
     It,
+    Ru,
+    #[value(aliases = ["pt-BR", "pt"])]
+    PtBr,
+}
+
+/// 日志格式
+#[derive(Clone, Copy, ValueEnum, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 便于阅读的文本格式
+    #[default]
+    Text,
+    /// 每条日志一行 JSON
+    Json,
+}
+
+#[derive(Clone, ValueEnum, Debug)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl From<OutputFormat> for commands::OutputFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Table => commands::OutputFormat::Table,
+            OutputFormat::Json => commands::OutputFormat::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug)]
+enum ArchiveFormat {
+    #[value(name = "tar.xz")]
+    TarXz,
+    #[value(name = "tar.gz")]
+    TarGz,
+}
+
+impl From<ArchiveFormat> for utils::ArchiveFormat {
+    fn from(format: ArchiveFormat) -> Self {
+        match format {
+            ArchiveFormat::TarXz => utils::ArchiveFormat::TarXz,
+            ArchiveFormat::TarGz => utils::ArchiveFormat::TarGz,
+        }
+    }
+}
+
+impl From<utils::ArchiveFormat> for ArchiveFormat {
+    fn from(format: utils::ArchiveFormat) -> Self {
+        match format {
+            utils::ArchiveFormat::TarXz => ArchiveFormat::TarXz,
+            utils::ArchiveFormat::TarGz => ArchiveFormat::TarGz,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug)]
+enum RestoreLayout {
+    Flat,
+    PerVolume,
+}
+
+impl From<RestoreLayout> for commands::restore::RestoreLayout {
+    fn from(layout: RestoreLayout) -> Self {
+        match layout {
+            RestoreLayout::Flat => commands::restore::RestoreLayout::Flat,
+            RestoreLayout::PerVolume => commands::restore::RestoreLayout::PerVolume,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug)]
+enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+impl From<ContainerEngine> for config::ContainerEngine {
+    fn from(engine: ContainerEngine) -> Self {
+        match engine {
+            ContainerEngine::Docker => config::ContainerEngine::Docker,
+            ContainerEngine::Podman => config::ContainerEngine::Podman,
+        }
+    }
+}
+
+impl Language {
+    /// 将 `en_US.UTF-8`、`zh-Hans-CN` 这类系统语言标识映射为支持的语言，无法识别时为 `None`
+    fn from_locale(locale: &str) -> Option<Self> {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "zh" => Some(Language::Zh),
+            "en" => Some(Language::En),
+            "ja" => Some(Language::Ja),
+            "ko" => Some(Language::Ko),
+            "es" => Some(Language::Es),
+            "fr" => Some(Language::Fr),
+            "de" => Some(Language::De),
+            "it" => Some(Language::It),
+            "ru" => Some(Language::Ru),
+            "pt" => Some(Language::PtBr),
+            _ => None,
+        }
+    }
+
+    /// 根据系统语言 (Unix 上依次读取 LANGUAGE、LC_ALL、LC_MESSAGES、LANG) 选择语言，默认为英文
+    pub(crate) fn detect() -> Self {
+        sys_locale::get_locale()
+            .as_deref()
+            .and_then(Self::from_locale)
+            .unwrap_or(Language::En)
+    }
 }
 
 impl From<Language> for String {
@@ -99,6 +351,8 @@ impl From<Language> for String {
             Language::Fr => "fr".to_string(),
             Language::De => "de".to_string(),
             Language::It => "it".to_string(),
+            Language::Ru => "ru".to_string(),
+            Language::PtBr => "pt-BR".to_string(),
         }
     }
 }
@@ -125,21 +379,230 @@ enum Commands {
     /// 4. 压缩备份挂载卷到输出目录
     /// 5. 如果设置了 --restart 选项，则重启容器
     Backup {
-        /// 容器名称或 ID
+        /// 容器名称或 ID，可重复指定多个容器 (`-c web -c db`)，每个容器备份为单独的归档文件
         #[arg(short, long)]
-        container: Option<String>,
+        container: Vec<String>,
+
+        /// 备份所有容器，每个容器备份为单独的归档文件 [default: false]
+        ///
+        /// 交互模式下从容器列表中多选；没有挂载卷的容器会被跳过。
+        /// 单个容器失败不会中止其余容器的备份，结束时汇总结果，任一容器失败时以非零状态退出
+        #[arg(long, default_value = "false", conflicts_with_all = ["container", "file"])]
+        all: bool,
+
+        /// 只备份带有该标签的容器，格式为 `key=value`，或只写 `key` 表示只要求标签存在，需与 --all 一起使用
+        ///
+        /// 可重复指定，多个标签需同时满足 (如 `--all --label backup=true --label tier=db`)
+        #[arg(long, value_name = "KEY[=VALUE]", requires = "all", value_parser = docker::parse_label_filter)]
+        label: Vec<String>,
+
+        /// 批量备份 (--all、多个 --container 或 --compose-file) 时同时备份的容器数
+        ///
+        /// 每个容器仍写入各自的归档文件，单个容器失败不会取消其余容器；
+        /// 未指定 --jobs 时由同时备份的容器平分 CPU，大于 1 时进度改为按行输出
+        #[arg(long, value_name = "N", default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        concurrency: u32,
 
-        /// 需要备份的路径 (file/dir)
+        /// 按 compose 项目名查找容器，需与 --service 一起使用，代替 --container
         ///
-        /// 如果设置了该选项，则将只备份该路径下的数据
+        /// 通过 `com.docker.compose.project` 与 `com.docker.compose.service` 标签查找服务的容器，
+        /// 无需猜测 compose 生成的容器名 (如 `myproj-web-1`)；没有容器带有这些标签时按 `<项目>-<服务>` 匹配容器名称
+        #[arg(long, value_name = "NAME", requires = "service", conflicts_with_all = ["container", "all", "compose_file"])]
+        project: Option<String>,
+
+        /// 与 --project 一起使用，compose 服务名
+        #[arg(long, value_name = "NAME", requires = "project")]
+        service: Option<String>,
+
+        /// 备份 docker compose 项目中的所有服务，每个服务备份为单独的归档文件
+        ///
+        /// 项目名按 compose 的规则确定 (COMPOSE_PROJECT_NAME > 文件中的 name > 文件所在目录名)，
+        /// 通过 `com.docker.compose.project` 标签查找项目中的容器。
+        /// 未指定 --output-name 时文件名为 `<项目>_<服务>_<类型>_<日期>_<时间>.tar.xz`
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["container", "all", "file"])]
+        compose_file: Option<PathBuf>,
+
+        /// 需要备份的路径 (file/dir)，可重复指定多个路径
+        ///
+        /// 如果设置了该选项，则将只备份这些路径下的数据
         /// 如果未设置该选项，则将备份容器内的所有 Volumes
         #[arg(short, long)]
-        file: Option<String>,
+        file: Vec<String>,
 
         /// 备份文件输出路径
         #[arg(short, long)]
         #[arg(default_value = "./backup/")]
         output: Option<String>,
+
+        /// 容器仍在运行时拒绝备份 [default: false]
+        ///
+        /// 不会自动停止容器，而是直接中止备份。适用于需要手动停止容器以保证数据一致性的场景
+        #[arg(long, default_value = "false")]
+        skip_if_running: bool,
+
+        /// 并行压缩的最大线程数 [default: 逻辑 CPU 数量]
+        ///
+        /// 多个挂载卷会分别压缩后再合并为同一个归档文件
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        /// 每个卷的 xz 压缩线程数，大于 1 时将数据分块并行压缩 [default: --jobs 中未用于并行压缩多个卷的线程]
+        ///
+        /// 生成的仍是标准 xz 流，任何 xz 工具都可以解压；线程越多占用的内存越多，与压缩级别无关
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        xz_threads: Option<u32>,
+
+        /// 多线程 xz 压缩时每个块的未压缩大小，如 `16M` [default: 12M]
+        ///
+        /// 块越小可并行的块越多，但压缩率略有下降；单线程压缩时不分块
+        #[arg(long, value_name = "SIZE", value_parser = utils::parse_size)]
+        xz_block_size: Option<u64>,
+
+        /// 备份成功后更新指向最新备份的 `<容器名>_latest.tar.xz` 符号链接 [default: false]
+        ///
+        /// Windows 下会写入记录最新备份文件名的 `<容器名>_latest.txt` 指针文件
+        #[arg(long, alias = "output-symlink-latest", default_value = "false")]
+        symlink_latest: bool,
+
+        /// 备份成功后只保留该容器最近的 N 个备份，更早的备份将被删除
+        #[arg(long)]
+        keep_last: Option<usize>,
+
+        /// 备份成功后删除该容器超过 D 天的备份 (按文件名中的时间戳计算)
+        #[arg(long)]
+        keep_days: Option<u64>,
+
+        /// 容器停止后、读取挂载卷前等待的秒数，给文件系统留出刷盘时间 [default: 0]
+        #[arg(long, default_value = "0")]
+        settle_secs: u64,
+
+        /// 读取挂载卷前检查是否仍有进程打开其中的文件 (仅 Linux，尽力而为) [default: false]
+        ///
+        /// 发现占用时中止备份，以免备份到写入一半的数据；无法检查时仅给出警告
+        #[arg(long, default_value = "false")]
+        pre_backup_snapshot_verify: bool,
+
+        /// 备份文件的所有者，格式为 `user[:group]`，可使用名称或数字 ID [default: 调用 sudo 的用户]
+        ///
+        /// 通过 sudo 运行时默认将备份文件交还给调用 sudo 的用户 (SUDO_UID/SUDO_GID)。
+        /// Windows 下忽略该选项
+        #[arg(long, value_name = "USER[:GROUP]", value_parser = utils::FileOwner::parse)]
+        output_owner: Option<utils::FileOwner>,
+
+        /// 增量备份：只打包相对输出目录中该容器上一个备份新增或修改过的文件 [default: false]
+        ///
+        /// 按文件修改时间与大小判断是否变化。恢复时会自动依次解压整条备份链，
+        /// 因此基础备份须与增量备份保存在同一目录
+        #[arg(long, default_value = "false")]
+        incremental: bool,
+
+        /// 读取挂载卷期间通过 cgroup freezer 冻结 (docker pause) 容器，而不是停止容器 [default: false]
+        ///
+        /// 容器内的进程被挂起但不会退出，读取完成后立即解冻，停机时间更短。
+        /// 与停止容器不同，应用没有机会刷新缓冲区或关闭文件，备份得到的是与断电时相同的
+        /// 崩溃一致 (crash-consistent) 数据，只适用于能从这种状态恢复的应用 (如带 WAL 的数据库)
+        #[arg(long, default_value = "false", conflicts_with_all = ["skip_if_running", "pre_backup_snapshot_verify"])]
+        freeze: bool,
+
+        /// 备份时不停止容器，直接读取运行中容器的挂载卷 (热备份) [default: false]
+        ///
+        /// 容器不会停机，但读取期间文件可能发生变化。容器镜像是常见的数据库 (如 postgres、mysql、mongo) 时会给出醒目的警告
+        #[arg(long, default_value = "false", conflicts_with_all = ["skip_if_running", "freeze"])]
+        no_stop: bool,
+
+        /// 备份前分析卷中各子目录的大小，报告占备份总大小比例不低于 RATIO 的子目录 [default: 0.25]
+        ///
+        /// 用于发现缓存等占据大部分空间的目录。仅作提示，不会自动排除；
+        /// 交互模式下可选择将其中的目录在本次备份中排除。RATIO 可写作 0.25 或 25%
+        #[arg(long, value_name = "RATIO", num_args = 0..=1, default_missing_value = "0.25", value_parser = utils::parse_ratio)]
+        warn_large_subtree: Option<f64>,
+
+        /// 备份文件名模板 [default: `{container}_{kind}_{date}_{time}.{ext}`]
+        ///
+        /// 支持的占位符：`{container}` 容器名、`{date}` 日期 (YYYYMMDD)、`{time}` 时间 (HHMMSS)、
+        /// `{kind}` 备份类型 (all/partial/incremental)、`{ext}` 扩展名。未以扩展名结尾时自动补上。
+        /// 必须包含 `{time}`，以免覆盖之前的备份。--keep-last/--keep-days 与 --incremental 通过归档中的
+        /// mapping 识别不符合默认格式的文件名，从目录选择备份时只列出默认格式的文件名
+        #[arg(long, value_name = "TEMPLATE", value_parser = commands::backup::OutputNameTemplate::parse)]
+        output_name: Option<commands::backup::OutputNameTemplate>,
+
+        /// 跳过修改时间早于该时长之前的文件，如 `30d`、`12h`、`1w2d` (单位 s/m/h/d/w)
+        ///
+        /// 与增量备份不同，这是一个绝对的时间截止点，可与 --exclude 同时使用。
+        /// 截止时间会记录在备份中，恢复时会提示该备份并不完整
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration)]
+        exclude_older_than: Option<std::time::Duration>,
+
+        /// 跳过大于该大小的文件，如 `100M`、`2G` (按 1024 进位)
+        ///
+        /// 适用于无法预知路径、但超过一定大小即可丢弃的缓存文件，可与 --exclude 同时使用
+        #[arg(long, value_name = "SIZE", value_parser = utils::parse_size)]
+        exclude_larger_than: Option<u64>,
+
+        /// 在备份文件旁写出 `<backup>.json` 清单 [default: false]
+        ///
+        /// 包含 mapping 信息、文件数、未压缩大小、压缩后大小、压缩比、压缩算法与 SHA-256，
+        /// 监控等工具无需打开归档即可读取备份元数据
+        #[arg(long, default_value = "false")]
+        manifest: bool,
+
+        /// 在归档中写入按卷的索引 [default: false]
+        ///
+        /// 使用 --volume 只恢复部分卷时，可直接跳到这些卷的数据，无需解压整个归档
+        #[arg(long, default_value = "false")]
+        index: bool,
+
+        /// 生成可以直接用 GNU tar 解压的标准 tar 归档 [default: false]
+        ///
+        /// 默认每个卷单独压缩为一个以 tar 结束块结尾的 xz 流，系统 tar 需要 `--ignore-zeros` 才能解压全部内容。
+        /// 启用后只在归档末尾保留结束块，条目使用 ustar 头，长路径使用 pax 扩展头，
+        /// 紧急情况下无需 rdbkp2 即可用 `tar -xJf <备份文件>` 恢复
+        #[arg(long, default_value = "false")]
+        output_format_compat: bool,
+
+        /// 归档的压缩格式 [default: tar.xz]
+        ///
+        /// `tar.xz` 压缩率最高；`tar.gz` 生成标准 gzip 压缩的 tar，不支持 xz 的图形界面解压工具也能打开。
+        /// 恢复时按文件内容识别压缩格式，两种格式都可以直接恢复
+        #[arg(long, value_name = "FORMAT", value_enum, default_value = "tar.xz")]
+        archive_format: ArchiveFormat,
+
+        /// 记录目录条目及每个条目的数字 uid/gid/权限，恢复时还原属主与权限 [default: false]
+        ///
+        /// 只有以 root (或 sudo) 运行恢复时才会通过 chown/chmod 还原；非特权恢复时文件属于当前用户，
+        /// 并给出警告。恢复到命名卷或使用 --into-container 时由 Docker 保留归档中的属主
+        #[arg(long, default_value = "false")]
+        preserve_ownership: bool,
+
+        /// 是否跟随卷中的符号链接，打包链接目标的内容 [default: false]
+        ///
+        /// 默认将符号链接作为链接条目写入归档，指向卷外的链接不会把卷外的数据打包进备份
+        #[arg(
+            long,
+            value_name = "BOOL",
+            default_value = "false",
+            action = clap::ArgAction::Set,
+            value_parser = clap::builder::BoolishValueParser::new()
+        )]
+        follow_symlinks: bool,
+
+        /// 停止容器前在容器内执行的命令 (通过 `sh -c`)，如数据库的刷盘命令
+        ///
+        /// 命令输出会写入日志；命令以非零状态退出时中止备份，指定 --yes 时只给出警告并继续
+        #[arg(long, value_name = "CMD", conflicts_with = "skip_if_running")]
+        pre_backup_exec: Option<String>,
+
+        /// 监听模式：持续运行，挂载卷中的文件变更后自动再备份一次，直到 Ctrl-C [default: false]
+        ///
+        /// 启动时先备份一次；每次变更后等待 --watch-debounce 内不再有新的变更再备份，
+        /// 每个备份写入带时间戳的新文件，可与 --keep-last 一起使用限制保留的备份数。
+        /// 默认会停止容器，建议配合 --no-stop、--freeze 或 --restart 使用
+        #[arg(long, visible_alias = "follow", default_value = "false", conflicts_with_all = ["all", "compose_file"])]
+        watch: bool,
+
+        /// 监听模式下最后一次变更后等待多久再备份，如 `30s`、`5m`
+        #[arg(long, value_name = "DURATION", default_value = "5s", requires = "watch", value_parser = utils::parse_duration)]
+        watch_debounce: std::time::Duration,
     },
 
     /// 恢复 Docker 容器数据
@@ -154,17 +617,178 @@ enum Commands {
         #[arg(short, long)]
         container: Option<String>,
 
-        /// 备份文件路径
+        /// 按 compose 项目名查找容器，需与 --service 一起使用，代替 --container
+        ///
+        /// 通过 `com.docker.compose.project` 与 `com.docker.compose.service` 标签查找服务的容器；
+        /// 没有容器带有这些标签时按 `<项目>-<服务>` 匹配容器名称
+        #[arg(
+            long,
+            value_name = "NAME",
+            requires = "service",
+            conflicts_with = "container"
+        )]
+        project: Option<String>,
+
+        /// 与 --project 一起使用，compose 服务名
+        #[arg(long, value_name = "NAME", requires = "project")]
+        service: Option<String>,
+
+        /// 备份文件路径，`-` 表示从标准输入读取 (如 `aws s3 cp s3://... - | rdbkp2 restore -f -`)
         #[arg(short, long)]
         file: Option<String>,
 
         /// 备份文件恢复输出路径
         #[arg(short, long)]
         output: Option<String>,
+
+        /// 跳过备份文件的 SHA-256 校验 [default: false]
+        ///
+        /// 默认会使用 `<备份文件>.sha256` 或备份内记录的校验和验证备份文件是否损坏
+        #[arg(long, default_value = "false")]
+        no_verify: bool,
+
+        /// 只恢复指定名称的卷，可重复指定 [default: 全部卷]
+        ///
+        /// 交互模式下未指定时，会提示从备份中选择要恢复的卷
+        #[arg(long = "volume", value_name = "NAME")]
+        volumes: Vec<String>,
+
+        /// 解压后按备份内的校验清单逐个校验文件 [default: false]
+        ///
+        /// 用于发现备份在传输或存储过程中产生的损坏，校验失败时中止恢复
+        #[arg(long, default_value = "false")]
+        verify_checksums: bool,
+
+        /// 与 --verify-checksums 一起使用，校验失败时仅警告而不中止恢复 [default: false]
+        #[arg(long, default_value = "false", requires = "verify_checksums")]
+        best_effort: bool,
+
+        /// 批量恢复：为每个存在匹配备份的容器恢复其最新的备份 [default: false]
+        ///
+        /// 备份按其中记录的容器名与容器匹配，主机上不存在的容器会被跳过
+        #[arg(
+            long,
+            default_value = "false",
+            conflicts_with_all = ["container", "project", "file", "output", "volumes", "since", "print_mapping"]
+        )]
+        all: bool,
+
+        /// 与 --all 一起使用，存放备份文件的目录 [default: 配置中的备份目录]
+        #[arg(long, value_name = "DIR", requires = "all")]
+        input_dir: Option<String>,
+
+        /// 通过 Docker API 将卷内容直接写入容器，而不是写入宿主机上的卷路径 [default: false]
+        ///
+        /// 适用于远程 Docker daemon 等无法访问宿主机卷路径的场景，无需管理员权限。
+        /// 解压后的数据以流的形式上传，不在本地暂存
+        #[arg(long, default_value = "false", conflicts_with_all = ["output", "verify_checksums"])]
+        into_container: bool,
+
+        /// 从目录中选择备份文件时，只列出该日期 (含) 之后创建的备份，格式为 YYYY-MM-DD
+        ///
+        /// 日期取自备份文件名中的时间戳
+        #[arg(long, value_name = "DATE")]
+        since: Option<chrono::NaiveDate>,
+
+        /// 解压前打印每个卷的记录路径、将被写入的位置、目标是否存在以及是否需要管理员权限 [default: table]
+        ///
+        /// 只读取备份中的 mapping，不解压任何数据。非交互模式下只打印不恢复；
+        /// 交互模式下打印后继续恢复流程 (仍会提示确认)
+        #[arg(long, value_name = "FORMAT", value_enum, num_args = 0..=1, default_missing_value = "table")]
+        print_mapping: Option<OutputFormat>,
+
+        /// 恢复并重启容器后在容器内执行的命令 (通过 `sh -c`)，如修复权限或数据库迁移
+        ///
+        /// 需要同时指定 --restart，只在重启成功后执行；命令输出会写入日志，非零退出视为恢复失败
+        #[arg(long, value_name = "CMD")]
+        post_restore_exec: Option<String>,
+
+        /// 先解压到临时目录再复制到卷时使用的目录 [default: $TMPDIR 或系统临时目录]
+        ///
+        /// 适用于 /tmp 为空间较小的 tmpfs 的系统。解压前会按备份记录的文件总大小检查可用空间，放不下时直接报错
+        #[arg(long, value_name = "PATH", env = "TMPDIR")]
+        temp_dir: Option<PathBuf>,
+
+        /// 允许恢复到与备份来源不同名的容器 (如重新创建的容器) [default: false]
+        ///
+        /// 默认容器名不一致时拒绝恢复；指定后改为警告，并按容器内的挂载路径将备份中的卷对应到目标容器的卷，
+        /// 目标容器中没有相同挂载路径的卷会被跳过
+        #[arg(long, default_value = "false")]
+        force_container: bool,
+
+        /// 原地恢复前先将卷的当前内容备份到备份目录中 [default: false]
+        ///
+        /// 快照保存为 `<容器名>_pre-restore_<时间戳>.tar.xz`，恢复中途失败时会打印其路径，可以用 `restore -f` 还原
+        #[arg(long, default_value = "false")]
+        snapshot_before_restore: bool,
+
+        /// 恢复到目录 (`--output`) 时的输出布局 [default: flat]
+        ///
+        /// `flat` 按压缩包原样解压；`per-volume` 将每个卷 (包括单文件卷和备份时合并的卷) 都放到 `<输出目录>/<卷名>/` 下
+        #[arg(long, value_enum, default_value = "flat", requires = "output")]
+        layout: RestoreLayout,
+
+        /// 原地恢复时只复制卷中大小或修改时间有变化的文件 [default: false]
+        ///
+        /// 先将备份解压到临时目录，再跳过卷中大小与修改时间都相同的文件，适合反复恢复同一个大卷。
+        /// 需要通过 sudo 复制时使用 `rsync -a`，找不到 rsync 时回退到完整复制。不会删除卷中多出的文件
+        #[arg(long, default_value = "false", conflicts_with_all = ["output", "into_container"])]
+        incremental_restore: bool,
+    },
+
+    /// 检查备份文件是否完整 (不解压到磁盘)
+    ///
+    /// 完整解码备份文件，检查 mapping.toml 与校验和，并列出其中的卷及大小。
+    /// 备份文件被截断或损坏时以非零状态码退出，便于在定时任务中检查备份
+    Verify {
+        /// 备份文件路径
+        #[arg(short, long)]
+        file: String,
+    },
+
+    /// 清理备份目录
+    ///
+    /// 按容器列出备份目录中的备份及其大小与时间，按 --older-than/--keep 筛选或在交互模式下手动选择要删除的备份。
+    /// 删除前需要确认 (--yes 跳过)，仍被保留的增量备份所依赖的基础备份不会被删除
+    Prune {
+        /// 存放备份文件的目录 [default: 配置中的备份目录]
+        #[arg(long, value_name = "DIR")]
+        dir: Option<String>,
+
+        /// 删除早于该时长之前创建的备份，如 `30d`、`12h`、`1w2d` (单位 s/m/h/d/w)
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration)]
+        older_than: Option<std::time::Duration>,
+
+        /// 每个容器只保留最近的 N 个备份
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+    },
+
+    /// 汇总备份目录中每个容器的备份情况
+    ///
+    /// 按每个备份中记录的容器名分组，列出备份数量、最近一次备份的时间与占用的磁盘空间
+    Status {
+        /// 存放备份文件的目录 [default: 配置中的备份目录]
+        #[arg(long, value_name = "DIR")]
+        dir: Option<String>,
+
+        /// 输出格式，json 格式便于脚本解析 [default: table]
+        #[arg(long, default_value = "table", value_enum)]
+        format: OutputFormat,
     },
 
     /// 列出可用的 Docker 容器
-    List,
+    List {
+        /// 输出格式，json 格式便于脚本解析 [default: table]
+        #[arg(long, default_value = "table", value_enum)]
+        format: OutputFormat,
+
+        /// 只列出带有该标签的容器，格式为 `key=value` 或 `key` (只要求标签存在)
+        ///
+        /// 可重复指定，多个标签需同时满足
+        #[arg(long, value_name = "KEY[=VALUE]", value_parser = docker::parse_label_filter)]
+        label: Vec<String>,
+    },
 
     /// 生成命令行补全脚本
     Completions {
@@ -176,7 +800,13 @@ enum Commands {
     /// 检查更新
     ///
     /// 检查是否有新版本可用，如果有则提示更新方法
-    Update,
+    Update {
+        /// 从 GitHub releases 下载当前平台的预编译二进制，校验 SHA-256 后替换当前程序 [default: false]
+        ///
+        /// 无需 Rust 工具链；程序所在目录需要可写 (如安装在 /usr/local/bin 时使用 sudo)
+        #[arg(long, default_value = "false")]
+        apply: bool,
+    },
 
     /// 完全卸载
     ///
@@ -187,6 +817,59 @@ enum Commands {
         #[command(subcommand)]
         action: LinkActions,
     },
+
+    /// 导出或导入用户设置 (备份目录、排除模式、语言、超时时间与容器引擎等)，便于在多台机器上使用相同的设置
+    Config {
+        #[command(subcommand)]
+        action: ConfigActions,
+    },
+
+    /// 调试工具，用于诊断备份文件的问题
+    #[command(hide = true)]
+    Debug {
+        #[command(subcommand)]
+        action: DebugActions,
+    },
+}
+
+/// 调试操作
+///
+/// 示例：
+/// ```bash
+/// rdbkp2 debug dump-headers ./backup/app_all_20250101_120000.tar.xz --format json
+/// ```
+#[derive(Subcommand)]
+enum DebugActions {
+    /// 打印备份文件中每个 tar 条目的原始头部信息 (不解压)
+    DumpHeaders {
+        /// 备份文件路径
+        archive: String,
+
+        /// 输出格式 [default: table]
+        #[arg(long, default_value = "table", value_enum)]
+        format: OutputFormat,
+    },
+}
+
+/// 设置操作
+///
+/// 示例：
+/// ```bash
+/// rdbkp2 config export > settings.toml
+/// rdbkp2 config import settings.toml
+/// ```
+#[derive(Subcommand)]
+enum ConfigActions {
+    /// 以 TOML 格式将当前生效的设置 (配置文件与命令行参数合并后) 输出到 stdout
+    Export,
+
+    /// 校验设置文件并写入用户配置文件，文件中未出现的设置恢复为默认值
+    ///
+    /// 文件中出现未知的键或无效的值时拒绝导入，不修改现有的配置文件
+    Import {
+        /// 设置文件路径 (如 `config export` 的输出)
+        file: PathBuf,
+    },
 }
 
 /// 链接操作
@@ -196,85 +879,294 @@ enum Commands {
 /// 示例：
 /// ```bash
 /// rdbkp2 link install
+/// rdbkp2 link install --path ~/.local/bin/rdbkp2
 /// rdbkp2 link uninstall
 /// ```
 #[derive(Subcommand)]
 enum LinkActions {
     /// 安装软连接链接 sudo ln -s $(where rdbkp2) /usr/local/bin/rdbkp2
-    Install,
+    Install {
+        /// 链接的位置 [default: /usr/local/bin/rdbkp2]
+        ///
+        /// 所在目录当前用户可写时 (如 ~/.local/bin) 无需管理员权限，目录不在 PATH 中时会给出提示
+        #[arg(long, value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
 
     /// 卸载软连接链接 sudo rm /usr/local/bin/rdbkp2
-    Uninstall,
+    Uninstall {
+        /// 要删除的链接的位置，应与安装时的 --path 相同 [default: /usr/local/bin/rdbkp2]
+        #[arg(long, value_name = "PATH")]
+        path: Option<PathBuf>,
+    },
 }
 
-#[instrument(level = "INFO")]
-fn init_config(
-    timeout_secs: u64,
-    interactive: bool,
-    restart: bool,
-    verbose: bool,
-    yes: bool,
-    exclude: String,
-    language: String,
-) -> Result<()> {
-    let cfg = config::Config {
-        timeout_secs,
-        interactive,
-        restart,
-        verbose,
-        yes,
-        exclude,
-        language,
-        ..config::Config::default()
+/// 参数是否由用户在命令行 (或环境变量) 中显式指定，而非取自默认值
+fn is_explicit(matches: &ArgMatches, id: &str) -> bool {
+    matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+/// 应用 `--profile` 指定的参数组合：全局设置写入 `file`，`backup` 的参数写入 `cli`，
+/// 命令行中显式指定的参数保持不变
+fn apply_profile(cli: &mut Cli, matches: &ArgMatches, file: &mut config::Config) -> Result<()> {
+    let Some(name) = cli.profile.as_deref() else {
+        return Ok(());
     };
-    config::Config::init(cfg)?;
+    let profile = file.profile(name)?.clone();
+    info!(name, ?profile, "Applying profile");
+
+    if let Some(exclude) = profile.exclude {
+        file.exclude = exclude;
+    }
+    if let Commands::Backup {
+        output,
+        archive_format,
+        keep_last,
+        ..
+    } = &mut cli.command
+        && let Some(("backup", matches)) = matches.subcommand()
+    {
+        let explicit = |id: &str| is_explicit(matches, id);
+        if let Some(profile_output) = profile.output.filter(|_| !explicit("output")) {
+            *output = Some(profile_output);
+        }
+        if let Some(format) = profile
+            .archive_format
+            .filter(|_| !explicit("archive_format"))
+        {
+            *archive_format = format.into();
+        }
+        if !explicit("keep_last") {
+            *keep_last = profile.keep_last.or(*keep_last);
+        }
+    }
     Ok(())
 }
 
+/// 在用户配置文件的基础上应用命令行参数，显式指定的参数优先于配置文件
+fn merge_config(cli: &Cli, matches: &ArgMatches, file: config::Config) -> config::Config {
+    let explicit = |id: &str| is_explicit(matches, id);
+    config::Config {
+        timeout_secs: if explicit("timeout") {
+            cli.timeout
+        } else {
+            file.timeout_secs
+        },
+        exclude: if explicit("exclude") {
+            cli.exclude.clone()
+        } else {
+            file.exclude
+        },
+        language: cli.language.clone().map(Into::into).or(file.language),
+        exclude_from: cli.exclude_from.clone(),
+        interactive: cli.interactive,
+        restart: cli.restart,
+        wait_healthy: cli.wait_healthy,
+        verbose: cli.verbose > 0,
+        yes: cli.yes,
+        no_locale_format: cli.no_locale_format,
+        no_canonicalize: cli.no_canonicalize,
+        dry_run: cli.dry_run,
+        no_progress: cli.no_progress,
+        io_limit: cli.io_limit.or(file.io_limit),
+        docker: config::DockerConfig {
+            host: cli.docker_host.clone().unwrap_or(file.docker.host),
+            tls: if explicit("docker_tls") {
+                cli.docker_tls
+            } else {
+                file.docker.tls
+            },
+            cert_path: cli.docker_cert_path.clone().or(file.docker.cert_path),
+            engine: if explicit("container_engine") {
+                cli.container_engine.into()
+            } else {
+                file.docker.engine
+            },
+            stop_signal: cli.stop_signal.clone().or(file.docker.stop_signal),
+            retries: cli.retries.or(file.docker.retries),
+            retry_delay_ms: cli.retry_delay_ms.or(file.docker.retry_delay_ms),
+        },
+        ..file
+    }
+}
+
 #[instrument(level = "INFO")]
 pub fn init_log(log_level: Level) -> Result<()> {
-    // 初始化日志
-    let mut log_fmt = fmt()
-        .with_env_filter(
-            EnvFilter::builder()
-                .with_default_directive(log_level.into())
-                .from_env_lossy(),
-        )
-        .with_level(true);
-
-    #[cfg(debug_assertions)]
-    {
-        log_fmt = log_fmt
-            .with_target(true)
-            .with_thread_ids(true)
-            .with_line_number(true)
-            .with_file(true);
+    init_log_with_format(log_level, LogFormat::Text)
+}
+
+/// 按指定格式初始化全局日志
+pub fn init_log_with_format(log_level: Level, format: LogFormat) -> Result<()> {
+    init_log_layers(log_level, format, None)
+}
+
+/// `-v` 的次数对应的日志级别
+fn verbosity_level(count: u8) -> Level {
+    match count {
+        0 => Level::ERROR,
+        1 => Level::WARN,
+        2 => Level::INFO,
+        3 => Level::DEBUG,
+        _ => Level::TRACE,
     }
+}
 
-    log_fmt.init();
-    Ok(())
+/// 按天轮转的日志文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogFile {
+    /// 日志文件的路径，实际文件名会追加日期
+    path: PathBuf,
+    /// 保留的文件个数
+    keep: usize,
 }
 
-#[instrument(level = "INFO")]
-fn init_docker_client(timeout_secs: u64) -> Result<()> {
-    docker::DockerClient::init(timeout_secs)?;
+impl LogFile {
+    fn new(path: Option<PathBuf>, keep: u16) -> Self {
+        let path = path.unwrap_or_else(|| {
+            dirs::data_local_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("rdbkp2")
+                .join("logs")
+                .join("rdbkp2.log")
+        });
+        Self {
+            path,
+            keep: keep.into(),
+        }
+    }
+
+    /// 文件日志的级别：至少为 INFO，终端的级别更详细时与终端相同
+    fn level(terminal: Level) -> Level {
+        terminal.max(Level::INFO)
+    }
+
+    fn appender(&self) -> Result<RollingFileAppender> {
+        let dir = self
+            .path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let prefix = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "rdbkp2.log".to_string());
+        std::fs::create_dir_all(dir)
+            .and_then(|_| {
+                RollingFileAppender::builder()
+                    .rotation(Rotation::DAILY)
+                    .filename_prefix(prefix)
+                    .max_log_files(self.keep)
+                    .build(dir)
+                    .map_err(io::Error::other)
+            })
+            .map_err(|e| {
+                anyhow::anyhow!(t!(
+                    "config.log_file_open_failed",
+                    "path" = self.path.display(),
+                    "error" = e
+                ))
+            })
+    }
+}
+
+fn env_filter(level: Level) -> EnvFilter {
+    EnvFilter::builder()
+        .with_default_directive(level.into())
+        .from_env_lossy()
+}
+
+fn fmt_layer<W>(writer: W, format: LogFormat, ansi: bool) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_level(true)
+        .with_target(true)
+        .with_thread_ids(cfg!(debug_assertions))
+        .with_line_number(cfg!(debug_assertions))
+        .with_file(cfg!(debug_assertions))
+        .with_writer(writer);
+    // 写入文件时不输出 ANSI 颜色，终端保持默认行为
+    let layer = if ansi { layer } else { layer.with_ansi(false) };
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
+fn init_log_layers(log_level: Level, format: LogFormat, log_file: Option<&LogFile>) -> Result<()> {
+    // 日志输出到 stderr，保证 stdout 只包含命令结果 (如 `list --format json`)
+    let mut layers = vec![
+        fmt_layer(io::stderr, format, true)
+            .with_filter(env_filter(log_level))
+            .boxed(),
+    ];
+    if let Some(log_file) = log_file {
+        layers.push(
+            fmt_layer(log_file.appender()?, format, false)
+                .with_filter(env_filter(LogFile::level(log_level)))
+                .boxed(),
+        );
+    }
+    tracing_subscriber::registry().with(layers).init();
     Ok(())
 }
 
+/// 错误已经以 JSON 日志的形式输出，main 不需要再打印
+#[derive(Debug)]
+pub struct ErrorLogged;
+
+impl std::fmt::Display for ErrorLogged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("error already logged")
+    }
+}
+
+impl std::error::Error for ErrorLogged {}
+
 #[instrument(level = "INFO")]
 pub async fn run() -> Result<()> {
     info!("Starting Docker container backup tool");
 
     // 解析命令行参数
-    let cli = Cli::parse();
-    let interactive = cli.interactive;
-    let timeout = cli.timeout;
-    let restart = cli.restart;
-    let exclude = cli.exclude;
-    let yes = cli.yes;
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     let verbose = cli.verbose;
-    let language: String = cli.language.into();
-    rust_i18n::set_locale(&language);
+    let log_format = cli.log_format;
+    let log_file = cli
+        .log_file
+        .clone()
+        .map(|path| LogFile::new(path, cli.log_keep));
+    let writes_data = matches!(
+        cli.command,
+        Commands::Backup { .. } | Commands::Restore { .. }
+    );
+    let update_check = !cli.no_update_check && writes_data;
+
+    // 读取用户配置文件，命令行中显式指定的参数优先
+    let (mut file_config, config_error) = match config::load_user_config() {
+        Ok(config) => (config, None),
+        Err(e) => (config::Config::default(), Some(e)),
+    };
+    // profile 不存在时的错误信息同样使用配置的语言，在读取 profile 前先设置语言
+    rust_i18n::set_locale(
+        &cli.language
+            .clone()
+            .map_or_else(|| file_config.effective_language(), Into::into),
+    );
+    // 配置文件读取失败时先打印读取失败的原因，再返回 profile 的错误
+    let profile_result = apply_profile(&mut cli, &matches, &mut file_config);
+    let config = merge_config(&cli, &matches, file_config);
+    rust_i18n::set_locale(&config.effective_language());
     // #[cfg(debug_assertions)]
     // {
     //     println!("1. langugage:{}", t!("language"));
@@ -284,51 +1176,219 @@ pub async fn run() -> Result<()> {
     // }
 
     // 初始化全局 runtime 配置
-    init_config(
-        timeout,
-        interactive,
-        restart,
-        verbose,
-        yes,
-        exclude,
-        language,
-    )?;
+    config::Config::init(config)?;
 
     // 设置日志级别，初始化全局日志
-    let log_level = if verbose { Level::DEBUG } else { Level::ERROR };
-    init_log(log_level)?;
+    let log_level = verbosity_level(verbose);
+    init_log_layers(log_level, log_format, log_file.as_ref())?;
+    if let Some(e) = config_error {
+        let message = t!("config.user_config_load_failed", "error" = e);
+        // JSON 日志模式下 stderr 只能包含 JSON，提示改为通过日志输出
+        match log_format {
+            LogFormat::Text => eprintln!("{message}"),
+            LogFormat::Json => tracing::error!("{}", message),
+        }
+    }
+    profile_result?;
 
-    // 初始化全局 docker client
-    init_docker_client(timeout)?;
+    // 备份与恢复被 Ctrl-C 中断时清理未完成的归档并重启被停止的容器
+    if writes_data {
+        commands::interrupt::install();
+    }
 
+    // docker client 在首次使用时才初始化，不需要 Docker 的子命令在 daemon 未运行时同样可用
     // 根据子命令执行相应的操作
-    do_action(cli.command).await?;
+    let result = do_action(cli.command).await;
+    if let (Err(e), LogFormat::Json) = (&result, log_format) {
+        // 由 main 打印的 `Error: ...` 不是 JSON，这里改为记录一条错误日志，main 只设置退出码
+        tracing::error!(error = format!("{e:#}"), "Command failed");
+        return Err(ErrorLogged.into());
+    }
+    result?;
+    if update_check {
+        commands::lifecycle::notify_if_outdated().await;
+    }
 
     info!("Operation completed successfully");
     Ok(())
 }
 
+/// clap 保证 --project 与 --service 同时出现
+fn compose_service(
+    project: Option<String>,
+    service: Option<String>,
+) -> Option<commands::container::ComposeService> {
+    Some(commands::container::ComposeService {
+        project: project?,
+        service: service?,
+    })
+}
+
 async fn do_action(action: Commands) -> Result<()> {
     match action {
         Commands::Backup {
             container,
+            all,
+            label,
+            concurrency,
+            project,
+            service,
+            compose_file,
             file,
             output,
+            skip_if_running,
+            jobs,
+            xz_threads,
+            xz_block_size,
+            symlink_latest,
+            keep_last,
+            keep_days,
+            settle_secs,
+            pre_backup_snapshot_verify,
+            output_owner,
+            incremental,
+            freeze,
+            no_stop,
+            warn_large_subtree,
+            output_name,
+            exclude_older_than,
+            exclude_larger_than,
+            manifest,
+            index,
+            output_format_compat,
+            archive_format,
+            preserve_ownership,
+            follow_symlinks,
+            pre_backup_exec,
+            watch,
+            watch_debounce,
         } => {
             info!(?container, ?file, ?output, "Executing backup command");
-            commands::backup(container, file, output).await?;
+            let options = commands::backup::BackupOptions {
+                skip_if_running,
+                jobs,
+                xz_threads,
+                xz_block_size,
+                symlink_latest,
+                retention: commands::retention::RetentionPolicy {
+                    keep_last,
+                    keep_days,
+                },
+                settle_secs,
+                snapshot_verify: pre_backup_snapshot_verify,
+                output_owner,
+                incremental,
+                freeze,
+                no_stop,
+                warn_large_subtree,
+                output_name,
+                exclude_older_than,
+                exclude_larger_than,
+                manifest,
+                index,
+                output_format_compat,
+                archive_format: archive_format.into(),
+                preserve_ownership,
+                follow_symlinks,
+                pre_backup_exec,
+                compose_service: compose_service(project, service),
+                concurrency: concurrency as usize,
+            };
+            if compose_file.is_none() && (all || container.len() > 1) && !file.is_empty() {
+                log_bail!("ERROR", "{}", t!("commands.file_requires_single_container"));
+            }
+            if watch && container.len() > 1 {
+                log_bail!(
+                    "ERROR",
+                    "{}",
+                    t!("commands.watch_requires_single_container")
+                );
+            }
+            let client = DockerClient::connected().await?;
+            if watch {
+                let container = container.into_iter().next();
+                commands::backup::watch(&client, container, file, output, options, watch_debounce)
+                    .await?;
+            } else if let Some(compose_file) = compose_file {
+                commands::backup::backup_compose(&client, compose_file, output, options).await?;
+            } else if all || container.len() > 1 {
+                commands::backup::backup_many(&client, container, all, &label, output, options)
+                    .await?;
+            } else {
+                commands::backup(&client, container.into_iter().next(), file, output, options)
+                    .await?;
+            }
         }
         Commands::Restore {
             container,
+            project,
+            service,
             file,
             output,
+            no_verify,
+            volumes,
+            verify_checksums,
+            best_effort,
+            all,
+            input_dir,
+            since,
+            into_container,
+            print_mapping,
+            post_restore_exec,
+            temp_dir,
+            force_container,
+            snapshot_before_restore,
+            layout,
+            incremental_restore,
         } => {
-            info!(?container, ?file, ?output, "Executing restore command");
-            commands::restore(container, file, output).await?;
+            info!(?container, ?file, ?output, all, "Executing restore command");
+            let options = commands::restore::RestoreOptions {
+                no_verify,
+                volumes,
+                verify_checksums,
+                best_effort,
+                since,
+                into_container,
+                print_mapping: print_mapping.map(Into::into),
+                post_restore_exec,
+                temp_dir,
+                force_container,
+                snapshot_before_restore,
+                layout: layout.into(),
+                compose_service: compose_service(project, service),
+                incremental_restore,
+            };
+            commands::restore::require_privileges(&options)?;
+            let client = DockerClient::connected().await?;
+            if all {
+                commands::restore::restore_all(&client, input_dir, options).await?;
+            } else {
+                commands::restore(&client, container, file, output, options).await?;
+            }
         }
-        Commands::List => {
-            info!("Executing list command");
-            commands::list_containers().await?;
+        Commands::Verify { file } => {
+            info!(?file, "Executing verify command");
+            commands::verify::verify(file).await?;
+        }
+        Commands::Prune {
+            dir,
+            older_than,
+            keep,
+        } => {
+            info!(?dir, ?older_than, ?keep, "Executing prune command");
+            commands::retention::prune_directory(
+                dir,
+                commands::retention::PruneFilter { older_than, keep },
+            )?;
+        }
+        Commands::Status { dir, format } => {
+            info!(?dir, ?format, "Executing status command");
+            commands::status::backup_status(dir, format.into())?;
+        }
+        Commands::List { format, label } => {
+            info!(?format, ?label, "Executing list command");
+            let client = DockerClient::connected().await?;
+            commands::list_containers(&client, format.into(), &label).await?;
         }
         Commands::Completions { shell } => {
             info!(?shell, "Generating shell completions");
@@ -337,22 +1397,38 @@ async fn do_action(action: Commands) -> Result<()> {
             let generator: clap_complete::aot::Shell = shell.into();
             clap_complete::generate(generator, &mut cmd, name, &mut io::stdout());
         }
-        Commands::Update => {
-            info!("Checking for updates");
-            commands::lifecycle::check_update().await?;
+        Commands::Update { apply } => {
+            info!(apply, "Checking for updates");
+            commands::lifecycle::check_update(apply).await?;
         }
         Commands::Uninstall => {
             info!("Executing uninstall command");
             commands::lifecycle::uninstall().await?;
         }
         Commands::Link { action } => match action {
-            LinkActions::Install => {
-                info!("Executing soft-link install command");
-                commands::symbollink::create_symbollink()?;
+            LinkActions::Install { path } => {
+                info!(?path, "Executing soft-link install command");
+                commands::symbollink::create_symbollink(path)?;
+            }
+            LinkActions::Uninstall { path } => {
+                info!(?path, "Executing soft-link uninstall command");
+                commands::symbollink::remove_symbollink(path)?;
             }
-            LinkActions::Uninstall => {
-                info!("Executing soft-link uninstall command");
-                commands::symbollink::remove_symbollink()?;
+        },
+        Commands::Config { action } => match action {
+            ConfigActions::Export => {
+                info!("Executing config export command");
+                commands::settings::export_settings()?;
+            }
+            ConfigActions::Import { file } => {
+                info!(?file, "Executing config import command");
+                commands::settings::import_settings(&file)?;
+            }
+        },
+        Commands::Debug { action } => match action {
+            DebugActions::DumpHeaders { archive, format } => {
+                info!(?archive, ?format, "Executing debug dump-headers command");
+                commands::debug::dump_headers(&archive, format.into())?;
             }
         },
     }
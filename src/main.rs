@@ -1,4 +1,14 @@
+use std::process::ExitCode;
+
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    rdbkp2::run().await
+async fn main() -> ExitCode {
+    match rdbkp2::run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        // JSON 日志模式下错误已经记录过了
+        Err(e) if e.is::<rdbkp2::ErrorLogged>() => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::FAILURE
+        }
+    }
 }
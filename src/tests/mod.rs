@@ -154,3 +154,379 @@ async fn test_backup_restore_workflow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn log_file_flag_takes_an_optional_path() {
+    use crate::{Cli, Commands, LogFile};
+    use clap::Parser;
+    use tracing::Level;
+
+    // 路径需要用 `=` 指定，不会把子命令当作路径
+    let cli = Cli::parse_from(["rdbkp2", "--log-file", "list"]);
+    assert_eq!(cli.log_file, Some(None));
+    assert!(matches!(cli.command, Commands::List { .. }));
+    let log_file = LogFile::new(None, cli.log_keep);
+    assert!(log_file.path.ends_with("rdbkp2/logs/rdbkp2.log"));
+    assert_eq!(log_file.keep, 7);
+
+    let cli = Cli::parse_from([
+        "rdbkp2",
+        "list",
+        "--log-file=/var/log/b.log",
+        "--log-keep",
+        "3",
+    ]);
+    assert_eq!(cli.log_file, Some(Some(PathBuf::from("/var/log/b.log"))));
+    assert_eq!(cli.log_keep, 3);
+    assert!(Cli::try_parse_from(["rdbkp2", "list", "--log-keep", "0"]).is_err());
+
+    assert_eq!(LogFile::level(Level::ERROR), Level::INFO);
+    assert_eq!(LogFile::level(Level::DEBUG), Level::DEBUG);
+}
+
+#[test]
+fn wait_healthy_flag_requires_restart() {
+    use crate::Cli;
+    use clap::Parser;
+    use std::time::Duration;
+
+    let cli = Cli::parse_from(["rdbkp2", "backup", "-r", "--wait-healthy"]);
+    assert_eq!(cli.wait_healthy, Some(Duration::from_secs(60)));
+    let cli = Cli::parse_from(["rdbkp2", "--restart", "--wait-healthy=2m", "restore"]);
+    assert_eq!(cli.wait_healthy, Some(Duration::from_secs(120)));
+    let cli = Cli::parse_from(["rdbkp2", "backup"]);
+    assert_eq!(cli.wait_healthy, None);
+
+    assert!(Cli::try_parse_from(["rdbkp2", "backup", "--wait-healthy"]).is_err());
+    assert!(Cli::try_parse_from(["rdbkp2", "backup", "-r", "--wait-healthy=soon"]).is_err());
+}
+
+#[test]
+fn label_filters_are_repeatable_and_require_all() {
+    use crate::{Cli, Commands};
+    use clap::Parser;
+
+    let cli = Cli::parse_from([
+        "rdbkp2",
+        "backup",
+        "--all",
+        "--label",
+        "backup=true",
+        "--label",
+        "tier",
+    ]);
+    let Commands::Backup { label, .. } = cli.command else {
+        panic!("expected backup command");
+    };
+    assert_eq!(label, ["backup=true", "tier"]);
+
+    assert!(Cli::try_parse_from(["rdbkp2", "backup", "--label", "backup=true"]).is_err());
+    assert!(Cli::try_parse_from(["rdbkp2", "list", "--label", "=true"]).is_err());
+    assert!(Cli::try_parse_from(["rdbkp2", "list", "--label", "backup=true"]).is_ok());
+}
+
+#[test]
+fn pre_backup_exec_conflicts_with_skip_if_running() {
+    use crate::Cli;
+    use clap::Parser;
+
+    // 容器必须已经停止时无法在容器内执行命令，不能静默忽略该命令
+    let args = [
+        "rdbkp2",
+        "backup",
+        "--skip-if-running",
+        "--pre-backup-exec",
+        "sync",
+    ];
+    assert!(Cli::try_parse_from(args).is_err());
+    assert!(Cli::try_parse_from(["rdbkp2", "backup", "--pre-backup-exec", "sync"]).is_ok());
+}
+
+#[test]
+fn repeated_verbose_flags_raise_the_log_level() {
+    use crate::{Cli, verbosity_level};
+    use clap::Parser;
+    use tracing::Level;
+
+    let levels = [
+        (vec!["rdbkp2", "list"], Level::ERROR),
+        (vec!["rdbkp2", "list", "-v"], Level::WARN),
+        (vec!["rdbkp2", "-vv", "list"], Level::INFO),
+        (
+            vec!["rdbkp2", "list", "-v", "--verbose", "-v"],
+            Level::DEBUG,
+        ),
+        (vec!["rdbkp2", "list", "-vvvvv"], Level::TRACE),
+    ];
+    for (args, level) in levels {
+        let cli = Cli::parse_from(&args);
+        assert_eq!(verbosity_level(cli.verbose), level, "{args:?}");
+    }
+}
+
+#[test]
+fn explicit_cli_flags_override_user_config() {
+    use crate::{Cli, config::Config, merge_config};
+    use clap::{CommandFactory, FromArgMatches};
+
+    let file = Config {
+        timeout_secs: 5,
+        exclude: "cache".into(),
+        language: Some("en".into()),
+        backup_dir: PathBuf::from("/srv/backups"),
+        ..Config::default()
+    };
+
+    // 未显式指定的参数使用配置文件中的值
+    let matches = Cli::command().get_matches_from(["rdbkp2", "list"]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    let config = merge_config(&cli, &matches, file.clone());
+    assert_eq!(config.timeout_secs, 5);
+    assert_eq!(config.exclude, "cache");
+    assert_eq!(config.language.as_deref(), Some("en"));
+    assert_eq!(config.backup_dir, PathBuf::from("/srv/backups"));
+
+    // 子命令之后指定的全局参数同样优先
+    let matches = Cli::command().get_matches_from(["rdbkp2", "list", "-t", "60", "-l", "ja"]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    let config = merge_config(&cli, &matches, file);
+    assert_eq!(config.timeout_secs, 60);
+    assert_eq!(config.language.as_deref(), Some("ja"));
+    assert_eq!(config.exclude, "cache");
+}
+
+#[test]
+fn docker_connection_flags_override_user_config() {
+    use crate::config::{Config, DockerConfig};
+    use crate::{Cli, merge_config};
+    use clap::{CommandFactory, FromArgMatches};
+
+    let file = Config {
+        docker: DockerConfig {
+            host: "tcp://10.0.0.5:2376".into(),
+            tls: true,
+            cert_path: Some(PathBuf::from("/etc/docker/certs")),
+            ..DockerConfig::default()
+        },
+        ..Config::default()
+    };
+
+    // 只在配置文件中设置时使用配置文件中的连接参数
+    let matches = Cli::command().get_matches_from(["rdbkp2", "list"]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    let config = merge_config(&cli, &matches, file.clone());
+    assert_eq!(config.docker.host, "tcp://10.0.0.5:2376");
+    assert!(config.docker.tls);
+    assert_eq!(
+        config.docker.cert_path,
+        Some(PathBuf::from("/etc/docker/certs"))
+    );
+
+    // 命令行参数优先于配置文件
+    let file = Config {
+        docker: DockerConfig {
+            tls: false,
+            ..file.docker
+        },
+        ..file
+    };
+    let matches = Cli::command().get_matches_from([
+        "rdbkp2",
+        "list",
+        "--docker-host",
+        "unix:///run/user/1000/docker.sock",
+        "--docker-tls",
+        "--docker-cert-path",
+        "/home/me/.docker",
+    ]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    let config = merge_config(&cli, &matches, file);
+    assert_eq!(config.docker.host, "unix:///run/user/1000/docker.sock");
+    assert!(config.docker.tls);
+    assert_eq!(
+        config.docker.cert_path,
+        Some(PathBuf::from("/home/me/.docker"))
+    );
+}
+
+#[test]
+fn profile_values_apply_unless_overridden() {
+    use crate::config::ProfileConfig;
+    use crate::{ArchiveFormat, Cli, Commands, apply_profile, config::Config, merge_config};
+    use clap::{CommandFactory, FromArgMatches};
+
+    let mut file = Config {
+        exclude: "cache".into(),
+        ..Config::default()
+    };
+    file.profiles.insert(
+        "nightly".into(),
+        ProfileConfig {
+            exclude: Some("logs".into()),
+            output: Some("/srv/nightly".into()),
+            archive_format: Some(crate::utils::ArchiveFormat::TarGz),
+            keep_last: Some(7),
+        },
+    );
+    let parse = |args: &[&str], file: &Config| {
+        let matches = Cli::command().get_matches_from(args);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        let mut file = file.clone();
+        apply_profile(&mut cli, &matches, &mut file).map(|()| {
+            let config = merge_config(&cli, &matches, file);
+            (cli.command, config)
+        })
+    };
+
+    // profile 中的值优先于配置文件与默认值
+    let (command, config) = parse(&["rdbkp2", "backup", "--profile", "nightly"], &file).unwrap();
+    assert_eq!(config.exclude, "logs");
+    let Commands::Backup {
+        output,
+        archive_format,
+        keep_last,
+        ..
+    } = command
+    else {
+        unreachable!()
+    };
+    assert_eq!(output.as_deref(), Some("/srv/nightly"));
+    assert!(matches!(archive_format, ArchiveFormat::TarGz));
+    assert_eq!(keep_last, Some(7));
+
+    // 命令行显式指定的参数优先于 profile
+    let args = [
+        "rdbkp2",
+        "backup",
+        "--profile",
+        "nightly",
+        "-o",
+        "./out",
+        "-e",
+        "tmp",
+        "--keep-last",
+        "2",
+        "--archive-format",
+        "tar.xz",
+    ];
+    let (command, config) = parse(&args, &file).unwrap();
+    assert_eq!(config.exclude, "tmp");
+    let Commands::Backup {
+        output,
+        archive_format,
+        keep_last,
+        ..
+    } = command
+    else {
+        unreachable!()
+    };
+    assert_eq!(output.as_deref(), Some("./out"));
+    assert!(matches!(archive_format, ArchiveFormat::TarXz));
+    assert_eq!(keep_last, Some(2));
+
+    // 不存在的 profile 报错
+    let Err(error) = parse(&["rdbkp2", "backup", "--profile", "weekly"], &file) else {
+        panic!("missing profile should be rejected");
+    };
+    assert!(error.to_string().contains("weekly"));
+    assert!(error.to_string().contains("nightly"));
+}
+
+#[test]
+fn maps_system_locale_to_language() {
+    use crate::Language;
+
+    assert_eq!(Language::from_locale("en_US.UTF-8"), Some(Language::En));
+    assert_eq!(Language::from_locale("ja_JP"), Some(Language::Ja));
+    assert_eq!(Language::from_locale("zh-Hans-CN"), Some(Language::Zh));
+    assert_eq!(Language::from_locale("de_DE@euro"), Some(Language::De));
+    assert_eq!(Language::from_locale("ru_RU.UTF-8"), Some(Language::Ru));
+    assert_eq!(Language::from_locale("pt_BR"), Some(Language::PtBr));
+    assert_eq!(Language::from_locale("C"), None);
+    assert_eq!(Language::from_locale("xx_YY"), None);
+}
+
+#[test]
+fn language_flag_is_optional() {
+    use crate::{Cli, Language};
+    use clap::Parser;
+
+    assert_eq!(Cli::parse_from(["rdbkp2", "list"]).language, None);
+    assert_eq!(
+        Cli::parse_from(["rdbkp2", "list", "-l", "ja"]).language,
+        Some(Language::Ja)
+    );
+    assert_eq!(
+        Cli::parse_from(["rdbkp2", "list", "-l", "pt-BR"]).language,
+        Some(Language::PtBr)
+    );
+}
+
+#[test]
+fn every_language_translates_every_referenced_key() {
+    use crate::Language;
+    use clap::ValueEnum;
+
+    // rust-i18n 会合并 locales 目录下的所有文件，一个键的各语言翻译可以分布在不同文件中
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let locales: Vec<serde_yaml::Value> = std::fs::read_dir(root.join("locales"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        })
+        .map(|path| serde_yaml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .collect();
+    let translated = |key: &str, language: &str| {
+        locales.iter().any(|locale| {
+            key.split('.')
+                .try_fold(locale, |node, part| node.get(part))
+                .and_then(|entry| entry.get(language))
+                .is_some()
+        })
+    };
+    let languages: Vec<String> = Language::value_variants()
+        .iter()
+        .cloned()
+        .map(String::from)
+        .collect();
+
+    let mut missing = std::collections::BTreeSet::new();
+    for entry in walkdir::WalkDir::new(root.join("src")) {
+        let entry = entry.unwrap();
+        if entry.path().extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        let content = std::fs::read_to_string(entry.path()).unwrap();
+        for (index, _) in content.match_indices("t!(") {
+            // 跳过 `format!(` 等以 `t!(` 结尾的宏与注释掉的代码
+            let line_start = content[..index].rfind('\n').map_or(0, |i| i + 1);
+            if content[..index]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                || content[line_start..].trim_start().starts_with("//")
+            {
+                continue;
+            }
+            let Some(key) = content[index + 3..]
+                .trim_start()
+                .strip_prefix('"')
+                .and_then(|rest| rest.split('"').next())
+                .filter(|key| {
+                    key.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+                })
+            else {
+                continue;
+            };
+            for language in &languages {
+                if !translated(key, language) {
+                    missing.insert(format!("{key} ({language})"));
+                }
+            }
+        }
+    }
+    assert!(missing.is_empty(), "missing translations: {missing:#?}");
+}
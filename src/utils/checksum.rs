@@ -0,0 +1,307 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use flate2::bufread::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, error};
+use xz2::stream::{Action, Status, Stream};
+
+use super::decoder::GZIP_MAGIC;
+
+/// 校验和文件的扩展名
+pub(crate) const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// `--manifest` 生成的备份清单文件的扩展名
+pub(crate) const MANIFEST_EXTENSION: &str = "json";
+
+/// 备份中每个文件的 SHA-256 清单，键为文件在归档中的路径 (以 `/` 分隔)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct ChecksumManifest {
+    pub files: BTreeMap<String, String>,
+}
+
+/// 清单中的一条校验失败记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChecksumFailure {
+    /// 文件内容与清单不一致
+    Mismatch(String),
+    /// 清单中记录的文件未被解压出来
+    Missing(String),
+}
+
+impl ChecksumManifest {
+    /// 校验已解压的文件，`locate` 返回清单条目解压后的实际路径，返回 `None` 的条目不检查
+    ///
+    /// 返回通过校验的文件数和失败列表
+    pub(crate) fn verify(
+        &self,
+        locate: impl Fn(&Path) -> Option<PathBuf>,
+    ) -> Result<(usize, Vec<ChecksumFailure>)> {
+        let mut verified = 0;
+        let mut failures = Vec::new();
+
+        for (name, expected) in &self.files {
+            let Some(path) = locate(Path::new(name)) else {
+                continue;
+            };
+            if !path.is_file() {
+                failures.push(ChecksumFailure::Missing(name.clone()));
+                continue;
+            }
+            if &sha256_file(&path)? == expected {
+                verified += 1;
+            } else {
+                failures.push(ChecksumFailure::Mismatch(name.clone()));
+            }
+        }
+
+        Ok((verified, failures))
+    }
+}
+
+/// 读取数据的同时计算 SHA-256
+pub(crate) struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// 返回已读取数据的 SHA-256
+    pub(crate) fn finalize(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// 计算任意数据流的 SHA-256，返回小写十六进制字符串
+pub(crate) fn sha256_reader<R: Read>(mut reader: R) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 计算文件的 SHA-256
+pub(crate) fn sha256_file<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| {
+        error!(?e, ?path, "Failed to open file for checksum");
+        e
+    })?;
+    let checksum = sha256_reader(BufReader::new(file))?;
+    debug!(?path, %checksum, "Computed file checksum");
+    Ok(checksum)
+}
+
+/// 计算归档中数据部分的 SHA-256
+///
+/// 归档的首个压缩流只包含元数据 (如 mapping.toml)，其后的所有字节为数据部分。
+/// 由于元数据中记录了该校验和，因此它无法覆盖元数据流本身
+pub(crate) fn payload_sha256<P: AsRef<Path>>(archive_path: P) -> Result<String> {
+    let archive_path = archive_path.as_ref();
+    let metadata_len = first_stream_len(BufReader::new(File::open(archive_path)?))?;
+    debug!(?archive_path, metadata_len, "Skipping metadata stream");
+
+    let mut file = File::open(archive_path)?;
+    file.seek(SeekFrom::Start(metadata_len))?;
+    sha256_reader(BufReader::new(file))
+}
+
+/// 解码首个压缩流 (xz 流或 gzip 成员)，返回其压缩后的字节数
+pub(crate) fn first_stream_len<R: BufRead>(mut reader: R) -> Result<u64> {
+    if reader.fill_buf()?.starts_with(GZIP_MAGIC) {
+        return first_gzip_member_len(reader);
+    }
+
+    let mut stream = Stream::new_stream_decoder(u64::MAX, 0)?;
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let input = reader.fill_buf()?;
+        let eof = input.is_empty();
+        let before = stream.total_in();
+        let action = if eof { Action::Finish } else { Action::Run };
+        let status = stream.process(input, &mut buf, action)?;
+        reader.consume((stream.total_in() - before) as usize);
+
+        if status == Status::StreamEnd {
+            return Ok(stream.total_in());
+        }
+        if eof {
+            anyhow::bail!("Unexpected end of xz stream");
+        }
+    }
+}
+
+/// 解码首个 gzip 成员，返回其压缩后的字节数
+fn first_gzip_member_len<R: BufRead>(reader: R) -> Result<u64> {
+    let mut counter = ConsumeCounter {
+        inner: reader,
+        consumed: 0,
+    };
+    io::copy(&mut GzDecoder::new(&mut counter), &mut io::sink())?;
+    Ok(counter.consumed)
+}
+
+/// 统计解码器实际消耗的字节数，预读但未消耗的数据不计入
+struct ConsumeCounter<R> {
+    inner: R,
+    consumed: u64,
+}
+
+impl<R: BufRead> Read for ConsumeCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for ConsumeCounter<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.consumed += amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+/// 在归档路径后追加扩展名，得到其附属文件的路径
+fn sidecar_file_path(archive_path: &Path, extension: &str) -> PathBuf {
+    let mut path = archive_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// 获取归档对应的校验和文件路径：`<archive>.sha256`
+pub(crate) fn checksum_file_path<P: AsRef<Path>>(archive_path: P) -> PathBuf {
+    sidecar_file_path(archive_path.as_ref(), CHECKSUM_EXTENSION)
+}
+
+/// 获取归档对应的备份清单文件路径：`<archive>.json`
+pub(crate) fn manifest_file_path<P: AsRef<Path>>(archive_path: P) -> PathBuf {
+    sidecar_file_path(archive_path.as_ref(), MANIFEST_EXTENSION)
+}
+
+/// 判断路径是否为校验和文件
+pub(crate) fn is_checksum_file<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| ext == CHECKSUM_EXTENSION)
+}
+
+/// 判断路径是否为归档的附属文件 (校验和文件或备份清单)，查找备份时应跳过
+pub(crate) fn is_sidecar_file<P: AsRef<Path>>(path: P) -> bool {
+    is_checksum_file(&path)
+        || path
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext == MANIFEST_EXTENSION)
+}
+
+/// 以 `sha256sum` 兼容的格式写入校验和文件，返回校验和文件路径
+pub(crate) fn write_checksum_file<P: AsRef<Path>>(
+    archive_path: P,
+    checksum: &str,
+) -> Result<PathBuf> {
+    let archive_path = archive_path.as_ref();
+    let checksum_path = checksum_file_path(archive_path);
+    let file_name = archive_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    std::fs::write(&checksum_path, format!("{}  {}\n", checksum, file_name)).map_err(|e| {
+        error!(?e, ?checksum_path, "Failed to write checksum file");
+        e
+    })?;
+    debug!(?checksum_path, "Checksum file written");
+    Ok(checksum_path)
+}
+
+/// 读取归档对应的校验和文件，不存在时返回 `None`
+pub(crate) fn read_checksum_file<P: AsRef<Path>>(archive_path: P) -> Result<Option<String>> {
+    let checksum_path = checksum_file_path(archive_path);
+    if !checksum_path.is_file() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&checksum_path)?;
+    let checksum = content
+        .split_whitespace()
+        .next()
+        .map(|s| s.to_lowercase())
+        .ok_or_else(|| anyhow::anyhow!("Empty checksum file: {}", checksum_path.display()))?;
+    Ok(Some(checksum))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{ArchiveFormat, CompressOptions, compress_parts};
+    use assert_fs::{TempDir, prelude::*};
+
+    #[test]
+    fn test_checksum_file_roundtrip() -> Result<()> {
+        let temp = TempDir::new()?;
+        let archive = temp.child("backup.tar.xz");
+        archive.write_str("content")?;
+
+        let checksum = sha256_file(archive.path())?;
+        let checksum_path = write_checksum_file(archive.path(), &checksum)?;
+
+        assert!(is_checksum_file(&checksum_path));
+        assert_eq!(checksum_path, temp.path().join("backup.tar.xz.sha256"));
+        assert_eq!(read_checksum_file(archive.path())?, Some(checksum));
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload_checksum_matches_parts() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        source.child("data.txt").write_str("hello")?;
+
+        for format in [ArchiveFormat::TarXz, ArchiveFormat::TarGz] {
+            let parts = compress_parts(
+                &[source.path()],
+                temp.path(),
+                &Default::default(),
+                1,
+                &BTreeMap::new(),
+                CompressOptions {
+                    format,
+                    ..Default::default()
+                },
+            )?;
+            let archive = temp.child(format!("backup.{}", format.extension()));
+            parts.write_archive(archive.path(), &[("mapping.toml", "name = 'test'")])?;
+
+            assert_eq!(payload_sha256(archive.path())?, parts.sha256()?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::Path;
+
+/// docker compose 为项目中的容器添加的项目名标签
+pub(crate) const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+
+/// docker compose 为项目中的容器添加的服务名标签
+pub(crate) const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// compose 文件中只关心顶层的 `name`
+#[derive(Debug, Deserialize)]
+struct ComposeFile {
+    name: Option<String>,
+}
+
+/// 按 docker compose 的规则确定 compose 文件所属的项目名
+///
+/// 优先级：环境变量 `COMPOSE_PROJECT_NAME` > 文件中的顶层 `name` > 文件所在目录名，
+/// 结果会按 compose 的规则规范化 (小写，只保留字母、数字、`_` 与 `-`)
+pub(crate) fn compose_project_name(compose_file: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(compose_file).map_err(|e| {
+        anyhow::anyhow!(t!(
+            "utils.compose.read_failed",
+            "path" = compose_file.to_string_lossy(),
+            "error" = e
+        ))
+    })?;
+    let file: ComposeFile = serde_yaml::from_str(&content).map_err(|e| {
+        anyhow::anyhow!(t!(
+            "utils.compose.read_failed",
+            "path" = compose_file.to_string_lossy(),
+            "error" = e
+        ))
+    })?;
+
+    let dir_name = std::path::absolute(compose_file)?
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    let name = std::env::var("COMPOSE_PROJECT_NAME")
+        .ok()
+        .or(file.name)
+        .or(dir_name)
+        .map(|name| normalize_project_name(&name))
+        .filter(|name| !name.is_empty());
+
+    name.ok_or_else(|| {
+        anyhow::anyhow!(t!(
+            "utils.compose.project_name_unknown",
+            "path" = compose_file.to_string_lossy()
+        ))
+    })
+}
+
+/// 规范化项目名：转为小写，去掉不允许的字符，并去掉开头的 `_`/`-`
+fn normalize_project_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .skip_while(|c| *c == '_' || *c == '-')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compose_project_name() -> Result<()> {
+        assert_eq!(normalize_project_name("My.Stack_1"), "mystack_1");
+        assert_eq!(normalize_project_name("-_web"), "web");
+
+        let dir = tempfile::tempdir()?;
+        let project_dir = dir.path().join("Blog.Site");
+        std::fs::create_dir(&project_dir)?;
+        let compose_file = project_dir.join("docker-compose.yaml");
+
+        std::fs::write(&compose_file, "services:\n  web:\n    image: nginx\n")?;
+        assert_eq!(compose_project_name(&compose_file)?, "blogsite");
+
+        std::fs::write(&compose_file, "name: Shop\nservices: {}\n")?;
+        assert_eq!(compose_project_name(&compose_file)?, "shop");
+
+        assert!(compose_project_name(&project_dir.join("missing.yaml")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::bufread::MultiGzDecoder;
+use xz2::bufread::XzDecoder;
+
+/// xz 流的魔数
+const XZ_MAGIC: &[u8] = &[0xFD, 0x37, 0x7A];
+
+/// zstd 帧的魔数
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+
+/// gzip 成员的魔数
+pub(super) const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+
+/// 按数据开头的魔数识别压缩格式的解码器，不依赖文件扩展名
+///
+/// 三种格式都按多流解码，以兼容并行压缩产生的多流拼接归档
+pub(crate) enum ArchiveDecoder<R: Read> {
+    Xz(XzDecoder<BufReader<R>>),
+    Zstd(zstd::Decoder<'static, BufReader<R>>),
+    Gzip(MultiGzDecoder<BufReader<R>>),
+}
+
+impl<R: Read> ArchiveDecoder<R> {
+    /// 预读数据开头并选择对应的解码器，无法识别时返回 `InvalidData` 错误
+    pub fn new(reader: R) -> io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let head = reader.fill_buf()?;
+        if head.starts_with(XZ_MAGIC) {
+            Ok(Self::Xz(XzDecoder::new_multi_decoder(reader)))
+        } else if head.starts_with(ZSTD_MAGIC) {
+            Ok(Self::Zstd(zstd::Decoder::with_buffer(reader)?))
+        } else if head.starts_with(GZIP_MAGIC) {
+            Ok(Self::Gzip(MultiGzDecoder::new(reader)))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unrecognized archive format (expected xz, zstd or gzip compressed tar)",
+            ))
+        }
+    }
+}
+
+impl<R: Read> Read for ArchiveDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Xz(decoder) => decoder.read(buf),
+            Self::Zstd(decoder) => decoder.read(buf),
+            Self::Gzip(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn decode(data: Vec<u8>) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        ArchiveDecoder::new(data.as_slice())?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn detects_compression_by_magic_bytes() -> anyhow::Result<()> {
+        let payload = b"tar payload".repeat(100);
+
+        let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(&payload)?;
+        assert_eq!(decode(xz.finish()?)?, payload);
+
+        assert_eq!(decode(zstd::encode_all(payload.as_slice(), 3)?)?, payload);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&payload)?;
+        assert_eq!(decode(gzip.finish()?)?, payload);
+
+        let error = decode(payload.clone()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::time::Duration;
+
+/// 解析 `30d`、`12h`、`1w2d` 形式的时长，支持的单位为 `s`/`m`/`h`/`d`/`w`
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let invalid = || anyhow::anyhow!(t!("utils.duration.invalid", "value" = s));
+
+    let mut total = 0u64;
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value = rest[..digits].parse::<u64>().map_err(|_| invalid())?;
+        let unit = rest[digits..].chars().next().ok_or_else(invalid)?;
+        let seconds = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        total = value
+            .checked_mul(seconds)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(invalid)?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() -> Result<()> {
+        assert_eq!(parse_duration("45s")?, Duration::from_secs(45));
+        assert_eq!(parse_duration("30d")?, Duration::from_secs(30 * 86400));
+        assert_eq!(parse_duration("1w2d")?, Duration::from_secs(9 * 86400));
+        assert_eq!(parse_duration("1h30m")?, Duration::from_secs(5400));
+        for invalid in ["", "30", "d", "3x", "-1d", "1.5d"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+}
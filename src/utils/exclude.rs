@@ -0,0 +1,209 @@
+use anyhow::Result;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// glob 元字符，包含其中任意一个的模式按 glob 匹配
+const GLOB_META_CHARS: [char; 4] = ['*', '?', '[', '{'];
+
+/// 编译后的排除模式
+///
+/// - 含 glob 元字符的模式 (如 `**/*.log`、`cache/*`) 与相对于源根目录的路径匹配，`*` 不跨越 `/`
+/// - 其余模式按路径组件匹配：`target` 只排除名为 `target` 的文件/目录，不会排除 `my_target_data`；
+///   `cache/tmp` 匹配相邻的两个组件；绝对路径则排除该路径及其下的所有内容
+///
+/// 另外可以排除修改时间早于某一时刻的文件 (`--exclude-older-than`) 与超过一定大小的文件 (`--exclude-larger-than`)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExcludeMatcher {
+    globs: GlobSet,
+    components: Vec<PathBuf>,
+    absolute: Vec<PathBuf>,
+    modified_before: Option<SystemTime>,
+    larger_than: Option<u64>,
+}
+
+impl ExcludeMatcher {
+    /// 编译排除模式，忽略空模式；glob 语法错误时返回错误
+    pub fn new(patterns: &[&str]) -> Result<Self> {
+        let mut globs = GlobSetBuilder::new();
+        let mut components = Vec::new();
+        let mut absolute = Vec::new();
+
+        for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            if pattern.contains(GLOB_META_CHARS) {
+                let glob = GlobBuilder::new(pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| {
+                        anyhow::anyhow!(t!(
+                            "utils.exclude.invalid_pattern",
+                            "pattern" = pattern,
+                            "error" = e
+                        ))
+                    })?;
+                globs.add(glob);
+            } else if Path::new(pattern).is_absolute() {
+                absolute.push(PathBuf::from(pattern));
+            } else {
+                let relative = Path::new(pattern)
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .collect::<PathBuf>();
+                if relative.as_os_str().is_empty() {
+                    continue;
+                }
+                components.push(relative);
+            }
+        }
+
+        Ok(Self {
+            globs: globs.build()?,
+            components,
+            absolute,
+            modified_before: None,
+            larger_than: None,
+        })
+    }
+
+    /// 额外排除修改时间早于 `cutoff` 的文件
+    pub fn with_modified_before(mut self, cutoff: Option<SystemTime>) -> Self {
+        self.modified_before = cutoff;
+        self
+    }
+
+    /// 额外排除大于 `limit` 字节的文件
+    pub fn with_larger_than(mut self, limit: Option<u64>) -> Self {
+        self.larger_than = limit;
+        self
+    }
+
+    /// 文件因大于大小上限而被排除时返回其大小，无法读取大小的文件不排除
+    pub fn oversized(&self, path: &Path) -> Option<u64> {
+        let limit = self.larger_than?;
+        std::fs::metadata(path)
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .filter(|len| *len > limit)
+    }
+
+    /// 判断文件是否因修改时间早于截止时间而被排除，无法读取修改时间的文件不排除
+    pub fn is_stale(&self, path: &Path) -> bool {
+        self.modified_before.is_some_and(|cutoff| {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+    }
+
+    /// 判断 `path` 是否被排除，`relative` 为其相对于源根目录的路径
+    pub fn is_excluded(&self, path: &Path, relative: &Path) -> bool {
+        if self.absolute.iter().any(|prefix| path.starts_with(prefix)) {
+            return true;
+        }
+        if relative.as_os_str().is_empty() {
+            return false;
+        }
+        self.globs.is_match(relative)
+            || self
+                .components
+                .iter()
+                .any(|pattern| contains_components(relative, pattern))
+    }
+}
+
+/// 判断 `path` 中是否存在与 `pattern` 的所有组件依次相同的连续组件
+fn contains_components(path: &Path, pattern: &Path) -> bool {
+    let path = path.components().collect::<Vec<_>>();
+    let pattern = pattern.components().collect::<Vec<_>>();
+    path.windows(pattern.len()).any(|window| window == pattern)
+}
+
+/// 读取 `--exclude-from` 文件中的排除模式：每行一个模式，忽略空行与 `#` 开头的注释行
+///
+/// 与 `--exclude` 不同，行内的逗号不会被拆分；文件不存在或无法读取时返回错误
+pub fn read_exclude_file(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!(t!(
+            "utils.exclude.read_exclude_file_failed",
+            "path" = path.to_string_lossy(),
+            "error" = e
+        ))
+    })?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_exclude_file_like_gitignore() -> Result<()> {
+        let temp = assert_fs::TempDir::new()?;
+        let file = temp.path().join(".rdbkp2ignore");
+        std::fs::write(
+            &file,
+            "# 构建产物\ntarget\n\n  **/*.log  \n# cache/\n{a,b}/tmp\n",
+        )?;
+        assert_eq!(
+            read_exclude_file(&file)?,
+            vec!["target", "**/*.log", "{a,b}/tmp"]
+        );
+
+        let missing = temp.path().join("missing");
+        let err = read_exclude_file(&missing).unwrap_err();
+        assert!(err.to_string().contains(&*missing.to_string_lossy()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_matcher() -> Result<()> {
+        let root = Path::new("/data/vol");
+        let excluded = |matcher: &ExcludeMatcher, relative: &str| {
+            matcher.is_excluded(&root.join(relative), Path::new(relative))
+        };
+
+        let matcher = ExcludeMatcher::new(&["target", "cache/tmp", "**/*.log", "logs/*"])?;
+        assert!(excluded(&matcher, "target"));
+        assert!(excluded(&matcher, "app/target/debug"));
+        assert!(!excluded(&matcher, "my_target_data"));
+        assert!(excluded(&matcher, "app/cache/tmp/x"));
+        assert!(!excluded(&matcher, "cache/tmpfile"));
+        assert!(excluded(&matcher, "a.log"));
+        assert!(excluded(&matcher, "app/deep/b.log"));
+        assert!(excluded(&matcher, "logs/today"));
+        assert!(!excluded(&matcher, "app/logs/today"));
+        assert!(!excluded(&matcher, ""));
+
+        let matcher = ExcludeMatcher::new(&["/data/vol/cache", ""])?;
+        assert!(excluded(&matcher, "cache/a"));
+        assert!(!excluded(&matcher, "cache2"));
+
+        assert!(ExcludeMatcher::new(&["[unclosed"]).is_err());
+
+        let file = tempfile::NamedTempFile::new()?;
+        let future = SystemTime::now() + std::time::Duration::from_secs(3600);
+        assert!(!matcher.is_stale(file.path()));
+        assert!(
+            matcher
+                .with_modified_before(Some(future))
+                .is_stale(file.path())
+        );
+
+        std::fs::write(file.path(), [0u8; 100])?;
+        let matcher = ExcludeMatcher::default();
+        assert_eq!(matcher.oversized(file.path()), None);
+        let limited = matcher.with_larger_than(Some(99));
+        assert_eq!(limited.oversized(file.path()), Some(100));
+        assert_eq!(
+            limited.with_larger_than(Some(100)).oversized(file.path()),
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use crate::config::Config;
+
+/// 文件大小单位 (二进制前缀)
+const BYTE_UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+/// 数字格式：千位分隔符与小数点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NumberFormat {
+    pub thousands: &'static str,
+    pub decimal: &'static str,
+}
+
+impl NumberFormat {
+    /// 不使用千位分隔符的格式，便于脚本解析
+    pub const PLAIN: NumberFormat = NumberFormat {
+        thousands: "",
+        decimal: ".",
+    };
+
+    /// 根据语言获取数字格式
+    pub fn for_locale(locale: &str) -> Self {
+        match locale.split(['-', '_']).next().unwrap_or_default() {
+            "de" | "es" | "it" | "pt" => NumberFormat {
+                thousands: ".",
+                decimal: ",",
+            },
+            "fr" => NumberFormat {
+                thousands: "\u{202f}",
+                decimal: ",",
+            },
+            "ru" => NumberFormat {
+                thousands: "\u{a0}",
+                decimal: ",",
+            },
+            _ => NumberFormat {
+                thousands: ",",
+                decimal: ".",
+            },
+        }
+    }
+
+    /// 获取当前语言对应的数字格式，设置了 `--no-locale-format` 时返回 [`NumberFormat::PLAIN`]
+    pub fn current() -> Self {
+        let disabled = Config::global()
+            .map(|c| c.no_locale_format)
+            .unwrap_or(false);
+        if disabled {
+            Self::PLAIN
+        } else {
+            Self::for_locale(&rust_i18n::locale())
+        }
+    }
+
+    /// 格式化整数
+    pub fn number(&self, n: u64) -> String {
+        let digits = n.to_string();
+        let mut result = String::with_capacity(digits.len() * 2);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                result.push_str(self.thousands);
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    /// 格式化文件大小，例如 `1.5 MiB`
+    pub fn bytes(&self, bytes: u64) -> String {
+        if bytes < 1024 {
+            return format!("{} {}", self.number(bytes), BYTE_UNITS[0]);
+        }
+
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        format!("{} {}", self.fixed1(value), BYTE_UNITS[unit])
+    }
+
+    /// 格式化保留一位小数的非负数，例如 `4.2`
+    pub fn fixed1(&self, value: f64) -> String {
+        let rounded = (value * 10.0).round() as u64;
+        format!(
+            "{}{}{}",
+            self.number(rounded / 10),
+            self.decimal,
+            rounded % 10
+        )
+    }
+}
+
+/// 按当前语言格式化整数，例如德语下 `1234567` 显示为 `1.234.567`
+pub(crate) fn format_number(n: u64) -> String {
+    NumberFormat::current().number(n)
+}
+
+/// 按当前语言格式化文件大小，例如德语下显示为 `1,5 MiB`
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    NumberFormat::current().bytes(bytes)
+}
+
+/// 按当前语言格式化压缩比 (未压缩大小 / 压缩后大小)，例如 `4.2:1`
+pub(crate) fn format_ratio(ratio: f64) -> String {
+    format!("{}:1", NumberFormat::current().fixed1(ratio))
+}
+
+/// 解析 `512K`、`16M`、`1G` 形式的大小 (按 1024 进位，可带 `B`/`iB` 后缀)，不带单位时按字节计算
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let invalid = || t!("utils.format.invalid_size", "value" = s).to_string();
+
+    let value = s.trim();
+    let value = value
+        .strip_suffix("iB")
+        .or_else(|| value.strip_suffix('B'))
+        .unwrap_or(value);
+    let (digits, shift) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 10),
+        Some('M') => (&value[..value.len() - 1], 20),
+        Some('G') => (&value[..value.len() - 1], 30),
+        _ => (value, 0),
+    };
+    let size = digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(invalid)?;
+    if size == 0 {
+        return Err(invalid());
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512K"), Ok(512 * 1024));
+        assert_eq!(parse_size("16M"), Ok(16 << 20));
+        assert_eq!(parse_size("16MiB"), Ok(16 << 20));
+        assert_eq!(parse_size("1GB"), Ok(1 << 30));
+        assert_eq!(parse_size("8k"), Ok(8 * 1024));
+        for invalid in ["", "0", "M", "1.5M", "-1K", "3T"] {
+            assert!(parse_size(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_format_number_by_locale() {
+        assert_eq!(NumberFormat::for_locale("en").number(1234567), "1,234,567");
+        assert_eq!(NumberFormat::for_locale("de").number(1234567), "1.234.567");
+        assert_eq!(
+            NumberFormat::for_locale("fr").number(1234567),
+            "1\u{202f}234\u{202f}567"
+        );
+        assert_eq!(
+            NumberFormat::for_locale("ru").number(1234567),
+            "1\u{a0}234\u{a0}567"
+        );
+        assert_eq!(
+            NumberFormat::for_locale("pt-BR").number(1234567),
+            "1.234.567"
+        );
+        assert_eq!(NumberFormat::for_locale("zh-CN").number(999), "999");
+        assert_eq!(NumberFormat::PLAIN.number(1234567), "1234567");
+    }
+
+    #[test]
+    fn test_format_bytes_by_locale() {
+        assert_eq!(NumberFormat::for_locale("en").bytes(512), "512 B");
+        assert_eq!(NumberFormat::for_locale("en").bytes(1536), "1.5 KiB");
+        assert_eq!(NumberFormat::for_locale("de").bytes(1536 * 1024), "1,5 MiB");
+        assert_eq!(
+            NumberFormat::for_locale("it").bytes(1500 * 1024 * 1024),
+            "1,5 GiB"
+        );
+        assert_eq!(NumberFormat::PLAIN.bytes(1536), "1.5 KiB");
+        assert_eq!(NumberFormat::for_locale("fr").fixed1(4.25), "4,3");
+    }
+}
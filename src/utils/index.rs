@@ -0,0 +1,100 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+use tracing::debug;
+
+use super::{INDEX_FILE_NAME, first_stream_len, read_file_from_archive};
+
+/// 按卷建立的归档索引，使用 `--index` 备份时作为元数据文件 (`index.toml`) 写入归档
+///
+/// 归档中每个卷单独压缩为一个压缩流 (xz 流或 gzip 成员)，索引记录各个流相对数据部分起点 (元数据流之后) 的偏移与长度。
+/// 只恢复部分卷时可以直接跳到对应的流，无需解压整个归档
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ArchiveIndex {
+    pub volumes: Vec<IndexedVolume>,
+}
+
+/// 索引中的一个卷
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct IndexedVolume {
+    /// 卷名，即卷在归档中的顶层目录名
+    pub name: String,
+    /// 该卷的压缩流相对数据部分起点的偏移
+    pub offset: u64,
+    /// 该卷的压缩流的长度
+    pub length: u64,
+}
+
+impl ArchiveIndex {
+    /// 由按顺序拼接的各个流的卷名与长度构建索引
+    pub fn from_lengths<'a>(streams: impl IntoIterator<Item = (&'a str, u64)>) -> Self {
+        let mut offset = 0;
+        let volumes = streams
+            .into_iter()
+            .map(|(name, length)| {
+                let volume = IndexedVolume {
+                    name: name.to_string(),
+                    offset,
+                    length,
+                };
+                offset += length;
+                volume
+            })
+            .collect();
+        Self { volumes }
+    }
+
+    /// 读取归档中的索引，归档未带索引或索引无法解析时返回 `None`
+    pub fn read(archive_path: &Path) -> Option<Self> {
+        let content = read_file_from_archive(archive_path, INDEX_FILE_NAME).ok()?;
+        toml::from_str(&content).ok()
+    }
+}
+
+/// 打开归档的原始 (压缩) 数据用于读取条目，返回读取器与将要读取的字节数
+///
+/// `volumes` 不为空且归档的索引包含所有这些卷时，只读取元数据流与这些卷所在的流；
+/// 否则读取整个归档
+pub(crate) fn open_archive_volumes(
+    archive_path: &Path,
+    volumes: &[&str],
+) -> Result<(Box<dyn Read>, u64)> {
+    let ranges = (!volumes.is_empty())
+        .then(|| ArchiveIndex::read(archive_path))
+        .flatten()
+        .and_then(|index| {
+            volumes
+                .iter()
+                .map(|name| index.volumes.iter().find(|v| v.name == *name).cloned())
+                .collect::<Option<Vec<_>>>()
+        });
+    let Some(mut selected) = ranges else {
+        let file = File::open(archive_path)?;
+        let len = file.metadata()?.len();
+        return Ok((Box::new(file), len));
+    };
+
+    let metadata_len = first_stream_len(BufReader::new(File::open(archive_path)?))?;
+    selected.sort_by_key(|v| v.offset);
+    selected.dedup_by_key(|v| v.offset);
+    debug!(
+        ?archive_path,
+        metadata_len,
+        volumes = ?selected.iter().map(|v| &v.name).collect::<Vec<_>>(),
+        "Reading selected volumes through the archive index"
+    );
+
+    let mut reader: Box<dyn Read> = Box::new(File::open(archive_path)?.take(metadata_len));
+    let mut len = metadata_len;
+    for volume in selected {
+        let mut file = File::open(archive_path)?;
+        file.seek(SeekFrom::Start(metadata_len + volume.offset))?;
+        reader = Box::new(reader.chain(file.take(volume.length)));
+        len += volume.length;
+    }
+    Ok((reader, len))
+}
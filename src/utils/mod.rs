@@ -1,28 +1,79 @@
+mod checksum;
+mod compose;
+mod decoder;
+mod duration;
+mod exclude;
+mod format;
+mod index;
 mod out;
+mod owner;
+mod partial;
 mod path;
-
-// pub(crate) use out::*;
+mod process;
+mod progress;
+mod subtree;
+mod sync;
+mod tar_format;
+mod throttle;
+
+pub(crate) use checksum::*;
+pub(crate) use compose::*;
+use decoder::ArchiveDecoder;
+pub(crate) use duration::*;
+pub(crate) use exclude::*;
+pub(crate) use format::*;
+pub(crate) use index::*;
+pub(crate) use out::*;
+pub(crate) use owner::*;
+pub(crate) use partial::*;
 pub(crate) use path::*;
+pub(crate) use process::*;
+pub(crate) use progress::*;
+pub(crate) use subtree::*;
+pub(crate) use sync::*;
+use tar_format::{TrailerStripper, append_entry, append_symlink};
+use throttle::{IoLimiter, ThrottledReader};
 
 use anyhow::Result;
+use flate2::write::GzEncoder;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use tracing::{debug, error, info, warn};
 use walkdir::WalkDir;
-use xz2::read::XzDecoder;
+use xz2::stream::{Check, MtStreamBuilder};
 use xz2::write::XzEncoder;
 
 use crate::{log_println, update_print};
 
+/// 备份中记录容器与卷映射关系的元数据文件
+pub(crate) const MAPPING_FILE_NAME: &str = "mapping.toml";
+
+/// 备份中记录逐文件校验和的元数据文件
+pub(crate) const CHECKSUMS_FILE_NAME: &str = "checksums.toml";
+
+/// 备份中按卷记录各数据流位置的索引文件 (`--index`)
+pub(crate) const INDEX_FILE_NAME: &str = "index.toml";
+
+/// 所有元数据文件名
+///
+/// 元数据文件总是作为内存文件写在压缩包的最前面 (并行压缩时单独占据第一个 xz 流)，
+/// 因此读取元数据时遇到第一个非元数据条目即可停止，无需解压卷数据
+pub(crate) const METADATA_FILE_NAMES: [&str; 3] =
+    [MAPPING_FILE_NAME, CHECKSUMS_FILE_NAME, INDEX_FILE_NAME];
+
 /// 压缩目录/文件 (列表)，并在压缩包中添加额外的内存文件
 ///
 /// # Arguments
 ///
 /// * `sources` - 要压缩的源目录或文件路径 (列表)
 /// * `output_file` - 压缩后的输出文件路径
-/// * `memory_files` - 要添加到压缩包中的额外的内存文件列表，每个元素是一个元组 (文件名，文件内容)
+/// * `memory_files` - 要添加到压缩包中的额外的内存文件列表，每个元素是一个元组 (文件名，文件内容)，始终写在压缩包最前面
 /// * `exclude_patterns` - 要排除的文件/目录模式列表，为空则不排除
+/// * `options` - 读取源文件时使用的选项 (读取限速、符号链接与所有权等)，输出固定为单个 xz 流
 ///
 /// # Returns
 ///
@@ -36,13 +87,14 @@ use crate::{log_println, update_print};
 /// let memory_files = vec![("test.txt", "Hello World")];
 /// let excludes = vec![".git", "node_modules"];
 /// // let non-excludes = vec![];
-/// compress_with_memory_file(source, output, &memory_files, &excludes)?;
+/// compress_with_memory_file(source, output, &memory_files, &excludes, CompressOptions::default())?;
 /// ```
 pub fn compress_with_memory_file<P: AsRef<Path>>(
     sources: &[P],
     output_file: P,
     memory_files: &[(&str, &str)],
     exclude_patterns: &[&str],
+    options: CompressOptions,
 ) -> Result<()> {
     log_println!("INFO", "Start compressing items");
 
@@ -64,20 +116,36 @@ pub fn compress_with_memory_file<P: AsRef<Path>>(
     })?;
 
     // 使用 XZ 压缩，压缩级别为 3, 兼具压缩速度和压缩率
-    let xz = XzEncoder::new(file, 3);
+    let xz = XzEncoder::new(file, XZ_LEVEL);
     let mut tar = tar::Builder::new(xz);
-    debug!("Creating XZ encoder with compression level 3");
+    debug!("Creating XZ encoder with compression level {XZ_LEVEL}");
 
     let mut items_count = 0;
 
     // 首先添加内存中的文件
     items_count += append_memory_files(memory_files, &mut tar)?;
 
-    // 处理每个源目录/文件
-    for source in sources {
-        // 然后添加源目录/文件
-        items_count += append_items(source, exclude_patterns, &mut tar)?;
+    // 然后添加源目录/文件
+    let exclude = ExcludeMatcher::new(exclude_patterns)?;
+    let limiter = IoLimiter::new(options.io_limit);
+    let source_files = sources
+        .iter()
+        .map(|source| collect_source_files(source, &exclude, options.follow_symlinks))
+        .collect::<Result<Vec<_>>>()?;
+    let progress = compress_progress(&source_files, source_files_bytes(&source_files, options));
+    for files in &source_files {
+        progress.start_volume();
+        let (count, _) = append_items(
+            files,
+            &mut tar,
+            &mut ChecksumManifest::default(),
+            &progress,
+            &limiter,
+            options,
+        )?;
+        items_count += count;
     }
+    progress.finish();
 
     debug!("Finalizing archive");
     tar.finish().map_err(|e| {
@@ -97,21 +165,558 @@ pub fn compress_with_memory_file<P: AsRef<Path>>(
     Ok(())
 }
 
-fn append_items<P: AsRef<Path>>(
+/// 分别压缩好的数据流 (每个源对应一个临时文件)，等待与元数据一起拼接为最终归档
+pub struct CompressedParts {
+    parts: Vec<tempfile::NamedTempFile>,
+    items_count: usize,
+    total_bytes: u64,
+    manifest: ChecksumManifest,
+    stamps: BTreeMap<String, FileStamp>,
+    oversized: (u64, u64),
+    compat: bool,
+    format: ArchiveFormat,
+    /// 中断时需要删除的临时文件 (`NamedTempFile` 只在正常丢弃时删除)
+    _partial: Vec<PartialFile>,
+}
+
+impl CompressedParts {
+    /// 压缩时所有源文件 (包括因未变化而跳过的文件) 的修改时间与大小
+    pub fn stamps(&self) -> &BTreeMap<String, FileStamp> {
+        &self.stamps
+    }
+
+    /// 因超过 `--exclude-larger-than` 上限而跳过的文件数与总大小
+    pub fn oversized(&self) -> (u64, u64) {
+        self.oversized
+    }
+
+    /// 实际写入压缩包的文件数
+    pub fn items_count(&self) -> usize {
+        self.items_count
+    }
+
+    /// 实际写入压缩包的文件的未压缩总大小
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// 每个数据流 (与传入 [`compress_parts`] 的源一一对应) 的长度
+    pub fn part_lengths(&self) -> Result<Vec<u64>> {
+        self.parts
+            .iter()
+            .map(|part| Ok(part.as_file().metadata()?.len()))
+            .collect()
+    }
+
+    /// 压缩过程中记录的每个文件的校验和
+    pub fn manifest(&self) -> &ChecksumManifest {
+        &self.manifest
+    }
+
+    /// 计算所有数据流按顺序拼接后的 SHA-256，即最终归档中元数据流之后部分的校验和
+    pub fn sha256(&self) -> Result<String> {
+        let readers = self
+            .parts
+            .iter()
+            .map(|part| part.reopen())
+            .collect::<io::Result<Vec<_>>>()?;
+        let chained = readers
+            .into_iter()
+            .fold(Box::new(io::empty()) as Box<dyn Read>, |acc, r| {
+                Box::new(acc.chain(r))
+            });
+        checksum::sha256_reader(chained)
+    }
+
+    /// 写出最终归档：内存文件作为首个压缩流，其后依次拼接所有数据流
+    ///
+    /// 兼容模式下只有最后一个流带有 tar 结束块，解压后即为单个标准 tar
+    pub fn write_archive(&self, output_file: &Path, memory_files: &[(&str, &str)]) -> Result<()> {
+        let file = File::create(output_file).map_err(|e| {
+            error!(?e, ?output_file, "Failed to create output file");
+            e
+        })?;
+
+        // 内存文件 (如 mapping.toml) 放在第一个流中，保证读取时最先被找到
+        let encoder = self.format.encoder(&XzOptions::default(), file)?;
+        let (memory_count, mut file) = if self.compat && !self.parts.is_empty() {
+            let mut tar = tar::Builder::new(TrailerStripper::new(encoder));
+            let count = append_memory_files(memory_files, &mut tar)?;
+            (count, tar.into_inner()?.into_inner()?.finish()?)
+        } else {
+            let mut tar = tar::Builder::new(encoder);
+            let count = append_memory_files(memory_files, &mut tar)?;
+            (count, tar.into_inner()?.finish()?)
+        };
+
+        for part in &self.parts {
+            io::copy(&mut part.reopen()?, &mut file)?;
+        }
+        file.flush()?;
+
+        info!(
+            items_count = self.items_count + memory_count,
+            output_file = ?output_file,
+            "Items compression completed successfully"
+        );
+        log_println!("INFO", "Compressing items completed successfully");
+        Ok(())
+    }
+}
+
+/// 压缩时写入条目的方式
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressOptions {
+    /// 生成可以直接用 GNU tar 解压的归档 (见 [`CompressedParts::write_archive`])
+    pub compat: bool,
+    /// 同时写入文件所在目录的条目，以便恢复时还原目录的属主与权限
+    pub ownership: bool,
+    /// 跟随符号链接打包其目标内容；为假时符号链接作为链接条目写入，不读取链接目标
+    pub follow_symlinks: bool,
+    /// 归档的压缩格式
+    pub format: ArchiveFormat,
+    /// 数据流的 xz 压缩参数，其他格式下忽略
+    pub xz: XzOptions,
+    /// 读取源文件的速率上限 (字节/秒)，所有并行压缩的数据流共享该限制，`None` 表示不限速
+    pub io_limit: Option<u64>,
+}
+
+/// 默认的 xz 压缩级别，兼具压缩速度和压缩率
+const XZ_LEVEL: u32 = 3;
+
+/// 归档的压缩格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ArchiveFormat {
+    /// xz 压缩，压缩率最高
+    #[default]
+    #[serde(rename = "tar.xz")]
+    TarXz,
+    /// gzip 压缩，几乎所有解压工具 (包括不支持 xz 的图形界面工具) 都能打开
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// 所有可以生成的格式
+    pub const ALL: [Self; 2] = [Self::TarXz, Self::TarGz];
+
+    /// 归档文件的扩展名 (不含开头的 `.`)
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::TarXz => "tar.xz",
+            Self::TarGz => "tar.gz",
+        }
+    }
+
+    /// 压缩算法的名称
+    pub fn compression(self) -> &'static str {
+        match self {
+            Self::TarXz => "xz",
+            Self::TarGz => "gzip",
+        }
+    }
+
+    /// 创建写入 `writer` 的编码器，每个编码器产生一个独立的压缩流 (xz 流或 gzip 成员)
+    fn encoder<W: Write>(self, xz: &XzOptions, writer: W) -> Result<StreamEncoder<W>> {
+        Ok(match self {
+            Self::TarXz => StreamEncoder::Xz(xz.encoder(writer)?),
+            Self::TarGz => {
+                StreamEncoder::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+        })
+    }
+}
+
+/// 按 [`ArchiveFormat`] 选择的压缩流编码器
+enum StreamEncoder<W: Write> {
+    Xz(XzEncoder<W>),
+    Gzip(GzEncoder<W>),
+}
+
+impl<W: Write> StreamEncoder<W> {
+    /// 写出压缩流的结尾，返回内部写入器
+    fn finish(self) -> io::Result<W> {
+        match self {
+            Self::Xz(encoder) => encoder.finish(),
+            Self::Gzip(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for StreamEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Xz(encoder) => encoder.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Xz(encoder) => encoder.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// xz 压缩参数
+#[derive(Debug, Clone, Copy)]
+pub struct XzOptions {
+    /// 压缩级别 (0-9)
+    pub level: u32,
+    /// 每个数据流的压缩线程数，大于 1 时使用多线程编码器将数据分块并行压缩
+    ///
+    /// 未设置时由 [`compress_parts`] 将 `jobs` 中未用于并行压缩多个源的线程分给每个数据流
+    pub threads: Option<u32>,
+    /// 多线程压缩时每个块的未压缩大小，未设置时由 liblzma 决定 (字典大小的 3 倍，级别 3 时为 12 MiB)
+    pub block_size: Option<u64>,
+}
+
+impl Default for XzOptions {
+    fn default() -> Self {
+        Self {
+            level: XZ_LEVEL,
+            threads: None,
+            block_size: None,
+        }
+    }
+}
+
+impl XzOptions {
+    /// 创建写入 `writer` 的 xz 编码器
+    ///
+    /// 多线程编码器输出的同样是标准 xz 流 (由多个块组成)，任何 xz 解码器都可以解压
+    fn encoder<W: Write>(&self, writer: W) -> Result<XzEncoder<W>> {
+        let threads = self.threads.unwrap_or(1);
+        if threads <= 1 {
+            return Ok(XzEncoder::new(writer, self.level));
+        }
+
+        let mut builder = MtStreamBuilder::new();
+        builder
+            .preset(self.level)
+            .threads(threads)
+            .check(Check::Crc64);
+        if let Some(block_size) = self.block_size {
+            builder.block_size(block_size);
+        }
+        Ok(XzEncoder::new_stream(writer, builder.encoder()?))
+    }
+}
+
+/// 使用最多 `jobs` 个线程并行压缩每个源，结果暂存在 `work_dir` 下的临时文件中
+///
+/// 修改时间与大小均与 `previous` 中的记录一致的文件会被跳过 (用于增量备份)，
+/// 完整备份时传入空的 `previous` 即可；修改时间早于 `exclude` 中截止时间的文件同样会被跳过
+pub fn compress_parts<P: AsRef<Path> + Sync>(
+    sources: &[P],
+    work_dir: &Path,
+    exclude: &ExcludeMatcher,
+    jobs: usize,
+    previous: &BTreeMap<String, FileStamp>,
+    options: CompressOptions,
+) -> Result<CompressedParts> {
+    log_println!("INFO", "Start compressing items");
+
+    let threads = jobs.max(1);
+    let jobs = jobs.clamp(1, sources.len().max(1));
+    // 并行压缩的源少于可用线程时，剩余的线程用于每个数据流内部的多线程 xz 压缩
+    let xz = XzOptions {
+        threads: Some(
+            options
+                .xz
+                .threads
+                .unwrap_or_else(|| u32::try_from(threads / jobs).unwrap_or(u32::MAX)),
+        ),
+        ..options.xz
+    };
+    info!(
+        sources_count = sources.len(),
+        jobs,
+        xz_threads = xz.threads,
+        xz_block_size = xz.block_size,
+        work_dir = ?work_dir,
+        "Starting parallel items compression"
+    );
+
+    let mut stamps = BTreeMap::new();
+    let mut stale = 0usize;
+    let (mut oversized, mut oversized_bytes) = (0u64, 0u64);
+    let mut source_files = sources
+        .iter()
+        .map(|source| collect_source_files(source, exclude, options.follow_symlinks))
+        .collect::<Result<Vec<_>>>()?;
+    for files in &mut source_files {
+        files.retain(|(path, name)| {
+            if exclude.is_stale(path) {
+                stale += 1;
+                return false;
+            }
+            if let Some(len) = exclude.oversized(path) {
+                debug!(?path, len, "Skipping file larger than the size limit");
+                oversized += 1;
+                oversized_bytes += len;
+                return false;
+            }
+            let key = archive_key(name);
+            let stamp = source_metadata(path, options)
+                .ok()
+                .and_then(|metadata| FileStamp::from_metadata(&metadata));
+            let unchanged = stamp.is_some() && previous.get(&key) == stamp.as_ref();
+            if let Some(stamp) = stamp {
+                stamps.insert(key, stamp);
+            }
+            !unchanged
+        });
+    }
+    if !previous.is_empty() {
+        info!(
+            unchanged = stamps.len() - source_files.iter().map(Vec::len).sum::<usize>(),
+            "Skipping files unchanged since the base backup"
+        );
+    }
+    if stale > 0 {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "utils.exclude.skipped_old_files",
+                "count" = format_number(stale as u64)
+            )
+        );
+    }
+    if oversized > 0 {
+        log_println!(
+            "INFO",
+            "{}",
+            t!(
+                "utils.exclude.skipped_large_files",
+                "count" = format_number(oversized),
+                "size" = format_bytes(oversized_bytes)
+            )
+        );
+    }
+    let estimated_bytes = source_files_bytes(&source_files, options);
+    let progress = compress_progress(&source_files, estimated_bytes);
+    let limiter = IoLimiter::new(options.io_limit);
+
+    // 每个源先压缩到临时文件中，避免占用大量内存
+    let parts = sources
+        .iter()
+        .map(|_| tempfile::NamedTempFile::new_in(work_dir))
+        .collect::<io::Result<Vec<_>>>()?;
+    let partial = parts
+        .iter()
+        .map(|part| PartialFile::track(part.path()))
+        .collect::<Vec<_>>();
+
+    let next = AtomicUsize::new(0);
+    let (items_count, total_bytes, manifest) =
+        std::thread::scope(|scope| -> Result<(usize, u64, ChecksumManifest)> {
+            let workers = (0..jobs)
+                .map(|_| {
+                    scope.spawn(|| -> Result<(usize, u64, ChecksumManifest)> {
+                        let (mut count, mut bytes) = (0, 0);
+                        let mut manifest = ChecksumManifest::default();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let (Some(files), Some(part)) =
+                                (source_files.get(index), parts.get(index))
+                            else {
+                                return Ok((count, bytes, manifest));
+                            };
+
+                            debug!(source = ?sources[index].as_ref(), index, "Compressing part");
+                            progress.start_volume();
+                            let encoder = options.format.encoder(&xz, part.reopen()?)?;
+                            // 兼容模式下只保留最后一个流的 tar 结束块
+                            let (part_count, part_bytes) =
+                                if options.compat && index + 1 < parts.len() {
+                                    let mut tar = tar::Builder::new(TrailerStripper::new(encoder));
+                                    let written = append_items(
+                                        files,
+                                        &mut tar,
+                                        &mut manifest,
+                                        &progress,
+                                        &limiter,
+                                        options,
+                                    )?;
+                                    tar.into_inner()?.into_inner()?.finish()?;
+                                    written
+                                } else {
+                                    let mut tar = tar::Builder::new(encoder);
+                                    let written = append_items(
+                                        files,
+                                        &mut tar,
+                                        &mut manifest,
+                                        &progress,
+                                        &limiter,
+                                        options,
+                                    )?;
+                                    tar.into_inner()?.finish()?;
+                                    written
+                                };
+                            count += part_count;
+                            bytes += part_bytes;
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let (mut total, mut total_bytes) = (0, 0);
+            let mut manifest = ChecksumManifest::default();
+            for worker in workers {
+                let (count, bytes, part_manifest) = worker
+                    .join()
+                    .map_err(|_| anyhow::anyhow!("Compression worker panicked"))??;
+                total += count;
+                total_bytes += bytes;
+                manifest.files.extend(part_manifest.files);
+            }
+            Ok((total, total_bytes, manifest))
+        })?;
+    progress.finish();
+
+    Ok(CompressedParts {
+        parts,
+        items_count,
+        total_bytes,
+        manifest,
+        stamps,
+        oversized: (oversized, oversized_bytes),
+        compat: options.compat,
+        format: options.format,
+        _partial: partial,
+    })
+}
+
+/// 文件的修改时间与大小，用于增量备份时判断文件是否变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileStamp {
+    /// 修改时间 (自 UNIX 纪元起的纳秒数)
+    pub mtime_ns: i64,
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// 由文件的元数据得到修改时间与大小，无法读取修改时间时返回 `None`
+    pub fn from_metadata(metadata: &fs::Metadata) -> Option<Self> {
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        Some(Self {
+            mtime_ns: i64::try_from(mtime.as_nanos()).ok()?,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// 文件在压缩包中的名称转换为清单中使用的键 (以 `/` 分隔)
+pub fn archive_key(name: &Path) -> String {
+    name.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 打开压缩的 tar 归档
+///
+/// 压缩格式 (xz/zstd/gzip) 按文件开头的魔数识别，与文件扩展名无关。
+/// 使用多流解码器并忽略 tar 结束块，以兼容并行压缩产生的多流拼接归档
+fn open_archive(archive_path: &Path) -> Result<tar::Archive<ArchiveDecoder<File>>> {
+    let file = File::open(archive_path).map_err(|e| {
+        error!(?e, ?archive_path, "Failed to open archive file");
+        e
+    })?;
+
+    archive_from_reader(file)
+}
+
+fn archive_from_reader<R: Read>(reader: R) -> Result<tar::Archive<ArchiveDecoder<R>>> {
+    let mut archive = tar::Archive::new(ArchiveDecoder::new(reader)?);
+    archive.set_ignore_zeros(true);
+    Ok(archive)
+}
+
+/// 预读的小文件大小上限，更大的文件由写入线程直接流式读取
+const PREFETCH_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// 每批预读的文件数，限制预读占用的内存
+const PREFETCH_BATCH: usize = 256;
+
+/// 每个数据流用于预读小文件的线程数
+const PREFETCH_READERS: usize = 4;
+
+/// 源目录/文件中待压缩的文件，每个元素为 (文件路径，压缩包内的名称)
+type SourceFiles = Vec<(PathBuf, PathBuf)>;
+
+/// 所有源中待压缩文件的总大小
+fn source_files_bytes(source_files: &[SourceFiles], options: CompressOptions) -> u64 {
+    source_files
+        .iter()
+        .flatten()
+        .filter_map(|(path, _)| source_metadata(path, options).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 创建压缩进度，总量为所有源中待压缩的文件数，`total_bytes` 为这些文件的总大小
+///
+/// 在终端中压缩多个源 (卷) 时改为按字节汇总的整体进度，显示已开始的卷数与预计剩余时间
+fn compress_progress(source_files: &[SourceFiles], total_bytes: u64) -> Progress {
+    let files = source_files.iter().flatten().collect::<Vec<_>>();
+    log_println!(
+        "INFO",
+        "{}",
+        t!(
+            "utils.progress.compress_summary",
+            "files" = format_number(files.len() as u64),
+            "size" = format_bytes(total_bytes)
+        )
+    );
+    if source_files.len() > 1 && ProgressMode::current() == ProgressMode::Bar {
+        return Progress::volumes(
+            t!("utils.progress.compressing"),
+            total_bytes,
+            source_files.len() as u64,
+        );
+    }
+    Progress::new(
+        t!("utils.progress.compressing"),
+        files.len() as u64,
+        ProgressUnit::Items,
+    )
+}
+
+/// 读取待压缩文件的元数据，不跟随符号链接时读取链接本身的元数据
+fn source_metadata(path: &Path, options: CompressOptions) -> io::Result<fs::Metadata> {
+    if options.follow_symlinks {
+        fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
+    }
+}
+
+/// 遍历源目录/文件，收集需要添加到压缩包中的文件 (已按排除模式过滤)
+///
+/// `follow_symlinks` 为假时不进入指向目录的符号链接，符号链接本身作为待压缩的条目返回
+fn collect_source_files<P: AsRef<Path>>(
     source: P,
-    exclude_patterns: &[&str],
-    tar: &mut tar::Builder<XzEncoder<File>>,
-) -> Result<usize> {
-    let mut items_count = 0;
+    exclude: &ExcludeMatcher,
+    follow_symlinks: bool,
+) -> Result<SourceFiles> {
+    let mut files = Vec::new();
     let source = source.as_ref();
 
     if source.is_dir() {
         let walker = WalkDir::new(source)
-            .follow_links(true)
+            .follow_links(follow_symlinks)
             .into_iter()
             .filter_entry(|e| {
-                let path = e.path().to_string_lossy();
-                let excluded = exclude_patterns.iter().any(|p| path.contains(p));
+                let relative = e.path().strip_prefix(source).unwrap_or(e.path());
+                let excluded = exclude.is_excluded(e.path(), relative);
                 if excluded {
                     debug!(path = ?e.path(), "Excluding path");
                 }
@@ -119,44 +724,244 @@ fn append_items<P: AsRef<Path>>(
             });
 
         for entry in walker.filter_map(|e| e.ok()) {
-            if entry.path().is_file() {
+            if entry.file_type().is_file() || entry.file_type().is_symlink() {
                 let name = entry
                     .path()
                     .strip_prefix(source.parent().unwrap_or(source))?;
-                debug!(path = ?entry.path(), name = ?name, "Adding file to archive");
-                tar.append_path_with_name(entry.path(), name)?;
-                items_count += 1;
-                update_print!("{}", name.to_string_lossy());
+                files.push((entry.path().to_path_buf(), name.to_path_buf()));
             }
         }
-        println!();
     } else if source.is_file() {
-        // 如果文件名包含排除模式，则不添加到压缩包中
-        if exclude_patterns
-            .iter()
-            .any(|p| source.to_string_lossy().contains(p))
-        {
+        // 单个文件按其文件名匹配排除模式
+        let name = source.file_name().map(Path::new).unwrap_or(source);
+        if exclude.is_excluded(source, name) {
             debug!(path = ?source, "Excluding file");
-            return Ok(items_count);
+            return Ok(files);
         }
 
         let name = source
             .file_name()
             .ok_or_else(|| anyhow::anyhow!("Failed to get file name"))?;
+        files.push((source.to_path_buf(), PathBuf::from(name)));
+    }
 
-        debug!(path = ?source, name = ?name, "Adding file to archive");
-        tar.append_path_with_name(source, name)?;
-        items_count += 1;
-        update_print!("{}", name.to_string_lossy());
-        println!();
+    Ok(files)
+}
+
+/// 添加文件到压缩包，返回添加的文件数与这些文件实际写入的未压缩字节数
+///
+/// `options.ownership` 为真时在每个文件之前写入其尚未写入的上级目录条目 (直到源的顶层目录)
+fn append_items<W: Write>(
+    files: &[(PathBuf, PathBuf)],
+    tar: &mut tar::Builder<W>,
+    manifest: &mut ChecksumManifest,
+    progress: &Progress,
+    limiter: &IoLimiter,
+    options: CompressOptions,
+) -> Result<(usize, u64)> {
+    append_items_prefetched(
+        files,
+        tar,
+        manifest,
+        progress,
+        limiter,
+        options,
+        PREFETCH_READERS,
+    )
+}
+
+/// 使用 `readers` 个线程预读小文件的 [`append_items`]，`readers` 为 0 时由写入线程逐个读取
+///
+/// 大量小文件时逐个打开、读取的系统调用开销远大于压缩本身。预读线程按批将小文件读入内存，
+/// 写入线程写入当前批的同时读取下一批；条目仍按 `files` 的顺序写入，归档内容与逐个读取时一致
+fn append_items_prefetched<W: Write>(
+    files: &[(PathBuf, PathBuf)],
+    tar: &mut tar::Builder<W>,
+    manifest: &mut ChecksumManifest,
+    progress: &Progress,
+    limiter: &IoLimiter,
+    options: CompressOptions,
+    readers: usize,
+) -> Result<(usize, u64)> {
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        if readers > 0 {
+            scope.spawn(move || {
+                for batch in files.chunks(PREFETCH_BATCH) {
+                    // 写入线程出错退出后停止预读
+                    if sender
+                        .send(prefetch_batch(batch, limiter, options, readers))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        } else {
+            drop(sender);
+        }
+        let mut prefetched = receiver.into_iter().flatten();
+
+        let mut written_dirs = HashSet::new();
+        let mut bytes = 0;
+        for (path, name) in files {
+            if options.ownership {
+                append_parent_dirs(tar, path, name, &mut written_dirs, options.compat)?;
+            }
+            let before = bytes;
+            match prefetched.next().unwrap_or(Prefetched::Deferred) {
+                Prefetched::Data(metadata, data) => {
+                    debug!(?path, ?name, "Adding prefetched file to archive");
+                    bytes +=
+                        append_hashed(tar, &metadata, name, &data[..], manifest, options.compat)?;
+                }
+                Prefetched::Deferred => {
+                    let metadata = source_metadata(path, options)?;
+                    if metadata.is_symlink() {
+                        let target = fs::read_link(path)?;
+                        debug!(?path, ?name, ?target, "Adding symlink to archive");
+                        append_symlink(tar, &metadata, name, &target, options.compat)?;
+                    } else {
+                        debug!(?path, ?name, "Adding file to archive");
+                        bytes +=
+                            append_file_hashed(tar, path, name, manifest, limiter, options.compat)?;
+                    }
+                }
+            }
+            progress.inc_file(bytes - before, || name.to_string_lossy().to_string());
+        }
+
+        Ok((files.len(), bytes))
+    })
+}
+
+/// 预读的文件
+enum Prefetched {
+    /// 已完整读入内存的小文件及其元数据
+    Data(fs::Metadata, Vec<u8>),
+    /// 符号链接、大文件或读取失败的文件，由写入线程按原方式处理 (读取错误也在写入时报告)
+    Deferred,
+}
+
+/// 使用 `readers` 个线程读取一批文件，结果与 `batch` 一一对应
+fn prefetch_batch(
+    batch: &[(PathBuf, PathBuf)],
+    limiter: &IoLimiter,
+    options: CompressOptions,
+    readers: usize,
+) -> Vec<Prefetched> {
+    let next = AtomicUsize::new(0);
+    let mut results = batch
+        .iter()
+        .map(|_| Prefetched::Deferred)
+        .collect::<Vec<_>>();
+    let read = std::thread::scope(|scope| {
+        let workers = (0..readers.min(batch.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut read = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let Some((path, _)) = batch.get(index) else {
+                            return read;
+                        };
+                        read.push((index, prefetch_file(path, limiter, options)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect::<Vec<_>>()
+    });
+    for (index, prefetched) in read {
+        results[index] = prefetched;
+    }
+    results
+}
+
+/// 将不超过 [`PREFETCH_MAX_FILE_SIZE`] 的普通文件读入内存，读取期间大小发生变化的文件同样交给写入线程处理
+fn prefetch_file(path: &Path, limiter: &IoLimiter, options: CompressOptions) -> Prefetched {
+    let read = || -> io::Result<Option<(fs::Metadata, Vec<u8>)>> {
+        if source_metadata(path, options)?.is_symlink() {
+            return Ok(None);
+        }
+        let mut file = File::open(path)?;
+        let metadata = file.metadata()?;
+        if metadata.len() > PREFETCH_MAX_FILE_SIZE {
+            return Ok(None);
+        }
+        let mut data = Vec::with_capacity(metadata.len() as usize);
+        ThrottledReader::new(&mut file, limiter).read_to_end(&mut data)?;
+        Ok((data.len() as u64 == metadata.len()).then_some((metadata, data)))
+    };
+    match read() {
+        Ok(Some((metadata, data))) => Prefetched::Data(metadata, data),
+        _ => Prefetched::Deferred,
+    }
+}
+
+/// 写入 `name` 的所有尚未写入的上级目录条目，`path` 为 `name` 对应的实际路径
+///
+/// 目录条目按从外到内的顺序写入，解压时先于其中的文件创建
+fn append_parent_dirs<W: Write>(
+    tar: &mut tar::Builder<W>,
+    path: &Path,
+    name: &Path,
+    written: &mut HashSet<PathBuf>,
+    compat: bool,
+) -> Result<()> {
+    let parents = path
+        .ancestors()
+        .skip(1)
+        .zip(name.ancestors().skip(1))
+        .take_while(|(_, name)| !name.as_os_str().is_empty())
+        .filter(|(_, name)| !written.contains(*name))
+        .collect::<Vec<_>>();
+    for (dir, dir_name) in parents.into_iter().rev() {
+        debug!(?dir, ?dir_name, "Adding directory to archive");
+        let metadata = fs::metadata(dir)?;
+        append_entry(tar, &metadata, dir_name, io::empty(), compat)?;
+        written.insert(dir_name.to_path_buf());
     }
+    Ok(())
+}
+
+/// 添加单个文件到压缩包，同时将其校验和记录到清单中，返回写入的文件大小
+fn append_file_hashed<W: Write>(
+    tar: &mut tar::Builder<W>,
+    path: &Path,
+    name: &Path,
+    manifest: &mut ChecksumManifest,
+    limiter: &IoLimiter,
+    compat: bool,
+) -> Result<u64> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let reader = ThrottledReader::new(file, limiter);
+    append_hashed(tar, &metadata, name, reader, manifest, compat)
+}
 
-    Ok(items_count)
+/// 写入内容来自 `data` 的文件条目并记录其校验和，返回写入的文件大小
+fn append_hashed<W: Write, R: Read>(
+    tar: &mut tar::Builder<W>,
+    metadata: &fs::Metadata,
+    name: &Path,
+    data: R,
+    manifest: &mut ChecksumManifest,
+    compat: bool,
+) -> Result<u64> {
+    let mut reader = HashingReader::new(data);
+    append_entry(tar, metadata, name, &mut reader, compat)?;
+
+    manifest.files.insert(archive_key(name), reader.finalize());
+    Ok(metadata.len())
 }
 
-fn append_memory_files(
+fn append_memory_files<W: Write>(
     memory_files: &[(&str, &str)],
-    tar: &mut tar::Builder<XzEncoder<File>>,
+    tar: &mut tar::Builder<W>,
 ) -> Result<usize> {
     for (name, content) in memory_files {
         let mut header = tar::Header::new_gnu();
@@ -170,48 +975,72 @@ fn append_memory_files(
     Ok(memory_files.len())
 }
 
-/// 解压缩 tar.xz 格式的归档文件到指定目录
-///
-/// # Arguments
-///
-/// * `archive_path` - 要解压的归档文件路径
-/// * `target_dir` - 解压的目标目录路径
+/// 解压压缩包中满足 `filter` 的条目到目标目录，其余条目直接跳过
 ///
-/// # Returns
-///
-/// 返回 `Result<()>`。如果解压成功则返回 `Ok(())`，否则返回相应的错误
-///
-/// # Errors
-///
-/// 此函数在以下情况会返回错误：
-/// - 无法打开归档文件
-/// - 无法创建 XZ 解码器
-/// - 解压过程中出现错误
-pub fn unpack_archive<P: AsRef<Path>>(archive_path: P, target_dir: P) -> Result<()> {
+/// `filter` 接收条目在压缩包中的相对路径，`volumes` 的含义与 [`unpack_archive_routed`] 相同
+pub fn unpack_archive_filtered<P: AsRef<Path>>(
+    archive_path: P,
+    target_dir: P,
+    volumes: &[&str],
+    filter: impl Fn(&Path) -> bool,
+) -> Result<()> {
     let archive_path = archive_path.as_ref();
     let target_dir = target_dir.as_ref();
 
-    info!(?archive_path, ?target_dir, "Starting archive extraction");
+    debug!(?target_dir, "Unpacking archive");
+    ensure_dir_exists(target_dir)?;
 
-    let file = File::open(archive_path).map_err(|e| {
+    unpack_archive_routed(archive_path, volumes, |path| {
+        filter(path).then(|| target_dir.join(path))
+    })
+}
+
+/// 流式解压压缩包，由 `route` 决定每个条目的写入位置，返回 `None` 的条目直接跳过
+///
+/// `route` 接收条目在压缩包中的相对路径。包含 `..` 的条目会被拒绝，避免写到目标目录之外。
+/// `volumes` 不为空且压缩包带有索引时只读取这些卷的数据 (见 [`open_archive_volumes`])
+pub fn unpack_archive_routed<P: AsRef<Path>>(
+    archive_path: P,
+    volumes: &[&str],
+    route: impl Fn(&Path) -> Option<PathBuf>,
+) -> Result<()> {
+    let archive_path = archive_path.as_ref();
+
+    info!(?archive_path, ?volumes, "Starting archive extraction");
+
+    let (reader, len) = open_archive_volumes(archive_path, volumes).map_err(|e| {
         error!(?e, ?archive_path, "Failed to open archive file");
         e
     })?;
-
-    debug!("Creating XZ decoder");
-    let xz = XzDecoder::new(file);
-    let mut archive = tar::Archive::new(xz);
-
-    debug!(?target_dir, "Unpacking archive");
-    ensure_dir_exists(target_dir)?;
+    // 按已读取的压缩数据量显示进度，无需预先遍历整个压缩包
+    let progress = Progress::new(t!("utils.progress.extracting"), len, ProgressUnit::Bytes);
+    let mut archive = archive_from_reader(ProgressReader::new(reader, &progress))?;
 
     // Unpack each entry while preserving paths
     let mut count = 0;
-    println!("Extracting files");
     for entry in archive.entries()? {
         let mut entry = entry?;
         let path = entry.path()?;
-        let target_path = target_dir.join(path);
+        if path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            warn!(
+                ?path,
+                "Skipping archive entry outside of the target directory"
+            );
+            continue;
+        }
+        let Some(target_path) = route(&path) else {
+            continue;
+        };
+
+        // 目录条目只创建目录，其权限由 [`apply_archive_ownership`] 统一还原，
+        // 避免只读目录导致其中的文件无法写入
+        if entry.header().entry_type().is_dir() {
+            fs::create_dir_all(&target_path)?;
+            continue;
+        }
 
         if let Some(parent) = target_path.parent().filter(|p| !p.exists()) {
             fs::create_dir_all(parent)?;
@@ -219,37 +1048,283 @@ pub fn unpack_archive<P: AsRef<Path>>(archive_path: P, target_dir: P) -> Result<
 
         debug!(path = ?target_path, "Extracting file");
         count += 1;
-        update_print!("{}. {}", count, target_path.to_string_lossy());
+        progress.inc(0, || {
+            format!(
+                "{}. {}",
+                format_number(count),
+                target_path.to_string_lossy()
+            )
+        });
         entry.unpack(&target_path)?;
     }
-    println!();
+    progress.finish();
 
-    info!(
-        ?archive_path,
-        ?target_dir,
-        "Archive extraction completed successfully"
-    );
+    info!(?archive_path, "Archive extraction completed successfully");
     Ok(())
 }
 
-/// 从压缩包中读取指定文件的内容
+/// 流式读取压缩包，将条目按 `route` 重命名后写入另一个 (未压缩的) tar 流，返回 `None` 的条目直接跳过
+///
+/// 用于通过 Docker API 把备份直接写入容器，无需先解压到磁盘。包含 `..` 的条目会被拒绝。
+/// `volumes` 的含义与 [`unpack_archive_routed`] 相同
+pub fn repack_archive_routed<P: AsRef<Path>, W: Write>(
+    archive_path: P,
+    volumes: &[&str],
+    builder: &mut tar::Builder<W>,
+    route: impl Fn(&Path) -> Option<PathBuf>,
+) -> Result<()> {
+    let archive_path = archive_path.as_ref();
+    info!(?archive_path, ?volumes, "Starting archive repacking");
+
+    let (reader, len) = open_archive_volumes(archive_path, volumes).map_err(|e| {
+        error!(?e, ?archive_path, "Failed to open archive file");
+        e
+    })?;
+    let progress = Progress::new(t!("utils.progress.extracting"), len, ProgressUnit::Bytes);
+    let mut archive = archive_from_reader(ProgressReader::new(reader, &progress))?;
+
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            warn!(
+                ?path,
+                "Skipping archive entry outside of the target directory"
+            );
+            continue;
+        }
+        let Some(target_path) = route(&path) else {
+            continue;
+        };
+
+        count += 1;
+        progress.inc(0, || {
+            format!(
+                "{}. {}",
+                format_number(count),
+                target_path.to_string_lossy()
+            )
+        });
+
+        let mut header = entry.header().clone();
+        let entry_type = header.entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let Some(link) = entry.link_name()?.map(|l| l.into_owned()) else {
+                continue;
+            };
+            // 硬链接指向压缩包内的其他条目，需要同样重命名
+            let link = if entry_type.is_hard_link() {
+                route(&link).unwrap_or(link)
+            } else {
+                link
+            };
+            builder.append_link(&mut header, &target_path, &link)?;
+        } else {
+            builder.append_data(&mut header, &target_path, &mut entry)?;
+        }
+    }
+    progress.finish();
+
+    info!(?archive_path, count, "Archive repacking completed");
+    Ok(())
+}
+
+/// tar 条目的原始头部信息
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ArchiveEntryHeader {
+    /// 解析后的完整路径 (包含 GNU 长路径扩展)
+    pub path: String,
+    /// 头部 name 字段中的原始路径
+    pub header_name: String,
+    pub size: u64,
+    /// 八进制权限，如 `0644`
+    pub mode: Option<String>,
+    /// 头部字段为空或无法解析时为 `None`
+    pub uid: Option<u64>,
+    pub gid: Option<u64>,
+    /// 条目类型标志，如 `0` (普通文件)、`5` (目录)、`L` (GNU 长路径)
+    pub typeflag: char,
+    /// 修改时间 (Unix 时间戳)
+    pub mtime: Option<u64>,
+}
+
+/// 读取压缩包中每个条目的头部信息，不解压条目内容
+pub fn read_archive_headers<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntryHeader>> {
+    let mut archive = open_archive(archive_path.as_ref())?;
+
+    let mut headers = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+        headers.push(ArchiveEntryHeader {
+            path: entry.path()?.to_string_lossy().to_string(),
+            header_name: String::from_utf8_lossy(&header.path_bytes()).to_string(),
+            size: header.size()?,
+            mode: header.mode().ok().map(|mode| format!("{mode:04o}")),
+            uid: header.uid().ok(),
+            gid: header.gid().ok(),
+            typeflag: header.entry_type().as_byte() as char,
+            mtime: header.mtime().ok(),
+        });
+    }
+
+    Ok(headers)
+}
+
+/// 按压缩包条目头中记录的数字 uid/gid 与权限，还原 `route` 映射到的已解压文件的属主与权限，
+/// 返回还原的条目数
+///
+/// 需要以 root 运行，否则 chown 会失败。目录在所有文件之后按从内到外的顺序处理，
+/// 以免先收紧目录权限导致无法访问其中的文件。不存在的目标与包含 `..` 的条目会被跳过；
+/// 非 Unix 平台上不做任何操作。`volumes` 的含义与 [`unpack_archive_routed`] 相同
+pub fn apply_archive_ownership<P: AsRef<Path>>(
+    archive_path: P,
+    volumes: &[&str],
+    route: impl Fn(&Path) -> Option<PathBuf>,
+) -> Result<usize> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let archive_path = archive_path.as_ref();
+        let (reader, _) = open_archive_volumes(archive_path, volumes)?;
+        let mut archive = archive_from_reader(reader)?;
+
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let path = entry.path()?;
+            if path
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                continue;
+            }
+            let Some(target_path) = route(&path).filter(|p| p.symlink_metadata().is_ok()) else {
+                continue;
+            };
+            let header = entry.header();
+            let owner = (header.uid()? as u32, header.gid()? as u32, header.mode()?);
+            if header.entry_type().is_dir() {
+                dirs.push((target_path, owner));
+            } else if header.entry_type().is_file() {
+                files.push((target_path, owner));
+            }
+        }
+
+        let count = files.len() + dirs.len();
+        for (path, (uid, gid, mode)) in files.into_iter().chain(dirs.into_iter().rev()) {
+            debug!(
+                ?path,
+                uid,
+                gid,
+                mode = format!("{mode:04o}"),
+                "Restoring ownership"
+            );
+            std::os::unix::fs::chown(&path, Some(uid), Some(gid))?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o7777))?;
+        }
+        info!(
+            ?archive_path,
+            count, "Ownership restored from archive headers"
+        );
+        Ok(count)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (archive_path, volumes, route);
+        Ok(0)
+    }
+}
+
+/// 读取压缩包开头的内存文件 (如 `mapping.toml`)
+///
+/// 内存文件总是位于压缩包最前面，遇到第一个不属于 [`METADATA_FILE_NAMES`] 的条目后
+/// 即停止查找，因此读取元数据不需要解压整个压缩包。`file_name` 不是元数据文件时直接返回错误
 pub fn read_file_from_archive<P: AsRef<Path>>(archive_path: P, file_name: &str) -> Result<String> {
-    let file = File::open(archive_path.as_ref())?;
-    let xz = XzDecoder::new(file);
-    let mut archive = tar::Archive::new(xz);
+    if !METADATA_FILE_NAMES.contains(&file_name) {
+        anyhow::bail!("Not a metadata file of the archive: {}", file_name)
+    }
+    let mut archive = open_archive(archive_path.as_ref())?;
 
     for entry in archive.entries()? {
         let mut entry = entry?;
-        if entry.path()?.to_string_lossy() == file_name {
+        let path = entry.path()?.to_string_lossy().to_string();
+        if path == file_name {
             let mut content = String::new();
             entry.read_to_string(&mut content)?;
             return Ok(content);
         }
+        if !METADATA_FILE_NAMES.contains(&path.as_str()) {
+            debug!(%path, file_name, "Reached end of metadata entries");
+            break;
+        }
     }
 
     anyhow::bail!("File not found in archive: {}", file_name)
 }
 
+/// 压缩包中某个顶层目录 (即一个卷) 的统计信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveDirStats {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// 完整读取压缩包得到的概要信息
+#[derive(Debug, Default)]
+pub struct ArchiveScan {
+    /// 压缩包根目录下的文件内容 (如 mapping.toml)
+    pub root_files: BTreeMap<String, String>,
+    /// 每个顶层目录的文件数与大小
+    pub dirs: BTreeMap<String, ArchiveDirStats>,
+    /// 顶层目录中每个文件的 SHA-256，键与 [`ChecksumManifest`] 一致
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// 从头到尾解码整个压缩包但不写入磁盘，用于检查压缩包是否完整
+///
+/// 压缩包被截断或数据损坏时，解码过程会返回错误
+pub fn scan_archive<P: AsRef<Path>>(archive_path: P) -> Result<ArchiveScan> {
+    let mut archive = open_archive(archive_path.as_ref())?;
+    let mut scan = ArchiveScan::default();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let mut components = path.components();
+        let first = components
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if components.as_path().as_os_str().is_empty() {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            scan.root_files.insert(first, content);
+            continue;
+        }
+
+        let mut reader = HashingReader::new(&mut entry);
+        let bytes = io::copy(&mut reader, &mut io::sink())?;
+        scan.checksums.insert(archive_key(&path), reader.finalize());
+
+        let stats = scan.dirs.entry(first).or_default();
+        stats.files += 1;
+        stats.bytes += bytes;
+    }
+
+    Ok(scan)
+}
+
 pub fn create_timestamp_filename(prefix: &str, ext: &str) -> String {
     use chrono::Local;
     let filename = format!("{}_{}{}", prefix, Local::now().format("%Y%m%d_%H%M%S"), ext);
@@ -257,6 +1332,31 @@ pub fn create_timestamp_filename(prefix: &str, ext: &str) -> String {
     filename
 }
 
+/// 解析由 [`create_timestamp_filename`] 生成的文件名中的时间戳
+///
+/// 文件名格式为 `<prefix>_<tag>_<%Y%m%d_%H%M%S><ext>`，其中 `tag` 不含下划线 (如 `all`/`partial`)，
+/// 以避免容器 `web` 误匹配到容器 `web_db` 的备份文件
+pub fn parse_timestamp_filename(file_name: &str, prefix: &str) -> Option<chrono::NaiveDateTime> {
+    let rest = file_name.strip_prefix(prefix)?.strip_prefix('_')?;
+    let stem = rest.split('.').next()?;
+    let (tag, timestamp) = stem.split_once('_')?;
+    if tag.is_empty() || timestamp.len() != 15 {
+        return None;
+    }
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S").ok()
+}
+
+/// 从 [`create_timestamp_filename`] 生成的文件名中解析出前缀 (即容器名) 与时间戳
+///
+/// 前缀本身可能包含下划线，因此从文件名末尾的时间戳向前解析，不符合该格式的文件名返回 `None`
+pub fn split_timestamp_filename(file_name: &str) -> Option<(&str, chrono::NaiveDateTime)> {
+    let mut parts = file_name.rsplitn(4, '_');
+    let (_time, _date, _tag) = (parts.next()?, parts.next()?, parts.next()?);
+    let prefix = parts.next().filter(|p| !p.is_empty())?;
+    let timestamp = parse_timestamp_filename(file_name, prefix)?;
+    Some((prefix, timestamp))
+}
+
 pub fn format_file_time(path: &PathBuf) -> Result<String> {
     let metadata = std::fs::metadata(path)?;
     let created = metadata.created()?;
@@ -346,6 +1446,26 @@ mod tests {
     use assert_fs::{TempDir, prelude::*};
     use predicates::prelude::*;
 
+    /// 并行压缩多个源并与内存文件拼接为一个归档，与备份时的流程相同 (见 [`compress_parts`])
+    fn compress_parallel_with_memory_file<P: AsRef<Path> + Sync>(
+        sources: &[P],
+        output_file: P,
+        memory_files: &[(&str, &str)],
+        exclude_patterns: &[&str],
+        jobs: usize,
+    ) -> Result<()> {
+        let output_file = output_file.as_ref();
+        let parts = compress_parts(
+            sources,
+            output_file.parent().unwrap_or_else(|| Path::new(".")),
+            &ExcludeMatcher::new(exclude_patterns)?,
+            jobs,
+            &BTreeMap::new(),
+            CompressOptions::default(),
+        )?;
+        parts.write_archive(output_file, memory_files)
+    }
+
     fn assert_content_match<P: AsRef<Path>>(a: P, b: P) -> Result<()> {
         let a = a.as_ref();
         let b = b.as_ref();
@@ -364,6 +1484,25 @@ mod tests {
         assert_eq!(filename.len(), 24); // test_YYYYMMDD_HHMMSS.txt
     }
 
+    #[test]
+    fn test_parse_timestamp_filename() {
+        let filename = create_timestamp_filename("web_all", ".tar.xz");
+        assert!(parse_timestamp_filename(&filename, "web").is_some());
+
+        let parsed = parse_timestamp_filename("web_partial_20240102_030405.tar.xz", "web");
+        assert_eq!(
+            parsed.map(|t| t.to_string()),
+            Some("2024-01-02 03:04:05".to_string())
+        );
+        assert!(parse_timestamp_filename("web_db_all_20240102_030405.tar.xz", "web").is_none());
+        assert!(parse_timestamp_filename("web_latest.tar.xz", "web").is_none());
+
+        let (prefix, _) = split_timestamp_filename("web_db_all_20240102_030405.tar.xz").unwrap();
+        assert_eq!(prefix, "web_db");
+        assert!(split_timestamp_filename("web_latest.tar.xz").is_none());
+        assert!(split_timestamp_filename("_all_20240102_030405.tar.xz").is_none());
+    }
+
     #[test]
     fn test_ensure_dir_exists() -> Result<()> {
         let temp = TempDir::new()?;
@@ -392,13 +1531,19 @@ mod tests {
 
         // 压缩
         let archive = temp.child("archive.tar.xz");
-        compress_with_memory_file(&[&source_dir], &archive, &[], &[])?;
+        compress_with_memory_file(
+            &[&source_dir],
+            &archive,
+            &[],
+            &[],
+            CompressOptions::default(),
+        )?;
         archive.assert(predicate::path::exists());
 
         // 解压
         let extract_dir = temp.child("extract");
         extract_dir.create_dir_all()?;
-        unpack_archive(&archive, &extract_dir)?;
+        unpack_archive_filtered(&archive, &extract_dir, &[], |_| true)?;
 
         // 验证
         let extracted_file = extract_dir.child(format!("{}/{}", "source", "test.txt"));
@@ -421,8 +1566,14 @@ mod tests {
         file.write_str(content)?;
 
         let archive_path = temp.child("archive.tar.xz");
-        compress_with_memory_file(&[&source], &archive_path, &[], &[])?;
-        unpack_archive(&archive_path, &extract)?;
+        compress_with_memory_file(
+            &[&source],
+            &archive_path,
+            &[],
+            &[],
+            CompressOptions::default(),
+        )?;
+        unpack_archive_filtered(&archive_path, &extract, &[], |_| true)?;
         assert_content_match(
             &file,
             &extract.child(format!(
@@ -435,6 +1586,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_metadata_without_full_decompression() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        // 伪随机数据几乎不可压缩，截断后卷数据必然损坏
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let noise = (0..512 * 1024)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect::<Vec<_>>();
+        fs::write(source.child("noise.bin").path(), &noise)?;
+
+        let archive = temp.child("archive.tar.xz");
+        compress_parallel_with_memory_file(
+            &[source.path()],
+            archive.path(),
+            &[(MAPPING_FILE_NAME, "volumes = 1")],
+            &[],
+            1,
+        )?;
+        let bytes = fs::read(archive.path())?;
+        fs::write(archive.path(), &bytes[..bytes.len() / 2])?;
+
+        assert!(scan_archive(archive.path()).is_err());
+        assert_eq!(
+            read_file_from_archive(archive.path(), MAPPING_FILE_NAME)?,
+            "volumes = 1"
+        );
+        let missing = read_file_from_archive(archive.path(), CHECKSUMS_FILE_NAME).unwrap_err();
+        assert!(missing.to_string().contains("File not found"));
+        Ok(())
+    }
+
     #[test]
     fn test_read_file_from_archive() -> Result<()> {
         let temp = TempDir::new()?;
@@ -442,15 +1631,25 @@ mod tests {
 
         // 创建一个包含内存文件的压缩包
         let test_content = "Hello from memory file!";
-        let memory_files = vec![("test.txt", test_content)];
-        compress_with_memory_file(&[temp.path()], &archive, &memory_files, &[])?;
+        let memory_files = vec![(MAPPING_FILE_NAME, test_content)];
+        compress_with_memory_file(
+            &[temp.path()],
+            &archive,
+            &memory_files,
+            &[],
+            CompressOptions::default(),
+        )?;
 
         // 从压缩包中读取文件
-        let content = read_file_from_archive(&archive, "test.txt")?;
+        let content = read_file_from_archive(&archive, MAPPING_FILE_NAME)?;
         assert_eq!(content, test_content);
 
-        // 测试读取不存在的文件
-        let result = read_file_from_archive(&archive, "nonexistent.txt");
+        // 测试读取不存在的元数据文件
+        let result = read_file_from_archive(&archive, INDEX_FILE_NAME);
+        assert!(result.is_err());
+
+        // 只能读取元数据文件
+        let result = read_file_from_archive(&archive, "data.txt");
         assert!(result.is_err());
 
         Ok(())
@@ -472,12 +1671,18 @@ mod tests {
             ("memory1.txt", "Memory file 1 content"),
             ("memory2.txt", "Memory file 2 content"),
         ];
-        compress_with_memory_file(&[&source_dir], &archive, &memory_files, &[])?;
+        compress_with_memory_file(
+            &[&source_dir],
+            &archive,
+            &memory_files,
+            &[],
+            CompressOptions::default(),
+        )?;
 
         // 验证压缩包内容
         let extract_dir = temp.child("extract");
         extract_dir.create_dir_all()?;
-        unpack_archive(&archive, &extract_dir)?;
+        unpack_archive_filtered(&archive, &extract_dir, &[], |_| true)?;
 
         // 检查内存文件
         let memory_file1 = extract_dir.child("memory1.txt");
@@ -495,4 +1700,468 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_read_archive_headers() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        let long_name = "a".repeat(120);
+        source.child(&long_name).write_str("hello")?;
+
+        let archive = temp.child("archive.tar.xz");
+        compress_parallel_with_memory_file(
+            &[source.path()],
+            archive.path(),
+            &[("mapping.toml", "volumes = 1")],
+            &[],
+            1,
+        )?;
+
+        let headers = read_archive_headers(archive.path())?;
+        assert_eq!(headers[0].path, "mapping.toml");
+        assert_eq!(headers[0].mode.as_deref(), Some("0644"));
+        assert_eq!(headers[0].uid, None);
+
+        let file = headers
+            .iter()
+            .find(|h| h.path == format!("vol1/{long_name}"))
+            .expect("long path entry");
+        assert_eq!(file.size, 5);
+        assert_eq!(file.typeflag, '0');
+        assert_ne!(file.header_name, file.path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_archive_detects_truncation() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        source.child("data.txt").write_str("hello")?;
+        source.child("nested/more.txt").write_str("world!")?;
+
+        let archive = temp.child("archive.tar.xz");
+        compress_parallel_with_memory_file(
+            &[source.path()],
+            archive.path(),
+            &[("mapping.toml", "volumes = 1")],
+            &[],
+            1,
+        )?;
+
+        let scan = scan_archive(archive.path())?;
+        assert_eq!(scan.root_files["mapping.toml"], "volumes = 1");
+        assert_eq!(
+            scan.dirs["vol1"],
+            ArchiveDirStats {
+                files: 2,
+                bytes: 11
+            }
+        );
+        assert!(scan.checksums.contains_key("vol1/nested/more.txt"));
+
+        let bytes = fs::read(archive.path())?;
+        let truncated = temp.child("truncated.tar.xz");
+        fs::write(truncated.path(), &bytes[..bytes.len() - 16])?;
+        assert!(scan_archive(truncated.path()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_compress_and_extract() -> Result<()> {
+        let temp = TempDir::new()?;
+
+        let sources = ["vol1", "vol2", "vol3"]
+            .iter()
+            .map(|name| -> Result<_> {
+                let dir = temp.child(name);
+                dir.create_dir_all()?;
+                dir.child("data.txt").write_str(name)?;
+                Ok(dir)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let sources = sources.iter().map(|d| d.path()).collect::<Vec<_>>();
+
+        let archive = temp.child("archive.tar.xz");
+        compress_parallel_with_memory_file(
+            &sources,
+            archive.path(),
+            &[("mapping.toml", "volumes = 3")],
+            &[],
+            2,
+        )?;
+        assert_eq!(
+            read_file_from_archive(&archive, "mapping.toml")?,
+            "volumes = 3"
+        );
+
+        let extract_dir = temp.child("extract");
+        unpack_archive_filtered(archive.path(), extract_dir.path(), &[], |_| true)?;
+        for name in ["vol1", "vol2", "vol3"] {
+            extract_dir
+                .child(format!("{}/data.txt", name))
+                .assert(predicate::str::contains(name));
+        }
+
+        Ok(())
+    }
+
+    /// 写入 `size` 字节可压缩的伪随机文本
+    fn write_compressible(path: &Path, size: usize, seed: u64) -> Result<()> {
+        const WORDS: [&[u8]; 8] = [
+            b"volume ",
+            b"backup ",
+            b"docker ",
+            b"restore ",
+            b"archive ",
+            b"stream ",
+            b"block ",
+            b"\n",
+        ];
+        let mut state = seed;
+        let mut data = Vec::with_capacity(size + 16);
+        while data.len() < size {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            data.extend_from_slice(WORDS[(state >> 61) as usize]);
+        }
+        data.truncate(size);
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_multithreaded_xz_round_trip() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        for i in 0..4 {
+            write_compressible(&source.path().join(format!("{i}.txt")), 512 * 1024, i)?;
+        }
+
+        let compress = |threads, file: &str| -> Result<PathBuf> {
+            let parts = compress_parts(
+                &[source.path()],
+                temp.path(),
+                &ExcludeMatcher::default(),
+                4,
+                &BTreeMap::new(),
+                CompressOptions {
+                    xz: XzOptions {
+                        threads,
+                        block_size: Some(256 * 1024),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )?;
+            let archive = temp.path().join(file);
+            parts.write_archive(&archive, &[("mapping.toml", "volumes = 1")])?;
+            Ok(archive)
+        };
+        let single = compress(Some(1), "single.tar.xz")?;
+        let multi = compress(None, "multi.tar.xz")?;
+        assert!(fs::read(&single)? != fs::read(&multi)?);
+
+        let extract_dir = temp.child("extract");
+        unpack_archive_filtered(multi.as_path(), extract_dir.path(), &[], |_| true)?;
+        for i in 0..4 {
+            assert_eq!(
+                fs::read(extract_dir.child(format!("vol1/{i}.txt")).path())?,
+                fs::read(source.child(format!("{i}.txt")).path())?
+            );
+        }
+        assert_eq!(
+            scan_archive(&multi)?.checksums,
+            scan_archive(&single)?.checksums
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "Benchmark with a ~500MB fixture, run manually with --ignored --nocapture"]
+    fn bench_multithreaded_xz_500mb() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.create_dir_all()?;
+        for i in 0..50 {
+            write_compressible(&source.path().join(format!("{i}.bin")), 10 << 20, i)?;
+        }
+        let jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        let compress = |threads| -> Result<(std::time::Duration, u64)> {
+            let start = std::time::Instant::now();
+            let parts = compress_parts(
+                &[source.path()],
+                temp.path(),
+                &ExcludeMatcher::default(),
+                jobs,
+                &BTreeMap::new(),
+                CompressOptions {
+                    xz: XzOptions {
+                        threads,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )?;
+            Ok((start.elapsed(), parts.part_lengths()?.iter().sum()))
+        };
+        let (single_time, single_size) = compress(Some(1))?;
+        let (multi_time, multi_size) = compress(None)?;
+        println!(
+            "500MB fixture: single-threaded {single_time:?} ({single_size} bytes), \
+             {jobs} threads {multi_time:?} ({multi_size} bytes)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefetched_reads_match_sequential() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        for i in 0..5000 {
+            source
+                .child(format!("{}/{i}.txt", i % 50))
+                .write_str(&format!("file {i}"))?;
+        }
+        let files = collect_source_files(source.path(), &ExcludeMatcher::default(), false)?;
+        let progress = Progress::new("test", files.len() as u64, ProgressUnit::Items);
+
+        let archive = |readers| -> Result<(Vec<u8>, ChecksumManifest)> {
+            let mut tar = tar::Builder::new(Vec::new());
+            let mut manifest = ChecksumManifest::default();
+            append_items_prefetched(
+                &files,
+                &mut tar,
+                &mut manifest,
+                &progress,
+                &IoLimiter::new(None),
+                CompressOptions::default(),
+                readers,
+            )?;
+            Ok((tar.into_inner()?, manifest))
+        };
+        let (sequential, sequential_manifest) = archive(0)?;
+        let (prefetched, prefetched_manifest) = archive(PREFETCH_READERS)?;
+
+        assert!(sequential == prefetched);
+        assert_eq!(sequential_manifest.files, prefetched_manifest.files);
+        assert_eq!(prefetched_manifest.files.len(), 5000);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlinks_outside_source_are_not_followed() -> Result<()> {
+        let temp = TempDir::new()?;
+        let outside = temp.child("outside");
+        outside.child("secret.txt").write_str("secret")?;
+        let source = temp.child("vol1");
+        source.child("data.txt").write_str("data")?;
+        source
+            .child("link.txt")
+            .symlink_to_file(outside.child("secret.txt").path())?;
+        source.child("dir").symlink_to_dir(outside.path())?;
+
+        let compress = |options: CompressOptions, name: &str| -> Result<PathBuf> {
+            let parts = compress_parts(
+                &[source.path()],
+                temp.path(),
+                &ExcludeMatcher::default(),
+                1,
+                &BTreeMap::new(),
+                options,
+            )?;
+            let archive = temp.path().join(name);
+            parts.write_archive(&archive, &[("mapping.toml", "volumes = 1")])?;
+            Ok(archive)
+        };
+
+        let archive = compress(CompressOptions::default(), "links.tar.xz")?;
+        let mut typeflags = read_archive_headers(&archive)?
+            .into_iter()
+            .map(|h| (h.path, h.typeflag))
+            .collect::<Vec<_>>();
+        typeflags.sort();
+        assert_eq!(
+            typeflags,
+            [
+                ("mapping.toml".to_string(), '0'),
+                ("vol1/data.txt".to_string(), '0'),
+                ("vol1/dir".to_string(), '2'),
+                ("vol1/link.txt".to_string(), '2'),
+            ]
+        );
+        let out = temp.child("out");
+        unpack_archive_filtered(archive.as_path(), out.path(), &[], |_| true)?;
+        assert_eq!(
+            fs::read_link(out.child("vol1/link.txt"))?,
+            outside.child("secret.txt").path()
+        );
+        assert_eq!(fs::read_link(out.child("vol1/dir"))?, outside.path());
+
+        let archive = compress(
+            CompressOptions {
+                follow_symlinks: true,
+                ..Default::default()
+            },
+            "followed.tar.xz",
+        )?;
+        let scan = scan_archive(&archive)?;
+        assert!(scan.checksums.contains_key("vol1/link.txt"));
+        assert!(scan.checksums.contains_key("vol1/dir/secret.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compress_parts_counts_oversized_files() -> Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.child("small.txt").write_str("ok")?;
+        source.child("large.bin").write_binary(&[0u8; 64])?;
+
+        let parts = compress_parts(
+            &[source.path()],
+            temp.path(),
+            &ExcludeMatcher::default().with_larger_than(Some(16)),
+            1,
+            &BTreeMap::new(),
+            CompressOptions::default(),
+        )?;
+        assert_eq!(parts.items_count(), 1);
+        assert_eq!(parts.oversized(), (1, 64));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_preserve_ownership_round_trip() -> Result<()> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let temp = TempDir::new()?;
+        let source = temp.child("vol1");
+        source.child("sub/data.txt").write_str("data")?;
+        fs::set_permissions(source.child("sub"), fs::Permissions::from_mode(0o750))?;
+        fs::set_permissions(
+            source.child("sub/data.txt"),
+            fs::Permissions::from_mode(0o600),
+        )?;
+
+        let parts = compress_parts(
+            &[source.path()],
+            temp.path(),
+            &ExcludeMatcher::default(),
+            1,
+            &BTreeMap::new(),
+            CompressOptions {
+                ownership: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(parts.items_count(), 1);
+        let archive = temp.child("archive.tar.xz");
+        parts.write_archive(archive.path(), &[("mapping.toml", "volumes = 1")])?;
+
+        let headers = read_archive_headers(archive.path())?;
+        let typeflags = headers
+            .iter()
+            .map(|h| (h.path.trim_end_matches('/'), h.typeflag))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            typeflags,
+            [
+                ("mapping.toml", '0'),
+                ("vol1", '5'),
+                ("vol1/sub", '5'),
+                ("vol1/sub/data.txt", '0')
+            ]
+        );
+        let uid = fs::metadata(source.path())?.uid() as u64;
+        assert!(headers[1..].iter().all(|h| h.uid == Some(uid)));
+
+        let out = temp.child("out");
+        unpack_archive_filtered(archive.path(), out.path(), &[], |p| p.starts_with("vol1"))?;
+        let mode = |path: &Path| -> Result<u32> { Ok(fs::metadata(path)?.mode() & 0o7777) };
+        fs::set_permissions(out.child("vol1/sub"), fs::Permissions::from_mode(0o755))?;
+        fs::set_permissions(
+            out.child("vol1/sub/data.txt"),
+            fs::Permissions::from_mode(0o644),
+        )?;
+
+        let count = apply_archive_ownership(archive.path(), &[], |p| {
+            p.starts_with("vol1").then(|| out.path().join(p))
+        })?;
+        assert_eq!(count, 3);
+        assert_eq!(mode(&out.child("vol1/sub"))?, 0o750);
+        assert_eq!(mode(&out.child("vol1/sub/data.txt"))?, 0o600);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_compat_archive_extracts_with_system_tar() -> Result<()> {
+        let temp = TempDir::new()?;
+        let long_dir = ["nested"; 40].join("/");
+        for name in ["vol1", "vol2", "vol3"] {
+            temp.child(format!("{name}/data.txt")).write_str(name)?;
+        }
+        temp.child(format!("vol2/{long_dir}/deep.txt"))
+            .write_str("deep")?;
+        let sources = ["vol1", "vol2", "vol3"].map(|name| temp.path().join(name));
+
+        let parts = compress_parts(
+            &sources,
+            temp.path(),
+            &ExcludeMatcher::default(),
+            2,
+            &BTreeMap::new(),
+            CompressOptions {
+                compat: true,
+                ..Default::default()
+            },
+        )?;
+        let archive = temp.child("archive.tar.xz");
+        parts.write_archive(archive.path(), &[("mapping.toml", "volumes = 3")])?;
+
+        let extract_dir = temp.child("extract");
+        extract_dir.create_dir_all()?;
+        let status = std::process::Command::new("tar")
+            .arg("-xJf")
+            .arg(archive.path())
+            .arg("-C")
+            .arg(extract_dir.path())
+            .status();
+        let Ok(status) = status else {
+            // 没有系统 tar 时无法验证
+            return Ok(());
+        };
+        assert!(status.success());
+
+        extract_dir
+            .child("mapping.toml")
+            .assert(predicate::str::contains("volumes = 3"));
+        for name in ["vol1", "vol2", "vol3"] {
+            extract_dir
+                .child(format!("{name}/data.txt"))
+                .assert(predicate::str::contains(name));
+        }
+        extract_dir
+            .child(format!("vol2/{long_dir}/deep.txt"))
+            .assert(predicate::str::contains("deep"));
+
+        // rdbkp2 自身仍然可以读取兼容模式的归档
+        assert_eq!(
+            read_file_from_archive(&archive, "mapping.toml")?,
+            "volumes = 3"
+        );
+        let own_dir = temp.child("own");
+        unpack_archive_filtered(archive.path(), own_dir.path(), &[], |_| true)?;
+        own_dir
+            .child(format!("vol2/{long_dir}/deep.txt"))
+            .assert(predicate::str::contains("deep"));
+        Ok(())
+    }
 }
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 #[macro_export]
 macro_rules! log_bail {
     // 带格式化参数的版本
@@ -12,7 +15,7 @@ macro_rules! log_bail {
             "TRACE" => tracing::trace!($fmt, $($arg)*),
             _ => tracing::debug!($fmt, $($arg)*),
         }
-        println!($fmt, $($arg)*);
+        $crate::utils::print_message(format_args!($fmt, $($arg)*));
         anyhow::bail!($fmt, $($arg)*);
 
     }};
@@ -29,11 +32,15 @@ macro_rules! log_bail {
             "TRACE" => tracing::trace!($msg),
             _ => tracing::debug!($msg),
         }
-        println!($msg);
+        $crate::utils::print_message(format_args!($msg));
         anyhow::bail!($msg);
     }};
 }
 
+/// 记录日志并将消息打印到 stdout
+///
+/// 日志写入 stderr (`--log-format json` 时为 JSON)，打印的提示信息不会混入日志流；
+/// stdout 被保留给机器可读的输出时 (见 [`StdoutReservation`]) 只记录日志
 #[macro_export]
 macro_rules! log_println {
     // 带格式化参数的版本
@@ -48,7 +55,7 @@ macro_rules! log_println {
             "TRACE" => tracing::trace!($fmt, $($arg)*),
             _ => tracing::debug!($fmt, $($arg)*),
         }
-        println!($fmt, $($arg)*);
+        $crate::utils::print_message(format_args!($fmt, $($arg)*));
         // anyhow::bail!($fmt, $($arg)*);
     }};
 
@@ -64,11 +71,78 @@ macro_rules! log_println {
             "TRACE" => tracing::trace!($msg),
             _ => tracing::debug!($msg),
         }
-        println!($msg);
+        $crate::utils::print_message(format_args!($msg));
         // anyhow::bail!($msg);
     }};
 }
 
+/// 为机器可读的输出保留 stdout 的作用域数量
+static STDOUT_RESERVATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// 守卫存在期间 stdout 只用于机器可读的输出 (如 `--print-mapping json`)，
+/// `log_println!`/`log_bail!` 的提示信息只写入日志，保证 stdout 可以被直接解析
+pub struct StdoutReservation(bool);
+
+impl StdoutReservation {
+    /// `reserve` 为假时不做任何事，便于按输出格式决定是否保留
+    pub fn acquire(reserve: bool) -> Self {
+        if reserve {
+            STDOUT_RESERVATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        Self(reserve)
+    }
+}
+
+impl Drop for StdoutReservation {
+    fn drop(&mut self) {
+        if self.0 {
+            STDOUT_RESERVATIONS.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// 打印提示信息，stdout 被保留时不打印
+pub fn print_message(args: fmt::Arguments) {
+    if STDOUT_RESERVATIONS.load(Ordering::SeqCst) == 0 {
+        print_output(args);
+    }
+}
+
+/// 打印命令结果 (如 JSON 文档)，不受 [`StdoutReservation`] 影响
+pub fn print_output(args: fmt::Arguments) {
+    #[cfg(test)]
+    if CAPTURED_STDOUT.with_borrow_mut(|captured| {
+        captured
+            .as_mut()
+            .map(|captured| captured.push_str(&format!("{args}\n")))
+            .is_some()
+    }) {
+        return;
+    }
+    println!("{args}");
+}
+
+#[cfg(test)]
+thread_local! {
+    static CAPTURED_STDOUT: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
+/// 在当前线程上捕获 [`print_message`] 与 [`print_output`] 的输出，用于检查 stdout 的完整内容
+#[cfg(test)]
+pub struct StdoutCapture;
+
+#[cfg(test)]
+impl StdoutCapture {
+    pub fn start() -> Self {
+        CAPTURED_STDOUT.set(Some(String::new()));
+        Self
+    }
+
+    pub fn finish(self) -> String {
+        CAPTURED_STDOUT.take().unwrap_or_default()
+    }
+}
+
 #[macro_export]
 macro_rules! update_print {
     // 带格式化参数的版本
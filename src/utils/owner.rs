@@ -0,0 +1,118 @@
+use anyhow::Result;
+use std::fmt;
+use std::path::Path;
+
+/// 文件的所有者 (数字形式的用户 ID 与组 ID)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileOwner {
+    pub uid: u32,
+    /// 为 `None` 时只修改所有者，不修改所属组
+    pub gid: Option<u32>,
+}
+
+impl FileOwner {
+    /// 解析 `user[:group]` 格式的所有者，用户与组均可使用名称或数字 ID
+    ///
+    /// 名称通过 `/etc/passwd` 与 `/etc/group` 查找
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (user, group) = match spec.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (spec, None),
+        };
+
+        let uid = resolve_id(user, "/etc/passwd")
+            .ok_or_else(|| anyhow::anyhow!(t!("utils.owner.unknown_user", "user" = user)))?;
+        let gid = group
+            .filter(|g| !g.is_empty())
+            .map(|group| {
+                resolve_id(group, "/etc/group").ok_or_else(|| {
+                    anyhow::anyhow!(t!("utils.owner.unknown_group", "group" = group))
+                })
+            })
+            .transpose()?;
+
+        Ok(Self { uid, gid })
+    }
+
+    /// 通过 sudo 运行时，调用 sudo 的原始用户 (来自 `SUDO_UID`/`SUDO_GID` 环境变量)
+    pub fn sudo_invoker() -> Option<Self> {
+        let uid = std::env::var("SUDO_UID").ok()?.parse().ok()?;
+        let gid = std::env::var("SUDO_GID")
+            .ok()
+            .and_then(|gid| gid.parse().ok());
+        Some(Self { uid, gid })
+    }
+}
+
+impl fmt::Display for FileOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.gid {
+            Some(gid) => write!(f, "{}:{}", self.uid, gid),
+            None => write!(f, "{}", self.uid),
+        }
+    }
+}
+
+/// 将名称或数字解析为 ID，名称在 `passwd`/`group` 格式的数据库文件中查找
+fn resolve_id(name: &str, database: &str) -> Option<u32> {
+    if let Ok(id) = name.parse() {
+        return Some(id);
+    }
+    let content = std::fs::read_to_string(database).ok()?;
+    find_id(&content, name)
+}
+
+/// 在 `name:password:id:...` 格式的内容中查找名称对应的 ID
+fn find_id(content: &str, name: &str) -> Option<u32> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            (fields.next()? == name)
+                .then(|| fields.nth(1)?.parse().ok())
+                .flatten()
+        })
+}
+
+/// 修改文件/目录的所有者，Windows 下不做任何操作
+pub fn chown_path(path: &Path, owner: &FileOwner) -> Result<()> {
+    #[cfg(unix)]
+    std::os::unix::fs::chown(path, Some(owner.uid), owner.gid)?;
+
+    #[cfg(not(unix))]
+    let _ = (path, owner);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_owner() -> Result<()> {
+        assert_eq!(
+            FileOwner::parse("1000:1001")?,
+            FileOwner {
+                uid: 1000,
+                gid: Some(1001)
+            }
+        );
+        assert_eq!(
+            FileOwner::parse("1000")?,
+            FileOwner {
+                uid: 1000,
+                gid: None
+            }
+        );
+        assert_eq!(FileOwner::parse("1000:")?.gid, None);
+        assert!(FileOwner::parse("no-such-user-rdbkp2").is_err());
+
+        let passwd =
+            "# comment\nroot:x:0:0:root:/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/sh";
+        assert_eq!(find_id(passwd, "alice"), Some(1000));
+        assert_eq!(find_id(passwd, "bob"), None);
+        Ok(())
+    }
+}
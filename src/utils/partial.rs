@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 尚未写完的输出文件，进程被中断时由 [`remove_partial_files`] 删除
+static PARTIAL_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// 正在写入的输出文件，值被丢弃前都视为未完成
+///
+/// 丢弃时只取消登记、不删除文件；正常返回与出错时的清理仍由文件的所有者负责
+#[derive(Debug)]
+pub(crate) struct PartialFile {
+    path: PathBuf,
+}
+
+impl PartialFile {
+    pub fn track(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(mut files) = PARTIAL_FILES.lock() {
+            files.push(path.clone());
+        }
+        Self { path }
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if let Ok(mut files) = PARTIAL_FILES.lock()
+            && let Some(index) = files.iter().position(|path| *path == self.path)
+        {
+            files.swap_remove(index);
+        }
+    }
+}
+
+/// 删除所有登记为未完成的输出文件，返回实际删除的文件
+pub(crate) fn remove_partial_files() -> Vec<PathBuf> {
+    match PARTIAL_FILES.lock() {
+        Ok(mut files) => remove_files(std::mem::take(&mut *files)),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn remove_files(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .filter(|path| match std::fs::remove_file(path) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!(?path, ?e, "Failed to remove partial file");
+                false
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_tracked(path: &Path) -> bool {
+        PARTIAL_FILES.lock().unwrap().iter().any(|p| p == path)
+    }
+
+    #[test]
+    fn tracks_files_until_written() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let partial = temp_dir.path().join("partial.tar.xz");
+        std::fs::write(&partial, "part")?;
+
+        let writing = PartialFile::track(&partial);
+        assert!(is_tracked(&partial));
+        drop(writing);
+        assert!(!is_tracked(&partial));
+        assert!(partial.exists());
+
+        // 其他测试可能同时登记了自己的文件，这里不调用 remove_partial_files
+        let missing = temp_dir.path().join("missing.tar.xz");
+        assert_eq!(
+            remove_files(vec![partial.clone(), missing]),
+            vec![partial.clone()]
+        );
+        assert!(!partial.exists());
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use anyhow::Result;
 use tracing::{debug, error, info};
 
-use crate::log_bail;
+use crate::{config::Config, log_bail};
 
 /// 获取默认的备份目录
 ///
@@ -131,6 +131,31 @@ pub(crate) fn absolute_canonicalize_path(path: &Path) -> io::Result<PathBuf> {
     }
 }
 
+/// 按全局配置将路径转换为绝对路径
+/// - 默认使用 [`absolute_canonicalize_path`]，路径不存在时报错
+/// - 启用 `--no-canonicalize` 时使用 [`absolute_simplified_path`]，不访问文件系统
+pub(crate) fn resolve_path(path: &Path) -> io::Result<PathBuf> {
+    let no_canonicalize = Config::global().map(|c| c.no_canonicalize).unwrap_or(false);
+
+    if no_canonicalize {
+        absolute_simplified_path(path)
+    } else {
+        absolute_canonicalize_path(path)
+    }
+}
+
+/// 将路径转换为绝对路径并简化冗余部分 (不访问文件系统)
+/// - 如果是相对路径，则基于当前工作目录转换为绝对路径
+/// - 不解析符号链接，也不检查路径是否存在
+pub(crate) fn absolute_simplified_path(path: &Path) -> io::Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    Ok(simplify_absolute_path(&absolute))
+}
+
 #[allow(dead_code)]
 /// 将路径转换为绝对路径并尽可能规范化
 /// - 如果是相对路径，则基于当前工作目录转换为绝对路径
@@ -160,7 +185,6 @@ pub(crate) fn ensure_absolute_canonical<P: AsRef<Path>>(
     }
 }
 
-#[allow(dead_code)]
 /// 简化绝对路径的冗余部分（不依赖文件系统存在性）
 fn simplify_absolute_path(path: &Path) -> PathBuf {
     let mut stack = Vec::new();
@@ -215,6 +239,18 @@ mod tests {
         assert_eq!(simplified, PathBuf::from("/foo/baz"));
     }
 
+    #[test]
+    fn test_absolute_simplified_path_skips_filesystem() -> anyhow::Result<()> {
+        let path = Path::new("missing/./dir/../file.txt");
+        let simplified = absolute_simplified_path(path)?;
+        assert_eq!(
+            simplified,
+            std::env::current_dir()?.join("missing/file.txt")
+        );
+        assert!(absolute_canonicalize_path(path).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_absolute_canonicalize_path_with_tempdir() -> anyhow::Result<()> {
         let temp_dir = assert_fs::TempDir::new()?;
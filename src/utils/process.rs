@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+/// 某个进程打开的、位于指定目录下的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenHandle {
+    /// 进程 ID
+    pub pid: u32,
+    /// 被打开的文件路径
+    pub path: PathBuf,
+}
+
+/// 查找仍打开了 `roots` 目录 (含子目录) 下文件的进程
+///
+/// 通过扫描 `/proc/<pid>/fd` 实现，仅在 Linux 上可用。
+/// 这是一个尽力而为的检查：无权限读取的进程会被跳过。
+///
+/// # Returns
+///
+/// * `Some(handles)` - 检查完成，返回找到的句柄 (可能为空)
+/// * `None` - 当前平台或环境不支持该检查
+pub fn find_open_handles(roots: &[&Path]) -> Option<Vec<OpenHandle>> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    let roots = roots
+        .iter()
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.to_path_buf()))
+        .collect::<Vec<_>>();
+    let proc_entries = std::fs::read_dir("/proc").ok()?;
+    let current_pid = std::process::id();
+
+    let mut handles = Vec::new();
+    for entry in proc_entries.filter_map(|e| e.ok()) {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == current_pid {
+            continue;
+        }
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.filter_map(|e| e.ok()) {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            if roots.iter().any(|root| target.starts_with(root)) {
+                handles.push(OpenHandle { pid, path: target });
+            }
+        }
+    }
+
+    Some(handles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::process::{Command, Stdio};
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn finds_files_held_by_other_processes() {
+        let dir = TempDir::new().unwrap();
+        let held = dir.path().join("held.txt");
+        File::create(&held).unwrap();
+
+        let mut child = Command::new("sleep")
+            .arg("5")
+            .stdin(Stdio::from(File::open(&held).unwrap()))
+            .spawn()
+            .unwrap();
+
+        let handles = find_open_handles(&[dir.path()]).expect("supported on linux");
+        child.kill().ok();
+        child.wait().ok();
+
+        assert!(handles.iter().any(|h| h.pid == child.id()));
+        assert!(handles.iter().all(|h| h.pid != std::process::id()));
+    }
+}
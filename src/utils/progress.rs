@@ -0,0 +1,324 @@
+use std::collections::VecDeque;
+use std::io::{IsTerminal, Read};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::log_println;
+use crate::print_progress;
+
+use super::format::{format_bytes, format_number};
+use super::out::PROGRESS_BAR_WIDTH;
+
+/// 进度条的最小刷新间隔，避免频繁刷新拖慢压缩/解压
+const BAR_RENDER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 非终端输出时打印进度日志的间隔
+const LOG_RENDER_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 计算预计剩余时间时参与平均的吞吐量采样的时间窗口
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(10);
+
+/// 进度的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProgressMode {
+    /// 终端内的 ANSI 进度条
+    Bar,
+    /// 定期打印一行进度日志，适用于 `--verbose` 或输出不是终端 (如 CI 日志) 的情况
+    Log,
+    /// 不显示进度 (`--no-progress`)
+    Hidden,
+}
+
+impl ProgressMode {
+    /// 根据全局配置与标准输出是否为终端选择显示方式
+    pub fn current() -> Self {
+        let (no_progress, log_progress) = Config::global()
+            .map(|c| (c.no_progress, c.verbose || c.log_progress))
+            .unwrap_or((false, false));
+        if no_progress {
+            Self::Hidden
+        } else if log_progress || !std::io::stdout().is_terminal() {
+            Self::Log
+        } else {
+            Self::Bar
+        }
+    }
+}
+
+/// 进度的计量单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProgressUnit {
+    /// 文件/条目个数
+    Items,
+    /// 字节数
+    Bytes,
+}
+
+impl ProgressUnit {
+    fn format(&self, n: u64) -> String {
+        match self {
+            Self::Items => format_number(n),
+            Self::Bytes => format_bytes(n),
+        }
+    }
+}
+
+/// 可在多个线程间共享的进度计数器
+pub(crate) struct Progress {
+    label: String,
+    mode: ProgressMode,
+    unit: ProgressUnit,
+    total: u64,
+    current: AtomicU64,
+    last_render: Mutex<Option<Instant>>,
+    volumes: Option<VolumeSummary>,
+}
+
+/// 同时压缩多个卷时的整体进度，代替逐个文件的进度消息
+struct VolumeSummary {
+    /// 卷的总数
+    total: u64,
+    /// 已开始处理的卷数
+    started: AtomicU64,
+    /// 最近的吞吐量采样 (时间，已处理的字节数)，用于估算剩余时间
+    samples: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl VolumeSummary {
+    /// 记录一次采样，按时间窗口内的平均吞吐量估算处理剩余 `remaining` 字节所需的时间
+    fn eta(&self, now: Instant, current: u64, remaining: u64) -> Option<Duration> {
+        let mut samples = self.samples.lock().ok()?;
+        samples.push_back((now, current));
+        while samples.len() > 2
+            && samples
+                .front()
+                .is_some_and(|(time, _)| now.duration_since(*time) > THROUGHPUT_WINDOW)
+        {
+            samples.pop_front();
+        }
+        let (first_time, first_bytes) = *samples.front()?;
+        let elapsed = now.duration_since(first_time).as_secs_f64();
+        let rate = current.saturating_sub(first_bytes) as f64 / elapsed;
+        (rate.is_finite() && rate > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / rate))
+    }
+}
+
+impl Progress {
+    /// 创建进度计数器，`total` 为以 `unit` 计量的总工作量
+    pub fn new(label: impl Into<String>, total: u64, unit: ProgressUnit) -> Self {
+        Self::with_mode(label, total, unit, ProgressMode::current())
+    }
+
+    pub fn with_mode(
+        label: impl Into<String>,
+        total: u64,
+        unit: ProgressUnit,
+        mode: ProgressMode,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            mode,
+            unit,
+            total: total.max(1),
+            current: AtomicU64::new(0),
+            last_render: Mutex::new(None),
+            volumes: None,
+        }
+    }
+
+    /// 创建按字节计量的多卷整体进度，显示已开始的卷数、已处理/总大小与预计剩余时间
+    pub fn volumes(label: impl Into<String>, total_bytes: u64, volumes: u64) -> Self {
+        Self {
+            volumes: Some(VolumeSummary {
+                total: volumes,
+                started: AtomicU64::new(0),
+                samples: Mutex::new(VecDeque::new()),
+            }),
+            ..Self::new(label, total_bytes, ProgressUnit::Bytes)
+        }
+    }
+
+    /// 记录开始处理下一个卷
+    pub fn start_volume(&self) {
+        if let Some(volumes) = &self.volumes {
+            volumes.started.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 记录处理完一个大小为 `bytes` 的文件，按进度的计量单位增加
+    pub fn inc_file(&self, bytes: u64, message: impl FnOnce() -> String) {
+        let delta = match self.unit {
+            ProgressUnit::Items => 1,
+            ProgressUnit::Bytes => bytes,
+        };
+        self.inc(delta, message);
+    }
+
+    /// 增加进度但不刷新显示
+    pub fn add(&self, delta: u64) {
+        self.current.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// 增加进度，`message` 仅在需要刷新显示时才会被调用
+    pub fn inc(&self, delta: u64, message: impl FnOnce() -> String) {
+        let current = self.current.fetch_add(delta, Ordering::Relaxed) + delta;
+        self.render(current, message, false);
+    }
+
+    /// 完成进度，进度条会补满到 100%
+    pub fn finish(&self) {
+        self.render(self.total, String::new, true);
+    }
+
+    fn render(&self, current: u64, message: impl FnOnce() -> String, force: bool) {
+        let interval = match self.mode {
+            ProgressMode::Hidden => return,
+            ProgressMode::Bar => BAR_RENDER_INTERVAL,
+            ProgressMode::Log => LOG_RENDER_INTERVAL,
+        };
+
+        let Ok(mut last_render) = self.last_render.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if !force && last_render.is_some_and(|last| now.duration_since(last) < interval) {
+            return;
+        }
+        *last_render = Some(now);
+
+        let current = current.min(self.total);
+        let message = match &self.volumes {
+            Some(volumes) => self.volume_summary(volumes, now, current),
+            None => message(),
+        };
+        match self.mode {
+            ProgressMode::Bar => {
+                // 进度条内显示原始数值，字节数按 KiB 显示以免数字过长
+                let (current, total) = match self.unit {
+                    ProgressUnit::Items => (current, self.total),
+                    ProgressUnit::Bytes => (current / 1024, (self.total / 1024).max(1)),
+                };
+                print_progress!(
+                    current,
+                    total,
+                    PROGRESS_BAR_WIDTH,
+                    "{} {}",
+                    self.label,
+                    message
+                );
+            }
+            ProgressMode::Log => {
+                log_println!(
+                    "INFO",
+                    "{} {:>3}% ({}/{}) {}",
+                    self.label,
+                    current * 100 / self.total,
+                    self.unit.format(current),
+                    self.unit.format(self.total),
+                    message
+                );
+            }
+            ProgressMode::Hidden => {}
+        }
+    }
+}
+
+impl Progress {
+    /// 多卷整体进度的消息，如 `3/5 volumes — 1.2 GB/4.0 GB — ETA 00:45`
+    fn volume_summary(&self, volumes: &VolumeSummary, now: Instant, current: u64) -> String {
+        let started = volumes.started.load(Ordering::Relaxed).min(volumes.total);
+        let eta = volumes
+            .eta(now, current, self.total - current)
+            .map(format_eta)
+            .unwrap_or_else(|| "--:--".to_string());
+        t!(
+            "utils.progress.volume_summary",
+            "started" = format_number(started),
+            "volumes" = format_number(volumes.total),
+            "current" = format_bytes(current),
+            "total" = format_bytes(self.total),
+            "eta" = eta
+        )
+        .to_string()
+    }
+}
+
+/// 将剩余时间格式化为 `MM:SS`，超过一小时时为 `H:MM:SS`
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// 读取时将读取的字节数累加到进度中的读取器
+pub(crate) struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a Progress,
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+    pub fn new(inner: R, progress: &'a Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.add(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_is_shared_across_threads() {
+        let progress = Progress::with_mode("test", 100, ProgressUnit::Items, ProgressMode::Hidden);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..25 {
+                        progress.inc(1, String::new);
+                    }
+                });
+            }
+        });
+        assert_eq!(progress.current.load(Ordering::Relaxed), 100);
+        progress.finish();
+    }
+
+    #[test]
+    fn test_volume_summary_estimates_remaining_time() {
+        let progress = Progress {
+            mode: ProgressMode::Hidden,
+            ..Progress::volumes("test", 4000, 5)
+        };
+        progress.start_volume();
+        progress.start_volume();
+        progress.inc_file(1000, String::new);
+        let Some(volumes) = &progress.volumes else {
+            unreachable!()
+        };
+
+        // 1000 字节/秒的速度处理剩余的 3000 字节约需 3 秒
+        let start = Instant::now();
+        assert_eq!(volumes.eta(start, 0, 4000), None);
+        let eta = volumes.eta(start + Duration::from_secs(1), 1000, 3000);
+        assert_eq!(eta, Some(Duration::from_secs(3)));
+
+        let summary = progress.volume_summary(volumes, start + Duration::from_secs(2), 1000);
+        assert!(summary.contains("2/5"), "{summary}");
+
+        assert_eq!(format_eta(Duration::from_secs(45)), "00:45");
+        assert_eq!(format_eta(Duration::from_secs(3725)), "1:02:05");
+    }
+}
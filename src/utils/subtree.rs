@@ -0,0 +1,117 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{ExcludeMatcher, collect_source_files};
+
+/// 最多报告的大子目录个数
+const LARGE_SUBTREE_LIMIT: usize = 5;
+
+/// 占备份总大小比例较高的子目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LargeSubtree {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// 解析 `(0, 1]` 范围内的比例，也接受 `25%` 形式的百分比
+pub fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio = match s.trim().strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|p| p / 100.0),
+        None => s.trim().parse::<f64>(),
+    }
+    .map_err(|e| e.to_string())?;
+
+    if ratio > 0.0 && ratio <= 1.0 {
+        Ok(ratio)
+    } else {
+        Err(t!("utils.subtree.invalid_ratio", "value" = s).to_string())
+    }
+}
+
+/// 统计各源中 (已按排除模式过滤) 每个子目录的大小，返回占总大小比例不低于 `min_ratio` 的子目录
+///
+/// `follow_symlinks` 的含义与 [`super::CompressOptions`] 相同。源目录本身不计入结果；结果按大小从大到小排列，最多 [`LARGE_SUBTREE_LIMIT`] 个，
+/// 同时返回所有源的总大小
+pub(crate) fn find_large_subtrees<P: AsRef<Path>>(
+    sources: &[P],
+    exclude: &ExcludeMatcher,
+    follow_symlinks: bool,
+    min_ratio: f64,
+) -> Result<(u64, Vec<LargeSubtree>)> {
+    let mut total = 0;
+    let mut sizes = BTreeMap::<PathBuf, u64>::new();
+    for source in sources {
+        let source = source.as_ref();
+        for (path, _) in collect_source_files(source, exclude, follow_symlinks)? {
+            let metadata = if follow_symlinks {
+                std::fs::metadata(&path)
+            } else {
+                std::fs::symlink_metadata(&path)
+            };
+            let size = metadata.map(|m| m.len()).unwrap_or(0);
+            total += size;
+            for dir in path
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| *dir != source && dir.starts_with(source))
+            {
+                *sizes.entry(dir.to_path_buf()).or_default() += size;
+            }
+        }
+    }
+
+    let mut subtrees = sizes
+        .into_iter()
+        .filter(|(_, size)| total > 0 && *size as f64 >= total as f64 * min_ratio)
+        .map(|(path, size)| LargeSubtree { path, size })
+        .collect::<Vec<_>>();
+    subtrees.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    subtrees.truncate(LARGE_SUBTREE_LIMIT);
+    Ok((total, subtrees))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_large_subtrees() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let volume = dir.path().join("vol");
+        std::fs::create_dir_all(volume.join("cache/pages"))?;
+        std::fs::create_dir_all(volume.join("data"))?;
+        std::fs::write(volume.join("cache/pages/a.bin"), vec![0u8; 800])?;
+        std::fs::write(volume.join("data/db.sqlite"), vec![0u8; 150])?;
+        std::fs::write(volume.join("config.toml"), vec![0u8; 50])?;
+
+        let (total, subtrees) =
+            find_large_subtrees(&[&volume], &ExcludeMatcher::default(), false, 0.5)?;
+        assert_eq!(total, 1000);
+        assert_eq!(
+            subtrees,
+            vec![
+                LargeSubtree {
+                    path: volume.join("cache"),
+                    size: 800
+                },
+                LargeSubtree {
+                    path: volume.join("cache/pages"),
+                    size: 800
+                },
+            ]
+        );
+
+        // 已排除的目录不参与统计
+        let (total, subtrees) =
+            find_large_subtrees(&[&volume], &ExcludeMatcher::new(&["cache"])?, false, 0.5)?;
+        assert_eq!(total, 200);
+        assert_eq!(subtrees[0].path, volume.join("data"));
+
+        assert_eq!(parse_ratio("25%"), Ok(0.25));
+        assert_eq!(parse_ratio("0.9"), Ok(0.9));
+        assert!(parse_ratio("0").is_err());
+        assert!(parse_ratio("1.5").is_err());
+        Ok(())
+    }
+}
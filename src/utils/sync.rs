@@ -0,0 +1,158 @@
+use anyhow::Result;
+use std::fs::{self, File};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::FileStamp;
+
+/// 增量复制的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStats {
+    /// 新增或内容有变化而被复制的文件数
+    pub copied: u64,
+    /// 大小与修改时间都相同而被跳过的文件数
+    pub skipped: u64,
+}
+
+/// 将文件或目录内容从 `from` 增量复制到 `to`，跳过目标中大小与修改时间都与源相同的文件
+///
+/// 与 `rsync` 默认的快速检查相同，不比较文件内容。复制的文件沿用源文件的修改时间，
+/// 以便下次复制时能识别为未变化；目标中多出的文件会被保留，与完整复制的行为一致
+pub fn sync_tree(from: &Path, to: &Path) -> Result<SyncStats> {
+    let mut stats = SyncStats::default();
+    for entry in WalkDir::new(from).follow_links(false) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(from)?;
+        // `from` 是文件时遍历结果只有它自身，直接复制到 `to`
+        let target = if relative.as_os_str().is_empty() {
+            to.to_path_buf()
+        } else {
+            to.join(relative)
+        };
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if file_type.is_symlink() {
+            sync_symlink(entry.path(), &target, &mut stats)?;
+        } else {
+            sync_file(entry.path(), &target, &mut stats)?;
+        }
+    }
+    Ok(stats)
+}
+
+fn sync_file(source: &Path, target: &Path, stats: &mut SyncStats) -> Result<()> {
+    let metadata = fs::metadata(source)?;
+    let stamp = FileStamp::from_metadata(&metadata);
+    let unchanged = fs::symlink_metadata(target).is_ok_and(|existing| {
+        existing.is_file() && stamp.is_some() && FileStamp::from_metadata(&existing) == stamp
+    });
+    if unchanged {
+        stats.skipped += 1;
+        return Ok(());
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // 目标是指向其他位置的符号链接时，fs::copy 会写入链接指向的文件
+    if fs::symlink_metadata(target).is_ok_and(|existing| existing.is_symlink()) {
+        fs::remove_file(target)?;
+    }
+    fs::copy(source, target)?;
+    File::options()
+        .write(true)
+        .open(target)?
+        .set_modified(metadata.modified()?)?;
+    stats.copied += 1;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_symlink(source: &Path, target: &Path, stats: &mut SyncStats) -> Result<()> {
+    let link = fs::read_link(source)?;
+    if fs::read_link(target).is_ok_and(|existing| existing == link) {
+        stats.skipped += 1;
+        return Ok(());
+    }
+    if fs::symlink_metadata(target).is_ok() {
+        fs::remove_file(target)?;
+    }
+    std::os::unix::fs::symlink(link, target)?;
+    stats.copied += 1;
+    Ok(())
+}
+
+/// 非 Unix 平台上与完整复制一样复制链接指向的文件
+#[cfg(not(unix))]
+fn sync_symlink(source: &Path, target: &Path, stats: &mut SyncStats) -> Result<()> {
+    sync_file(source, target, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn skips_files_with_matching_size_and_mtime() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let from = temp_dir.path().join("from");
+        let to = temp_dir.path().join("to");
+        fs::create_dir_all(from.join("sub"))?;
+        fs::write(from.join("a.txt"), "aaa")?;
+        fs::write(from.join("sub/b.txt"), "bbb")?;
+
+        let stats = sync_tree(&from, &to)?;
+        assert_eq!(
+            stats,
+            SyncStats {
+                copied: 2,
+                skipped: 0
+            }
+        );
+        assert_eq!(fs::read_to_string(to.join("sub/b.txt"))?, "bbb");
+
+        // 未变化的文件被跳过，目标中多出的文件保留
+        fs::write(to.join("extra.txt"), "keep")?;
+        let stats = sync_tree(&from, &to)?;
+        assert_eq!(
+            stats,
+            SyncStats {
+                copied: 0,
+                skipped: 2
+            }
+        );
+        assert!(to.join("extra.txt").exists());
+
+        // 大小相同但修改时间不同的文件会被重新复制
+        fs::write(to.join("a.txt"), "zzz")?;
+        File::options()
+            .write(true)
+            .open(to.join("a.txt"))?
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))?;
+        let stats = sync_tree(&from, &to)?;
+        assert_eq!(
+            stats,
+            SyncStats {
+                copied: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(fs::read_to_string(to.join("a.txt"))?, "aaa");
+        Ok(())
+    }
+
+    #[test]
+    fn syncs_a_single_file() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let from = temp_dir.path().join("config.ini");
+        let to = temp_dir.path().join("target/config.ini");
+        fs::write(&from, "key=value")?;
+
+        assert_eq!(sync_tree(&from, &to)?.copied, 1);
+        assert_eq!(sync_tree(&from, &to)?.skipped, 1);
+        assert_eq!(fs::read_to_string(&to)?, "key=value");
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use super::archive_key;
+
+/// tar 结束块 (两个全零的 512 字节块) 的长度
+const TAR_TRAILER_LEN: usize = 1024;
+
+/// ustar 头中 `name` 字段的长度
+const USTAR_NAME_LEN: usize = 100;
+
+/// 丢弃 tar 结束块的写入器
+///
+/// 按卷拆分的每个数据流都是独立完成的 tar，直接拼接后中间会出现结束块，GNU tar 读到第一个
+/// 结束块就会停止。兼容模式下除最后一个流外都通过该写入器去掉结束块，拼接结果即为单个 tar
+pub(crate) struct TrailerStripper<W: Write> {
+    inner: W,
+    tail: Vec<u8>,
+}
+
+impl<W: Write> TrailerStripper<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            tail: Vec::with_capacity(TAR_TRAILER_LEN * 2),
+        }
+    }
+
+    /// 丢弃最后写入的结束块，返回内部写入器
+    pub fn into_inner(self) -> io::Result<W> {
+        if self.tail.len() != TAR_TRAILER_LEN || self.tail.iter().any(|b| *b != 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "tar stream does not end with an end-of-archive trailer",
+            ));
+        }
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for TrailerStripper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tail.extend_from_slice(buf);
+        if self.tail.len() > TAR_TRAILER_LEN {
+            let flush_len = self.tail.len() - TAR_TRAILER_LEN;
+            self.inner.write_all(&self.tail[..flush_len])?;
+            self.tail.drain(..flush_len);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 写入一个条目
+///
+/// 兼容模式下使用 ustar 头，放不进 ustar 头的长路径通过 pax 扩展头记录，
+/// 否则使用 GNU 头与 GNU 长文件名扩展
+pub(crate) fn append_entry<W: Write, R: Read>(
+    tar: &mut tar::Builder<W>,
+    metadata: &std::fs::Metadata,
+    name: &Path,
+    data: R,
+    compat: bool,
+) -> io::Result<()> {
+    if !compat {
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        return tar.append_data(&mut header, name, data);
+    }
+
+    let (header, records) = ustar_header(metadata, name);
+    if !records.is_empty() {
+        append_pax_header(tar, &records)?;
+    }
+    tar.append(&header, data)
+}
+
+/// 写入一个符号链接条目，链接目标原样记录，不读取目标内容
+///
+/// 头部格式与 [`append_entry`] 相同，兼容模式下放不进 ustar 头的长链接目标同样通过 pax 扩展头记录
+pub(crate) fn append_symlink<W: Write>(
+    tar: &mut tar::Builder<W>,
+    metadata: &std::fs::Metadata,
+    name: &Path,
+    target: &Path,
+    compat: bool,
+) -> io::Result<()> {
+    if !compat {
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        return tar.append_link(&mut header, name, target);
+    }
+
+    let (mut header, mut records) = ustar_header(metadata, name);
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    if header.set_link_name(target).is_err() {
+        records.push_str(&pax_record("linkpath", &target.to_string_lossy()));
+    }
+    header.set_cksum();
+    if !records.is_empty() {
+        append_pax_header(tar, &records)?;
+    }
+    tar.append(&header, io::empty())
+}
+
+/// 生成 ustar 头，路径放不进 ustar 头时返回需要写入 pax 扩展头的记录
+fn ustar_header(metadata: &std::fs::Metadata, name: &Path) -> (tar::Header, String) {
+    let mut header = tar::Header::new_ustar();
+    header.set_metadata(metadata);
+    let mut records = String::new();
+    if header.set_path(name).is_err() {
+        let path = archive_key(name);
+        records.push_str(&pax_record("path", &path));
+        // ustar 头中只保留路径的前 100 字节，实际路径以 pax 头为准
+        let truncated = &path.as_bytes()[..USTAR_NAME_LEN.min(path.len())];
+        let field = &mut header.as_old_mut().name;
+        field.fill(0);
+        field[..truncated.len()].copy_from_slice(truncated);
+    }
+    header.set_cksum();
+    (header, records)
+}
+
+/// 写入包含 `records` 的 pax 扩展头，作用于紧随其后的条目
+fn append_pax_header<W: Write>(tar: &mut tar::Builder<W>, records: &str) -> io::Result<()> {
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::XHeader);
+    header.set_path("././@PaxHeader")?;
+    header.set_size(records.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append(&header, records.as_bytes())
+}
+
+/// 生成 `<长度> <键>=<值>\n` 形式的 pax 记录，长度包含其自身的位数
+fn pax_record(key: &str, value: &str) -> String {
+    let body_len = key.len() + value.len() + 3;
+    let mut len = body_len + 1;
+    while len != body_len + len.to_string().len() {
+        len = body_len + len.to_string().len();
+    }
+    format!("{len} {key}={value}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pax_record_and_trailer_stripper() -> io::Result<()> {
+        assert_eq!(pax_record("path", "a"), "9 path=a\n");
+        let record = pax_record("path", &"x".repeat(95));
+        assert_eq!(record.len(), 105);
+        assert!(record.starts_with("105 "));
+
+        let mut tar = tar::Builder::new(TrailerStripper::new(Vec::new()));
+        let mut header = tar::Header::new_ustar();
+        header.set_size(3);
+        header.set_cksum();
+        tar.append_data(&mut header, "a.txt", &b"abc"[..])?;
+        let stripped = tar.into_inner()?.into_inner()?;
+        assert_eq!(stripped.len(), 1024);
+
+        assert!(TrailerStripper::new(Vec::new()).into_inner().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 限制读取速率的令牌桶，在并行压缩的所有线程间共享，总读取速率不超过限制
+///
+/// 每次读取后按限速计算这些数据应占用的时间，读取超前时睡眠等待。空闲期间不积累额度，
+/// 因此不会出现突发的高速读取
+pub(crate) struct IoLimiter {
+    /// 每秒最多读取的字节数，`None` 表示不限速
+    bytes_per_sec: Option<u64>,
+    /// 已读取的数据按限速读完的时刻
+    next: Mutex<Instant>,
+}
+
+impl IoLimiter {
+    pub fn new(bytes_per_sec: Option<u64>) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.filter(|rate| *rate > 0),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 记录读取了 `bytes` 字节，超出限速时阻塞当前线程
+    pub fn consume(&self, bytes: u64) {
+        let Some(rate) = self.bytes_per_sec else {
+            return;
+        };
+        let now = Instant::now();
+        let wait = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let start = (*next).max(now);
+            *next = start + Duration::from_secs_f64(bytes as f64 / rate as f64);
+            start - now
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// 读取时按 [`IoLimiter`] 限速的读取器
+pub(crate) struct ThrottledReader<'a, R> {
+    inner: R,
+    limiter: &'a IoLimiter,
+}
+
+impl<'a, R> ThrottledReader<'a, R> {
+    pub fn new(inner: R, limiter: &'a IoLimiter) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.limiter.consume(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_throttles_shared_reads() -> anyhow::Result<()> {
+        let data = vec![0u8; 10 * 1024];
+
+        let unlimited = IoLimiter::new(None);
+        let source = data.repeat(100);
+        let started = Instant::now();
+        std::io::copy(
+            &mut ThrottledReader::new(source.as_slice(), &unlimited),
+            &mut std::io::sink(),
+        )?;
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // 100 KiB/s，两个线程共读 40 KiB，约需 0.4 秒
+        let limiter = IoLimiter::new(Some(100 * 1024));
+        let started = Instant::now();
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    let mut buf = [0u8; 1024];
+                    let source = data.repeat(2);
+                    let mut reader = ThrottledReader::new(source.as_slice(), &limiter);
+                    while reader.read(&mut buf[..]).unwrap() > 0 {}
+                });
+            }
+        });
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(350), "{elapsed:?}");
+        Ok(())
+    }
+}
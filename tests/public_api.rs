@@ -0,0 +1,23 @@
+//! 二进制 (`src/main.rs`) 依赖的库公共接口的冒烟测试
+//!
+//! 只检查接口签名能否链接，避免 `main.rs` 与 `lib.rs` 不一致时直到发布构建才发现
+
+use std::future::Future;
+
+fn assert_entry_point<F>(_: fn() -> F)
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+}
+
+#[test]
+fn binary_entry_points_are_public() {
+    assert_entry_point(rdbkp2::run);
+
+    let init_log: fn(tracing::Level) -> anyhow::Result<()> = rdbkp2::init_log;
+    let _ = init_log;
+
+    let init_log_with_format: fn(tracing::Level, rdbkp2::LogFormat) -> anyhow::Result<()> =
+        rdbkp2::init_log_with_format;
+    let _ = init_log_with_format;
+}